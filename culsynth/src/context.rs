@@ -1,7 +1,22 @@
 //! This module provides objects to reason about the processing context.
 //! Currently, the only information wrapped is the current audio sample rate.
+//!
+//! The sample rate may be changed at any time (see
+//! [GenericContext::set_sample_rate]).  The devices in this crate do not cache
+//! anything derived from the sample rate - phase increments, filter gains, and
+//! envelope time constants are all recalculated from the context passed to each
+//! call to `next`, so a rate change takes effect on the next sample without
+//! needing to reconstruct them.  The exceptions to be aware of are:
+//!
+//!  - [Arpeggiator](crate::voice::arp::Arpeggiator), which recalculates its step
+//!    length at the start of each step, so picks up the change on the next step.
+//!  - State that is explicitly specified in samples rather than seconds, such
+//!    as the [Meter](crate::voice::meter::Meter) window, which must be reset
+//!    by the caller if a constant duration is desired.
 
-use crate::{Float, ScalarFxP};
+#[cfg(feature = "float")]
+use crate::Float;
+use crate::ScalarFxP;
 
 /// Simple helper trait for types that can return a [GenericContext]
 pub trait GetContext {
@@ -9,6 +24,7 @@ pub trait GetContext {
     fn get_context(&self) -> &dyn GenericContext;
 }
 
+#[cfg(feature = "float")]
 impl<Smp: Float> GetContext for Context<Smp> {
     fn get_context(&self) -> &dyn GenericContext {
         self
@@ -28,28 +44,98 @@ pub trait GenericContext {
     fn sample_rate(&self) -> u32;
     /// Returns true if processing using fixed-point logic.
     fn is_fixed_point(&self) -> bool;
+    /// Change the sample rate, in Hz.  This returns an error (and leaves the
+    /// context unchanged) if the sample rate is not supported.
+    fn set_sample_rate(&mut self, sample_rate: u32) -> Result<(), &'static str>;
 }
 
+#[cfg(feature = "float")]
 #[derive(Clone, Copy)]
 /// A floating point (using the type `Smp`) processing context
 pub struct Context<Smp: Float> {
     /// The sample rate, in Hz, with the same type as a processing type
     pub sample_rate: Smp,
+    /// Whether to use libm or the internal approximations (see
+    /// [Context::set_use_libm])
+    #[cfg(feature = "libm")]
+    use_libm: bool,
 }
 
+#[cfg(feature = "float")]
 impl<Smp: Float> Context<Smp> {
     /// Create a new `Context`
     pub fn new(sample_rate: Smp) -> Self {
-        Self { sample_rate }
+        Self {
+            sample_rate,
+            #[cfg(feature = "libm")]
+            use_libm: true,
+        }
+    }
+    /// Return a copy of this `Context` with a new sample rate
+    pub fn with_sample_rate(mut self, sample_rate: Smp) -> Self {
+        self.sample_rate = sample_rate;
+        self
+    }
+    /// Select whether devices using this context use libm (the default) or
+    /// the crate's internal approximation functions for transcendental math.
+    /// This is intended for comparing the accuracy of the approximations.
+    #[cfg(feature = "libm")]
+    pub fn set_use_libm(&mut self, use_libm: bool) {
+        self.use_libm = use_libm;
+    }
+    /// True if devices using this context use libm for transcendental math,
+    /// or false if they use the internal approximation functions.  Without
+    /// the `libm` feature, this is always false (see [crate::USE_LIBM]).
+    pub fn use_libm(&self) -> bool {
+        #[cfg(feature = "libm")]
+        let ret = self.use_libm;
+        #[cfg(not(feature = "libm"))]
+        let ret = false;
+        ret
+    }
+    /// Returns the sine of `x`, respecting [Context::use_libm]
+    pub fn sin(&self, x: Smp) -> Smp {
+        if self.use_libm() {
+            x.fsin()
+        } else {
+            crate::float_approx::sin_approx(x)
+        }
+    }
+    /// Returns the cosine of `x`, respecting [Context::use_libm]
+    pub fn cos(&self, x: Smp) -> Smp {
+        if self.use_libm() {
+            x.fcos()
+        } else {
+            crate::float_approx::cos_approx(x)
+        }
+    }
+    /// Returns the tangent of `x`, respecting [Context::use_libm]
+    pub fn tan(&self, x: Smp) -> Smp {
+        if self.use_libm() {
+            x.ftan()
+        } else {
+            crate::float_approx::tan_approx(x)
+        }
+    }
+    /// Convert a MIDI note number to a frequency, respecting
+    /// [Context::use_libm]
+    pub fn midi_to_freq(&self, note: Smp) -> Smp {
+        if self.use_libm() {
+            note.midi_to_freq()
+        } else {
+            note.midi_to_freq_approx()
+        }
     }
 }
 
+#[cfg(feature = "float")]
 impl<Smp: Float> Default for Context<Smp> {
     fn default() -> Self {
         Self::new(<Smp as From<u16>>::from(44100u16))
     }
 }
 
+#[cfg(feature = "float")]
 impl<Smp: Float> GenericContext for Context<Smp> {
     fn sample_rate(&self) -> u32 {
         self.sample_rate.to_u32().unwrap_or_default()
@@ -57,6 +143,11 @@ impl<Smp: Float> GenericContext for Context<Smp> {
     fn is_fixed_point(&self) -> bool {
         false
     }
+    fn set_sample_rate(&mut self, sample_rate: u32) -> Result<(), &'static str> {
+        self.sample_rate =
+            <Smp as num_traits::NumCast>::from(sample_rate).ok_or("Unsupported Sample Rate")?;
+        Ok(())
+    }
 }
 
 #[derive(Default, Clone, Copy)]
@@ -92,6 +183,10 @@ impl ContextFxP {
             None
         }
     }
+    /// Return a copy of this context with a new sample rate
+    pub const fn with_sample_rate(self, sample_rate: FixedSampleRate) -> Self {
+        Self { sample_rate }
+    }
 }
 
 impl GenericContext for ContextFxP {
@@ -103,6 +198,11 @@ impl GenericContext for ContextFxP {
     fn is_fixed_point(&self) -> bool {
         true
     }
+    /// Change the sample rate.  Only the [FixedSampleRate]s are supported.
+    fn set_sample_rate(&mut self, sample_rate: u32) -> Result<(), &'static str> {
+        self.sample_rate = FixedSampleRate::try_from(sample_rate)?;
+        Ok(())
+    }
 }
 
 #[derive(Default, Clone, Copy)]
@@ -145,3 +245,72 @@ impl TryFrom<u32> for FixedSampleRate {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "float")]
+    use super::*;
+    #[cfg(feature = "float")]
+    use crate::devices::{Device, Osc};
+    #[cfg(feature = "float")]
+    use crate::{DspFormat, NoteFxP};
+
+    /// Run `osc` at A440 for `samples` samples, returning the number of cycles
+    #[cfg(feature = "float")]
+    fn count_cycles<T: DspFormat>(osc: &mut Osc<T>, ctx: &T::Context, samples: usize) -> usize {
+        let note = T::note_from_fixed(NoteFxP::lit("69"));
+        let mut last = 0f32;
+        (0..samples)
+            .filter(|_| {
+                let saw = T::sample_to_float(osc.next(ctx, note, Default::default()).saw);
+                let wrapped = saw < last - 1f32;
+                last = saw;
+                wrapped
+            })
+            .count()
+    }
+
+    #[cfg(feature = "libm")]
+    #[test]
+    fn libm_matches_approximation() {
+        let mut libm_ctx = Context::new(48000f32);
+        let mut approx_ctx = libm_ctx;
+        approx_ctx.set_use_libm(false);
+        assert!(libm_ctx.use_libm() && !approx_ctx.use_libm());
+        let mut libm_osc = Osc::<f32>::new();
+        let mut approx_osc = Osc::<f32>::new();
+        let mut max_err = 0f32;
+        for note in [21f32, 69f32, 105f32] {
+            for _ in 0..4800 {
+                let libm = libm_osc.next(&libm_ctx, note, Default::default());
+                let approx = approx_osc.next(&approx_ctx, note, Default::default());
+                max_err = max_err.max((libm.sin - approx.sin).abs());
+            }
+            libm_ctx = libm_ctx.with_sample_rate(44100f32);
+            approx_ctx = approx_ctx.with_sample_rate(44100f32);
+        }
+        assert!(!approx_ctx.use_libm());
+        assert!(max_err > 0f32 && max_err < 0.01, "{max_err}");
+    }
+
+    #[cfg(feature = "float")]
+    #[test]
+    fn sample_rate_change_mid_stream() {
+        let mut ctx = Context::new(48000f32);
+        let mut osc = Osc::<f32>::new();
+        assert!(count_cycles(&mut osc, &ctx, 48000).abs_diff(440) <= 1);
+        ctx.set_sample_rate(96000).unwrap();
+        assert!(count_cycles(&mut osc, &ctx, 48000).abs_diff(220) <= 1);
+        ctx = ctx.with_sample_rate(44100f32);
+        assert!(count_cycles(&mut osc, &ctx, 44100).abs_diff(440) <= 1);
+
+        let mut ctx = ContextFxP::new_480();
+        let mut osc = Osc::<i16>::new();
+        assert!(count_cycles(&mut osc, &ctx, 48000).abs_diff(440) <= 2);
+        assert!(ctx.set_sample_rate(96000).is_err());
+        ctx.set_sample_rate(44100).unwrap();
+        assert!(count_cycles(&mut osc, &ctx, 44100).abs_diff(440) <= 2);
+        ctx = ctx.with_sample_rate(FixedSampleRate::Khz48_0);
+        assert!(count_cycles(&mut osc, &ctx, 24000).abs_diff(220) <= 2);
+    }
+}
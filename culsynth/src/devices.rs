@@ -1,22 +1,37 @@
 //! This module contains definitions of several different DSP primitives.
 
-use crate::{DspFloat, DspFormat, DspFormatBase, DspType};
+#[cfg(feature = "float")]
+use crate::DspFloat;
+use crate::{DspFormat, DspFormatBase, DspType};
+use arrayvec::ArrayVec;
 use core::iter::{repeat, Iterator, Repeat};
 
 pub(crate) mod amp;
+pub(crate) mod biquad;
+pub(crate) mod click;
+pub(crate) mod combinator;
+pub(crate) mod delay;
 pub(crate) mod env;
 pub(crate) mod filt;
+pub(crate) mod follower;
+pub(crate) mod formant;
+pub(crate) mod glide;
 pub(crate) mod lfo;
 pub(crate) mod mixer;
 pub(crate) mod mixosc;
 pub(crate) mod modfilt;
 pub(crate) mod osc;
+pub(crate) mod reverb;
 pub(crate) mod ringmod;
+pub(crate) mod waveshaper;
 
 mod iter;
 
-use crate::context::{Context, ContextFxP};
+#[cfg(feature = "float")]
+use crate::context::Context;
+use crate::context::ContextFxP;
 use crate::{fixedmath, EnvParamFxP, NoteFxP, SampleFxP, ScalarFxP};
+use crate::{EnvParamFxP32, NoteFxP32, SampleFxP32, ScalarFxP32};
 
 /// A DSP Device
 ///
@@ -39,7 +54,7 @@ use crate::{fixedmath, EnvParamFxP, NoteFxP, SampleFxP, ScalarFxP};
 /// and not need any glue code or to worry about sensible defaults/uninitialized
 /// members based on one of the functions having limited information on the
 /// synth's state.
-pub trait Device<T: DspFormat> {
+pub trait Device<T: DspFormatBase> {
     /// The input type for this device.  Used to represent signals within the
     /// synthesizer, e.g. a control voltage, audio signal, etc.
     type Input;
@@ -75,12 +90,39 @@ pub trait Device<T: DspFormat> {
             params,
         }
     }
+    /// This is similar to [Device::process], but groups the output into
+    /// blocks of `block` samples (e.g. for an FFT or to match a host's buffer
+    /// size).  The final block may be shorter if the inputs run out partway
+    /// through a block.
+    ///
+    /// The blocks are stored in an [ArrayVec] with capacity `N`, and `block`
+    /// is clamped between 1 and `N`.
+    fn block_iter<
+        'a,
+        const N: usize,
+        InputIt: Iterator<Item = Self::Input>,
+        ParamIt: Iterator<Item = Self::Params>,
+    >(
+        &'a mut self,
+        context: &'a T::Context,
+        input: InputIt,
+        params: ParamIt,
+        block: usize,
+    ) -> DeviceBlockIter<'a, T, Self, InputIt, ParamIt, N>
+    where
+        Self: Sized,
+    {
+        DeviceBlockIter {
+            iter: self.process(context, input, params),
+            block: block.clamp(1, N),
+        }
+    }
 }
 
 /// An iterator over a [Device] returned by [Device::process]
 pub struct DeviceIter<
     'a,
-    T: DspFormat,
+    T: DspFormatBase,
     D: Device<T>,
     InputIt: Iterator<Item = D::Input>,
     ParamIt: Iterator<Item = D::Params>,
@@ -93,7 +135,26 @@ pub struct DeviceIter<
 
 impl<
         'a,
-        T: DspFormat,
+        T: DspFormatBase,
+        D: Device<T>,
+        InputIt: Iterator<Item = D::Input>,
+        ParamIt: Iterator<Item = D::Params>,
+    > DeviceIter<'a, T, D, InputIt, ParamIt>
+{
+    /// Replace the context used for the remaining samples (e.g. after a
+    /// sample rate change).  The device's state, such as an oscillator's
+    /// phase, and the remaining input and parameters are preserved.
+    pub fn with_context(self, context: &'a T::Context) -> Self {
+        Self {
+            ctx: context,
+            ..self
+        }
+    }
+}
+
+impl<
+        'a,
+        T: DspFormatBase,
         D: Device<T>,
         InputIt: Iterator<Item = D::Input>,
         ParamIt: Iterator<Item = D::Params>,
@@ -105,9 +166,69 @@ impl<
     }
 }
 
+/// An iterator over blocks of the output of a [Device] returned by
+/// [Device::block_iter]
+pub struct DeviceBlockIter<
+    'a,
+    T: DspFormatBase,
+    D: Device<T>,
+    InputIt: Iterator<Item = D::Input>,
+    ParamIt: Iterator<Item = D::Params>,
+    const N: usize,
+> {
+    iter: DeviceIter<'a, T, D, InputIt, ParamIt>,
+    block: usize,
+}
+
+impl<
+        'a,
+        T: DspFormatBase,
+        D: Device<T>,
+        InputIt: Iterator<Item = D::Input>,
+        ParamIt: Iterator<Item = D::Params>,
+        const N: usize,
+    > DeviceBlockIter<'a, T, D, InputIt, ParamIt, N>
+{
+    /// Replace the context used for the remaining blocks (see
+    /// [DeviceIter::with_context])
+    pub fn with_context(self, context: &'a T::Context) -> Self {
+        Self {
+            iter: self.iter.with_context(context),
+            ..self
+        }
+    }
+}
+
+impl<
+        'a,
+        T: DspFormatBase,
+        D: Device<T>,
+        InputIt: Iterator<Item = D::Input>,
+        ParamIt: Iterator<Item = D::Params>,
+        const N: usize,
+    > Iterator for DeviceBlockIter<'a, T, D, InputIt, ParamIt, N>
+{
+    type Item = ArrayVec<D::Output, N>;
+    fn next(&mut self) -> Option<Self::Item> {
+        let block: ArrayVec<D::Output, N> = self.iter.by_ref().take(self.block).collect();
+        if block.is_empty() {
+            None
+        } else {
+            Some(block)
+        }
+    }
+}
+
 pub use amp::Amp;
-pub use env::{Env, EnvParams};
+pub use biquad::{Biquad, BiquadCoeffs};
+pub use click::{Click, ClickParams};
+pub use combinator::{Parallel, Series};
+pub use delay::{Delay, DelayParams, Flanger, FlangerParams, PingPongDelay, PingPongDelayParams};
+pub use env::{Env, EnvMode, EnvParams};
 pub use filt::{Filt, FiltOutput, FiltParams};
+pub use follower::{EnvelopeFollower, EnvelopeFollowerParams};
+pub use formant::{FormantFilter, FormantFilterParams};
+pub use glide::{Glide, GlideMode, GlideParams};
 pub use iter::env::{new_env_param_iter, EnvParamIter};
 pub use iter::filt::{new_filt_param_iter, FiltParamIter};
 pub use iter::lfo::{new_lfo_param_iter, LfoParamIter};
@@ -126,6 +247,164 @@ pub use iter::ringmod::{
 pub use lfo::{Lfo, LfoOptions, LfoParams, LfoWave};
 pub use mixer::Mixer;
 pub use mixosc::{MixOsc, MixOscParams, SyncedMixOscs, SyncedMixOscsOutput, SyncedMixOscsParams};
-pub use modfilt::{ModFilt, ModFiltInput, ModFiltParams};
-pub use osc::{Osc, OscOutput, OscParams, SyncedOscs, SyncedOscsOutput, SyncedOscsParams};
-pub use ringmod::{RingMod, RingModInput, RingModParams};
+pub use modfilt::{FilterPoles, FilterType, ModFilt, ModFiltInput, ModFiltParams};
+pub use osc::{
+    Osc, OscOutput, OscParams, OscSync, OscSyncMode, SyncedOscs, SyncedOscsOutput, SyncedOscsParams,
+};
+pub use reverb::{Reverb, ReverbParams};
+pub use ringmod::{RingMod, RingModInput, RingModParams, StereoRingMod};
+pub use waveshaper::{Waveshaper, WaveshaperParams};
+
+#[cfg(all(test, feature = "float"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn blocks_tile_output() {
+        let ctx = Context::new(48000f32);
+        let input = (0..10).map(|x| x as f32);
+        let gain = repeat(0.5f32);
+        let mut amp = Amp::<f32>::default();
+        let blocks: ArrayVec<ArrayVec<f32, 8>, 4> =
+            amp.block_iter(&ctx, input.clone(), gain.clone(), 4).collect();
+        assert_eq!(
+            blocks.iter().map(ArrayVec::len).collect::<ArrayVec<_, 4>>()[..],
+            [4, 4, 2]
+        );
+        let expected = amp.process(&ctx, input, gain);
+        assert!(blocks.iter().flatten().copied().eq(expected));
+    }
+
+    #[test]
+    fn context_change_keeps_phase() {
+        let (ctx_48k, ctx_24k) = (Context::new(48000f32), Context::new(24000f32));
+        let mut osc = Osc::<f32>::new();
+        let mut iter = osc.process(&ctx_48k, repeat(69f32), new_osc_param_iter());
+        let mut saw = iter.by_ref().take(1000).map(|out| out.saw).collect::<ArrayVec<_, 2000>>();
+        let mut iter = iter.with_context(&ctx_24k);
+        saw.extend(iter.by_ref().take(1000).map(|out| out.saw));
+        // The saw rises by 2 * 440 / sample_rate each sample, except where it
+        // wraps (so skip those samples)
+        let deltas = saw.windows(2).map(|w| w[1] - w[0]).collect::<ArrayVec<_, 2000>>();
+        let check = |range: core::ops::Range<usize>, sample_rate: f32| {
+            let expected = 880f32 / sample_rate;
+            deltas[range]
+                .iter()
+                .filter(|delta| **delta > 0f32)
+                .all(|delta| (delta - expected).abs() < 1e-4)
+        };
+        // The last sample before the change was advanced at the old rate
+        assert!(check(0..1000, 48000f32));
+        // The phase picks up where it left off, with no discontinuity
+        assert!(check(1000..1999, 24000f32));
+    }
+
+    /// Conversion of the inputs and parameters of a fixed point device to
+    /// those of the equivalent floating point device (see [formats_agree])
+    pub(crate) trait ToFloat {
+        type Float;
+        fn to_float(&self) -> Self::Float;
+    }
+
+    impl ToFloat for bool {
+        type Float = bool;
+        fn to_float(&self) -> bool {
+            *self
+        }
+    }
+
+    impl ToFloat for SampleFxP {
+        type Float = f32;
+        fn to_float(&self) -> f32 {
+            self.to_num()
+        }
+    }
+
+    impl ToFloat for ScalarFxP {
+        type Float = f32;
+        fn to_float(&self) -> f32 {
+            self.to_num()
+        }
+    }
+
+    macro_rules! impl_to_float {
+        ($($t:ident),*) => {
+            $(impl ToFloat for $t<i16> {
+                type Float = $t<f32>;
+                fn to_float(&self) -> $t<f32> {
+                    self.into()
+                }
+            })*
+        };
+    }
+    impl_to_float!(RingModInput, RingModParams, EnvParams);
+
+    /// Run `fixed` and `float` side by side for `n` samples, taking the fixed
+    /// point input and parameters for each sample from `gen` (and converting
+    /// them for `float`).  Asserts that the fixed point output is within
+    /// `lsb` LSBs of the floating point output on every sample.
+    ///
+    /// See [assert_formats_agree] to instantiate both devices from one type.
+    pub(crate) fn formats_agree<Fx, Fl>(
+        mut fixed: Fx,
+        mut float: Fl,
+        lsb: u32,
+        n: usize,
+        mut gen: impl FnMut(usize) -> (Fx::Input, Fx::Params),
+    ) where
+        Fx: Device<i16>,
+        Fl: Device<f32, Output = f32>,
+        Fx::Input: ToFloat<Float = Fl::Input>,
+        Fx::Params: ToFloat<Float = Fl::Params>,
+        Fx::Output: fixed::traits::Fixed,
+    {
+        use fixed::traits::Fixed;
+        let (ctx_fixed, ctx_float) = (ContextFxP::new_480(), Context::new(48000f32));
+        let delta = Fx::Output::DELTA.to_num::<f32>();
+        for i in 0..n {
+            let (input, params) = gen(i);
+            let (float_input, float_params) = (input.to_float(), params.to_float());
+            let fixed_out = fixed.next(&ctx_fixed, input, params).to_num::<f32>();
+            let float_out = float.next(&ctx_float, float_input, float_params);
+            let error = (fixed_out - float_out).abs() / delta;
+            assert!(
+                error <= lsb as f32,
+                "sample {i}: {fixed_out} vs {float_out} ({error} LSB)"
+            );
+        }
+    }
+
+    /// Instantiate the device type `$dev` for both `i16` and `f32`, and check
+    /// that the two implementations agree (see [formats_agree]):
+    ///
+    /// ```ignore
+    /// assert_formats_agree!(Amp, lsb, n, |i| (input, params));
+    /// ```
+    macro_rules! assert_formats_agree {
+        ($dev:ident, $lsb:expr, $n:expr, $gen:expr) => {
+            $crate::devices::tests::formats_agree(
+                $dev::<i16>::default(),
+                $dev::<f32>::default(),
+                $lsb,
+                $n,
+                $gen,
+            )
+        };
+    }
+    pub(crate) use assert_formats_agree;
+
+    /// Feed a loud signal into `dev`, which returns the state of its feedback
+    /// path, followed by silence.  Check that the state decays to exactly zero
+    /// without ever becoming denormal.
+    pub(crate) fn decays_cleanly<const N: usize>(mut dev: impl FnMut(f32) -> [f32; N]) {
+        for i in 0..4800 {
+            dev(if i % 100 < 50 { 0.9f32 } else { -0.9f32 });
+        }
+        let mut state = [1f32; N];
+        for i in 0..96000 {
+            state = dev(0f32);
+            assert!(state.iter().all(|x| !x.is_subnormal()), "{i}: {state:?}");
+        }
+        assert!(state.iter().all(|x| *x == 0f32), "{state:?}");
+    }
+}
@@ -9,18 +9,99 @@ use crate::{DspFormat, DspType};
 /// 4-quadrant VCA with less restrictions on gain values will be implemented
 /// in the future.
 ///
+/// An optional tremolo may be enabled with [Amp::set_tremolo], which
+/// modulates the gain with an internal sine [Lfo].
+///
 /// It implements [Device] taking a Sample as input, a Scalar parameter (the
 /// gain) and outputting a Sample (see [DspFormat] for more information).
 #[derive(Default, Clone)]
 pub struct Amp<T: DspFormat> {
-    phantom: core::marker::PhantomData<T>,
+    lfo: Lfo<T>,
+    tremolo: Option<LfoParams<T>>,
+}
+
+impl<T: DspFormat> Amp<T> {
+    /// Enable a tremolo at `rate` Hz.  The gain is reduced by up to `depth`
+    /// (between 0 and 1) once per cycle, so the output swings between the
+    /// full gain and `1 - depth` times the gain.  A `depth` of zero disables
+    /// the tremolo (the default).
+    pub fn set_tremolo(&mut self, rate: T::LfoFreq, depth: T::Scalar) {
+        self.tremolo = (depth != T::Scalar::zero()).then(|| LfoParams {
+            freq: rate,
+            depth,
+            opts: LfoOptions::new(LfoWave::Sine, false, false),
+            phase_offset: T::Scalar::zero(),
+        });
+    }
 }
 
 impl<T: DspFormat> Device<T> for Amp<T> {
     type Input = T::Sample;
     type Params = T::Scalar;
     type Output = T::Sample;
-    fn next(&mut self, _: &T::Context, signal: T::Sample, gain: T::Scalar) -> T::Sample {
-        signal.scale(gain)
+    fn next(&mut self, context: &T::Context, signal: T::Sample, gain: T::Scalar) -> T::Sample {
+        let out = signal.scale(gain);
+        match &self.tremolo {
+            Some(params) => {
+                // The unipolar LFO is between 0 and depth, so this cannot overflow
+                let reduction = self.lfo.next(context, false, params.clone());
+                out - out.multiply(reduction)
+            }
+            None => out,
+        }
+    }
+}
+
+#[cfg(all(test, feature = "float"))]
+mod tests {
+    use super::*;
+    use crate::devices::tests::assert_formats_agree;
+
+    #[test]
+    fn fixed_matches_float() {
+        assert_formats_agree!(Amp, 1, 4800, |i| {
+            let signal = SampleFxP::from_num((i as f32 / 20f32).sin() * 4f32);
+            (signal, ScalarFxP::from_bits((i * 13) as u16))
+        });
+    }
+
+    /// Run a constant signal of 0.5 through an amp with a tremolo at half
+    /// depth for one second, returning the number of cycles of the output
+    /// and its (min, max) amplitude
+    fn tremolo_cycles<T: DspFormat>(ctx: &T::Context, rate: T::LfoFreq) -> (usize, f32, f32) {
+        let mut amp = Amp::<T>::default();
+        amp.set_tremolo(rate, T::scalar_from_fixed(ScalarFxP::lit("0.5")));
+        let signal = T::sample_from_fixed(crate::IScalarFxP::lit("0.5"));
+        let (mut cycles, mut min, mut max, mut low) = (0, f32::MAX, f32::MIN, false);
+        for _ in 0..48000 {
+            let out = T::sample_to_float(amp.next(ctx, signal, T::Scalar::one()));
+            // Count rising edges with some hysteresis about the midpoint
+            if out < 0.3 {
+                low = true;
+            } else if out > 0.45 && low {
+                cycles += 1;
+                low = false;
+            }
+            (min, max) = (min.min(out), max.max(out));
+        }
+        (cycles, min, max)
+    }
+
+    #[test]
+    fn tremolo_oscillates_at_rate_and_depth() {
+        let check = |(cycles, min, max): (usize, f32, f32)| {
+            assert_eq!(cycles, 5);
+            assert!((max - 0.5).abs() < 0.01, "{max}");
+            assert!((min - 0.25).abs() < 0.01, "{min}");
+        };
+        check(tremolo_cycles::<f32>(&Context::new(48000f32), 5f32));
+        check(tremolo_cycles::<i16>(
+            &ContextFxP::new_480(),
+            crate::LfoFreqFxP::lit("5"),
+        ));
+        // The tremolo is disabled by default
+        let mut amp = Amp::<f32>::default();
+        let ctx = Context::new(48000f32);
+        assert!((0..4800).all(|_| amp.next(&ctx, 0.5f32, 1f32) == 0.5f32));
     }
 }
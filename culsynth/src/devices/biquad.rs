@@ -0,0 +1,315 @@
+use super::*;
+#[cfg(feature = "float")]
+use crate::context::GetContext;
+#[cfg(feature = "float")]
+use crate::Float;
+
+pub(crate) mod detail {
+    use super::*;
+    pub trait BiquadOps: DspFormatBase {
+        type BiquadCoeff: Copy + Default + Send;
+        type BiquadState: Copy + Default + Send;
+        const BIQUAD_COEFF_ONE: Self::BiquadCoeff;
+        #[cfg(feature = "float")]
+        fn biquad_coeff(value: f32) -> Self::BiquadCoeff;
+        fn calc_biquad(
+            coeffs: &BiquadCoeffs<Self>,
+            signal: Self::Sample,
+            z1: &mut Self::BiquadState,
+            z2: &mut Self::BiquadState,
+        ) -> Self::Sample;
+    }
+}
+
+/// The coefficients for a [Biquad], normalized so that `a0` is 1.
+///
+/// The transfer function is:
+///
+/// ```text
+///        b0 + b1*z^-1 + b2*z^-2
+/// H(z) = ----------------------
+///         1 + a1*z^-1 + a2*z^-2
+/// ```
+///
+/// For fixed-point types, the coefficients are stored with 8 integral bits,
+/// so coefficients outside of +/-128 will saturate.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BiquadCoeffs<T: DspFormatBase + detail::BiquadOps> {
+    /// The feedforward coefficient for the current input
+    pub b0: T::BiquadCoeff,
+    /// The feedforward coefficient for the input delayed by one sample
+    pub b1: T::BiquadCoeff,
+    /// The feedforward coefficient for the input delayed by two samples
+    pub b2: T::BiquadCoeff,
+    /// The feedback coefficient for the output delayed by one sample
+    pub a1: T::BiquadCoeff,
+    /// The feedback coefficient for the output delayed by two samples
+    pub a2: T::BiquadCoeff,
+}
+
+#[cfg(feature = "float")]
+impl<T: DspFloat> From<&BiquadCoeffs<i16>> for BiquadCoeffs<T> {
+    fn from(value: &BiquadCoeffs<i16>) -> Self {
+        Self {
+            b0: value.b0.to_num(),
+            b1: value.b1.to_num(),
+            b2: value.b2.to_num(),
+            a1: value.a1.to_num(),
+            a2: value.a2.to_num(),
+        }
+    }
+}
+
+/// Defaults to a pass-through filter (b0 = 1, all other coefficients 0)
+impl<T: DspFormatBase + detail::BiquadOps> Default for BiquadCoeffs<T> {
+    fn default() -> Self {
+        Self {
+            b0: T::BIQUAD_COEFF_ONE,
+            b1: Default::default(),
+            b2: Default::default(),
+            a1: Default::default(),
+            a2: Default::default(),
+        }
+    }
+}
+
+/// The coefficient design functions are calculated in floating point, so
+/// are only available with the `float` feature.  Without it, set the
+/// coefficients directly.
+#[cfg(feature = "float")]
+impl<T: DspFormatBase + detail::BiquadOps> BiquadCoeffs<T> {
+    /// Create a set of coefficients from their floating point values, which
+    /// must already be normalized so that `a0` is 1.
+    pub fn new(b0: f32, b1: f32, b2: f32, a1: f32, a2: f32) -> Self {
+        Self {
+            b0: T::biquad_coeff(b0),
+            b1: T::biquad_coeff(b1),
+            b2: T::biquad_coeff(b2),
+            a1: T::biquad_coeff(a1),
+            a2: T::biquad_coeff(a2),
+        }
+    }
+    /// Normalize unnormalized coefficients by `a0`
+    fn from_unnormalized(b: [f32; 3], a: [f32; 3]) -> Self {
+        Self::new(
+            b[0] / a[0],
+            b[1] / a[0],
+            b[2] / a[0],
+            a[1] / a[0],
+            a[2] / a[0],
+        )
+    }
+    /// Returns (cos(w0), alpha), where w0 is the normalized angular frequency
+    fn omega(context: &T::Context, freq: f32, q: f32) -> (f32, f32) {
+        let sr = context.get_context().sample_rate() as f32;
+        // Clamp to [0, pi] to keep the sine approximation accurate
+        let w0 = (core::f32::consts::TAU * freq / sr).clamp(0f32, core::f32::consts::PI);
+        // cos(x) = sin(pi/2 - x), which keeps the argument within [-pi/2, pi/2]
+        let cos = (core::f32::consts::FRAC_PI_2 - w0).fsin();
+        (cos, w0.fsin() / (2f32 * q))
+    }
+    /// A second-order low-pass filter with cutoff `freq` (in Hz) and quality
+    /// factor `q` (a `q` of 1/sqrt(2) results in a Butterworth response)
+    pub fn low_pass(context: &T::Context, freq: f32, q: f32) -> Self {
+        let (cos, alpha) = Self::omega(context, freq, q);
+        let b1 = 1f32 - cos;
+        Self::from_unnormalized(
+            [b1 / 2f32, b1, b1 / 2f32],
+            [1f32 + alpha, -2f32 * cos, 1f32 - alpha],
+        )
+    }
+    /// A second-order high-pass filter with cutoff `freq` (in Hz) and quality
+    /// factor `q` (a `q` of 1/sqrt(2) results in a Butterworth response)
+    pub fn high_pass(context: &T::Context, freq: f32, q: f32) -> Self {
+        let (cos, alpha) = Self::omega(context, freq, q);
+        let b1 = 1f32 + cos;
+        Self::from_unnormalized(
+            [b1 / 2f32, -b1, b1 / 2f32],
+            [1f32 + alpha, -2f32 * cos, 1f32 - alpha],
+        )
+    }
+    /// A peaking EQ filter centered on `freq` (in Hz) with a bandwidth set by
+    /// `q`, applying the linear amplitude `gain` at the center frequency
+    pub fn peaking(context: &T::Context, freq: f32, q: f32, gain: f32) -> Self {
+        let (cos, alpha) = Self::omega(context, freq, q);
+        let a = crate::util::sqrt_approx(gain);
+        Self::from_unnormalized(
+            [1f32 + alpha * a, -2f32 * cos, 1f32 - alpha * a],
+            [1f32 + alpha / a, -2f32 * cos, 1f32 - alpha / a],
+        )
+    }
+    /// A low shelf filter applying the linear amplitude `gain` below `freq`
+    /// (in Hz), with the slope of the transition set by `q`
+    pub fn low_shelf(context: &T::Context, freq: f32, q: f32, gain: f32) -> Self {
+        let (cos, alpha) = Self::omega(context, freq, q);
+        let a = crate::util::sqrt_approx(gain);
+        let (ap1, am1) = (a + 1f32, a - 1f32);
+        let k = 2f32 * crate::util::sqrt_approx(a) * alpha;
+        Self::from_unnormalized(
+            [
+                a * (ap1 - am1 * cos + k),
+                2f32 * a * (am1 - ap1 * cos),
+                a * (ap1 - am1 * cos - k),
+            ],
+            [
+                ap1 + am1 * cos + k,
+                -2f32 * (am1 + ap1 * cos),
+                ap1 + am1 * cos - k,
+            ],
+        )
+    }
+    /// A high shelf filter applying the linear amplitude `gain` above `freq`
+    /// (in Hz), with the slope of the transition set by `q`
+    pub fn high_shelf(context: &T::Context, freq: f32, q: f32, gain: f32) -> Self {
+        let (cos, alpha) = Self::omega(context, freq, q);
+        let a = crate::util::sqrt_approx(gain);
+        let (ap1, am1) = (a + 1f32, a - 1f32);
+        let k = 2f32 * crate::util::sqrt_approx(a) * alpha;
+        Self::from_unnormalized(
+            [
+                a * (ap1 + am1 * cos + k),
+                -2f32 * a * (am1 + ap1 * cos),
+                a * (ap1 + am1 * cos - k),
+            ],
+            [
+                ap1 - am1 * cos + k,
+                2f32 * (am1 - ap1 * cos),
+                ap1 - am1 * cos - k,
+            ],
+        )
+    }
+}
+
+/// A biquad (second-order IIR) filter with arbitrary coefficients
+///
+/// This is useful for EQ and for filter shapes not provided by [Filt].  It is
+/// implemented using the transposed direct form II structure.  Coefficients
+/// can be calculated with the helper constructors on [BiquadCoeffs] or loaded
+/// directly.
+///
+/// The fixed-point implementation keeps its internal state with 16 integral
+/// and 48 fractional bits and saturates rather than wrapping, so poorly
+/// conditioned or high-gain coefficients will clip instead of overflowing.
+///
+/// This implements [Device] taking a Sample as input and [BiquadCoeffs] as
+/// parameters and outputting a Sample.
+#[derive(Clone, Default)]
+pub struct Biquad<T: DspFormat> {
+    z1: T::BiquadState,
+    z2: T::BiquadState,
+}
+
+impl<T: DspFormat> Biquad<T> {
+    /// Constructor
+    pub fn new() -> Self {
+        Default::default()
+    }
+}
+
+impl<T: DspFormat> Device<T> for Biquad<T> {
+    type Input = T::Sample;
+    type Params = BiquadCoeffs<T>;
+    type Output = T::Sample;
+    fn next(&mut self, _: &T::Context, signal: T::Sample, coeffs: BiquadCoeffs<T>) -> T::Sample {
+        T::calc_biquad(&coeffs, signal, &mut self.z1, &mut self.z2)
+    }
+}
+
+#[cfg(feature = "float")]
+impl<T: DspFloat> detail::BiquadOps for T {
+    type BiquadCoeff = T;
+    type BiquadState = T;
+    const BIQUAD_COEFF_ONE: T = T::ONE;
+    fn biquad_coeff(value: f32) -> T {
+        <T as num_traits::NumCast>::from(value).unwrap_or_default()
+    }
+    fn calc_biquad(coeffs: &BiquadCoeffs<T>, signal: T, z1: &mut T, z2: &mut T) -> T {
+        let out = coeffs.b0 * signal + *z1;
+        *z1 = coeffs.b1 * signal - coeffs.a1 * out + *z2;
+        *z2 = coeffs.b2 * signal - coeffs.a2 * out;
+        *z1 = z1.flush_denormal();
+        *z2 = z2.flush_denormal();
+        out
+    }
+}
+
+impl detail::BiquadOps for i16 {
+    type BiquadCoeff = crate::fixedmath::I8F24;
+    type BiquadState = crate::fixedmath::I16F48;
+    const BIQUAD_COEFF_ONE: Self::BiquadCoeff = Self::BiquadCoeff::ONE;
+    #[cfg(feature = "float")]
+    fn biquad_coeff(value: f32) -> Self::BiquadCoeff {
+        Self::BiquadCoeff::saturating_from_num(value)
+    }
+    fn calc_biquad(
+        coeffs: &BiquadCoeffs<i16>,
+        signal: SampleFxP,
+        z1: &mut Self::BiquadState,
+        z2: &mut Self::BiquadState,
+    ) -> SampleFxP {
+        use crate::fixedmath::I8F24;
+        let x = I8F24::from_num(signal);
+        let out = coeffs.b0.wide_mul(x).saturating_add(*z1);
+        // Feed back the output with the same precision as the input
+        let y = I8F24::saturating_from_num(out);
+        *z1 = coeffs.b1.wide_mul(x).saturating_sub(coeffs.a1.wide_mul(y)).saturating_add(*z2);
+        *z2 = coeffs.b2.wide_mul(x).saturating_sub(coeffs.a2.wide_mul(y));
+        SampleFxP::saturating_from_num(out)
+    }
+}
+
+#[cfg(all(test, feature = "float"))]
+mod tests {
+    use super::*;
+
+    /// The peak amplitude of the response of `dev` to a sine wave at `freq`
+    fn sine_response<T: DspFormat>(mut dev: impl FnMut(T::Sample) -> T::Sample, freq: f32) -> f32 {
+        const SR: f32 = 48000f32;
+        (0..9600)
+            .map(|i| {
+                let phase = core::f32::consts::TAU * freq * (i as f32) / SR;
+                let input = T::sample_from_fixed(crate::IScalarFxP::from_num(phase.sin() / 2f32));
+                (i, T::sample_to_float(dev(input)))
+            })
+            .filter(|(i, _)| *i >= 4800)
+            .fold(0f32, |acc, (_, smp)| acc.max(smp.abs() * 2f32))
+    }
+
+    fn compare_to_svf<T: DspFormat>(ctx: &T::Context, cutoff: T::Note, resonance: T::Scalar) {
+        // MIDI note 84, with a Q of 1/sqrt(2) corresponding to a damping of
+        // 1/sqrt(2) (i.e. a resonance of 1 - 0.7071 = 0.2929)
+        const CUTOFF_HZ: f32 = 1046.5f32;
+        let coeffs = BiquadCoeffs::<T>::low_pass(ctx, CUTOFF_HZ, core::f32::consts::FRAC_1_SQRT_2);
+        for freq in [250f32, CUTOFF_HZ, 4000f32] {
+            let mut biquad = Biquad::<T>::new();
+            let mut svf = Filt::<T>::new();
+            let bq = sine_response::<T>(|x| biquad.next(ctx, x, coeffs), freq);
+            let filt = sine_response::<T>(
+                |x| svf.next(ctx, x, FiltParams { cutoff, resonance }).low,
+                freq,
+            );
+            assert!((bq / filt - 1f32).abs() < 0.05, "{freq}Hz: {bq} vs {filt}");
+        }
+    }
+
+    #[test]
+    fn float_state_flushes_denormals() {
+        let ctx = Context::new(48000f32);
+        let mut biquad = Biquad::<f32>::new();
+        let coeffs = BiquadCoeffs::low_pass(&ctx, 200f32, 2f32);
+        crate::devices::tests::decays_cleanly(|x| {
+            biquad.next(&ctx, x, coeffs);
+            [biquad.z1, biquad.z2]
+        });
+    }
+
+    #[test]
+    fn low_pass_matches_svf() {
+        compare_to_svf::<f32>(&Context::new(48000f32), 84f32, 0.2929f32);
+        compare_to_svf::<i16>(
+            &ContextFxP::new_480(),
+            NoteFxP::lit("84"),
+            ScalarFxP::lit("0.2929"),
+        );
+    }
+}
@@ -0,0 +1,168 @@
+use super::*;
+use crate::util::{GateDetect, GateEdge};
+use crate::IScalarFxP;
+
+/// A Hann-windowed half-band sinc kernel, normalized for unity gain at DC.
+/// The response at Nyquist is about -56dB.
+const KERNEL: [IScalarFxP; 15] = [
+    IScalarFxP::lit("-0.00173"),
+    IScalarFxP::ZERO,
+    IScalarFxP::lit("0.01962"),
+    IScalarFxP::ZERO,
+    IScalarFxP::lit("-0.07324"),
+    IScalarFxP::ZERO,
+    IScalarFxP::lit("0.30573"),
+    IScalarFxP::lit("0.49924"),
+    IScalarFxP::lit("0.30573"),
+    IScalarFxP::ZERO,
+    IScalarFxP::lit("-0.07324"),
+    IScalarFxP::ZERO,
+    IScalarFxP::lit("0.01962"),
+    IScalarFxP::ZERO,
+    IScalarFxP::lit("-0.00173"),
+];
+
+/// Parameters for a [Click]
+#[derive(Clone, Debug, PartialEq)]
+pub struct ClickParams<T: DspFormatBase> {
+    /// The level of the impulse, between 0 and 1
+    pub level: T::Scalar,
+}
+
+impl<T: DspFormatBase> Default for ClickParams<T> {
+    fn default() -> Self {
+        Self {
+            level: T::Scalar::one(),
+        }
+    }
+}
+
+#[cfg(feature = "float")]
+impl<T: DspFloat> From<&ClickParams<i16>> for ClickParams<T> {
+    fn from(value: &ClickParams<i16>) -> Self {
+        Self {
+            level: value.level.to_num(),
+        }
+    }
+}
+
+/// A band-limited impulse generator
+///
+/// Each rising edge of the trigger input emits a single impulse, lowpassed to
+/// half of the Nyquist frequency so that it does not alias at any sample rate.
+/// The impulse has unity gain at DC and is spread over 15 samples, peaking at
+/// about half of [ClickParams::level] 7 samples after the trigger.  Retriggering
+/// while an impulse is playing restarts it.
+///
+/// This is useful as a percussive transient, or to measure the impulse
+/// response of other devices.
+///
+/// This implements [Device] taking a trigger (`bool`) as input and
+/// [ClickParams] as parameters and outputting a Sample.
+#[derive(Clone)]
+pub struct Click<T: DspFormat> {
+    trigger: GateDetect,
+    idx: usize,
+    phantom: core::marker::PhantomData<T>,
+}
+
+impl<T: DspFormat> Default for Click<T> {
+    fn default() -> Self {
+        Self {
+            trigger: GateDetect::new(),
+            idx: KERNEL.len(),
+            phantom: Default::default(),
+        }
+    }
+}
+
+impl<T: DspFormat> Click<T> {
+    /// Constructor
+    pub fn new() -> Self {
+        Default::default()
+    }
+    /// Returns true if no impulse is currently playing
+    pub fn is_idle(&self) -> bool {
+        self.idx >= KERNEL.len()
+    }
+}
+
+impl<T: DspFormat> Device<T> for Click<T> {
+    type Input = bool;
+    type Params = ClickParams<T>;
+    type Output = T::Sample;
+    fn next(&mut self, _: &T::Context, trigger: bool, params: ClickParams<T>) -> T::Sample {
+        if self.trigger.next(trigger) == Some(GateEdge::Rising) {
+            self.idx = 0;
+        }
+        match KERNEL.get(self.idx) {
+            Some(k) => {
+                self.idx += 1;
+                T::sample_from_fixed(*k).scale(params.level)
+            }
+            None => T::Sample::zero(),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "float"))]
+mod tests {
+    use super::*;
+
+    /// Feed a click into a [Filt] and return the magnitudes of the first few
+    /// positive peaks of the low-pass impulse response once the click has
+    /// finished, along with the sum of the click's samples with alternating
+    /// sign (its response at Nyquist)
+    fn filt_impulse_peaks<T: DspFormat>(
+        ctx: &T::Context,
+        params: FiltParams<T>,
+    ) -> ([f32; 3], f32) {
+        let mut click = Click::<T>::new();
+        let mut filt = Filt::<T>::new();
+        let (mut peaks, mut n) = ([0f32; 3], 0);
+        let (mut nyquist, mut sign) = (0f32, 1f32);
+        let (mut prev, mut last) = (0f32, 0f32);
+        for i in 0..2000 {
+            let x = click.next(ctx, i == 0, Default::default());
+            nyquist += sign * T::sample_to_float(x);
+            sign = -sign;
+            let low = T::sample_to_float(filt.next(ctx, x, params.clone()).low);
+            if click.is_idle() && last > prev && last > low && n < peaks.len() {
+                peaks[n] = last;
+                n += 1;
+            }
+            (prev, last) = (last, low);
+        }
+        (peaks, nyquist)
+    }
+
+    #[test]
+    fn click_measures_filt_decay() {
+        // With a damping of 1 - resonance = 1/8, the response should decay by
+        // exp(-2 * pi * R / sqrt(1 - R^2)) every cycle
+        let damping = 0.125f32;
+        let expected =
+            (-2f32 * core::f32::consts::PI * damping / (1f32 - damping * damping).sqrt()).exp();
+        let check = |(peaks, nyquist): ([f32; 3], f32)| {
+            assert!(nyquist.abs() < 0.002, "{nyquist}");
+            for w in peaks.windows(2) {
+                let ratio = w[1] / w[0];
+                assert!((ratio - expected).abs() < 0.02, "{peaks:?}: {ratio}");
+            }
+        };
+        check(filt_impulse_peaks::<f32>(
+            &Context::new(48000f32),
+            FiltParams {
+                cutoff: 84f32,
+                resonance: 1f32 - damping,
+            },
+        ));
+        check(filt_impulse_peaks::<i16>(
+            &ContextFxP::new_480(),
+            FiltParams {
+                cutoff: NoteFxP::lit("84"),
+                resonance: ScalarFxP::lit("0.875"),
+            },
+        ));
+    }
+}
@@ -0,0 +1,248 @@
+use super::*;
+
+/// Two devices chained in series, with the output of `A` feeding the input
+/// of `B`.  The output type of `A` must be the same as the input type of `B`,
+/// or this will not compile.
+///
+/// This implements [Device], taking the input of `A` as input, a tuple of
+/// the parameters of (`A`, `B`) as parameters, and outputting the output of
+/// `B`.  Since a [Series] is itself a [Device], longer chains may be built by
+/// nesting them.
+#[derive(Clone, Default)]
+pub struct Series<T: DspFormatBase, A, B> {
+    first: A,
+    second: B,
+    phantom: core::marker::PhantomData<T>,
+}
+
+impl<T, A, B> Series<T, A, B>
+where
+    T: DspFormatBase,
+    A: Device<T>,
+    B: Device<T, Input = A::Output>,
+{
+    /// Chain `first` into `second`
+    pub fn new(first: A, second: B) -> Self {
+        Self {
+            first,
+            second,
+            phantom: Default::default(),
+        }
+    }
+    /// Get a reference to the first device in the chain
+    pub fn first(&self) -> &A {
+        &self.first
+    }
+    /// Get a mutable reference to the first device in the chain
+    pub fn first_mut(&mut self) -> &mut A {
+        &mut self.first
+    }
+    /// Get a reference to the second device in the chain
+    pub fn second(&self) -> &B {
+        &self.second
+    }
+    /// Get a mutable reference to the second device in the chain
+    pub fn second_mut(&mut self) -> &mut B {
+        &mut self.second
+    }
+}
+
+impl<T, A, B> Device<T> for Series<T, A, B>
+where
+    T: DspFormatBase,
+    A: Device<T>,
+    B: Device<T, Input = A::Output>,
+{
+    type Input = A::Input;
+    type Params = (A::Params, B::Params);
+    type Output = B::Output;
+    fn next(
+        &mut self,
+        context: &T::Context,
+        input: A::Input,
+        (params_a, params_b): (A::Params, B::Params),
+    ) -> B::Output {
+        let intermediate = self.first.next(context, input, params_a);
+        self.second.next(context, intermediate, params_b)
+    }
+}
+
+/// Two devices run in parallel on the same input, with their outputs mixed
+/// together by a [Mixer].  Setting both gains to 1 sums the outputs, while
+/// complementary gains crossfade between them.
+///
+/// Both devices must take the same input type and output Samples, or this
+/// will not compile.  For example, a [Filt] outputs a [FiltOutput] rather
+/// than a Sample, so it can't be used directly:
+///
+/// ```compile_fail
+/// use culsynth::devices::*;
+///
+/// let _ = Parallel::new(Filt::<f32>::new(), Biquad::<f32>::new());
+/// ```
+///
+/// This implements [Device], taking the (common) input of `A` and `B` as
+/// input, a tuple of the parameters of (`A`, `B`) and the gains to apply to
+/// each of their outputs as parameters, and outputting a Sample.
+#[derive(Clone, Default)]
+pub struct Parallel<T: DspFormat, A, B> {
+    first: A,
+    second: B,
+    mixer: Mixer<T, 2>,
+}
+
+impl<T, A, B> Parallel<T, A, B>
+where
+    T: DspFormat,
+    A: Device<T, Output = T::Sample>,
+    B: Device<T, Input = A::Input, Output = T::Sample>,
+    A::Input: Clone,
+{
+    /// Run `first` and `second` in parallel
+    pub fn new(first: A, second: B) -> Self {
+        Self {
+            first,
+            second,
+            mixer: Default::default(),
+        }
+    }
+    /// Get a reference to the first device
+    pub fn first(&self) -> &A {
+        &self.first
+    }
+    /// Get a mutable reference to the first device
+    pub fn first_mut(&mut self) -> &mut A {
+        &mut self.first
+    }
+    /// Get a reference to the second device
+    pub fn second(&self) -> &B {
+        &self.second
+    }
+    /// Get a mutable reference to the second device
+    pub fn second_mut(&mut self) -> &mut B {
+        &mut self.second
+    }
+}
+
+impl<T, A, B> Device<T> for Parallel<T, A, B>
+where
+    T: DspFormat,
+    A: Device<T, Output = T::Sample>,
+    B: Device<T, Input = A::Input, Output = T::Sample>,
+    A::Input: Clone,
+{
+    type Input = A::Input;
+    type Params = (A::Params, B::Params, [T::Scalar; 2]);
+    type Output = T::Sample;
+    fn next(
+        &mut self,
+        context: &T::Context,
+        input: A::Input,
+        (params_a, params_b, gains): (A::Params, B::Params, [T::Scalar; 2]),
+    ) -> T::Sample {
+        let out_a = self.first.next(context, input.clone(), params_a);
+        let out_b = self.second.next(context, input, params_b);
+        self.mixer.next(context, [out_a, out_b], gains)
+    }
+}
+
+#[cfg(all(test, feature = "float"))]
+mod tests {
+    use super::*;
+
+    fn series_matches_manual<T: DspFormat>(
+        ctx: &T::Context,
+        note: T::Note,
+        osc_params: MixOscParams<T>,
+        filt_params: FiltParams<T>,
+    ) where
+        T::Sample: PartialEq + core::fmt::Debug,
+    {
+        let mut series = Series::new(MixOsc::<T>::default(), Filt::<T>::new());
+        let (mut osc, mut filt) = (MixOsc::<T>::default(), Filt::<T>::new());
+        for _ in 0..1000 {
+            let chained = series.next(ctx, note, (osc_params.clone(), filt_params.clone()));
+            let manual = filt.next(
+                ctx,
+                osc.next(ctx, note, osc_params.clone()),
+                filt_params.clone(),
+            );
+            assert_eq!(chained.low, manual.low);
+            assert_eq!(chained.band, manual.band);
+            assert_eq!(chained.high, manual.high);
+        }
+    }
+
+    /// The peak output of a low pass and high pass biquad at `cutoff` in
+    /// parallel in response to a sine wave at `freq`, along with whether it
+    /// matched the output of the same filters mixed manually
+    fn parallel_filters(cutoff: f32, freq: f32) -> (f32, bool) {
+        let ctx = Context::new(48000f32);
+        let q = core::f32::consts::FRAC_1_SQRT_2;
+        let (lp, hp) = (
+            BiquadCoeffs::low_pass(&ctx, cutoff, q),
+            BiquadCoeffs::high_pass(&ctx, cutoff, q),
+        );
+        let mut parallel = Parallel::new(Biquad::<f32>::new(), Biquad::new());
+        let (mut first, mut second) = (Biquad::<f32>::new(), Biquad::<f32>::new());
+        let gains = [1f32, 1f32];
+        let mut matched = true;
+        let mut peak = 0f32;
+        for i in 0..9600 {
+            let input = (core::f32::consts::TAU * freq * (i as f32) / 48000f32).sin() / 2f32;
+            let out = parallel.next(&ctx, input, (lp, hp, gains));
+            let manual = Mixer::<f32, 2>::mix(
+                [first.next(&ctx, input, lp), second.next(&ctx, input, hp)],
+                gains,
+            );
+            matched &= out == manual;
+            if i >= 4800 {
+                peak = peak.max(out.abs() * 2f32);
+            }
+        }
+        (peak, matched)
+    }
+
+    #[test]
+    fn parallel_filters_sum() {
+        // Summing a low pass and high pass at the same cutoff passes
+        // frequencies far from the cutoff and notches out the cutoff itself
+        for (freq, min, max) in [
+            (100f32, 0.95f32, 1.05f32),
+            (1000f32, 0f32, 0.05f32),
+            (10000f32, 0.95f32, 1.05f32),
+        ] {
+            let (peak, matched) = parallel_filters(1000f32, freq);
+            assert!(matched);
+            assert!(peak >= min && peak <= max, "{freq}Hz: {peak}");
+        }
+    }
+
+    #[test]
+    fn osc_into_filt() {
+        series_matches_manual::<f32>(
+            &Context::new(48000f32),
+            57f32,
+            MixOscParams {
+                saw: 1f32,
+                ..Default::default()
+            },
+            FiltParams {
+                cutoff: 80f32,
+                resonance: 0.5f32,
+            },
+        );
+        series_matches_manual::<i16>(
+            &ContextFxP::new_480(),
+            NoteFxP::lit("57"),
+            MixOscParams {
+                saw: ScalarFxP::MAX,
+                ..Default::default()
+            },
+            FiltParams {
+                cutoff: NoteFxP::lit("80"),
+                resonance: ScalarFxP::lit("0.5"),
+            },
+        );
+    }
+}
@@ -0,0 +1,369 @@
+use super::*;
+
+/// Parameters for a [Delay]
+#[derive(Clone, Default, Debug, PartialEq)]
+pub struct DelayParams<T: DspFormatBase> {
+    /// The delay time, in samples.  This is clamped between 1 and the length
+    /// of the delay buffer.
+    pub time: usize,
+    /// The amount of the delayed signal fed back into the delay line
+    pub feedback: T::Scalar,
+    /// The dry/wet mix, from 0 (only the input) to 1 (only the delayed signal)
+    pub mix: T::Scalar,
+}
+
+#[cfg(feature = "float")]
+impl<T: DspFloat> From<&DelayParams<i16>> for DelayParams<T> {
+    fn from(value: &DelayParams<i16>) -> Self {
+        Self {
+            time: value.time,
+            feedback: value.feedback.to_num(),
+            mix: value.mix.to_num(),
+        }
+    }
+}
+
+/// Parameters for a [PingPongDelay]
+#[derive(Clone, Default, Debug, PartialEq)]
+pub struct PingPongDelayParams<T: DspFormatBase> {
+    /// The delay time of the left channel, in samples.  This is clamped
+    /// between 1 and the length of the delay buffer.
+    pub time_l: usize,
+    /// The delay time of the right channel, in samples.  This is clamped
+    /// between 1 and the length of the delay buffer.
+    pub time_r: usize,
+    /// The amount of each channel's delayed signal fed into the other channel
+    pub feedback: T::Scalar,
+    /// The dry/wet mix, from 0 (only the input) to 1 (only the delayed signal)
+    pub mix: T::Scalar,
+}
+
+#[cfg(feature = "float")]
+impl<T: DspFloat> From<&PingPongDelayParams<i16>> for PingPongDelayParams<T> {
+    fn from(value: &PingPongDelayParams<i16>) -> Self {
+        Self {
+            time_l: value.time_l,
+            time_r: value.time_r,
+            feedback: value.feedback.to_num(),
+            mix: value.mix.to_num(),
+        }
+    }
+}
+
+/// Parameters for a [Flanger]
+#[derive(Clone, Default, Debug, PartialEq)]
+pub struct FlangerParams<T: DspFormatBase> {
+    /// The center delay time, in samples.  For a flanger, this is typically
+    /// between 1 and 10 ms.
+    pub time: usize,
+    /// The maximum distance, in samples, that the LFO sweeps the delay time
+    /// away from `time`.  The delay time is clamped between 1 and the length
+    /// of the delay buffer.
+    pub sweep: usize,
+    /// The parameters of the LFO sweeping the delay time.  The LFO depth
+    /// scales `sweep`.
+    pub lfo: LfoParams<T>,
+    /// The amount of the delayed signal fed back into the delay line
+    pub feedback: T::Scalar,
+    /// Invert the polarity of the delayed signal, in both the feedback path
+    /// and the output.  This moves the notches of the comb filter to where
+    /// the peaks would otherwise be (including DC).
+    pub invert: bool,
+    /// The dry/wet mix, from 0 (only the input) to 1 (only the delayed signal)
+    pub mix: T::Scalar,
+}
+
+#[cfg(feature = "float")]
+impl<T: DspFloat> From<&FlangerParams<i16>> for FlangerParams<T> {
+    fn from(value: &FlangerParams<i16>) -> Self {
+        Self {
+            time: value.time,
+            sweep: value.sweep,
+            lfo: (&value.lfo).into(),
+            feedback: value.feedback.to_num(),
+            invert: value.invert,
+            mix: value.mix.to_num(),
+        }
+    }
+}
+
+/// A single statically-sized delay line
+#[derive(Clone)]
+pub(super) struct DelayLine<T: DspFormatBase, const N: usize> {
+    pub(super) buf: [T::Sample; N],
+}
+
+impl<T: DspFormatBase, const N: usize> Default for DelayLine<T, N> {
+    fn default() -> Self {
+        Self {
+            buf: [T::Sample::zero(); N],
+        }
+    }
+}
+
+impl<T: DspFormatBase, const N: usize> DelayLine<T, N> {
+    /// Read the sample written `time` samples before position `idx`
+    pub(super) fn read(&self, idx: usize, time: usize) -> T::Sample {
+        let time = time.clamp(1, N);
+        self.buf[(idx + N - time) % N]
+    }
+}
+
+/// Mix `dry` and `wet` according to `mix` (see [DelayParams::mix])
+pub(super) fn dry_wet<T: DspFormatBase>(
+    dry: T::Sample,
+    wet: T::Sample,
+    mix: T::Scalar,
+) -> T::Sample {
+    dry.scale(T::Scalar::one() - mix).dsp_saturating_add(wet.scale(mix))
+}
+
+/// A mono feedback delay
+///
+/// The buffer is statically sized to `N` samples, which sets the maximum
+/// delay time.
+///
+/// This implements [Device] taking a Sample as input and [DelayParams] as
+/// parameters and outputting a Sample.
+#[derive(Clone, Default)]
+pub struct Delay<T: DspFormat, const N: usize> {
+    line: DelayLine<T, N>,
+    idx: usize,
+}
+
+impl<T: DspFormat, const N: usize> Delay<T, N> {
+    /// Constructor
+    pub fn new() -> Self {
+        Default::default()
+    }
+}
+
+impl<T: DspFormat, const N: usize> Device<T> for Delay<T, N> {
+    type Input = T::Sample;
+    type Params = DelayParams<T>;
+    type Output = T::Sample;
+    fn next(&mut self, _: &T::Context, signal: T::Sample, params: DelayParams<T>) -> T::Sample {
+        let wet = self.line.read(self.idx, params.time);
+        self.line.buf[self.idx] =
+            T::flush_denormal(signal.dsp_saturating_add(wet.scale(params.feedback)));
+        self.idx = (self.idx + 1) % N;
+        dry_wet::<T>(signal, wet, params.mix)
+    }
+}
+
+/// A stereo ping-pong delay
+///
+/// Each channel's input and delayed signal are fed into the delay line for
+/// the opposite channel, so echoes bounce back and forth between the left
+/// and right channels.  An impulse on the left channel will first appear on
+/// the right channel after `time_r` samples, then on the left channel
+/// `time_l` samples after that, and so on.
+///
+/// The buffers are statically sized to `N` samples per channel, which sets
+/// the maximum delay time.
+///
+/// This implements [Device] taking a tuple of (left, right) Samples as input
+/// and [PingPongDelayParams] as parameters, and outputting a tuple of (left,
+/// right) Samples.
+#[derive(Clone, Default)]
+pub struct PingPongDelay<T: DspFormat, const N: usize> {
+    left: DelayLine<T, N>,
+    right: DelayLine<T, N>,
+    idx: usize,
+}
+
+impl<T: DspFormat, const N: usize> PingPongDelay<T, N> {
+    /// Constructor
+    pub fn new() -> Self {
+        Default::default()
+    }
+}
+
+impl<T: DspFormat, const N: usize> Device<T> for PingPongDelay<T, N> {
+    type Input = (T::Sample, T::Sample);
+    type Params = PingPongDelayParams<T>;
+    type Output = (T::Sample, T::Sample);
+    fn next(
+        &mut self,
+        _: &T::Context,
+        (in_l, in_r): (T::Sample, T::Sample),
+        params: PingPongDelayParams<T>,
+    ) -> (T::Sample, T::Sample) {
+        let wet_l = self.left.read(self.idx, params.time_l);
+        let wet_r = self.right.read(self.idx, params.time_r);
+        self.left.buf[self.idx] =
+            T::flush_denormal(in_r.dsp_saturating_add(wet_r.scale(params.feedback)));
+        self.right.buf[self.idx] =
+            T::flush_denormal(in_l.dsp_saturating_add(wet_l.scale(params.feedback)));
+        self.idx = (self.idx + 1) % N;
+        (
+            dry_wet::<T>(in_l, wet_l, params.mix),
+            dry_wet::<T>(in_r, wet_r, params.mix),
+        )
+    }
+}
+
+/// A flanger
+///
+/// This is a short feedback delay with its delay time swept by an internal
+/// [Lfo].  Mixing the delayed signal back in with the input produces a comb
+/// filter with notches at odd multiples of half the sample rate divided by
+/// the delay time (or at multiples of it, including DC, with
+/// [FlangerParams::invert] set), which sweep up and down with the LFO.
+/// Feedback deepens the notches and adds resonant peaks between them.
+///
+/// The delay time is rounded to a whole number of samples.
+///
+/// The buffer is statically sized to `N` samples, which sets the maximum
+/// delay time.
+///
+/// This implements [Device] taking a Sample as input and [FlangerParams] as
+/// parameters and outputting a Sample.
+#[derive(Clone, Default)]
+pub struct Flanger<T: DspFormat, const N: usize> {
+    line: DelayLine<T, N>,
+    lfo: Lfo<T>,
+    idx: usize,
+}
+
+impl<T: DspFormat, const N: usize> Flanger<T, N> {
+    /// Constructor
+    pub fn new() -> Self {
+        Default::default()
+    }
+}
+
+impl<T: DspFormat, const N: usize> Device<T> for Flanger<T, N> {
+    type Input = T::Sample;
+    type Params = FlangerParams<T>;
+    type Output = T::Sample;
+    fn next(&mut self, ctx: &T::Context, signal: T::Sample, params: FlangerParams<T>) -> T::Sample {
+        let lfo = T::sample_to_fixed(self.lfo.next(ctx, false, params.lfo));
+        let offset = (i32::from(lfo.to_bits()) * params.sweep as i32) >> 15;
+        let time = (params.time as i32 + offset).max(1) as usize;
+        let mut wet = self.line.read(self.idx, time);
+        if params.invert {
+            wet = T::Sample::zero() - wet;
+        }
+        self.line.buf[self.idx] =
+            T::flush_denormal(signal.dsp_saturating_add(wet.scale(params.feedback)));
+        self.idx = (self.idx + 1) % N;
+        dry_wet::<T>(signal, wet, params.mix)
+    }
+}
+
+#[cfg(all(test, feature = "float"))]
+mod tests {
+    use super::*;
+
+    /// Returns the (sample index, is left channel) of every nonzero output
+    fn ping_pong_impulse<T: DspFormat>(
+        ctx: &T::Context,
+        params: PingPongDelayParams<T>,
+    ) -> [(usize, bool); 4] {
+        let mut delay = PingPongDelay::<T, 64>::new();
+        let impulse = T::sample_from_fixed(crate::IScalarFxP::lit("0.5"));
+        let mut echoes = [(0, false); 4];
+        let mut found = 0;
+        for i in 0..28 {
+            let input = if i == 0 { impulse } else { T::Sample::zero() };
+            let (l, r) = delay.next(ctx, (input, T::Sample::zero()), params.clone());
+            for (smp, is_left) in [(l, true), (r, false)] {
+                if smp != T::Sample::zero() {
+                    echoes[found] = (i, is_left);
+                    found += 1;
+                }
+            }
+        }
+        assert_eq!(found, 4);
+        echoes
+    }
+
+    #[test]
+    fn float_state_flushes_denormals() {
+        let ctx = Context::new(48000f32);
+        let mut delay = Delay::<f32, 64>::new();
+        let params = DelayParams {
+            time: 37,
+            feedback: 0.5f32,
+            mix: 0.5f32,
+        };
+        crate::devices::tests::decays_cleanly(|x| {
+            delay.next(&ctx, x, params.clone());
+            delay.line.buf
+        });
+    }
+
+    #[test]
+    fn ping_pong_alternates() {
+        // Right after 5 samples, then left 7 samples later, and so on
+        let expected = [(5, false), (12, true), (17, false), (24, true)];
+        let params = PingPongDelayParams::<f32> {
+            time_l: 7,
+            time_r: 5,
+            feedback: 0.5,
+            mix: 1.0,
+        };
+        assert_eq!(ping_pong_impulse(&Context::new(48000f32), params), expected);
+        let params = PingPongDelayParams::<i16> {
+            time_l: 7,
+            time_r: 5,
+            feedback: ScalarFxP::lit("0.5"),
+            mix: ScalarFxP::MAX,
+        };
+        assert_eq!(ping_pong_impulse(&ContextFxP::new_480(), params), expected);
+    }
+
+    /// Returns the peak output level for a sine input at `freq` Hz, once the
+    /// flanger has settled
+    fn flanger_gain<T: DspFormat>(ctx: &T::Context, params: FlangerParams<T>, freq: f32) -> f32 {
+        let mut flanger = Flanger::<T, 64>::new();
+        let mut peak = 0f32;
+        for i in 0..4800 {
+            let x = 0.5 * (core::f32::consts::TAU * freq * i as f32 / 48000f32).sin();
+            let input = T::sample_from_fixed(crate::IScalarFxP::from_num(x));
+            let out = T::sample_to_float(flanger.next(ctx, input, params.clone()));
+            if i >= 3840 {
+                peak = peak.max(out.abs());
+            }
+        }
+        peak / 0.5
+    }
+
+    fn flanger_notches<T: DspFormat>(ctx: &T::Context, depth: T::Scalar, invert: bool) -> [f32; 4] {
+        // A square LFO that never advances holds the delay time at
+        // `time + sweep * depth`
+        let params = FlangerParams::<T> {
+            time: 24,
+            sweep: 16,
+            lfo: LfoParams {
+                depth,
+                opts: LfoOptions::new(LfoWave::Square, true, false),
+                ..Default::default()
+            },
+            feedback: T::Scalar::one().divide_by_two(),
+            invert,
+            mix: T::Scalar::one().divide_by_two(),
+        };
+        [600f32, 1000f32, 1200f32, 2000f32].map(|f| flanger_gain(ctx, params.clone(), f))
+    }
+
+    fn check_flanger<T: DspFormat>(ctx: &T::Context) {
+        // 24 samples: notches at 1000 Hz, peaks at 2000 Hz
+        let [_, g1000, _, g2000] = flanger_notches::<T>(ctx, T::Scalar::zero(), false);
+        assert!(g1000 < 0.2 * g2000, "{g1000} {g2000}");
+        // 40 samples: notches at 600 Hz, peaks at 1200 Hz
+        let [g600, g1000_swept, g1200, _] = flanger_notches::<T>(ctx, T::Scalar::one(), false);
+        assert!(g600 < 0.2 * g1200, "{g600} {g1200}");
+        assert!(g1000_swept > 2.0 * g1000, "{g1000_swept} {g1000}");
+        // Inverted, 24 samples: notches at 2000 Hz, peaks at 1000 Hz
+        let [_, g1000, _, g2000] = flanger_notches::<T>(ctx, T::Scalar::zero(), true);
+        assert!(g2000 < 0.2 * g1000, "{g1000} {g2000}");
+    }
+
+    #[test]
+    fn flanger_notches_sweep() {
+        check_flanger::<f32>(&Context::new(48000f32));
+        check_flanger::<i16>(&ContextFxP::new_480());
+    }
+}
@@ -1,11 +1,15 @@
 use super::*;
+use crate::util::{GateDetect, GateEdge};
+use crate::{IScalarFxP, IScalarFxP32};
+
 pub(crate) mod detail {
     use super::*;
 
+    pub use crate::fixedmath::I32F32 as EnvSignalFxP32;
     pub use crate::fixedmath::I3F29 as EnvSignalFxP;
 
     #[derive(Eq, PartialEq, Clone, Copy, Default)]
-    pub enum EnvMode {
+    pub enum EnvStage {
         #[default]
         Release,
         Attack,
@@ -22,6 +26,13 @@ pub(crate) mod detail {
         }
     }
 
+    impl EnvType<i32> for EnvSignalFxP32 {
+        fn to_scalar(self) -> ScalarFxP32 {
+            ScalarFxP32::saturating_from_num(self)
+        }
+    }
+
+    #[cfg(feature = "float")]
     impl<T: crate::Float + Send> EnvType<T> for T
     where
         T: From<crate::IScalarFxP> + From<crate::NoteFxP>,
@@ -35,7 +46,19 @@ pub(crate) mod detail {
         const SIGNAL_MIN: Self::EnvSignal;
         const SIGNAL_MAX: Self::EnvSignal;
         const ATTACK_THRESHOLD: Self::EnvSignal;
+        const LOOP_THRESHOLD: Self::EnvSignal;
+        const IDLE_THRESHOLD: Self::EnvSignal;
         const ADR_DEFAULT: Self::EnvParam;
+        fn scale_attack(
+            attack: Self::EnvParam,
+            amount: Self::IScalar,
+            velocity: Self::Scalar,
+        ) -> Self::EnvParam;
+        fn scale_decay(
+            decay: Self::EnvParam,
+            amount: Self::IScalar,
+            note: Self::Note,
+        ) -> Self::EnvParam;
         fn calc_env(
             context: &Self::Context,
             setpoint: Self::EnvSignal,
@@ -43,19 +66,104 @@ pub(crate) mod detail {
             last: Self::EnvSignal,
             rise_time: Self::EnvParam,
         ) -> Self::EnvSignal;
+        /// The same as [EnvOps::calc_env], but calibrated so the signal
+        /// covers 98% of the distance to the setpoint in `rise_time` seconds
+        fn calc_env_accurate(
+            context: &Self::Context,
+            setpoint: Self::EnvSignal,
+            setpoint_old: Self::EnvSignal,
+            last: Self::EnvSignal,
+            rise_time: Self::EnvParam,
+        ) -> Self::EnvSignal;
+        /// Precalculated data for [EnvOps::apply_curve], so it need not divide
+        /// by the length of the segment every sample
+        type CurveScale: Copy + Default + Send;
+        /// Calculate the [EnvOps::CurveScale] of a segment moving from `start`
+        /// to `target`
+        fn curve_scale(start: Self::EnvSignal, target: Self::EnvSignal) -> Self::CurveScale;
+        /// Bend the level `signal` of a segment moving from `start` to
+        /// `target` by `curve` (see [EnvParams::attack_curve]), where `scale`
+        /// is the [EnvOps::curve_scale] of the segment
+        fn apply_curve(
+            start: Self::EnvSignal,
+            target: Self::EnvSignal,
+            signal: Self::EnvSignal,
+            scale: Self::CurveScale,
+            curve: Self::IScalar,
+        ) -> Self::EnvSignal;
+    }
+
+    /// The reciprocal of the length of a fixed point envelope segment
+    #[derive(Clone, Copy, Default)]
+    pub struct CurveScaleFxP {
+        /// 1 / |length| == recip * 2^(16 - shift)
+        recip: crate::fixedmath::U1F15,
+        shift: u32,
+        falling: bool,
+    }
+
+    impl CurveScaleFxP {
+        /// Calculate the scale of a segment of length `range` (with 32
+        /// fractional bits), or None if it is too short to bend
+        pub fn new(range: i64) -> Option<Self> {
+            use crate::fixedmath::{one_over_one_plus_refined, U16F16};
+            // Reinterpret the length with 16 fractional bits to scale it up
+            // by 2^16, so it is at least one for any meaningful segment
+            let len = range.unsigned_abs().min(u32::MAX as u64) as u32;
+            let scaled = U16F16::from_bits(len);
+            if scaled <= U16F16::ONE {
+                return None;
+            }
+            let (recip, shift) = one_over_one_plus_refined(scaled - U16F16::ONE);
+            Some(Self {
+                recip,
+                shift,
+                falling: range < 0,
+            })
+        }
+        /// The progress through the segment, from 0 to 1 with 16 fractional
+        /// bits, given the `offset` from its start (with 32 fractional bits)
+        pub fn progress(&self, offset: i64) -> u32 {
+            let offset = if self.falling { -offset } else { offset };
+            if offset <= 0 {
+                return 0;
+            }
+            // offset has 32 fractional bits and recip has 15, so shift the
+            // product back to 16 fractional bits (accounting for the 2^16)
+            let x = (offset as u64 * self.recip.to_bits() as u64) >> (15 + self.shift);
+            x.min(1 << 16) as u32
+        }
     }
 }
 
-use detail::{EnvMode, EnvSignalFxP, EnvType};
+use detail::{CurveScaleFxP, EnvSignalFxP, EnvSignalFxP32, EnvStage, EnvType};
 
-/// Parameters for an [Env].  Note that the time parameters are not
-/// strictly time-accurate - the goal here is to give more of a qualitative feel
-/// for the range of the parameters than allow for precise timing.  If precise
-/// timing is desired, the data displayed to the user can be refined on the UI
-/// side.
-///
-/// TODO:  Determine formula for converting to a precise rise/fall time
-#[derive(Clone)]
+/// The triggering behavior of an [Env]
+#[derive(Eq, PartialEq, Clone, Copy, Default, Debug)]
+pub enum EnvMode {
+    /// A standard ADSR envelope, which holds at the sustain level while the
+    /// gate is high and releases when the gate falls
+    #[default]
+    Adsr,
+    /// A one-shot attack-decay envelope for percussive sounds.  Each rising
+    /// edge of the gate plays the full attack, then decays to zero (using the
+    /// decay time) regardless of when the gate falls.  The sustain and release
+    /// parameters are ignored.
+    OneShot,
+    /// A looping attack-decay envelope, for use as an extra modulation
+    /// source.  While the gate is held, the envelope decays towards zero
+    /// (using the decay time) and restarts the attack as soon as it falls
+    /// below 2%, so it cycles with a period of roughly the attack time plus
+    /// the decay time.  When the gate falls it releases as normal.  The
+    /// sustain parameter is ignored.
+    Loop,
+}
+
+/// Parameters for an [Env].  Note that by default the time parameters are
+/// not strictly time-accurate - the goal here is to give more of a qualitative
+/// feel for the range of the parameters than allow for precise timing.  If
+/// precise timing is desired, see [Env::set_accurate_timing].
+#[derive(Clone, Debug, PartialEq)]
 pub struct EnvParams<T: DspFormatBase> {
     /// Attack time, in seconds (approx)
     pub attack: T::EnvParam,
@@ -65,6 +173,28 @@ pub struct EnvParams<T: DspFormatBase> {
     pub sustain: T::Scalar,
     /// Release time, in seconds (approx)
     pub release: T::EnvParam,
+    /// Velocity to attack time scaling, from -1 to 1.  The attack time is
+    /// multiplied by `1 - vel_attack * velocity`, so positive values shorten
+    /// the attack for harder notes.  Zero (the default) is neutral.
+    pub vel_attack: T::IScalar,
+    /// Key to decay time scaling, from -1 to 1.  The decay time is multiplied
+    /// by `1 - key_decay * (note - 60) / 64`, so positive values shorten the
+    /// decay for higher notes.  Zero (the default) is neutral.
+    pub key_decay: T::IScalar,
+    /// Release velocity to release time scaling, from -1 to 1.  The release
+    /// time is multiplied by `1 - vel_release * release_velocity`, so positive
+    /// values shorten the release for notes released harder.  Zero (the
+    /// default) is neutral.
+    pub vel_release: T::IScalar,
+    /// The curvature of the attack, from -1 to 1.  Zero (the default) leaves
+    /// the segment unchanged.  Positive values bend it to start more slowly
+    /// and finish more quickly (towards an exponential curve), and negative
+    /// values do the opposite (towards a logarithmic curve).
+    pub attack_curve: T::IScalar,
+    /// The curvature of the decay (see [EnvParams::attack_curve])
+    pub decay_curve: T::IScalar,
+    /// The curvature of the release (see [EnvParams::attack_curve])
+    pub release_curve: T::IScalar,
 }
 
 impl<T: DspFormatBase + detail::EnvOps> Default for EnvParams<T> {
@@ -74,10 +204,17 @@ impl<T: DspFormatBase + detail::EnvOps> Default for EnvParams<T> {
             decay: T::ADR_DEFAULT,
             sustain: T::Scalar::one(),
             release: T::ADR_DEFAULT,
+            vel_attack: T::IScalar::zero(),
+            key_decay: T::IScalar::zero(),
+            vel_release: T::IScalar::zero(),
+            attack_curve: T::IScalar::zero(),
+            decay_curve: T::IScalar::zero(),
+            release_curve: T::IScalar::zero(),
         }
     }
 }
 
+#[cfg(feature = "float")]
 impl<T: DspFloat> From<&EnvParams<i16>> for EnvParams<T> {
     fn from(value: &EnvParams<i16>) -> Self {
         EnvParams::<T> {
@@ -85,67 +222,337 @@ impl<T: DspFloat> From<&EnvParams<i16>> for EnvParams<T> {
             decay: value.decay.to_num(),
             sustain: value.sustain.to_num(),
             release: value.release.to_num(),
+            vel_attack: value.vel_attack.to_num(),
+            key_decay: value.key_decay.to_num(),
+            vel_release: value.vel_release.to_num(),
+            attack_curve: value.attack_curve.to_num(),
+            decay_curve: value.decay_curve.to_num(),
+            release_curve: value.release_curve.to_num(),
         }
     }
 }
 
 /// An ADSR Envelope Generator
+///
+/// By default, retriggering the envelope before it has gone idle starts the
+/// attack from the current output level, so there is no discontinuity.  To
+/// instead restart the attack from zero (like some analog envelopes), see
+/// [Env::set_retrigger_from_zero].  For percussive sounds, the envelope may
+/// instead be run as a one-shot attack-decay envelope, or looped to act as an
+/// extra modulation source (see [Env::set_mode]).
+///
+/// Each stage approaches its target exponentially, with a time constant of
+/// roughly a quarter of the stage's time parameter.  For precise timing, see
+/// [Env::set_accurate_timing].  The shape of each stage may be bent further
+/// (see [EnvParams::attack_curve]), in which case each stage starts from the
+/// bent output level of the last.
 #[derive(Clone, Default)]
 pub struct Env<T: DspFormatBase + detail::EnvOps> {
     setpoint: T::EnvSignal,
     signal: T::EnvSignal,
+    /// The level at the start of the current stage
+    start: T::EnvSignal,
+    /// The output level, after applying the stage's curvature
+    output: T::EnvSignal,
+    /// The start and target that `curve_scale` was calculated for
+    curve_segment: (T::EnvSignal, T::EnvSignal),
+    curve_scale: T::CurveScale,
+    stage: EnvStage,
     mode: EnvMode,
+    gate: GateDetect,
+    retrigger_from_zero: bool,
+    accurate_timing: bool,
 }
 
-impl<T: DspFormat> Device<T> for Env<T> {
-    type Input = bool;
-    type Params = EnvParams<T>;
-    type Output = T::Scalar;
-    fn next(&mut self, context: &T::Context, gate: bool, params: EnvParams<T>) -> T::Scalar {
-        let setpoint_old = self.setpoint;
-        if !gate {
-            self.mode = EnvMode::Release;
-            self.setpoint = T::SIGNAL_MIN;
-        } else if self.mode == EnvMode::Release {
-            self.mode = EnvMode::Attack;
-            self.setpoint = T::SIGNAL_MAX;
-        } else if self.mode == EnvMode::Attack && self.signal > T::ATTACK_THRESHOLD {
-            self.mode = EnvMode::Decay;
-        }
-        let rise = match self.mode {
-            EnvMode::Attack => params.attack,
-            EnvMode::Decay => {
+impl<T: DspFormatBase + detail::EnvOps> Env<T> {
+    /// Returns true if the envelope has been released and has decayed below
+    /// -80dB, i.e. it is effectively silent
+    pub fn is_idle(&self) -> bool {
+        let released = match self.mode {
+            EnvMode::Adsr | EnvMode::Loop => self.stage == EnvStage::Release,
+            EnvMode::OneShot => self.stage != EnvStage::Attack,
+        };
+        released && self.output < T::IDLE_THRESHOLD
+    }
+    /// The current output level of the envelope (i.e. the value most
+    /// recently returned from [Device::next])
+    pub fn level(&self) -> T::Scalar {
+        self.output.to_scalar()
+    }
+    /// Set the triggering behavior of the envelope (see [EnvMode])
+    pub fn set_mode(&mut self, mode: EnvMode) {
+        self.mode = mode;
+    }
+    /// Reset the envelope to its initial (idle) state, as if newly
+    /// constructed, keeping its settings (e.g. [Env::set_mode])
+    pub fn reset(&mut self) {
+        *self = Self {
+            mode: self.mode,
+            retrigger_from_zero: self.retrigger_from_zero,
+            accurate_timing: self.accurate_timing,
+            ..Default::default()
+        };
+    }
+    /// If true, reset the output to zero at the start of each attack.  If
+    /// false (the default), the attack ramps up from the current level.
+    pub fn set_retrigger_from_zero(&mut self, from_zero: bool) {
+        self.retrigger_from_zero = from_zero;
+    }
+    /// If true, calibrate the envelope so that each stage covers 98% of the
+    /// distance to its target in exactly the stage's time parameter (e.g. the
+    /// attack finishes after `attack` seconds).  If false (the default), the
+    /// legacy timing is used, which can be off by a few percent depending on
+    /// the format and time.
+    pub fn set_accurate_timing(&mut self, accurate: bool) {
+        self.accurate_timing = accurate;
+    }
+}
+
+impl<T: DspFormatBase + detail::EnvOps> Env<T> {
+    /// This is the same as [Device::next], but applies the velocity to attack,
+    /// key to decay, and release velocity to release scaling (see
+    /// [EnvParams::vel_attack], [EnvParams::key_decay], and
+    /// [EnvParams::vel_release]) for the provided note and velocities.
+    pub fn next_with_note(
+        &mut self,
+        context: &T::Context,
+        gate: bool,
+        note: T::Note,
+        velocity: T::Scalar,
+        release_velocity: T::Scalar,
+        mut params: EnvParams<T>,
+    ) -> T::Scalar {
+        params.attack = T::scale_attack(params.attack, params.vel_attack, velocity);
+        params.decay = T::scale_decay(params.decay, params.key_decay, note);
+        // Release velocity scaling works exactly the same as attack scaling
+        params.release = T::scale_attack(params.release, params.vel_release, release_velocity);
+        self.next(context, gate, params)
+    }
+    /// This is the same as [Device::next], but takes an additional `hold`
+    /// input.  While `hold` is asserted, the envelope is frozen at its current
+    /// level: the output does not change and the gate is ignored.  When `hold`
+    /// is released, the envelope resumes from where it left off (responding
+    /// to any change in the gate at that point).  Unlike sustain, this may
+    /// pause any stage, which is useful for rhythmic gating of modulation.
+    pub fn next_with_hold(
+        &mut self,
+        context: &T::Context,
+        gate: bool,
+        hold: bool,
+        params: EnvParams<T>,
+    ) -> T::Scalar {
+        if hold {
+            return self.output.to_scalar();
+        }
+        let mut setpoint_old = self.setpoint;
+        let stage_old = self.stage;
+        let edge = self.gate.next(gate);
+        match edge {
+            Some(GateEdge::Rising) => {
+                self.stage = EnvStage::Attack;
+                self.setpoint = T::SIGNAL_MAX;
+                if self.retrigger_from_zero {
+                    self.signal = T::SIGNAL_MIN;
+                    setpoint_old = T::SIGNAL_MIN;
+                }
+            }
+            Some(GateEdge::Falling) if self.mode != EnvMode::OneShot => {
+                self.stage = EnvStage::Release;
+                self.setpoint = T::SIGNAL_MIN;
+            }
+            _ => {
+                if self.stage == EnvStage::Attack && self.output > T::ATTACK_THRESHOLD {
+                    self.stage = EnvStage::Decay;
+                } else if self.mode == EnvMode::Loop
+                    && self.stage == EnvStage::Decay
+                    && self.output < T::LOOP_THRESHOLD
+                {
+                    self.stage = EnvStage::Attack;
+                    self.setpoint = T::SIGNAL_MAX;
+                }
+            }
+        }
+        if edge == Some(GateEdge::Rising) || self.stage != stage_old {
+            // Continue from the curved output, so there is no discontinuity
+            if edge != Some(GateEdge::Rising) || !self.retrigger_from_zero {
+                self.signal = self.output;
+            }
+            self.start = self.signal;
+        }
+        let (rise, curve) = match self.stage {
+            EnvStage::Attack => (params.attack, params.attack_curve),
+            EnvStage::Decay => {
                 // Need setpoint control here since the state transition will only
                 // fire once, and we might be modulated
-                self.setpoint = params.sustain.into();
-                params.decay
+                self.setpoint = match self.mode {
+                    EnvMode::Adsr => params.sustain.into(),
+                    EnvMode::OneShot | EnvMode::Loop => T::SIGNAL_MIN,
+                };
+                (params.decay, params.decay_curve)
             }
-            EnvMode::Release => params.release,
+            EnvStage::Release => (params.release, params.release_curve),
+        };
+        self.signal = if self.accurate_timing {
+            T::calc_env_accurate(context, self.setpoint, setpoint_old, self.signal, rise)
+        } else {
+            T::calc_env(context, self.setpoint, setpoint_old, self.signal, rise)
         };
-        self.signal = T::calc_env(context, self.setpoint, setpoint_old, self.signal, rise);
-        self.signal.to_scalar()
+        // The sustain level may be modulated, so check the target as well as
+        // the start of the segment
+        if self.curve_segment != (self.start, self.setpoint) {
+            self.curve_segment = (self.start, self.setpoint);
+            self.curve_scale = T::curve_scale(self.start, self.setpoint);
+        }
+        self.output = T::apply_curve(
+            self.start,
+            self.setpoint,
+            self.signal,
+            self.curve_scale,
+            curve,
+        );
+        self.output.to_scalar()
     }
 }
 
+impl<T: DspFormatBase + detail::EnvOps> Device<T> for Env<T> {
+    type Input = bool;
+    type Params = EnvParams<T>;
+    type Output = T::Scalar;
+    fn next(&mut self, context: &T::Context, gate: bool, params: EnvParams<T>) -> T::Scalar {
+        self.next_with_hold(context, gate, false, params)
+    }
+}
+
+#[cfg(feature = "float")]
 impl<T: DspFloat> detail::EnvOps for T {
     const SIGNAL_MIN: T = T::ZERO;
     const SIGNAL_MAX: T = T::ONE;
     const ATTACK_THRESHOLD: T = T::POINT_NINE_EIGHT;
+    const LOOP_THRESHOLD: T = T::POINT_ZERO_TWO;
+    const IDLE_THRESHOLD: T = T::ENV_IDLE;
     const ADR_DEFAULT: T = T::POINT_ONE;
+    fn scale_attack(attack: T, amount: T, velocity: T) -> T {
+        attack * (T::ONE - amount * velocity)
+    }
+    fn scale_decay(decay: T, amount: T, note: T) -> T {
+        let key = (note - T::from_u16(60)) / T::from_u16(64);
+        let key = key.max(T::ONE.neg()).min(T::ONE);
+        decay * (T::ONE - amount * key)
+    }
     fn calc_env(context: &Context<T>, setpoint: T, setpoint_old: T, last: T, rise_time: T) -> T {
         // This is equivalen to saying rise time = 4 time constants...
         let k = rise_time * (context.sample_rate / T::TWO) + T::ONE;
         let pro = setpoint_old + setpoint - last - last;
         let delta = pro / k;
-        last + delta
+        (last + delta).flush_denormal()
+    }
+    fn calc_env_accurate(
+        context: &Context<T>,
+        setpoint: T,
+        setpoint_old: T,
+        last: T,
+        rise_time: T,
+    ) -> T {
+        // calc_env covers 98% of the distance in ln(50)/4 of the rise time
+        let scale = <T as num_traits::NumCast>::from(FRAC_4_LN_50).unwrap_or(T::ONE);
+        Self::calc_env(context, setpoint, setpoint_old, last, rise_time * scale)
+    }
+    type CurveScale = T;
+    fn curve_scale(start: T, target: T) -> T {
+        if start == target {
+            T::ZERO
+        } else {
+            T::ONE / (target - start)
+        }
+    }
+    fn apply_curve(start: T, target: T, signal: T, scale: T, curve: T) -> T {
+        if curve == T::ZERO || scale == T::ZERO {
+            return signal;
+        }
+        let progress = ((signal - start) * scale).max(T::ZERO).min(T::ONE);
+        let bent = bend_curve_float(progress.as_f32(), curve.as_f32());
+        start + (target - start) * <T as num_traits::NumCast>::from(bent).unwrap_or(progress)
     }
 }
 
+/// The ratio between the time constant of the legacy envelope timing and the
+/// time constant required to cover 98% of the distance in the rise time,
+/// i.e. 4/ln(50)
+#[cfg(feature = "float")]
+const FRAC_4_LN_50: f32 = 1.022_489;
+
+/// The curve used for a curvature of 1, (e^4x - 1) / (e^4 - 1), sampled at
+/// 17 evenly spaced points from 0 to 1, with 16 fractional bits
+const CURVE_TABLE: [u32; 17] = [
+    0, 347, 793, 1366, 2101, 3045, 4257, 5814, 7812, 10378, 13673, 17904, 23336, 30312, 39268,
+    50769, 65536,
+];
+
+/// Bend `x` (from 0 to 1, with 16 fractional bits) by `curve` (from -1 to 1,
+/// with 15 fractional bits), interpolating between `x` itself at a curvature
+/// of 0 and [CURVE_TABLE] (or its reflection, for negative curvature)
+fn bend_curve(x: u32, curve: i32) -> u32 {
+    let lookup = |x: u32| {
+        let idx = (x >> 12) as usize;
+        if idx >= 16 {
+            return CURVE_TABLE[16];
+        }
+        let (lo, hi) = (CURVE_TABLE[idx], CURVE_TABLE[idx + 1]);
+        lo + (((hi - lo) * (x & 0xFFF)) >> 12)
+    };
+    let bent = if curve >= 0 {
+        lookup(x)
+    } else {
+        (1 << 16) - lookup((1 << 16) - x)
+    };
+    let delta = (bent as i64 - x as i64) * curve.unsigned_abs() as i64;
+    (x as i64 + (delta >> 15)) as u32
+}
+
+/// The floating point version of [bend_curve]
+#[cfg(feature = "float")]
+fn bend_curve_float(x: f32, curve: f32) -> f32 {
+    let lookup = |x: f32| {
+        let pos = x * 16f32;
+        let idx = core::cmp::min(pos as usize, 15);
+        let (lo, hi) = (CURVE_TABLE[idx] as f32, CURVE_TABLE[idx + 1] as f32);
+        (lo + (hi - lo) * (pos - idx as f32)) / 65536f32
+    };
+    let bent = if curve >= 0f32 {
+        lookup(x)
+    } else {
+        1f32 - lookup(1f32 - x)
+    };
+    x + curve.abs() * (bent - x)
+}
+
+/// Multiply `time` by `1 - prod`, where `prod` has 15 fractional bits and
+/// lies in [-1, 1]
+fn scale_env_time(time: EnvParamFxP, prod: i32) -> EnvParamFxP {
+    let factor = ((1i32 << 15) - prod) as u32;
+    let scaled = (time.to_bits() as u32 * factor) >> 15;
+    EnvParamFxP::from_bits(core::cmp::min(scaled, u16::MAX as u32) as u16)
+}
+
 impl detail::EnvOps for i16 {
     const ATTACK_THRESHOLD: EnvSignalFxP = EnvSignalFxP::lit("0.98");
+    const LOOP_THRESHOLD: EnvSignalFxP = EnvSignalFxP::lit("0.02");
+    const IDLE_THRESHOLD: EnvSignalFxP = EnvSignalFxP::lit("0.0001");
     const SIGNAL_MAX: EnvSignalFxP = EnvSignalFxP::lit("0x0.FFFC");
     const SIGNAL_MIN: EnvSignalFxP = EnvSignalFxP::lit("0x0.0004");
     const ADR_DEFAULT: EnvParamFxP = EnvParamFxP::lit("0.1");
+    fn scale_attack(attack: EnvParamFxP, amount: IScalarFxP, velocity: ScalarFxP) -> EnvParamFxP {
+        // amount * velocity, with 15 fractional bits:
+        let prod = (amount.to_bits() as i32 * velocity.to_bits() as i32) >> 16;
+        scale_env_time(attack, prod)
+    }
+    fn scale_decay(decay: EnvParamFxP, amount: IScalarFxP, note: NoteFxP) -> EnvParamFxP {
+        // (note - 60) / 64 with 15 fractional bits has the same bits as
+        // (note - 60) with 9 fractional bits
+        let key = (note.to_bits() as i32 - (60 << 9)).clamp(-(1 << 15), 1 << 15);
+        let prod = (amount.to_bits() as i32 * key) >> 15;
+        scale_env_time(decay, prod)
+    }
     fn calc_env(
         context: &ContextFxP,
         setpoint: EnvSignalFxP,
@@ -153,14 +560,582 @@ impl detail::EnvOps for i16 {
         last: EnvSignalFxP,
         rise_time: EnvParamFxP,
     ) -> EnvSignalFxP {
-        use crate::fixedmath::{one_over_one_plus, I2F14, U16F0};
+        calc_env_fxp(context, setpoint, setpoint_old, last, rise_time, false)
+    }
+    fn calc_env_accurate(
+        context: &ContextFxP,
+        setpoint: EnvSignalFxP,
+        setpoint_old: EnvSignalFxP,
+        last: EnvSignalFxP,
+        rise_time: EnvParamFxP,
+    ) -> EnvSignalFxP {
+        let rise_time = scale_env_time(rise_time, FRAC_4_LN_50_MINUS_1_FXP);
+        calc_env_fxp(context, setpoint, setpoint_old, last, rise_time, true)
+    }
+    type CurveScale = Option<CurveScaleFxP>;
+    fn curve_scale(start: EnvSignalFxP, target: EnvSignalFxP) -> Option<CurveScaleFxP> {
+        CurveScaleFxP::new(((target - start).to_bits() as i64) << 3)
+    }
+    fn apply_curve(
+        start: EnvSignalFxP,
+        target: EnvSignalFxP,
+        signal: EnvSignalFxP,
+        scale: Option<CurveScaleFxP>,
+        curve: IScalarFxP,
+    ) -> EnvSignalFxP {
+        let scale = match scale {
+            Some(scale) if curve != IScalarFxP::ZERO => scale,
+            _ => return signal,
+        };
+        let range = target - start;
+        let progress = scale.progress(((signal - start).to_bits() as i64) << 3);
+        let bent = bend_curve(progress, curve.to_bits() as i32);
+        start + EnvSignalFxP::from_bits(((range.to_bits() as i64 * bent as i64) >> 16) as i32)
+    }
+}
+
+/// -(FRAC_4_LN_50 - 1), with 15 fractional bits (see [scale_env_time])
+const FRAC_4_LN_50_MINUS_1_FXP: i32 = -737;
+
+/// The fixed point envelope calculation.  If `accurate`, the gain is calculated
+/// more precisely and each step is rounded rather than truncated (which
+/// otherwise slows down rising and speeds up falling envelopes)
+fn calc_env_fxp(
+    context: &ContextFxP,
+    setpoint: EnvSignalFxP,
+    setpoint_old: EnvSignalFxP,
+    last: EnvSignalFxP,
+    rise_time: EnvParamFxP,
+    accurate: bool,
+) -> EnvSignalFxP {
+    use crate::fixedmath::{one_over_one_plus, one_over_one_plus_refined, I2F14, U16F0};
+    // This is equivalent to saying rise time = 4 time constants...
+    let sr = U16F0::from_bits(context.sample_rate.value() >> 1);
+    let k = rise_time.wide_mul(sr);
+    let (gain, shift) = if accurate {
+        one_over_one_plus_refined(k)
+    } else {
+        one_over_one_plus(k)
+    };
+    // Need saturating here to avoid panic if A == 0 && S == 0:
+    let pro = I2F14::saturating_from_num(setpoint_old + setpoint - last.unwrapped_shl(1));
+    let mut delta = pro.wide_mul_unsigned(gain);
+    if accurate && shift > 0 {
+        delta += EnvSignalFxP::DELTA.unwrapped_shl(shift - 1);
+    }
+    last + delta.unwrapped_shr(shift)
+}
+
+/// Multiply `time` by `1 - prod`, where `prod` has 31 fractional bits and
+/// lies in [-1, 1]
+fn scale_env_time32(time: EnvParamFxP32, prod: i64) -> EnvParamFxP32 {
+    let factor = ((1i64 << 31) - prod) as u64;
+    let scaled = (time.to_bits() as u64 * factor) >> 31;
+    EnvParamFxP32::from_bits(core::cmp::min(scaled, u32::MAX as u64) as u32)
+}
+
+impl detail::EnvOps for i32 {
+    const ATTACK_THRESHOLD: EnvSignalFxP32 = EnvSignalFxP32::lit("0.98");
+    const LOOP_THRESHOLD: EnvSignalFxP32 = EnvSignalFxP32::lit("0.02");
+    const IDLE_THRESHOLD: EnvSignalFxP32 = EnvSignalFxP32::lit("0.0001");
+    const SIGNAL_MAX: EnvSignalFxP32 = EnvSignalFxP32::lit("0x0.FFFFFFFC");
+    const SIGNAL_MIN: EnvSignalFxP32 = EnvSignalFxP32::lit("0x0.00000004");
+    const ADR_DEFAULT: EnvParamFxP32 = EnvParamFxP32::lit("0.1");
+    fn scale_attack(
+        attack: EnvParamFxP32,
+        amount: IScalarFxP32,
+        velocity: ScalarFxP32,
+    ) -> EnvParamFxP32 {
+        // amount * velocity, with 31 fractional bits:
+        let prod = (amount.to_bits() as i64 * velocity.to_bits() as i64) >> 32;
+        scale_env_time32(attack, prod)
+    }
+    fn scale_decay(decay: EnvParamFxP32, amount: IScalarFxP32, note: NoteFxP32) -> EnvParamFxP32 {
+        // (note - 60) / 64 with 31 fractional bits has the same bits as
+        // (note - 60) with 25 fractional bits
+        let key = (note.to_bits() as i64 - (60 << 25)).clamp(-(1 << 31), 1 << 31);
+        let prod = (amount.to_bits() as i64 * key) >> 31;
+        scale_env_time32(decay, prod)
+    }
+    fn calc_env(
+        context: &ContextFxP,
+        setpoint: EnvSignalFxP32,
+        setpoint_old: EnvSignalFxP32,
+        last: EnvSignalFxP32,
+        rise_time: EnvParamFxP32,
+    ) -> EnvSignalFxP32 {
+        use crate::fixedmath::{one_over_one_plus_refined32, U32F0};
         // This is equivalent to saying rise time = 4 time constants...
-        let sr = U16F0::from_bits(context.sample_rate.value() >> 1);
-        let k = rise_time.wide_mul(sr);
-        let (gain, shift) = one_over_one_plus(k);
-        // Need saturating here to avoid panic if A == 0 && S == 0:
-        let pro = I2F14::saturating_from_num(setpoint_old + setpoint - last.unwrapped_shl(1));
-        let delta = pro.wide_mul_unsigned(gain).unwrapped_shr(shift);
-        last + delta
+        let sr = U32F0::from_num(context.sample_rate.value() >> 1);
+        let (gain, shift) = one_over_one_plus_refined32(rise_time.wide_mul(sr));
+        let pro = setpoint_old + setpoint - last.unwrapped_shl(1);
+        last + (pro * EnvSignalFxP32::from_num(gain)).unwrapped_shr(shift)
+    }
+    fn calc_env_accurate(
+        context: &ContextFxP,
+        setpoint: EnvSignalFxP32,
+        setpoint_old: EnvSignalFxP32,
+        last: EnvSignalFxP32,
+        rise_time: EnvParamFxP32,
+    ) -> EnvSignalFxP32 {
+        // -(FRAC_4_LN_50 - 1), with 31 fractional bits
+        const SCALE: i64 = -48_294_490;
+        let rise_time = scale_env_time32(rise_time, SCALE);
+        Self::calc_env(context, setpoint, setpoint_old, last, rise_time)
+    }
+    type CurveScale = Option<CurveScaleFxP>;
+    fn curve_scale(start: EnvSignalFxP32, target: EnvSignalFxP32) -> Option<CurveScaleFxP> {
+        CurveScaleFxP::new((target - start).to_bits())
+    }
+    fn apply_curve(
+        start: EnvSignalFxP32,
+        target: EnvSignalFxP32,
+        signal: EnvSignalFxP32,
+        scale: Option<CurveScaleFxP>,
+        curve: IScalarFxP32,
+    ) -> EnvSignalFxP32 {
+        let scale = match scale {
+            Some(scale) if curve != IScalarFxP32::ZERO => scale,
+            _ => return signal,
+        };
+        let range = target - start;
+        let progress = scale.progress((signal - start).to_bits());
+        let bent = bend_curve(progress, curve.to_bits() >> 16);
+        start + EnvSignalFxP32::from_bits((range.to_bits() * bent as i64) >> 16)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The number of samples for the envelope to exceed `threshold`
+    #[cfg(feature = "float")]
+    fn attack_samples<T: DspFormatBase + detail::EnvOps>(
+        ctx: &T::Context,
+        velocity: T::Scalar,
+        params: EnvParams<T>,
+        threshold: T::Scalar,
+    ) -> usize {
+        let mut env = Env::<T>::default();
+        let note = T::default_note();
+        (0..48000)
+            .position(|_| {
+                env.next_with_note(ctx, true, note, velocity, T::Scalar::zero(), params.clone())
+                    > threshold
+            })
+            .unwrap()
+    }
+
+    /// The number of samples for the envelope to fall below `threshold` after
+    /// being released with `release_velocity` from the sustain level
+    #[cfg(feature = "float")]
+    fn release_samples<T: DspFormatBase + detail::EnvOps>(
+        ctx: &T::Context,
+        release_velocity: T::Scalar,
+        params: EnvParams<T>,
+        threshold: T::Scalar,
+    ) -> usize {
+        let mut env = Env::<T>::default();
+        let note = T::default_note();
+        let vel = T::Scalar::one();
+        for _ in 0..24000 {
+            env.next_with_note(ctx, true, note, vel, release_velocity, params.clone());
+        }
+        (0..48000)
+            .position(|_| {
+                env.next_with_note(ctx, false, note, vel, release_velocity, params.clone())
+                    < threshold
+            })
+            .unwrap()
+    }
+
+    #[cfg(feature = "float")]
+    #[test]
+    fn release_velocity_shortens_release() {
+        let ctx = Context::new(48000f32);
+        let params = EnvParams::<f32> {
+            vel_release: 0.5,
+            ..Default::default()
+        };
+        let soft = release_samples(&ctx, 0f32, params.clone(), 0.1) as f32;
+        let hard = release_samples(&ctx, 1f32, params.clone(), 0.1) as f32;
+        assert!((hard / soft - 0.5).abs() < 0.02);
+
+        let ctx = ContextFxP::new_480();
+        let params = EnvParams::<i16> {
+            vel_release: IScalarFxP::lit("0.5"),
+            ..Default::default()
+        };
+        let thresh = ScalarFxP::lit("0.1");
+        let soft = release_samples(&ctx, ScalarFxP::ZERO, params.clone(), thresh) as f32;
+        let hard = release_samples(&ctx, ScalarFxP::MAX, params.clone(), thresh) as f32;
+        assert!((hard / soft - 0.5).abs() < 0.02);
+    }
+
+    /// Retrigger an envelope partway through its decay, returning the level
+    /// it was retriggered from, the level on the first sample after the
+    /// retrigger, and the largest sample-to-sample change over the attack
+    #[cfg(feature = "float")]
+    fn retrigger<T: DspFormatBase + detail::EnvOps>(
+        ctx: &T::Context,
+        params: EnvParams<T>,
+        from_zero: bool,
+        to_float: impl Fn(T::Scalar) -> f32,
+    ) -> (f32, f32, f32) {
+        let mut env = Env::<T>::default();
+        env.set_retrigger_from_zero(from_zero);
+        // 0.1s attack, then 0.1s into the 0.5s decay
+        for _ in 0..9600 {
+            env.next(ctx, true, params.clone());
+        }
+        env.next(ctx, false, params.clone());
+        let held = to_float(env.level());
+        let first = to_float(env.next(ctx, true, params.clone()));
+        let mut last = first;
+        let max_delta = (0..4800).fold(0f32, |acc, _| {
+            let level = to_float(env.next(ctx, true, params.clone()));
+            let delta = (level - last).abs();
+            last = level;
+            acc.max(delta)
+        });
+        (held, first, max_delta)
+    }
+
+    #[cfg(feature = "float")]
+    #[test]
+    fn retrigger_from_current_level() {
+        let ctx = Context::new(48000f32);
+        let params = EnvParams::<f32> {
+            decay: 0.5,
+            sustain: 0.2,
+            ..Default::default()
+        };
+        let (held, first, max_delta) = retrigger(&ctx, params.clone(), false, |x| x);
+        assert!(held > 0.3 && held < 0.9);
+        assert!((first - held).abs() < 0.01);
+        assert!(max_delta < 0.01);
+        let (_, first, _) = retrigger(&ctx, params, true, |x| x);
+        assert!(first < 0.01);
+
+        let ctx = ContextFxP::new_480();
+        let params = EnvParams::<i16> {
+            decay: EnvParamFxP::lit("0.5"),
+            sustain: ScalarFxP::lit("0.2"),
+            ..Default::default()
+        };
+        let (held, first, max_delta) = retrigger(&ctx, params.clone(), false, |x| x.to_num());
+        assert!(held > 0.3 && held < 0.9);
+        assert!((first - held).abs() < 0.01);
+        assert!(max_delta < 0.01);
+        let (_, first, _) = retrigger(&ctx, params, true, |x| x.to_num());
+        assert!(first < 0.01);
+    }
+
+    /// The attack time, in seconds, of an envelope with accurate timing
+    #[cfg(feature = "float")]
+    fn accurate_attack<T: DspFormatBase + detail::EnvOps>(
+        ctx: &T::Context,
+        attack: T::EnvParam,
+        threshold: T::Scalar,
+    ) -> f32 {
+        let mut env = Env::<T>::default();
+        env.set_accurate_timing(true);
+        let params = EnvParams {
+            attack,
+            ..Default::default()
+        };
+        let samples = (0..480000).position(|_| env.next(ctx, true, params.clone()) > threshold);
+        samples.unwrap() as f32 / 48000f32
+    }
+
+    #[cfg(feature = "float")]
+    #[test]
+    fn accurate_attack_time() {
+        for secs in [0.01f32, 0.1f32, 0.5f32, 2f32, 7.5f32] {
+            let ctx = Context::new(48000f32);
+            let measured = accurate_attack::<f32>(&ctx, secs, 0.98);
+            assert!(
+                (measured / secs - 1f32).abs() < 0.02,
+                "f32 {secs}: {measured}"
+            );
+            let ctx = ContextFxP::new_480();
+            let attack = EnvParamFxP::from_num(secs);
+            let measured = accurate_attack::<i16>(&ctx, attack, ScalarFxP::lit("0.98"));
+            assert!(
+                (measured / secs - 1f32).abs() < 0.02,
+                "i16 {secs}: {measured}"
+            );
+            let attack = EnvParamFxP32::from_num(secs);
+            let measured = accurate_attack::<i32>(&ctx, attack, ScalarFxP32::lit("0.98"));
+            assert!(
+                (measured / secs - 1f32).abs() < 0.02,
+                "i32 {secs}: {measured}"
+            );
+        }
+    }
+
+    #[cfg(feature = "float")]
+    #[test]
+    fn velocity_shortens_attack() {
+        let ctx = Context::new(48000f32);
+        let params = EnvParams::<f32> {
+            vel_attack: 0.5,
+            ..Default::default()
+        };
+        let soft = attack_samples(&ctx, 0f32, params.clone(), 0.9) as f32;
+        let hard = attack_samples(&ctx, 1f32, params.clone(), 0.9) as f32;
+        assert!((hard / soft - 0.5).abs() < 0.02);
+
+        let ctx = ContextFxP::new_480();
+        let params = EnvParams::<i16> {
+            vel_attack: IScalarFxP::lit("0.5"),
+            ..Default::default()
+        };
+        let thresh = ScalarFxP::lit("0.9");
+        let soft = attack_samples(&ctx, ScalarFxP::ZERO, params.clone(), thresh) as f32;
+        let hard = attack_samples(&ctx, ScalarFxP::MAX, params.clone(), thresh) as f32;
+        assert!((hard / soft - 0.5).abs() < 0.02);
+
+        let params = EnvParams::<i32> {
+            vel_attack: IScalarFxP32::lit("0.5"),
+            ..Default::default()
+        };
+        let thresh = ScalarFxP32::lit("0.9");
+        let soft = attack_samples(&ctx, ScalarFxP32::ZERO, params.clone(), thresh) as f32;
+        let hard = attack_samples(&ctx, ScalarFxP32::MAX, params.clone(), thresh) as f32;
+        assert!((hard / soft - 0.5).abs() < 0.02);
+    }
+
+    /// Run a one-shot envelope with the gate released 1ms into the attack,
+    /// returning the peak level and the number of samples until it is idle
+    #[cfg(feature = "float")]
+    fn one_shot<T: DspFormatBase + detail::EnvOps>(
+        ctx: &T::Context,
+        params: EnvParams<T>,
+    ) -> (T::Scalar, usize) {
+        let mut env = Env::<T>::default();
+        env.set_mode(EnvMode::OneShot);
+        let mut peak = T::Scalar::zero();
+        for i in 0..48000 {
+            let level = env.next(ctx, i < 48, params.clone());
+            if level > peak {
+                peak = level;
+            }
+            if env.is_idle() {
+                return (peak, i);
+            }
+        }
+        panic!("one-shot envelope never went idle");
+    }
+
+    #[cfg(feature = "float")]
+    #[test]
+    fn one_shot_ignores_gate_release() {
+        let ctx = Context::new(48000f32);
+        let params = EnvParams::<f32> {
+            attack: 0.01,
+            decay: 0.05,
+            sustain: 1.0,
+            release: 5.0,
+            ..Default::default()
+        };
+        let (peak, idle) = one_shot(&ctx, params);
+        assert!(peak > 0.97);
+        // Decays well before the (long) release time
+        assert!(idle > 480 && idle < 9600, "f32: {idle}");
+
+        let ctx = ContextFxP::new_480();
+        let params = EnvParams::<i16> {
+            attack: EnvParamFxP::lit("0.01"),
+            decay: EnvParamFxP::lit("0.05"),
+            sustain: ScalarFxP::MAX,
+            release: EnvParamFxP::lit("5"),
+            ..Default::default()
+        };
+        let (peak, idle) = one_shot(&ctx, params);
+        assert!(peak > ScalarFxP::lit("0.97"), "{peak}");
+        assert!(idle > 480 && idle < 9600, "i16: {idle}");
+    }
+
+    /// Run a looping envelope with the gate held, returning the sample
+    /// indices at which the first four attacks after the initial one start
+    #[cfg(feature = "float")]
+    fn loop_starts<T: DspFormatBase + detail::EnvOps>(
+        ctx: &T::Context,
+        params: EnvParams<T>,
+    ) -> [usize; 4] {
+        let mut env = Env::<T>::default();
+        env.set_mode(EnvMode::Loop);
+        let mut starts = [0; 4];
+        let mut found = 0;
+        let mut last = T::Scalar::zero();
+        let mut falling = false;
+        for i in 0..48000 {
+            let level = env.next(ctx, true, params.clone());
+            if falling && level > last {
+                starts[found] = i;
+                found += 1;
+                if found == starts.len() {
+                    assert!(!env.is_idle());
+                    return starts;
+                }
+            }
+            falling = level < last;
+            last = level;
+        }
+        panic!("looping envelope did not retrigger");
+    }
+
+    #[cfg(feature = "float")]
+    #[test]
+    fn loop_is_periodic() {
+        let ctx = Context::new(48000f32);
+        let params = EnvParams::<f32> {
+            attack: 0.01,
+            decay: 0.02,
+            sustain: 1.0,
+            ..Default::default()
+        };
+        let starts = loop_starts(&ctx, params);
+        let period = starts[1] - starts[0];
+        assert!(period > 480 && period < 4800, "f32: {period}");
+        assert!(
+            starts.windows(2).all(|w| w[1] - w[0] == period),
+            "f32: {starts:?}"
+        );
+
+        let ctx = ContextFxP::new_480();
+        let params = EnvParams::<i16> {
+            attack: EnvParamFxP::lit("0.01"),
+            decay: EnvParamFxP::lit("0.02"),
+            sustain: ScalarFxP::MAX,
+            ..Default::default()
+        };
+        let starts = loop_starts(&ctx, params);
+        let period = starts[1] - starts[0];
+        assert!(period > 480 && period < 4800, "i16: {period}");
+        assert!(
+            starts.windows(2).all(|w| w[1] - w[0] == period),
+            "i16: {starts:?}"
+        );
+    }
+
+    /// The level of an attack with the given curvature after the unshaped
+    /// attack reaches half scale, and the peak level it reaches
+    #[cfg(feature = "float")]
+    fn curved_attack<T: DspFormatBase + detail::EnvOps>(
+        ctx: &T::Context,
+        curve: T::IScalar,
+        to_float: impl Fn(T::Scalar) -> f32,
+    ) -> (f32, f32) {
+        let mut env = Env::<T>::default();
+        let mut unshaped = Env::<T>::default();
+        let params = EnvParams::<T> {
+            attack_curve: curve,
+            ..Default::default()
+        };
+        let mut half = None;
+        let mut peak = 0f32;
+        for _ in 0..9600 {
+            let level = to_float(env.next(ctx, true, params.clone()));
+            let reference = to_float(unshaped.next(ctx, true, Default::default()));
+            if half.is_none() && reference >= 0.5 {
+                half = Some(level);
+            }
+            peak = peak.max(level);
+        }
+        (half.unwrap(), peak)
+    }
+
+    #[cfg(feature = "float")]
+    #[test]
+    fn curvature_bends_attack() {
+        assert_eq!(bend_curve(0x8000, 0), 0x8000);
+        for curve in [-0x8000, -0x4000, 0x4000, 0x7FFF] {
+            assert_eq!(bend_curve(0, curve), 0);
+            assert_eq!(bend_curve(0x10000, curve), 0x10000);
+            let float = bend_curve_float(0.3, curve as f32 / 32768f32);
+            assert!((bend_curve(0x4CCD, curve) as f32 / 65536f32 - float).abs() < 0.001);
+        }
+        let ctx = Context::new(48000f32);
+        let (linear, _) = curved_attack::<f32>(&ctx, 0f32, |x| x);
+        assert!((linear - 0.5).abs() < 0.01, "{linear}");
+        let (exp, exp_peak) = curved_attack::<f32>(&ctx, 1f32, |x| x);
+        let (log, log_peak) = curved_attack::<f32>(&ctx, -1f32, |x| x);
+        assert!(exp < 0.25 && log > 0.75, "{exp} {log}");
+        assert!(exp_peak > 0.98 && log_peak > 0.98);
+
+        let ctx = ContextFxP::new_480();
+        let (linear, _) = curved_attack::<i16>(&ctx, IScalarFxP::ZERO, |x| x.to_num());
+        assert!((linear - 0.5).abs() < 0.01, "{linear}");
+        let (exp, exp_peak) = curved_attack::<i16>(&ctx, IScalarFxP::MAX, |x| x.to_num());
+        let (log, log_peak) = curved_attack::<i16>(&ctx, IScalarFxP::MIN, |x| x.to_num());
+        assert!(exp < 0.25 && log > 0.75, "{exp} {log}");
+        assert!(exp_peak > 0.98 && log_peak > 0.98);
+    }
+
+    #[cfg(feature = "float")]
+    #[test]
+    fn fixed_matches_float() {
+        use crate::devices::tests::assert_formats_agree;
+        let params = EnvParams::<i16> {
+            attack: EnvParamFxP::lit("0.05"),
+            decay: EnvParamFxP::lit("0.2"),
+            sustain: ScalarFxP::lit("0.5"),
+            release: EnvParamFxP::lit("0.1"),
+            ..Default::default()
+        };
+        // The fixed and floating point envelopes approximate the exponential
+        // differently, so only check that they agree to about 0.2%
+        assert_formats_agree!(Env, 128, 9600, |i| (i < 4800, params.clone()));
+    }
+
+    /// Pause an envelope partway through its attack, checking that it holds
+    /// its level and then continues exactly as an envelope that was never
+    /// paused
+    #[cfg(feature = "float")]
+    fn hold_and_resume<T: DspFormatBase + detail::EnvOps>(ctx: &T::Context) {
+        let params = EnvParams::<T>::default();
+        let mut env = Env::<T>::default();
+        let mut reference = Env::<T>::default();
+        for _ in 0..1000 {
+            assert_eq!(
+                env.next(ctx, true, params.clone()),
+                reference.next(ctx, true, params.clone())
+            );
+        }
+        let held = env.level();
+        for _ in 0..500 {
+            assert_eq!(env.next_with_hold(ctx, true, true, params.clone()), held);
+        }
+        for _ in 0..2000 {
+            assert_eq!(
+                env.next_with_hold(ctx, true, false, params.clone()),
+                reference.next(ctx, true, params.clone())
+            );
+        }
+        assert!(env.level() > held);
+    }
+
+    #[cfg(feature = "float")]
+    #[test]
+    fn hold_freezes_level() {
+        hold_and_resume::<f32>(&Context::new(48000f32));
+        hold_and_resume::<i16>(&ContextFxP::new_480());
+    }
+
+    #[test]
+    fn curve_scale_matches_division() {
+        assert!(CurveScaleFxP::new(0).is_none());
+        assert!(CurveScaleFxP::new(1 << 16).is_none());
+        for range in [0.001f64, 0.02, 0.3, 0.5, 0.99, -0.001, -0.4, -0.98] {
+            let scale = CurveScaleFxP::new((range * 2f64.powi(32)) as i64).unwrap();
+            for frac in [-0.5f64, 0., 0.01, 0.25, 0.5, 0.75, 0.999, 1., 1.5] {
+                let offset = (range * frac * 2f64.powi(32)) as i64;
+                let expected = (frac.clamp(0., 1.) * 65536.) as i64;
+                assert!((scale.progress(offset) as i64 - expected).abs() <= 4);
+            }
+        }
     }
 }
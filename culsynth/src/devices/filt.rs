@@ -15,13 +15,18 @@ pub(crate) mod detail {
             low_z: &mut Self::FiltFeedback,
             band_z: &mut Self::FiltFeedback,
         ) -> filt::FiltOutput<Self>;
+        fn flush_denormals(z: &mut Self::FiltFeedback);
     }
 }
 
 /// Parameters for a [Filt]
-#[derive(Clone, Default)]
+#[derive(Clone, Default, Debug, PartialEq)]
 pub struct FiltParams<T: DspFormatBase> {
     /// Cutoff frequency, as a MIDI note number
+    ///
+    /// The cutoff is clamped to a maximum of about 0.32 times the sample rate
+    /// (and, for floating point types, to the range of valid MIDI notes) to
+    /// keep the filter stable as the cutoff approaches Nyquist.
     pub cutoff: T::Note,
     /// Resonance, as a value between 0 and 1
     ///
@@ -31,6 +36,7 @@ pub struct FiltParams<T: DspFormatBase> {
     pub resonance: T::Scalar,
 }
 
+#[cfg(feature = "float")]
 impl<T: DspFloat> From<&FiltParams<i16>> for FiltParams<T> {
     fn from(value: &FiltParams<i16>) -> Self {
         FiltParams::<T> {
@@ -41,7 +47,7 @@ impl<T: DspFloat> From<&FiltParams<i16>> for FiltParams<T> {
 }
 
 /// Output of a [Filt]
-#[derive(Clone, Default)]
+#[derive(Clone, Default, Debug, PartialEq)]
 pub struct FiltOutput<T: DspFormatBase> {
     /// The low-pass signal
     pub low: T::Sample,
@@ -61,19 +67,33 @@ pub struct FiltOutput<T: DspFormatBase> {
 /// of [FiltParams], and outputs a [FiltOutput], which consists of Samples for
 /// the low, band, and high pass signals.
 #[derive(Default, Clone)]
-pub struct Filt<T: DspFormat> {
+pub struct Filt<T: DspFormatBase + detail::FiltOps> {
     low_z: T::FiltFeedback,
     band_z: T::FiltFeedback,
 }
 
-impl<T: DspFormat> Filt<T> {
+impl<T: DspFormatBase + detail::FiltOps> Filt<T> {
     /// Constructor
     pub fn new() -> Self {
         Default::default()
     }
+    /// Hard reset: zero all of the integrators, so the filter behaves exactly
+    /// like a newly constructed one.  This will cause a discontinuity in the
+    /// output if the filter is currently ringing or passing a signal.
+    pub fn reset(&mut self) {
+        self.low_z = Default::default();
+        self.band_z = Default::default();
+    }
+    /// Soft reset: leave the integrator state intact (so the output remains
+    /// continuous), but flush any denormal values to zero.  This is a no-op
+    /// for fixed point types, which have no denormals.
+    pub fn soft_reset(&mut self) {
+        T::flush_denormals(&mut self.low_z);
+        T::flush_denormals(&mut self.band_z);
+    }
 }
 
-impl<T: DspFormat> Device<T> for Filt<T> {
+impl<T: DspFormatBase + detail::FiltOps> Device<T> for Filt<T> {
     type Input = T::Sample;
     type Params = FiltParams<T>;
     type Output = FiltOutput<T>;
@@ -100,13 +120,25 @@ impl<T: DspFormat> Device<T> for Filt<T> {
     }
 }
 
+#[cfg(feature = "float")]
 impl<T: DspFloat> detail::FiltOps for T {
     const RES_MAX: T = T::RES_MAX;
     type FiltGain = T;
     type FiltFeedback = T;
     fn prewarped_gain(context: &Context<Self>, cutoff: T) -> T {
-        let f_c = cutoff.midi_to_freq();
-        T::ftan(T::PI * f_c / context.sample_rate)
+        // Keep the note within the range of the frequency approximation
+        let cutoff = if cutoff < T::ZERO {
+            T::ZERO
+        } else if cutoff > T::NOTE_MAX {
+            T::NOTE_MAX
+        } else {
+            cutoff
+        };
+        let f_c = context.midi_to_freq(cutoff);
+        // Limit the prewarped angle to 1 radian (the same limit as the fixed
+        // point implementation), as the gain grows without bound at Nyquist
+        let omega_d = T::PI * f_c / context.sample_rate;
+        context.tan(if omega_d < T::ONE { omega_d } else { T::ONE })
     }
     fn calc_filt(
         context: &Self::Context,
@@ -128,8 +160,13 @@ impl<T: DspFloat> detail::FiltOps for T {
         let low = low_gain + *low_z;
         *low_z = low + low_gain;
 
+        Self::flush_denormals(band_z);
+        Self::flush_denormals(low_z);
         FiltOutput { low, band, high }
     }
+    fn flush_denormals(z: &mut T) {
+        *z = z.flush_denormal();
+    }
 }
 
 impl detail::FiltOps for i16 {
@@ -139,7 +176,8 @@ impl detail::FiltOps for i16 {
     fn prewarped_gain(context: &ContextFxP, cutoff: NoteFxP) -> Self::FiltGain {
         use crate::fixedmath::{midi_note_to_frequency, tan_fixed, U14F2};
         let f_c = U14F2::from_num(midi_note_to_frequency(cutoff));
-        let omega_d = ScalarFxP::from_num(
+        // Saturate just below 1 radian to keep the gain bounded near Nyquist
+        let omega_d = ScalarFxP::saturating_from_num(
             f_c.wide_mul(context.sample_rate.frac_2pi4096_sr()).unwrapped_shr(13),
         );
         tan_fixed(omega_d)
@@ -185,4 +223,247 @@ impl detail::FiltOps for i16 {
 
         FiltOutput { low, band, high }
     }
+    fn flush_denormals(_: &mut Self::FiltFeedback) {}
+}
+
+impl detail::FiltOps for i32 {
+    const RES_MAX: ScalarFxP32 = ScalarFxP32::lit("0x0.F");
+    type FiltGain = crate::fixedmath::I32F32;
+    type FiltFeedback = crate::fixedmath::I32F32;
+    fn prewarped_gain(context: &ContextFxP, cutoff: NoteFxP32) -> Self::FiltGain {
+        // The gain itself only sets the cutoff (and doesn't contribute to the
+        // noise floor), so the 16 bit approximation is sufficient here
+        let gain = <i16 as detail::FiltOps>::prewarped_gain(context, NoteFxP::from_num(cutoff));
+        Self::FiltGain::from_num(gain)
+    }
+    fn calc_filt(
+        context: &Self::Context,
+        signal: Self::Sample,
+        cutoff: Self::Note,
+        res: Self::Scalar,
+        low_z: &mut Self::FiltFeedback,
+        band_z: &mut Self::FiltFeedback,
+    ) -> filt::FiltOutput<i32> {
+        use crate::fixedmath::{one_over_one_plus_refined32, I32F32, U32F32};
+        let gain = Self::prewarped_gain(context, cutoff);
+        let res = I32F32::from_num(res);
+        let res2 = res.unwrapped_shl(1);
+        let k = U32F32::from_num(gain * gain + res2 * gain);
+        let (denom_inv, shift) = one_over_one_plus_refined32(k);
+        let high_num = I32F32::from_num(signal) - (res2 + gain) * (*band_z) - *low_z;
+        let high = (high_num * I32F32::from_num(denom_inv)).unwrapped_shr(shift);
+
+        let band_gain = gain * high;
+        let band = band_gain + *band_z;
+        *band_z = band + band_gain;
+
+        let low_gain = gain * band;
+        let low = low_gain + *low_z;
+        *low_z = low + low_gain;
+
+        FiltOutput {
+            low: SampleFxP32::saturating_from_num(low),
+            band: SampleFxP32::saturating_from_num(band),
+            high: SampleFxP32::saturating_from_num(high),
+        }
+    }
+    fn flush_denormals(_: &mut Self::FiltFeedback) {}
+}
+
+#[cfg(all(test, feature = "float"))]
+mod tests {
+    use super::*;
+    use crate::Float;
+
+    /// Mean squared error of the low-pass output of a `T` filter relative to
+    /// a floating point reference filter using the same prewarped gain, which
+    /// measures the noise floor contributed by the format's quantization
+    fn low_pass_error<T: DspFormatBase<Context = ContextFxP> + detail::FiltOps>(
+        cutoff: T::Note,
+        resonance: T::Scalar,
+        to_sample: impl Fn(SampleFxP) -> T::Sample,
+        gain: impl Fn(T::FiltGain) -> f64,
+    ) -> f64 {
+        let ctx = ContextFxP::new_480();
+        let g = gain(T::prewarped_gain(&ctx, cutoff));
+        // resonance is 0.5, so the damping term is 1 - 0.5 = 0.5
+        let res = 0.5f64;
+        let mut filt = Filt::<T>::new();
+        let (mut low_z, mut band_z) = (0f64, 0f64);
+        let mut err = 0f64;
+        const N: usize = 4800;
+        for n in 0..N {
+            // 240Hz sine, wrapped to [-pi, pi)
+            let phase = core::f64::consts::TAU * (n % 200) as f64 / 200f64 - core::f64::consts::PI;
+            let input = SampleFxP::from_num(Float::fsin(phase) * 0.5);
+            let out = filt.next(&ctx, to_sample(input), FiltParams { cutoff, resonance });
+            let signal = input.to_num::<f64>();
+            let high =
+                (signal - (2f64 * res + g) * band_z - low_z) / (g * g + 2f64 * res * g + 1f64);
+            let band = g * high + band_z;
+            band_z = band + g * high;
+            let low = g * band + low_z;
+            low_z = low + g * band;
+            let diff = T::sample_to_float(out.low) as f64 - low;
+            err += diff * diff;
+        }
+        err / N as f64
+    }
+
+    /// The peak low-pass response to a 0.5 amplitude, 8kHz sine wave at
+    /// each cutoff in `cutoffs`
+    fn cutoff_sweep<T: DspFormatBase + detail::FiltOps>(
+        ctx: T::Context,
+        sample_rate: f32,
+        cutoffs: impl Iterator<Item = T::Note>,
+    ) -> impl Iterator<Item = f32> {
+        cutoffs.map(move |cutoff| {
+            let mut filt = Filt::<T>::new();
+            let params = FiltParams {
+                cutoff,
+                resonance: T::Scalar::zero(),
+            };
+            (0..4096)
+                .map(|i| {
+                    let phase = core::f32::consts::TAU * 8000f32 * (i as f32) / sample_rate;
+                    let input = crate::IScalarFxP::from_num(phase.sin() / 2f32);
+                    let out = filt.next(&ctx, T::sample_from_fixed(input), params.clone());
+                    (i, T::sample_to_float(out.low))
+                })
+                .filter(|(i, _)| *i >= 2048)
+                .fold(0f32, |acc, (_, smp)| acc.max(smp.abs()))
+        })
+    }
+
+    fn assert_stable_and_monotonic(responses: impl Iterator<Item = f32>) {
+        let mut last = 0f32;
+        for (i, resp) in responses.enumerate() {
+            // With no resonance, the low pass response never exceeds unity
+            assert!(resp.is_finite() && resp < 0.55f32, "step {i}: {resp}");
+            // Allow for quantization and settling at very low cutoffs
+            assert!(
+                resp >= last * 0.99f32 - 0.001f32,
+                "step {i}: {resp} < {last}"
+            );
+            last = resp;
+        }
+        // By the end of the sweep, most of the tone should pass through
+        assert!(last > 0.3f32, "{last}");
+    }
+
+    #[test]
+    fn cutoff_sweep_near_nyquist() {
+        let notes = || (0..=64).map(|i| i as f32 * 2f32);
+        for sr in [44100f32, 96000f32] {
+            let ctx = Context::new(sr);
+            let cutoffs = notes().map(|n| n.min(f32::NOTE_MAX));
+            assert_stable_and_monotonic(cutoff_sweep::<f32>(ctx, sr, cutoffs));
+        }
+        let cutoffs = notes().map(|n| NoteFxP::saturating_from_num(n)).chain([NoteFxP::MAX]);
+        assert_stable_and_monotonic(cutoff_sweep::<i16>(
+            ContextFxP::new_441(),
+            44100f32,
+            cutoffs,
+        ));
+        // Out of range notes and low sample rates are clamped
+        let ctx = Context::new(22050f32);
+        let cutoffs = [-20f32, 100f32, 200f32].into_iter();
+        assert!(cutoff_sweep::<f32>(ctx, 22050f32, cutoffs).all(|x| x.is_finite() && x < 1f32));
+    }
+
+    /// Settle a filter on a DC input, then apply a hard or soft reset and
+    /// change the cutoff.  Returns the low-pass output before the reset and
+    /// the output after, along with the output of a reference filter that is
+    /// either freshly constructed (hard) or was never reset (soft).
+    fn reset_then_retune<T: DspFormatBase + detail::FiltOps>(
+        ctx: &T::Context,
+        cutoffs: [T::Note; 2],
+        hard: bool,
+    ) -> (f32, [(f32, f32); 256]) {
+        let input = T::sample_from_fixed(crate::IScalarFxP::lit("0.5"));
+        let params = |cutoff| FiltParams {
+            cutoff,
+            resonance: T::Scalar::zero(),
+        };
+        let mut filt = Filt::<T>::new();
+        let mut last = T::Sample::zero();
+        for _ in 0..4800 {
+            last = filt.next(ctx, input, params(cutoffs[0])).low;
+        }
+        let mut reference = if hard { Filt::new() } else { filt.clone() };
+        if hard {
+            filt.reset();
+        } else {
+            filt.soft_reset();
+        }
+        let out = core::array::from_fn(|_| {
+            let out = filt.next(ctx, input, params(cutoffs[1])).low;
+            let expected = reference.next(ctx, input, params(cutoffs[1])).low;
+            (T::sample_to_float(out), T::sample_to_float(expected))
+        });
+        (T::sample_to_float(last), out)
+    }
+
+    fn check_reset<T: DspFormatBase + detail::FiltOps>(ctx: &T::Context, cutoffs: [T::Note; 2]) {
+        // A hard reset matches a fresh filter, but jumps back to zero
+        let (last, out) = reset_then_retune::<T>(ctx, cutoffs, true);
+        assert!(out.iter().all(|(out, expected)| out == expected));
+        assert!((out[0].0 - last).abs() > 0.4, "{} -> {}", last, out[0].0);
+        // A soft reset keeps the state, so the output stays continuous
+        let (last, out) = reset_then_retune::<T>(ctx, cutoffs, false);
+        assert!(out.iter().all(|(out, expected)| out == expected));
+        assert!((out[0].0 - last).abs() < 0.01, "{} -> {}", last, out[0].0);
+    }
+
+    #[test]
+    fn hard_and_soft_reset() {
+        check_reset::<f32>(&Context::new(48000f32), [60f32, 72f32]);
+        check_reset::<i16>(
+            &ContextFxP::new_480(),
+            [NoteFxP::lit("60"), NoteFxP::lit("72")],
+        );
+    }
+
+    #[test]
+    fn float_state_flushes_denormals() {
+        let ctx = Context::new(48000f32);
+        let mut filt = Filt::<f32>::new();
+        let params = FiltParams {
+            cutoff: 60f32,
+            resonance: 0.5f32,
+        };
+        crate::devices::tests::decays_cleanly(|x| {
+            filt.next(&ctx, x, params.clone());
+            [filt.low_z, filt.band_z]
+        });
+    }
+
+    #[test]
+    fn soft_reset_flushes_denormals() {
+        let mut filt = Filt::<f32> {
+            low_z: f32::MIN_POSITIVE / 2f32,
+            band_z: 0.25f32,
+        };
+        filt.soft_reset();
+        assert_eq!((filt.low_z, filt.band_z), (0f32, 0.25f32));
+    }
+
+    #[test]
+    fn noise_floor_32_vs_16() {
+        let err16 = low_pass_error::<i16>(
+            NoteFxP::lit("80"),
+            ScalarFxP::lit("0.5"),
+            |x| x,
+            |g| g.to_num(),
+        );
+        let err32 = low_pass_error::<i32>(
+            NoteFxP32::lit("80"),
+            ScalarFxP32::lit("0.5"),
+            SampleFxP32::from_num,
+            |g| g.to_num(),
+        );
+        // The 32 bit noise floor should be at least 60dB below the 16 bit one
+        assert!(err16 > 0f64);
+        assert!(err32 * 1e6 < err16);
+    }
 }
@@ -0,0 +1,157 @@
+use super::*;
+use env::detail::EnvType;
+
+pub(crate) mod detail {
+    pub trait FollowerOps: crate::DspFormatBase {
+        /// The magnitude of `signal`, saturating at one
+        fn rectify(signal: Self::Sample) -> Self::Scalar;
+    }
+}
+
+/// Parameters for an [EnvelopeFollower]
+#[derive(Clone, Debug, PartialEq)]
+pub struct EnvelopeFollowerParams<T: DspFormatBase> {
+    /// The time, in seconds, for the output to rise 98% of the way to a
+    /// louder input level.  Zero (the default) follows rising levels
+    /// instantly.
+    pub attack: T::EnvParam,
+    /// The time, in seconds, for the output to fall 98% of the way to a
+    /// quieter input level.  Defaults to 0.1s.
+    pub release: T::EnvParam,
+}
+
+impl<T: DspFormat> Default for EnvelopeFollowerParams<T> {
+    fn default() -> Self {
+        Self {
+            attack: T::EnvParam::zero(),
+            release: T::ADR_DEFAULT,
+        }
+    }
+}
+
+#[cfg(feature = "float")]
+impl<T: DspFloat> From<&EnvelopeFollowerParams<i16>> for EnvelopeFollowerParams<T> {
+    fn from(value: &EnvelopeFollowerParams<i16>) -> Self {
+        Self {
+            attack: value.attack.to_num(),
+            release: value.release.to_num(),
+        }
+    }
+}
+
+/// An envelope follower, tracking the level of an audio signal
+///
+/// The input is rectified and smoothed with the same one-pole response as an
+/// [Env] stage, rising with the attack time and falling with the release
+/// time.  The output is useful as a modulation source, e.g. sweeping a filter
+/// for an auto-wah, or ducking one signal by the level of another.
+///
+/// This implements [Device], taking a Sample as input and
+/// [EnvelopeFollowerParams] as parameters and outputting a Scalar.
+#[derive(Clone, Default)]
+pub struct EnvelopeFollower<T: DspFormat> {
+    level: T::EnvSignal,
+}
+
+impl<T: DspFormat> EnvelopeFollower<T> {
+    /// Constructor
+    pub fn new() -> Self {
+        Default::default()
+    }
+    /// The current output level (i.e. the value most recently returned from
+    /// [Device::next])
+    pub fn level(&self) -> T::Scalar {
+        self.level.to_scalar()
+    }
+}
+
+impl<T: DspFormat> Device<T> for EnvelopeFollower<T> {
+    type Input = T::Sample;
+    type Params = EnvelopeFollowerParams<T>;
+    type Output = T::Scalar;
+    fn next(
+        &mut self,
+        context: &T::Context,
+        signal: T::Sample,
+        params: EnvelopeFollowerParams<T>,
+    ) -> T::Scalar {
+        let target = T::EnvSignal::from(T::rectify(signal));
+        let time = if target > self.level {
+            params.attack
+        } else {
+            params.release
+        };
+        self.level = if time == T::EnvParam::zero() {
+            target
+        } else {
+            T::calc_env_accurate(context, target, target, self.level, time)
+        };
+        self.level.to_scalar()
+    }
+}
+
+#[cfg(feature = "float")]
+impl<T: DspFloat> detail::FollowerOps for T {
+    fn rectify(signal: T) -> T {
+        T::min(signal.abs(), T::ONE)
+    }
+}
+
+impl detail::FollowerOps for i16 {
+    fn rectify(signal: SampleFxP) -> ScalarFxP {
+        ScalarFxP::saturating_from_num(signal.unsigned_abs())
+    }
+}
+
+#[cfg(all(test, feature = "float"))]
+mod tests {
+    use super::*;
+
+    /// Feed a 0.1s burst of a half amplitude 440Hz sine into a follower,
+    /// returning the level at the end of the burst and the number of
+    /// samples afterwards for the level to fall by 98%
+    fn burst<T: DspFormat>(
+        ctx: &T::Context,
+        params: EnvelopeFollowerParams<T>,
+        to_float: fn(T::Scalar) -> f32,
+    ) -> (f32, usize) {
+        let mut follower = EnvelopeFollower::<T>::new();
+        let mut rising = [0f32; 4];
+        for i in 0..4800 {
+            let phase = core::f32::consts::TAU * 440f32 * (i as f32) / 48000f32;
+            let signal = T::sample_from_fixed(crate::IScalarFxP::from_num(0.5 * phase.sin()));
+            let level = to_float(follower.next(ctx, signal, params.clone()));
+            // Track the loudest level in each quarter of the attack time
+            if i < 480 {
+                rising[i / 120] = f32::max(rising[i / 120], level);
+            }
+        }
+        for w in rising.windows(2) {
+            assert!(w[0] < w[1], "{rising:?}");
+        }
+        let peak = to_float(follower.level());
+        let silence = T::Sample::zero();
+        let release = (0..48000)
+            .position(|_| to_float(follower.next(ctx, silence, params.clone())) < peak * 0.02);
+        (peak, release.unwrap())
+    }
+
+    #[test]
+    fn follower_tracks_burst() {
+        let check = |(peak, release): (f32, usize)| {
+            assert!(peak > 0.3 && peak <= 0.5, "{peak}");
+            // The release time is 0.1s
+            assert!(release.abs_diff(4800) < 240, "{release}");
+        };
+        let params = EnvelopeFollowerParams::<f32> {
+            attack: 0.01f32,
+            release: 0.1f32,
+        };
+        check(burst::<f32>(&Context::new(48000f32), params, |x| x));
+        let params = EnvelopeFollowerParams::<i16> {
+            attack: EnvParamFxP::lit("0.01"),
+            release: EnvParamFxP::lit("0.1"),
+        };
+        check(burst::<i16>(&ContextFxP::new_480(), params, |x| x.to_num()));
+    }
+}
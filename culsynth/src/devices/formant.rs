@@ -0,0 +1,188 @@
+use super::*;
+
+/// The number of formants (resonant peaks) modelled for each vowel
+const NUM_FORMANTS: usize = 3;
+
+/// The first three formant frequencies for the vowels A, E, I, O, and U, as
+/// MIDI note numbers (800/1150/2900Hz, 350/2000/2800Hz, 270/2140/2950Hz,
+/// 450/800/2830Hz, and 325/700/2700Hz respectively)
+const FORMANTS: [[NoteFxP; NUM_FORMANTS]; 5] = [
+    [
+        NoteFxP::lit("79.35"),
+        NoteFxP::lit("85.63"),
+        NoteFxP::lit("101.65"),
+    ],
+    [
+        NoteFxP::lit("65.04"),
+        NoteFxP::lit("95.21"),
+        NoteFxP::lit("101.04"),
+    ],
+    [
+        NoteFxP::lit("60.55"),
+        NoteFxP::lit("96.38"),
+        NoteFxP::lit("101.94"),
+    ],
+    [
+        NoteFxP::lit("69.39"),
+        NoteFxP::lit("79.35"),
+        NoteFxP::lit("101.22"),
+    ],
+    [
+        NoteFxP::lit("63.76"),
+        NoteFxP::lit("77.04"),
+        NoteFxP::lit("100.41"),
+    ],
+];
+
+/// Parameters for a [FormantFilter]
+#[derive(Clone, Default, Debug, PartialEq)]
+pub struct FormantFilterParams<T: DspFormatBase> {
+    /// The vowel position, from 0 to 1.  This sweeps through the vowels A, E,
+    /// I, O, and U (at 0, 1/4, 1/2, 3/4, and 1 respectively), linearly
+    /// interpolating the formant frequencies in between.
+    pub vowel: ScalarFxP,
+    /// The resonance of each formant, as a value between 0 and 1 (see
+    /// [FiltParams::resonance])
+    pub resonance: T::Scalar,
+}
+
+#[cfg(feature = "float")]
+impl<T: DspFloat> From<&FormantFilterParams<i16>> for FormantFilterParams<T> {
+    fn from(value: &FormantFilterParams<i16>) -> Self {
+        Self {
+            vowel: value.vowel,
+            resonance: value.resonance.to_num(),
+        }
+    }
+}
+
+impl<T: DspFormatBase> FormantFilterParams<T> {
+    /// The formant frequencies for the current vowel position
+    fn formants(&self) -> [NoteFxP; NUM_FORMANTS] {
+        // Four segments between five vowels, so the top two bits are the index
+        // and the remaining 14 bits are the position within the segment
+        let bits = self.vowel.to_bits() as i32;
+        let (idx, frac) = ((bits >> 14) as usize, bits & 0x3FFF);
+        core::array::from_fn(|i| {
+            let (a, b) = (
+                FORMANTS[idx][i].to_bits() as i32,
+                FORMANTS[idx + 1][i].to_bits() as i32,
+            );
+            NoteFxP::from_bits((a + (((b - a) * frac) >> 14)) as u16)
+        })
+    }
+}
+
+/// A vowel formant filter
+///
+/// This emulates the resonances of the vocal tract using a bank of parallel
+/// band-pass filters (the band output of a [Filt]) tuned to the first three
+/// formant frequencies of a vowel.  Each band is normalized to unity peak
+/// gain, and the second and third formants are attenuated by 6dB and 12dB
+/// respectively.
+///
+/// This implements [Device] taking a Sample as input and [FormantFilterParams]
+/// as parameters and outputting a Sample.
+#[derive(Clone, Default)]
+pub struct FormantFilter<T: DspFormat> {
+    filters: [Filt<T>; NUM_FORMANTS],
+}
+
+impl<T: DspFormat> FormantFilter<T> {
+    /// Constructor
+    pub fn new() -> Self {
+        Default::default()
+    }
+}
+
+impl<T: DspFormat> Device<T> for FormantFilter<T> {
+    type Input = T::Sample;
+    type Params = FormantFilterParams<T>;
+    type Output = T::Sample;
+    fn next(
+        &mut self,
+        context: &T::Context,
+        signal: T::Sample,
+        params: FormantFilterParams<T>,
+    ) -> T::Sample {
+        // The band-pass peak gain is 1/(2 * damping), so scale each band by
+        // the damping and double it to normalize
+        let damping = T::Scalar::one() - params.resonance;
+        let mut bands = self.filters.iter_mut().zip(params.formants()).map(|(f, note)| {
+            let band = f
+                .next(
+                    context,
+                    signal,
+                    FiltParams {
+                        cutoff: T::note_from_fixed(note),
+                        resonance: params.resonance,
+                    },
+                )
+                .band
+                .scale(damping);
+            band.dsp_saturating_add(band)
+        });
+        let (f1, f2, f3) = (
+            bands.next().unwrap_or_default(),
+            bands.next().unwrap_or_default(),
+            bands.next().unwrap_or_default(),
+        );
+        f1.dsp_saturating_add(f2.dsp_saturating_add(f3.divide_by_two()).divide_by_two())
+    }
+}
+
+#[cfg(all(test, feature = "float"))]
+mod tests {
+    use super::*;
+
+    /// Magnitude of the impulse response of a [FormantFilter] set to the vowel
+    /// "A" at each of the given frequencies
+    fn vowel_a_response<T: DspFormat>(
+        ctx: &T::Context,
+        resonance: T::Scalar,
+        freqs: &[f32; 6],
+    ) -> [f32; 6] {
+        const SR: f32 = 48000f32;
+        let mut filt = FormantFilter::<T>::new();
+        let params = FormantFilterParams {
+            vowel: ScalarFxP::ZERO,
+            resonance,
+        };
+        let mut response = [(0f32, 0f32); 6];
+        for i in 0..4800 {
+            let input = if i == 0 {
+                T::sample_from_fixed(crate::IScalarFxP::MAX)
+            } else {
+                T::Sample::zero()
+            };
+            let smp = T::sample_to_float(filt.next(ctx, input, params.clone()));
+            for (freq, (re, im)) in freqs.iter().zip(response.iter_mut()) {
+                let phase = core::f32::consts::TAU * freq * (i as f32) / SR;
+                *re += smp * phase.cos();
+                *im += smp * phase.sin();
+            }
+        }
+        response.map(|(re, im)| (re * re + im * im).sqrt())
+    }
+
+    #[test]
+    fn vowel_a_peaks() {
+        // Formants at 800, 1150, and 2900Hz, with troughs in between
+        let freqs = [800f32, 1150f32, 2900f32, 500f32, 975f32, 2000f32];
+        let check = |r: [f32; 6]| {
+            assert!(r[0] > r[3] && r[0] > r[4]);
+            assert!(r[1] > r[4] && r[1] > r[5]);
+            assert!(r[2] > r[5]);
+        };
+        check(vowel_a_response::<f32>(
+            &Context::new(48000f32),
+            0.9,
+            &freqs,
+        ));
+        check(vowel_a_response::<i16>(
+            &ContextFxP::new_480(),
+            ScalarFxP::lit("0.9"),
+            &freqs,
+        ));
+    }
+}
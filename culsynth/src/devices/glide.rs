@@ -0,0 +1,236 @@
+use super::*;
+
+pub(crate) mod detail {
+    pub trait GlideOps: crate::DspFormatBase {
+        /// A higher precision pitch, so slow glides do not stall between
+        /// steps of [crate::DspFormatBase::Note]
+        type GlideNote: Copy + Default + PartialEq + Send;
+        /// The distance of one octave
+        fn octave() -> Self::GlideNote;
+        fn widen_note(note: Self::Note) -> Self::GlideNote;
+        fn narrow_note(note: Self::GlideNote) -> Self::Note;
+        /// The absolute distance between `a` and `b`
+        fn glide_distance(a: Self::GlideNote, b: Self::GlideNote) -> Self::GlideNote;
+        /// The step per sample to cover `distance` in `time` seconds, which
+        /// must be nonzero
+        fn glide_step(
+            context: &Self::Context,
+            distance: Self::GlideNote,
+            time: Self::EnvParam,
+        ) -> Self::GlideNote;
+        /// Move `current` towards `target` by `step`, without overshooting
+        fn glide_towards(
+            current: Self::GlideNote,
+            target: Self::GlideNote,
+            step: Self::GlideNote,
+        ) -> Self::GlideNote;
+    }
+}
+
+/// The shape of the pitch slew of a [Glide]
+#[derive(Eq, PartialEq, Clone, Copy, Default, Debug)]
+pub enum GlideMode {
+    /// Every glide takes [GlideParams::time], regardless of the interval, so
+    /// wide leaps sweep faster than narrow ones
+    #[default]
+    ConstantTime,
+    /// Glide at a fixed rate of one octave per [GlideParams::time], so a two
+    /// octave leap takes twice as long as a one octave leap
+    ConstantRate,
+}
+
+/// Parameters for a [Glide]
+#[derive(Clone, Debug, PartialEq)]
+pub struct GlideParams<T: DspFormatBase> {
+    /// The glide time, in seconds (see [GlideMode]).  Zero (the default)
+    /// disables the glide.
+    pub time: T::EnvParam,
+    /// The shape of the glide
+    pub mode: GlideMode,
+}
+
+impl<T: DspFormatBase> Default for GlideParams<T> {
+    fn default() -> Self {
+        Self {
+            time: T::EnvParam::zero(),
+            mode: GlideMode::default(),
+        }
+    }
+}
+
+#[cfg(feature = "float")]
+impl<T: DspFloat> From<&GlideParams<i16>> for GlideParams<T> {
+    fn from(value: &GlideParams<i16>) -> Self {
+        Self {
+            time: value.time.to_num(),
+            mode: value.mode,
+        }
+    }
+}
+
+/// A portamento (pitch slew) generator
+///
+/// Whenever the input note changes, the output slides linearly from its
+/// current pitch to the new note, with the speed set by [GlideParams::time]
+/// and [GlideParams::mode].  The first note after construction (or
+/// [Glide::reset]) is output immediately, as there is nothing to glide from.
+///
+/// This implements [Device] taking a Note as input and [GlideParams] as
+/// parameters and outputting a Note.
+#[derive(Clone, Default)]
+pub struct Glide<T: DspFormat> {
+    current: Option<T::GlideNote>,
+    target: T::GlideNote,
+    /// The distance from the start of the current glide to its target
+    span: T::GlideNote,
+}
+
+impl<T: DspFormat> Glide<T> {
+    /// Constructor
+    pub fn new() -> Self {
+        Default::default()
+    }
+    /// Forget the current pitch, so the next note is output immediately
+    pub fn reset(&mut self) {
+        *self = Default::default();
+    }
+}
+
+impl<T: DspFormat> Device<T> for Glide<T> {
+    type Input = T::Note;
+    type Params = GlideParams<T>;
+    type Output = T::Note;
+    fn next(&mut self, context: &T::Context, note: T::Note, params: GlideParams<T>) -> T::Note {
+        let target = T::widen_note(note);
+        let current = match self.current {
+            Some(current) if params.time != T::EnvParam::zero() => current,
+            _ => target,
+        };
+        if target != self.target {
+            self.target = target;
+            self.span = T::glide_distance(current, target);
+        }
+        let next = if current == target {
+            target
+        } else {
+            let distance = match params.mode {
+                GlideMode::ConstantTime => self.span,
+                GlideMode::ConstantRate => T::octave(),
+            };
+            let step = T::glide_step(context, distance, params.time);
+            T::glide_towards(current, target, step)
+        };
+        self.current = Some(next);
+        T::narrow_note(next)
+    }
+}
+
+#[cfg(feature = "float")]
+impl<T: DspFloat> detail::GlideOps for T {
+    type GlideNote = T;
+    fn octave() -> T {
+        T::from_u16(12)
+    }
+    fn widen_note(note: T) -> T {
+        note
+    }
+    fn narrow_note(note: T) -> T {
+        note
+    }
+    fn glide_distance(a: T, b: T) -> T {
+        (a - b).abs()
+    }
+    fn glide_step(context: &Context<T>, distance: T, time: T) -> T {
+        distance / (time * context.sample_rate)
+    }
+    fn glide_towards(current: T, target: T, step: T) -> T {
+        if current < target {
+            T::min(current + step, target)
+        } else {
+            T::max(current - step, target)
+        }
+    }
+}
+
+impl detail::GlideOps for i16 {
+    type GlideNote = NoteFxP32;
+    fn octave() -> NoteFxP32 {
+        NoteFxP32::lit("12")
+    }
+    fn widen_note(note: NoteFxP) -> NoteFxP32 {
+        NoteFxP32::from_num(note)
+    }
+    fn narrow_note(note: NoteFxP32) -> NoteFxP {
+        NoteFxP::from_num(note)
+    }
+    fn glide_distance(a: NoteFxP32, b: NoteFxP32) -> NoteFxP32 {
+        a.abs_diff(b)
+    }
+    fn glide_step(context: &ContextFxP, distance: NoteFxP32, time: EnvParamFxP) -> NoteFxP32 {
+        use crate::fixedmath::{one_over_one_plus_refined, U16F0};
+        let sr = U16F0::from_bits(context.sample_rate.value());
+        let (gain, shift) = one_over_one_plus_refined(time.wide_mul(sr));
+        let step = (distance.to_bits() as u64 * gain.to_bits() as u64) >> (15 + shift);
+        // Always make progress, even for the slowest and shortest glides
+        NoteFxP32::from_bits(core::cmp::max(step as u32, 1))
+    }
+    fn glide_towards(current: NoteFxP32, target: NoteFxP32, step: NoteFxP32) -> NoteFxP32 {
+        if current < target {
+            core::cmp::min(current.saturating_add(step), target)
+        } else {
+            core::cmp::max(current.saturating_sub(step), target)
+        }
+    }
+}
+
+#[cfg(all(test, feature = "float"))]
+mod tests {
+    use super::*;
+
+    /// The number of samples taken to glide from `from` to `to`
+    fn glide_samples<T: DspFormat>(
+        ctx: &T::Context,
+        from: T::Note,
+        to: T::Note,
+        params: GlideParams<T>,
+    ) -> usize {
+        let mut glide = Glide::<T>::new();
+        assert_eq!(glide.next(ctx, from, params.clone()), from);
+        1 + (0..480000).position(|_| glide.next(ctx, to, params.clone()) == to).unwrap()
+    }
+
+    /// Glide up one and two octaves, returning the ratio of the glide times
+    fn two_octave_ratio<T: DspFormat>(ctx: &T::Context, time: T::EnvParam, mode: GlideMode) -> f32 {
+        let params = GlideParams::<T> { time, mode };
+        let note = |n| T::note_from_fixed(NoteFxP::from_num(n));
+        let one = glide_samples(ctx, note(48), note(60), params.clone());
+        let two = glide_samples(ctx, note(48), note(72), params.clone());
+        two as f32 / one as f32
+    }
+
+    #[test]
+    fn constant_rate_scales_with_interval() {
+        let ctx = Context::new(48000f32);
+        let ratio = two_octave_ratio::<f32>(&ctx, 0.1f32, GlideMode::ConstantRate);
+        assert!((ratio - 2f32).abs() < 0.01, "{ratio}");
+        let ratio = two_octave_ratio::<f32>(&ctx, 0.1f32, GlideMode::ConstantTime);
+        assert!((ratio - 1f32).abs() < 0.01, "{ratio}");
+        let ctx = ContextFxP::new_480();
+        let time = EnvParamFxP::lit("0.1");
+        let ratio = two_octave_ratio::<i16>(&ctx, time, GlideMode::ConstantRate);
+        assert!((ratio - 2f32).abs() < 0.01, "{ratio}");
+        let ratio = two_octave_ratio::<i16>(&ctx, time, GlideMode::ConstantTime);
+        assert!((ratio - 1f32).abs() < 0.01, "{ratio}");
+        // A one octave constant rate glide should take about the glide time
+        let samples = glide_samples::<f32>(
+            &Context::new(48000f32),
+            48f32,
+            60f32,
+            GlideParams {
+                time: 0.1f32,
+                mode: GlideMode::ConstantRate,
+            },
+        );
+        assert!(samples.abs_diff(4800) <= 5, "{samples}");
+    }
+}
@@ -5,37 +5,49 @@ use crate::devices::env::detail;
 ///
 /// Use this to easily build iterators to [EnvParams] out of iterators to
 /// its constituent parts.
-pub struct EnvParamIter<T: DspFormatBase + detail::EnvOps, A, D, S, R>
+pub struct EnvParamIter<T: DspFormatBase + detail::EnvOps, A, D, S, R, V, K, RV>
 where
     A: Iterator<Item = T::EnvParam>,
     D: Iterator<Item = T::EnvParam>,
     S: Iterator<Item = T::Scalar>,
     R: Iterator<Item = T::EnvParam>,
+    V: Iterator<Item = T::IScalar>,
+    K: Iterator<Item = T::IScalar>,
+    RV: Iterator<Item = T::IScalar>,
 {
     a: A,
     d: D,
     s: S,
     r: R,
+    v: V,
+    k: K,
+    rv: RV,
     phantom: core::marker::PhantomData<T>,
 }
 
-impl<T: DspFormatBase + detail::EnvOps, A, D, S, R> EnvParamIter<T, A, D, S, R>
+impl<T: DspFormatBase + detail::EnvOps, A, D, S, R, V, K, RV> EnvParamIter<T, A, D, S, R, V, K, RV>
 where
     A: Iterator<Item = T::EnvParam>,
     D: Iterator<Item = T::EnvParam>,
     S: Iterator<Item = T::Scalar>,
     R: Iterator<Item = T::EnvParam>,
+    V: Iterator<Item = T::IScalar>,
+    K: Iterator<Item = T::IScalar>,
+    RV: Iterator<Item = T::IScalar>,
 {
     /// Replace the current attack source with the one provided
     pub fn with_attack<NewA: Iterator<Item = T::EnvParam>>(
         self,
         newa: NewA,
-    ) -> EnvParamIter<T, NewA, D, S, R> {
+    ) -> EnvParamIter<T, NewA, D, S, R, V, K, RV> {
         EnvParamIter {
             a: newa,
             d: self.d,
             s: self.s,
             r: self.r,
+            v: self.v,
+            k: self.k,
+            rv: self.rv,
             phantom: self.phantom,
         }
     }
@@ -43,12 +55,15 @@ where
     pub fn with_decay<NewD: Iterator<Item = T::EnvParam>>(
         self,
         newd: NewD,
-    ) -> EnvParamIter<T, A, NewD, S, R> {
+    ) -> EnvParamIter<T, A, NewD, S, R, V, K, RV> {
         EnvParamIter {
             a: self.a,
             d: newd,
             s: self.s,
             r: self.r,
+            v: self.v,
+            k: self.k,
+            rv: self.rv,
             phantom: self.phantom,
         }
     }
@@ -56,12 +71,15 @@ where
     pub fn with_sustain<NewS: Iterator<Item = T::Scalar>>(
         self,
         news: NewS,
-    ) -> EnvParamIter<T, A, D, NewS, R> {
+    ) -> EnvParamIter<T, A, D, NewS, R, V, K, RV> {
         EnvParamIter {
             a: self.a,
             d: self.d,
             s: news,
             r: self.r,
+            v: self.v,
+            k: self.k,
+            rv: self.rv,
             phantom: self.phantom,
         }
     }
@@ -69,24 +87,80 @@ where
     pub fn with_release<NewR: Iterator<Item = T::EnvParam>>(
         self,
         newr: NewR,
-    ) -> EnvParamIter<T, A, D, S, NewR> {
+    ) -> EnvParamIter<T, A, D, S, NewR, V, K, RV> {
         EnvParamIter {
             a: self.a,
             d: self.d,
             s: self.s,
             r: newr,
+            v: self.v,
+            k: self.k,
+            rv: self.rv,
+            phantom: self.phantom,
+        }
+    }
+    /// Replace the current velocity to attack scaling source with the one
+    /// provided
+    pub fn with_vel_attack<NewV: Iterator<Item = T::IScalar>>(
+        self,
+        newv: NewV,
+    ) -> EnvParamIter<T, A, D, S, R, NewV, K, RV> {
+        EnvParamIter {
+            a: self.a,
+            d: self.d,
+            s: self.s,
+            r: self.r,
+            v: newv,
+            k: self.k,
+            rv: self.rv,
+            phantom: self.phantom,
+        }
+    }
+    /// Replace the current key to decay scaling source with the one provided
+    pub fn with_key_decay<NewK: Iterator<Item = T::IScalar>>(
+        self,
+        newk: NewK,
+    ) -> EnvParamIter<T, A, D, S, R, V, NewK, RV> {
+        EnvParamIter {
+            a: self.a,
+            d: self.d,
+            s: self.s,
+            r: self.r,
+            v: self.v,
+            k: newk,
+            rv: self.rv,
+            phantom: self.phantom,
+        }
+    }
+    /// Replace the current release velocity to release scaling source with
+    /// the one provided
+    pub fn with_vel_release<NewRV: Iterator<Item = T::IScalar>>(
+        self,
+        newrv: NewRV,
+    ) -> EnvParamIter<T, A, D, S, R, V, K, NewRV> {
+        EnvParamIter {
+            a: self.a,
+            d: self.d,
+            s: self.s,
+            r: self.r,
+            v: self.v,
+            k: self.k,
+            rv: newrv,
             phantom: self.phantom,
         }
     }
 }
 
-impl<T, A, D, S, R> Iterator for EnvParamIter<T, A, D, S, R>
+impl<T, A, D, S, R, V, K, RV> Iterator for EnvParamIter<T, A, D, S, R, V, K, RV>
 where
     T: DspFormatBase + detail::EnvOps,
     A: Iterator<Item = T::EnvParam>,
     D: Iterator<Item = T::EnvParam>,
     S: Iterator<Item = T::Scalar>,
     R: Iterator<Item = T::EnvParam>,
+    V: Iterator<Item = T::IScalar>,
+    K: Iterator<Item = T::IScalar>,
+    RV: Iterator<Item = T::IScalar>,
 {
     type Item = EnvParams<T>;
     fn next(&mut self) -> Option<EnvParams<T>> {
@@ -95,6 +169,10 @@ where
             decay: self.d.next()?,
             sustain: self.s.next()?,
             release: self.r.next()?,
+            vel_attack: self.v.next()?,
+            key_decay: self.k.next()?,
+            vel_release: self.rv.next()?,
+            ..Default::default()
         })
     }
 }
@@ -102,14 +180,24 @@ where
 /// Create a new [EnvParamIter], which initially creates instances of
 /// [EnvParams::default] until calling the `with_*()` methods.
 #[allow(clippy::type_complexity)]
-pub fn new_env_param_iter<T: DspFormatBase + detail::EnvOps>(
-) -> EnvParamIter<T, Repeat<T::EnvParam>, Repeat<T::EnvParam>, Repeat<T::Scalar>, Repeat<T::EnvParam>>
-{
+pub fn new_env_param_iter<T: DspFormatBase + detail::EnvOps>() -> EnvParamIter<
+    T,
+    Repeat<T::EnvParam>,
+    Repeat<T::EnvParam>,
+    Repeat<T::Scalar>,
+    Repeat<T::EnvParam>,
+    Repeat<T::IScalar>,
+    Repeat<T::IScalar>,
+    Repeat<T::IScalar>,
+> {
     EnvParamIter {
         a: repeat(T::ADR_DEFAULT),
         d: repeat(T::ADR_DEFAULT),
         s: repeat(T::Scalar::one()),
         r: repeat(T::ADR_DEFAULT),
+        v: repeat(T::IScalar::zero()),
+        k: repeat(T::IScalar::zero()),
+        rv: repeat(T::IScalar::zero()),
         phantom: Default::default(),
     }
 }
@@ -4,33 +4,37 @@ use super::*;
 ///
 /// Use this to easily build iterators to [LfoParams] out of iterators to
 /// its constituent parts.
-pub struct LfoParamIter<T: DspFormatBase, F, D, O>
+pub struct LfoParamIter<T: DspFormatBase, F, D, O, P>
 where
     F: Iterator<Item = T::LfoFreq>,
     D: Iterator<Item = T::Scalar>,
     O: Iterator<Item = LfoOptions>,
+    P: Iterator<Item = T::Scalar>,
 {
     f: F,
     d: D,
     o: O,
+    p: P,
     phantom: core::marker::PhantomData<T>,
 }
 
-impl<T: DspFormatBase, F, D, O> LfoParamIter<T, F, D, O>
+impl<T: DspFormatBase, F, D, O, P> LfoParamIter<T, F, D, O, P>
 where
     F: Iterator<Item = T::LfoFreq>,
     D: Iterator<Item = T::Scalar>,
     O: Iterator<Item = LfoOptions>,
+    P: Iterator<Item = T::Scalar>,
 {
     /// Replace the current frequenchy source with the one provided
     pub fn with_freq<New: Iterator<Item = T::LfoFreq>>(
         self,
         new: New,
-    ) -> LfoParamIter<T, New, D, O> {
+    ) -> LfoParamIter<T, New, D, O, P> {
         LfoParamIter {
             f: new,
             d: self.d,
             o: self.o,
+            p: self.p,
             phantom: self.phantom,
         }
     }
@@ -38,11 +42,12 @@ where
     pub fn with_depth<New: Iterator<Item = T::Scalar>>(
         self,
         new: New,
-    ) -> LfoParamIter<T, F, New, O> {
+    ) -> LfoParamIter<T, F, New, O, P> {
         LfoParamIter {
             f: self.f,
             d: new,
             o: self.o,
+            p: self.p,
             phantom: self.phantom,
         }
     }
@@ -50,21 +55,36 @@ where
     pub fn with_options<New: Iterator<Item = LfoOptions>>(
         self,
         new: New,
-    ) -> LfoParamIter<T, F, D, New> {
+    ) -> LfoParamIter<T, F, D, New, P> {
         LfoParamIter {
             f: self.f,
             d: self.d,
             o: new,
+            p: self.p,
+            phantom: self.phantom,
+        }
+    }
+    /// Replace the current phase offset source with the one provided
+    pub fn with_phase_offset<New: Iterator<Item = T::Scalar>>(
+        self,
+        new: New,
+    ) -> LfoParamIter<T, F, D, O, New> {
+        LfoParamIter {
+            f: self.f,
+            d: self.d,
+            o: self.o,
+            p: new,
             phantom: self.phantom,
         }
     }
 }
 
-impl<T: DspFormatBase, F, D, O> Iterator for LfoParamIter<T, F, D, O>
+impl<T: DspFormatBase, F, D, O, P> Iterator for LfoParamIter<T, F, D, O, P>
 where
     F: Iterator<Item = T::LfoFreq>,
     D: Iterator<Item = T::Scalar>,
     O: Iterator<Item = LfoOptions>,
+    P: Iterator<Item = T::Scalar>,
 {
     type Item = LfoParams<T>;
     fn next(&mut self) -> Option<LfoParams<T>> {
@@ -72,20 +92,22 @@ where
             freq: self.f.next()?,
             depth: self.d.next()?,
             opts: self.o.next()?,
+            phase_offset: self.p.next()?,
         })
     }
 }
 
 /// Create a new [LfoParamIter], which initially creates instances of
-/// [LfoParams] with frequency 1Hz, depth 1, and default [LfoOptions] until
-/// calling the `with_*()` methods.
+/// [LfoParams] with frequency 1Hz, depth 1, no phase offset, and default
+/// [LfoOptions] until calling the `with_*()` methods.
 #[allow(clippy::type_complexity)]
 pub fn new_lfo_param_iter<T: DspFormatBase>(
-) -> LfoParamIter<T, Repeat<T::LfoFreq>, Repeat<T::Scalar>, Repeat<LfoOptions>> {
+) -> LfoParamIter<T, Repeat<T::LfoFreq>, Repeat<T::Scalar>, Repeat<LfoOptions>, Repeat<T::Scalar>> {
     LfoParamIter {
         f: repeat(T::LfoFreq::one()),
         d: repeat(T::Scalar::one()),
         o: repeat(LfoOptions::default()),
+        p: repeat(T::Scalar::zero()),
         phantom: Default::default(),
     }
 }
@@ -4,7 +4,7 @@ use super::*;
 ///
 /// Use this to easily build iterators to [MixOscParams] out of iterators to
 /// its constituent parts.
-pub struct MixOscParamIter<T, A, B, C, D, E, F>
+pub struct MixOscParamIter<T, A, B, C, D, E, F, G, H>
 where
     T: DspFormatBase,
     A: Iterator<Item = T::NoteOffset>,
@@ -13,6 +13,8 @@ where
     D: Iterator<Item = T::Scalar>,
     E: Iterator<Item = T::Scalar>,
     F: Iterator<Item = T::Scalar>,
+    G: Iterator<Item = T::Scalar>,
+    H: Iterator<Item = Option<T::Scalar>>,
 {
     tune: A,
     shape: B,
@@ -20,10 +22,12 @@ where
     sq: D,
     tri: E,
     saw: F,
+    osc_level: G,
+    wave_morph: H,
     phantom: core::marker::PhantomData<T>,
 }
 
-impl<T, A, B, C, D, E, F> MixOscParamIter<T, A, B, C, D, E, F>
+impl<T, A, B, C, D, E, F, G, H> MixOscParamIter<T, A, B, C, D, E, F, G, H>
 where
     T: DspFormatBase,
     A: Iterator<Item = T::NoteOffset>,
@@ -32,12 +36,14 @@ where
     D: Iterator<Item = T::Scalar>,
     E: Iterator<Item = T::Scalar>,
     F: Iterator<Item = T::Scalar>,
+    G: Iterator<Item = T::Scalar>,
+    H: Iterator<Item = Option<T::Scalar>>,
 {
     /// Replace the current tuning source with the one provided
     pub fn with_tune<New: Iterator<Item = T::NoteOffset>>(
         self,
         new: New,
-    ) -> MixOscParamIter<T, New, B, C, D, E, F> {
+    ) -> MixOscParamIter<T, New, B, C, D, E, F, G, H> {
         MixOscParamIter {
             tune: new,
             shape: self.shape,
@@ -45,6 +51,8 @@ where
             sq: self.sq,
             tri: self.tri,
             saw: self.saw,
+            osc_level: self.osc_level,
+            wave_morph: self.wave_morph,
             phantom: self.phantom,
         }
     }
@@ -53,7 +61,7 @@ where
     pub fn with_shape<New: Iterator<Item = T::Scalar>>(
         self,
         new: New,
-    ) -> MixOscParamIter<T, A, New, C, D, E, F> {
+    ) -> MixOscParamIter<T, A, New, C, D, E, F, G, H> {
         MixOscParamIter {
             tune: self.tune,
             shape: new,
@@ -61,6 +69,8 @@ where
             sq: self.sq,
             tri: self.tri,
             saw: self.saw,
+            osc_level: self.osc_level,
+            wave_morph: self.wave_morph,
             phantom: self.phantom,
         }
     }
@@ -68,7 +78,7 @@ where
     pub fn with_sin<New: Iterator<Item = T::Scalar>>(
         self,
         new: New,
-    ) -> MixOscParamIter<T, A, B, New, D, E, F> {
+    ) -> MixOscParamIter<T, A, B, New, D, E, F, G, H> {
         MixOscParamIter {
             tune: self.tune,
             shape: self.shape,
@@ -76,6 +86,8 @@ where
             sq: self.sq,
             tri: self.tri,
             saw: self.saw,
+            osc_level: self.osc_level,
+            wave_morph: self.wave_morph,
             phantom: self.phantom,
         }
     }
@@ -83,7 +95,7 @@ where
     pub fn with_sq<New: Iterator<Item = T::Scalar>>(
         self,
         new: New,
-    ) -> MixOscParamIter<T, A, B, C, New, E, F> {
+    ) -> MixOscParamIter<T, A, B, C, New, E, F, G, H> {
         MixOscParamIter {
             tune: self.tune,
             shape: self.shape,
@@ -91,6 +103,8 @@ where
             sq: new,
             tri: self.tri,
             saw: self.saw,
+            osc_level: self.osc_level,
+            wave_morph: self.wave_morph,
             phantom: self.phantom,
         }
     }
@@ -98,7 +112,7 @@ where
     pub fn with_tri<New: Iterator<Item = T::Scalar>>(
         self,
         new: New,
-    ) -> MixOscParamIter<T, A, B, C, D, New, F> {
+    ) -> MixOscParamIter<T, A, B, C, D, New, F, G, H> {
         MixOscParamIter {
             tune: self.tune,
             shape: self.shape,
@@ -106,6 +120,8 @@ where
             sq: self.sq,
             tri: new,
             saw: self.saw,
+            osc_level: self.osc_level,
+            wave_morph: self.wave_morph,
             phantom: self.phantom,
         }
     }
@@ -113,7 +129,7 @@ where
     pub fn with_saw<New: Iterator<Item = T::Scalar>>(
         self,
         new: New,
-    ) -> MixOscParamIter<T, A, B, C, D, E, New> {
+    ) -> MixOscParamIter<T, A, B, C, D, E, New, G, H> {
         MixOscParamIter {
             tune: self.tune,
             shape: self.shape,
@@ -121,12 +137,48 @@ where
             sq: self.sq,
             tri: self.tri,
             saw: new,
+            osc_level: self.osc_level,
+            wave_morph: self.wave_morph,
+            phantom: self.phantom,
+        }
+    }
+    /// Replace the current output level source with the one provided
+    pub fn with_osc_level<New: Iterator<Item = T::Scalar>>(
+        self,
+        new: New,
+    ) -> MixOscParamIter<T, A, B, C, D, E, F, New, H> {
+        MixOscParamIter {
+            tune: self.tune,
+            shape: self.shape,
+            sin: self.sin,
+            sq: self.sq,
+            tri: self.tri,
+            saw: self.saw,
+            osc_level: new,
+            wave_morph: self.wave_morph,
+            phantom: self.phantom,
+        }
+    }
+    /// Replace the current wave morph source with the one provided
+    pub fn with_wave_morph<New: Iterator<Item = Option<T::Scalar>>>(
+        self,
+        new: New,
+    ) -> MixOscParamIter<T, A, B, C, D, E, F, G, New> {
+        MixOscParamIter {
+            tune: self.tune,
+            shape: self.shape,
+            sin: self.sin,
+            sq: self.sq,
+            tri: self.tri,
+            saw: self.saw,
+            osc_level: self.osc_level,
+            wave_morph: new,
             phantom: self.phantom,
         }
     }
 }
 
-impl<T, A, B, C, D, E, F> Iterator for MixOscParamIter<T, A, B, C, D, E, F>
+impl<T, A, B, C, D, E, F, G, H> Iterator for MixOscParamIter<T, A, B, C, D, E, F, G, H>
 where
     T: DspFormatBase,
     A: Iterator<Item = T::NoteOffset>,
@@ -135,6 +187,8 @@ where
     D: Iterator<Item = T::Scalar>,
     E: Iterator<Item = T::Scalar>,
     F: Iterator<Item = T::Scalar>,
+    G: Iterator<Item = T::Scalar>,
+    H: Iterator<Item = Option<T::Scalar>>,
 {
     type Item = MixOscParams<T>;
     fn next(&mut self) -> Option<MixOscParams<T>> {
@@ -145,6 +199,10 @@ where
             sq: self.sq.next()?,
             tri: self.tri.next()?,
             saw: self.saw.next()?,
+            osc_level: self.osc_level.next()?,
+            wave_morph: self.wave_morph.next()?,
+            invert: false,
+            key_sync: false,
         })
     }
 }
@@ -161,6 +219,8 @@ pub fn new_mixosc_param_iter<T: DspFormatBase>() -> MixOscParamIter<
     Repeat<T::Scalar>,
     Repeat<T::Scalar>,
     Repeat<T::Scalar>,
+    Repeat<T::Scalar>,
+    Repeat<Option<T::Scalar>>,
 > {
     MixOscParamIter {
         tune: repeat(T::NoteOffset::zero()),
@@ -169,6 +229,8 @@ pub fn new_mixosc_param_iter<T: DspFormatBase>() -> MixOscParamIter<
         sq: repeat(T::Scalar::zero()),
         tri: repeat(T::Scalar::zero()),
         saw: repeat(T::Scalar::one()),
+        osc_level: repeat(T::Scalar::one()),
+        wave_morph: repeat(None),
         phantom: Default::default(),
     }
 }
@@ -177,35 +239,39 @@ pub fn new_mixosc_param_iter<T: DspFormatBase>() -> MixOscParamIter<
 ///
 /// Use this to easily build iterators to [SyncedMixOscsParams] out of iterators
 /// to its constituent parts.
-pub struct SyncedMixOscsParamIter<T, A, B, C>
+pub struct SyncedMixOscsParamIter<T, A, B, C, D>
 where
     T: DspFormatBase,
     A: Iterator<Item = MixOscParams<T>>,
     B: Iterator<Item = MixOscParams<T>>,
-    C: Iterator<Item = bool>,
+    C: Iterator<Item = OscSyncMode>,
+    D: Iterator<Item = T::Scalar>,
 {
     primary: A,
     secondary: B,
     sync: C,
+    fm_index: D,
     phantom: core::marker::PhantomData<T>,
 }
 
-impl<T, A, B, C> SyncedMixOscsParamIter<T, A, B, C>
+impl<T, A, B, C, D> SyncedMixOscsParamIter<T, A, B, C, D>
 where
     T: DspFormatBase,
     A: Iterator<Item = MixOscParams<T>>,
     B: Iterator<Item = MixOscParams<T>>,
-    C: Iterator<Item = bool>,
+    C: Iterator<Item = OscSyncMode>,
+    D: Iterator<Item = T::Scalar>,
 {
     /// Replace the current tuning source with the one provided
     pub fn with_primary<New: Iterator<Item = MixOscParams<T>>>(
         self,
         new: New,
-    ) -> SyncedMixOscsParamIter<T, New, B, C> {
+    ) -> SyncedMixOscsParamIter<T, New, B, C, D> {
         SyncedMixOscsParamIter {
             primary: new,
             secondary: self.secondary,
             sync: self.sync,
+            fm_index: self.fm_index,
             phantom: self.phantom,
         }
     }
@@ -213,34 +279,50 @@ where
     pub fn with_secondary<New: Iterator<Item = MixOscParams<T>>>(
         self,
         new: New,
-    ) -> SyncedMixOscsParamIter<T, A, New, C> {
+    ) -> SyncedMixOscsParamIter<T, A, New, C, D> {
         SyncedMixOscsParamIter {
             primary: self.primary,
             secondary: new,
             sync: self.sync,
+            fm_index: self.fm_index,
             phantom: self.phantom,
         }
     }
     /// Replace the current tuning source with the one provided
-    pub fn with_sync<New: Iterator<Item = bool>>(
+    pub fn with_sync<New: Iterator<Item = OscSyncMode>>(
         self,
         new: New,
-    ) -> SyncedMixOscsParamIter<T, A, B, New> {
+    ) -> SyncedMixOscsParamIter<T, A, B, New, D> {
         SyncedMixOscsParamIter {
             primary: self.primary,
             secondary: self.secondary,
             sync: new,
+            fm_index: self.fm_index,
+            phantom: self.phantom,
+        }
+    }
+    /// Replace the current phase modulation index source with the one provided
+    pub fn with_fm_index<New: Iterator<Item = T::Scalar>>(
+        self,
+        new: New,
+    ) -> SyncedMixOscsParamIter<T, A, B, C, New> {
+        SyncedMixOscsParamIter {
+            primary: self.primary,
+            secondary: self.secondary,
+            sync: self.sync,
+            fm_index: new,
             phantom: self.phantom,
         }
     }
 }
 
-impl<T, A, B, C> Iterator for SyncedMixOscsParamIter<T, A, B, C>
+impl<T, A, B, C, D> Iterator for SyncedMixOscsParamIter<T, A, B, C, D>
 where
     T: DspFormatBase,
     A: Iterator<Item = MixOscParams<T>>,
     B: Iterator<Item = MixOscParams<T>>,
-    C: Iterator<Item = bool>,
+    C: Iterator<Item = OscSyncMode>,
+    D: Iterator<Item = T::Scalar>,
 {
     type Item = SyncedMixOscsParams<T>;
     fn next(&mut self) -> Option<SyncedMixOscsParams<T>> {
@@ -248,6 +330,7 @@ where
             primary: self.primary.next()?,
             secondary: self.secondary.next()?,
             sync: self.sync.next()?,
+            fm_index: self.fm_index.next()?,
         })
     }
 }
@@ -255,8 +338,13 @@ where
 /// Create a new [SyncedMixOscsParamIter], which initially creates instances of
 /// [SyncedMixOscsParams] with the defaults for each
 #[allow(clippy::type_complexity)]
-pub fn new_synced_mixoscs_param_iter<T: DspFormatBase>(
-) -> SyncedMixOscsParamIter<T, Repeat<MixOscParams<T>>, Repeat<MixOscParams<T>>, Repeat<bool>> {
+pub fn new_synced_mixoscs_param_iter<T: DspFormatBase>() -> SyncedMixOscsParamIter<
+    T,
+    Repeat<MixOscParams<T>>,
+    Repeat<MixOscParams<T>>,
+    Repeat<OscSyncMode>,
+    Repeat<T::Scalar>,
+> {
     SyncedMixOscsParamIter {
         primary: repeat(MixOscParams {
             tune: T::NoteOffset::zero(),
@@ -265,6 +353,10 @@ pub fn new_synced_mixoscs_param_iter<T: DspFormatBase>(
             sq: T::Scalar::zero(),
             tri: T::Scalar::zero(),
             saw: T::Scalar::one(),
+            osc_level: T::Scalar::one(),
+            wave_morph: None,
+            invert: false,
+            key_sync: false,
         }),
         secondary: repeat(MixOscParams {
             tune: T::NoteOffset::zero(),
@@ -273,8 +365,13 @@ pub fn new_synced_mixoscs_param_iter<T: DspFormatBase>(
             sq: T::Scalar::zero(),
             tri: T::Scalar::zero(),
             saw: T::Scalar::one(),
+            osc_level: T::Scalar::one(),
+            wave_morph: None,
+            invert: false,
+            key_sync: false,
         }),
-        sync: repeat(false),
+        sync: repeat(OscSyncMode::Off),
+        fm_index: repeat(T::Scalar::zero()),
         phantom: Default::default(),
     }
 }
@@ -197,12 +197,15 @@ where
         Some(ModFiltParams {
             env_mod: self.env_mod.next()?,
             vel_mod: self.vel_mod.next()?,
+            env_vel: T::Scalar::zero(),
             kbd_tracking: self.kbd_tracking.next()?,
             cutoff: self.cutoff.next()?,
             resonance: self.resonance.next()?,
             low_mix: self.low_mix.next()?,
             band_mix: self.band_mix.next()?,
             high_mix: self.high_mix.next()?,
+            drive: T::Scalar::zero(),
+            poles: FilterPoles::Two,
         })
     }
 }
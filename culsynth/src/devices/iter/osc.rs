@@ -74,35 +74,39 @@ pub fn new_osc_param_iter<T: DspFormatBase>(
 ///
 /// Use this to easily build iterators to [SyncedOscsParams] out of iterators
 /// to its constituent parts.
-pub struct SyncedOscsParamIter<T, A, B, C>
+pub struct SyncedOscsParamIter<T, A, B, C, D>
 where
     T: DspFormatBase,
     A: Iterator<Item = OscParams<T>>,
     B: Iterator<Item = OscParams<T>>,
-    C: Iterator<Item = bool>,
+    C: Iterator<Item = OscSyncMode>,
+    D: Iterator<Item = T::Scalar>,
 {
     primary: A,
     secondary: B,
     sync: C,
+    fm_index: D,
     phantom: core::marker::PhantomData<T>,
 }
 
-impl<T, A, B, C> SyncedOscsParamIter<T, A, B, C>
+impl<T, A, B, C, D> SyncedOscsParamIter<T, A, B, C, D>
 where
     T: DspFormatBase,
     A: Iterator<Item = OscParams<T>>,
     B: Iterator<Item = OscParams<T>>,
-    C: Iterator<Item = bool>,
+    C: Iterator<Item = OscSyncMode>,
+    D: Iterator<Item = T::Scalar>,
 {
     /// Replace the current primary OscParams source with the one provided
     pub fn with_primary<New: Iterator<Item = OscParams<T>>>(
         self,
         new: New,
-    ) -> SyncedOscsParamIter<T, New, B, C> {
+    ) -> SyncedOscsParamIter<T, New, B, C, D> {
         SyncedOscsParamIter {
             primary: new,
             secondary: self.secondary,
             sync: self.sync,
+            fm_index: self.fm_index,
             phantom: self.phantom,
         }
     }
@@ -110,34 +114,50 @@ where
     pub fn with_secondary<New: Iterator<Item = OscParams<T>>>(
         self,
         new: New,
-    ) -> SyncedOscsParamIter<T, A, New, C> {
+    ) -> SyncedOscsParamIter<T, A, New, C, D> {
         SyncedOscsParamIter {
             primary: self.primary,
             secondary: new,
             sync: self.sync,
+            fm_index: self.fm_index,
             phantom: self.phantom,
         }
     }
     /// Replace the current oscillator sync source with the one provided
-    pub fn with_sync<New: Iterator<Item = bool>>(
+    pub fn with_sync<New: Iterator<Item = OscSyncMode>>(
         self,
         new: New,
-    ) -> SyncedOscsParamIter<T, A, B, New> {
+    ) -> SyncedOscsParamIter<T, A, B, New, D> {
         SyncedOscsParamIter {
             primary: self.primary,
             secondary: self.secondary,
             sync: new,
+            fm_index: self.fm_index,
+            phantom: self.phantom,
+        }
+    }
+    /// Replace the current phase modulation index source with the one provided
+    pub fn with_fm_index<New: Iterator<Item = T::Scalar>>(
+        self,
+        new: New,
+    ) -> SyncedOscsParamIter<T, A, B, C, New> {
+        SyncedOscsParamIter {
+            primary: self.primary,
+            secondary: self.secondary,
+            sync: self.sync,
+            fm_index: new,
             phantom: self.phantom,
         }
     }
 }
 
-impl<T, A, B, C> Iterator for SyncedOscsParamIter<T, A, B, C>
+impl<T, A, B, C, D> Iterator for SyncedOscsParamIter<T, A, B, C, D>
 where
     T: DspFormatBase,
     A: Iterator<Item = OscParams<T>>,
     B: Iterator<Item = OscParams<T>>,
-    C: Iterator<Item = bool>,
+    C: Iterator<Item = OscSyncMode>,
+    D: Iterator<Item = T::Scalar>,
 {
     type Item = SyncedOscsParams<T>;
     fn next(&mut self) -> Option<SyncedOscsParams<T>> {
@@ -145,6 +165,7 @@ where
             primary: self.primary.next()?,
             secondary: self.secondary.next()?,
             sync: self.sync.next()?,
+            fm_index: self.fm_index.next()?,
         })
     }
 }
@@ -152,8 +173,13 @@ where
 /// Create a new [SyncedOscsParamIter], which initially creates instances of
 /// [SyncedOscsParams] with the defaults for each
 #[allow(clippy::type_complexity)]
-pub fn new_synced_oscs_param_iter<T: DspFormatBase>(
-) -> SyncedOscsParamIter<T, Repeat<OscParams<T>>, Repeat<OscParams<T>>, Repeat<bool>> {
+pub fn new_synced_oscs_param_iter<T: DspFormatBase>() -> SyncedOscsParamIter<
+    T,
+    Repeat<OscParams<T>>,
+    Repeat<OscParams<T>>,
+    Repeat<OscSyncMode>,
+    Repeat<T::Scalar>,
+> {
     SyncedOscsParamIter {
         primary: repeat(OscParams {
             tune: T::NoteOffset::zero(),
@@ -163,7 +189,8 @@ pub fn new_synced_oscs_param_iter<T: DspFormatBase>(
             tune: T::NoteOffset::zero(),
             shape: T::Scalar::zero(),
         }),
-        sync: repeat(false),
+        sync: repeat(OscSyncMode::Off),
+        fm_index: repeat(T::Scalar::zero()),
         phantom: Default::default(),
     }
 }
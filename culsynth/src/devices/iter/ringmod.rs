@@ -77,35 +77,39 @@ pub fn new_ringmod_input_iter<T: DspFormatBase>(
 ///
 /// Use this to easily build iterators to [RingModParams] out of iterators
 /// to its constituent parts.
-pub struct RingModParamIter<T, A, B, C>
+pub struct RingModParamIter<T, A, B, C, D>
 where
     T: DspFormatBase,
     A: Iterator<Item = T::Scalar>,
     B: Iterator<Item = T::Scalar>,
     C: Iterator<Item = T::Scalar>,
+    D: Iterator<Item = T::Scalar>,
 {
     mix_a: A,
     mix_b: B,
     mix_mod: C,
+    spread: D,
     phantom: core::marker::PhantomData<T>,
 }
 
-impl<T, A, B, C> RingModParamIter<T, A, B, C>
+impl<T, A, B, C, D> RingModParamIter<T, A, B, C, D>
 where
     T: DspFormatBase,
     A: Iterator<Item = T::Scalar>,
     B: Iterator<Item = T::Scalar>,
     C: Iterator<Item = T::Scalar>,
+    D: Iterator<Item = T::Scalar>,
 {
     /// Replace the current carrier gain source with the one provided
     pub fn with_mix_a<New: Iterator<Item = T::Scalar>>(
         self,
         new: New,
-    ) -> RingModParamIter<T, New, B, C> {
+    ) -> RingModParamIter<T, New, B, C, D> {
         RingModParamIter {
             mix_a: new,
             mix_b: self.mix_b,
             mix_mod: self.mix_mod,
+            spread: self.spread,
             phantom: self.phantom,
         }
     }
@@ -113,11 +117,12 @@ where
     pub fn with_mix_b<New: Iterator<Item = T::Scalar>>(
         self,
         new: New,
-    ) -> RingModParamIter<T, A, New, C> {
+    ) -> RingModParamIter<T, A, New, C, D> {
         RingModParamIter {
             mix_a: self.mix_a,
             mix_b: new,
             mix_mod: self.mix_mod,
+            spread: self.spread,
             phantom: self.phantom,
         }
     }
@@ -125,22 +130,37 @@ where
     pub fn with_mix_mod<New: Iterator<Item = T::Scalar>>(
         self,
         new: New,
-    ) -> RingModParamIter<T, A, B, New> {
+    ) -> RingModParamIter<T, A, B, New, D> {
         RingModParamIter {
             mix_a: self.mix_a,
             mix_b: self.mix_b,
             mix_mod: new,
+            spread: self.spread,
+            phantom: self.phantom,
+        }
+    }
+    /// Replace the current stereo spread source with the one provided
+    pub fn with_spread<New: Iterator<Item = T::Scalar>>(
+        self,
+        new: New,
+    ) -> RingModParamIter<T, A, B, C, New> {
+        RingModParamIter {
+            mix_a: self.mix_a,
+            mix_b: self.mix_b,
+            mix_mod: self.mix_mod,
+            spread: new,
             phantom: self.phantom,
         }
     }
 }
 
-impl<T, A, B, C> Iterator for RingModParamIter<T, A, B, C>
+impl<T, A, B, C, D> Iterator for RingModParamIter<T, A, B, C, D>
 where
     T: DspFormatBase,
     A: Iterator<Item = T::Scalar>,
     B: Iterator<Item = T::Scalar>,
     C: Iterator<Item = T::Scalar>,
+    D: Iterator<Item = T::Scalar>,
 {
     type Item = RingModParams<T>;
     fn next(&mut self) -> Option<RingModParams<T>> {
@@ -148,20 +168,23 @@ where
             mix_a: self.mix_a.next()?,
             mix_b: self.mix_b.next()?,
             mix_mod: self.mix_mod.next()?,
+            spread: self.spread.next()?,
         })
     }
 }
 
 /// Create a new [RingModParamIter], which initially creates instances of
-/// [RingModParams] with the unity gain for the carrier signal and zero gain
-/// for the modulator and output signals
+/// [RingModParams] with the unity gain for the carrier signal, zero gain
+/// for the modulator and output signals, and no stereo spread
 #[allow(clippy::type_complexity)]
 pub fn new_ringmod_param_iter<T: DspFormatBase>(
-) -> RingModParamIter<T, Repeat<T::Scalar>, Repeat<T::Scalar>, Repeat<T::Scalar>> {
+) -> RingModParamIter<T, Repeat<T::Scalar>, Repeat<T::Scalar>, Repeat<T::Scalar>, Repeat<T::Scalar>>
+{
     RingModParamIter {
         mix_a: repeat(T::Scalar::one()),
         mix_b: repeat(T::Scalar::zero()),
         mix_mod: repeat(T::Scalar::zero()),
+        spread: repeat(T::Scalar::zero()),
         phantom: Default::default(),
     }
 }
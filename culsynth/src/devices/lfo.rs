@@ -1,6 +1,6 @@
 use super::*;
+use crate::util::{GateDetect, GateEdge};
 use crate::{IScalarFxP, PhaseFxP};
-use core::mem::transmute;
 use core::option::Option;
 use rand::{rngs::SmallRng, RngCore, SeedableRng};
 
@@ -12,7 +12,9 @@ pub(crate) mod detail {
 
     pub trait LfoOps: crate::DspFormatBase {
         fn phase_per_smp(context: &Self::Context, frequency: Self::LfoFreq) -> Self::Phase;
+        fn offset_phase(phase: Self::Phase, offset: Self::Scalar) -> Self::Phase;
         fn calc_lfo(
+            context: &Self::Context,
             phase: Self::Phase,
             wave: lfo::LfoWave,
             rands: &[Self::Sample; 2],
@@ -21,7 +23,7 @@ pub(crate) mod detail {
 }
 
 #[repr(transparent)]
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, PartialEq, Eq)]
 /// A struct to package together the various LFO configuration options in one
 /// convenient struct that fits in 16 bits.  We could get away with packing
 /// it in 8 bits, but we'll use 16 to allow for future expansion
@@ -32,6 +34,7 @@ pub struct LfoOptions {
 impl LfoOptions {
     const BIPOLAR: u16 = 1 << 8;
     const RETRIGGER: u16 = 1 << 9;
+    const EXTERNAL_CLOCK: u16 = 1 << 10;
     /// The LFO Waveform (Sine, Square, Sample+Hold, etc.)
     pub fn wave(&self) -> Option<LfoWave> {
         let value = (self.bits & 0xFF) as u8;
@@ -45,6 +48,23 @@ impl LfoOptions {
     pub fn retrigger(&self) -> bool {
         self.bits & Self::RETRIGGER != 0
     }
+    /// Are new random values for the sample and hold/glide waveforms taken
+    /// on the rising edge of an external clock (see [Lfo::next_with_clock])
+    /// rather than at the LFO's own rate?
+    pub fn external_clock(&self) -> bool {
+        self.bits & Self::EXTERNAL_CLOCK != 0
+    }
+    /// Returns a copy of these options with the external clock mode set (see
+    /// [LfoOptions::external_clock])
+    pub fn with_external_clock(self, external_clock: bool) -> Self {
+        LfoOptions {
+            bits: if external_clock {
+                self.bits | Self::EXTERNAL_CLOCK
+            } else {
+                self.bits & !Self::EXTERNAL_CLOCK
+            },
+        }
+    }
     /// Pack the LFO parameters into a `LfoOptions` value
     pub fn new(wave: LfoWave, bipolar: bool, retrigger: bool) -> Self {
         LfoOptions {
@@ -55,6 +75,17 @@ impl LfoOptions {
     }
 }
 
+impl core::fmt::Debug for LfoOptions {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("LfoOptions")
+            .field("wave", &self.wave())
+            .field("bipolar", &self.bipolar())
+            .field("retrigger", &self.retrigger())
+            .field("external_clock", &self.external_clock())
+            .finish()
+    }
+}
+
 impl Default for LfoOptions {
     /// The default value is a bipolar, retriggering sine wave
     fn default() -> Self {
@@ -62,7 +93,7 @@ impl Default for LfoOptions {
     }
 }
 
-#[derive(Default, Clone, Copy)]
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq)]
 #[repr(u8)]
 /// The LFO waveform in use
 pub enum LfoWave {
@@ -130,16 +161,15 @@ impl From<LfoWave> for &'static str {
 impl TryFrom<u8> for LfoWave {
     type Error = &'static str;
     fn try_from(value: u8) -> Result<Self, &'static str> {
-        if value >= LfoWave::Sine as u8 && value <= LfoWave::SampleGlide as u8 {
-            unsafe { Ok(transmute::<u8, LfoWave>(value)) }
-        } else {
-            Err("Conversion of u8 to LfoWave Overflowed")
-        }
+        Self::ELEM
+            .get(value as usize)
+            .copied()
+            .ok_or("Conversion of u8 to LfoWave Overflowed")
     }
 }
 
 /// A struct packaging together several slices to act as parameters for an LFO
-#[derive(Default, Clone)]
+#[derive(Default, Clone, Debug, PartialEq)]
 pub struct LfoParams<T: DspFormatBase> {
     /// The frequency of the LFO, in Hz
     pub freq: T::LfoFreq,
@@ -147,14 +177,21 @@ pub struct LfoParams<T: DspFormatBase> {
     pub depth: T::Scalar,
     /// The options, including waveform and retriggering (see [LfoOptions])
     pub opts: LfoOptions,
+    /// The phase offset, as a fraction of a cycle between 0 and 1.  This is
+    /// applied when reading the waveform, so two LFOs at the same rate with
+    /// offsets 0.25 apart will be in quadrature.  This has no effect on the
+    /// random (sample and hold/glide) waveforms.
+    pub phase_offset: T::Scalar,
 }
 
+#[cfg(feature = "float")]
 impl<T: DspFloat> From<&LfoParams<i16>> for LfoParams<T> {
     fn from(value: &LfoParams<i16>) -> Self {
         LfoParams::<T> {
             freq: value.freq.to_num(),
             depth: value.depth.to_num(),
             opts: value.opts,
+            phase_offset: value.phase_offset.to_num(),
         }
     }
 }
@@ -162,25 +199,35 @@ impl<T: DspFloat> From<&LfoParams<i16>> for LfoParams<T> {
 /// An LFO
 #[derive(Clone)]
 pub struct Lfo<T: DspFormatBase + detail::LfoOps> {
+    seed: u64,
     rng: SmallRng,
     phase: T::Phase,
     rand_smps: [T::Sample; 2],
-    last_gate: bool,
+    gate: GateDetect,
+    clock: GateDetect,
 }
 
 impl<T: DspFormatBase + detail::LfoOps> Lfo<T> {
     /// Constructor
     pub fn new(seed: u64) -> Self {
         let mut retval = Self {
+            seed,
             rng: SmallRng::seed_from_u64(seed),
             phase: T::Phase::zero(),
             rand_smps: [T::Sample::zero(); 2],
-            last_gate: false,
+            gate: GateDetect::new(),
+            clock: GateDetect::new(),
         };
         retval.update_rands();
         retval.update_rands();
         retval
     }
+    /// Reset the LFO to its initial state, with zero phase, and reseed the
+    /// random number generator so the sample and hold/glide waveforms repeat
+    /// the same sequence of values as a newly constructed LFO
+    pub fn reset(&mut self) {
+        *self = Self::new(self.seed);
+    }
     fn update_rands(&mut self) {
         self.rand_smps[1] = self.rand_smps[0];
         let rand_num = self.rng.next_u32() & (u16::MAX as u32);
@@ -189,32 +236,73 @@ impl<T: DspFormatBase + detail::LfoOps> Lfo<T> {
     }
 }
 
-impl<T: DspFormat> Device<T> for Lfo<T> {
-    type Input = bool;
-    type Params = LfoParams<T>;
-    type Output = T::Sample;
-    /// Generate the LFO signal
-    fn next(&mut self, context: &T::Context, gate: bool, params: LfoParams<T>) -> T::Sample {
-        if params.opts.retrigger() && gate && !self.last_gate {
+impl<T: DspFormat> Lfo<T> {
+    /// This is the same as [Device::next], but takes an additional `clock`
+    /// input.  If [LfoOptions::external_clock] is set, the sample and
+    /// hold/glide waveforms take a new random value on each rising edge of
+    /// `clock` instead of once per LFO cycle.
+    pub fn next_with_clock(
+        &mut self,
+        context: &T::Context,
+        gate: bool,
+        clock: bool,
+        params: LfoParams<T>,
+    ) -> T::Sample {
+        self.next_with_trigger(context, gate, clock, params).0
+    }
+    /// This is the same as [Lfo::next_with_clock], but also returns a trigger
+    /// signal, which is true for one sample per cycle: the sample on which
+    /// the phase of the waveform (including [LfoParams::phase_offset])
+    /// crosses zero, or is reset to zero by a retrigger.  This is the rising
+    /// zero crossing of the sine and sawtooth waves, and the rising edge of
+    /// the square wave.  Use this to clock other devices, e.g. the sample and
+    /// hold of another LFO (see [LfoOptions::external_clock]).
+    pub fn next_with_trigger(
+        &mut self,
+        context: &T::Context,
+        gate: bool,
+        clock: bool,
+        params: LfoParams<T>,
+    ) -> (T::Sample, bool) {
+        let clock_edge = self.clock.next(clock) == Some(GateEdge::Rising);
+        let external_clock = params.opts.external_clock();
+        if external_clock && clock_edge {
+            self.update_rands();
+        }
+        if self.gate.next(gate) == Some(GateEdge::Rising) && params.opts.retrigger() {
             self.phase = T::Phase::zero();
         }
-        self.last_gate = gate;
-        let mut value = T::calc_lfo(
-            self.phase,
-            params.opts.wave().unwrap_or_default(),
-            &self.rand_smps,
-        );
+        let wave = params.opts.wave().unwrap_or_default();
+        let phase = match wave {
+            LfoWave::SampleHold | LfoWave::SampleGlide => self.phase,
+            _ => T::offset_phase(self.phase, params.phase_offset),
+        };
+        let mut value = T::calc_lfo(context, phase, wave, &self.rand_smps);
         if !params.opts.bipolar() {
             value = (value + T::Sample::one()).divide_by_two();
         }
         value = value.scale(params.depth);
-        self.phase = self.phase + T::phase_per_smp(context, params.freq);
+        let phase_per_smp = T::phase_per_smp(context, params.freq);
+        let trigger = phase >= T::Phase::zero() && phase < phase_per_smp;
+        self.phase = self.phase + phase_per_smp;
         // Check if we've crossed from positive phase back to negative:
         if self.phase >= T::Phase::PI {
             self.phase = self.phase - T::Phase::TAU;
-            self.update_rands();
+            if !external_clock {
+                self.update_rands();
+            }
         }
-        value
+        (value, trigger)
+    }
+}
+
+impl<T: DspFormat> Device<T> for Lfo<T> {
+    type Input = bool;
+    type Params = LfoParams<T>;
+    type Output = T::Sample;
+    /// Generate the LFO signal
+    fn next(&mut self, context: &T::Context, gate: bool, params: LfoParams<T>) -> T::Sample {
+        self.next_with_clock(context, gate, false, params)
     }
 }
 
@@ -225,7 +313,12 @@ impl<T: DspFormatBase + detail::LfoOps> Default for Lfo<T> {
 }
 
 impl detail::LfoOps for i16 {
-    fn calc_lfo(phase: PhaseFxP, wave: lfo::LfoWave, rands: &[SampleFxP; 2]) -> SampleFxP {
+    fn calc_lfo(
+        _: &ContextFxP,
+        phase: PhaseFxP,
+        wave: lfo::LfoWave,
+        rands: &[SampleFxP; 2],
+    ) -> SampleFxP {
         use crate::fixed_traits::Fixed16;
         use crate::fixedmath::{cos_fixed, sin_fixed};
         const TWO: SampleFxP = SampleFxP::lit("2");
@@ -250,16 +343,16 @@ impl detail::LfoOps for i16 {
             }
             LfoWave::Sine => {
                 if phase < PhaseFxP::FRAC_PI_2.unwrapped_neg() {
-                    // phase in [-pi, pi/2)
+                    // phase in [-pi, -pi/2)
                     // Use the identity sin(x) = -cos(x+pi/2) since our taylor series
                     // approximations are centered about zero and this will be more accurate
                     cos_fixed(SampleFxP::from_num(phase + PhaseFxP::FRAC_PI_2)).unwrapped_neg()
                 } else if phase < PhaseFxP::FRAC_PI_2 {
+                    sin_fixed(SampleFxP::from_num(phase))
+                } else {
                     // phase in [pi/2, pi)
                     // sin(x) = cos(x-pi/2)
                     cos_fixed(SampleFxP::from_num(phase - PhaseFxP::FRAC_PI_2))
-                } else {
-                    sin_fixed(SampleFxP::from_num(phase))
                 }
             }
             LfoWave::SampleHold => rands[0],
@@ -273,10 +366,20 @@ impl detail::LfoOps for i16 {
             frequency.wide_mul(context.sample_rate.frac_2pi4096_sr()).unwrapped_shr(12),
         )
     }
+    fn offset_phase(phase: PhaseFxP, offset: ScalarFxP) -> PhaseFxP {
+        use crate::fixedmath::U3F13;
+        let mut offset = PhaseFxP::from_num(offset.wide_mul(U3F13::TAU));
+        // Map the offset into [-pi, pi) so adding it to the phase cannot overflow
+        if offset >= PhaseFxP::PI {
+            offset -= PhaseFxP::TAU;
+        }
+        crate::fixedmath::wrap_phase(phase + offset)
+    }
 }
 
+#[cfg(feature = "float")]
 impl<T: DspFloat> detail::LfoOps for T {
-    fn calc_lfo(phase: T, wave: lfo::LfoWave, rands: &[T; 2]) -> T {
+    fn calc_lfo(context: &Context<T>, phase: T, wave: lfo::LfoWave, rands: &[T; 2]) -> T {
         let frac_2phase_pi = (phase + phase) / T::PI;
         let pi_2 = T::FRAC_PI_2;
         match wave {
@@ -299,16 +402,16 @@ impl<T: DspFloat> detail::LfoOps for T {
             }
             LfoWave::Sine => {
                 if phase < pi_2.neg() {
-                    // phase in [-pi, pi/2)
+                    // phase in [-pi, -pi/2)
                     // Use the identity sin(x) = -cos(x+pi/2) since our taylor series
                     // approximations are centered about zero and this will be more accurate
-                    T::fcos(phase + pi_2).neg()
+                    context.cos(phase + pi_2).neg()
                 } else if phase < pi_2 {
+                    context.sin(phase)
+                } else {
                     // phase in [pi/2, pi)
                     // sin(x) = cos(x-pi/2)
-                    T::fcos(phase - pi_2)
-                } else {
-                    T::fsin(phase)
+                    context.cos(phase - pi_2)
                 }
             }
             LfoWave::SampleHold => rands[0],
@@ -318,4 +421,146 @@ impl<T: DspFloat> detail::LfoOps for T {
     fn phase_per_smp(context: &Context<T>, frequency: T) -> T {
         (frequency * T::TAU) / context.sample_rate
     }
+    fn offset_phase(phase: T, offset: T) -> T {
+        let phase = phase + offset * T::TAU;
+        if phase >= T::PI {
+            phase - T::TAU
+        } else {
+            phase
+        }
+    }
+}
+
+#[cfg(all(test, feature = "float"))]
+mod tests {
+    use super::*;
+
+    /// The largest difference between a 1Hz triangle LFO with the given phase
+    /// offset and a zero-offset LFO started `lead` samples earlier
+    fn offset_error<T: DspFormat>(ctx: &T::Context, offset: T::Scalar, lead: usize) -> f32 {
+        let params = |phase_offset| LfoParams::<T> {
+            freq: T::LfoFreq::one(),
+            depth: T::Scalar::one(),
+            opts: LfoOptions::new(LfoWave::Triangle, true, false),
+            phase_offset,
+        };
+        let (mut lfo, mut shifted) = (Lfo::<T>::default(), Lfo::<T>::default());
+        let (zero, offset) = (params(T::Scalar::zero()), params(offset));
+        for _ in 0..lead {
+            lfo.next(ctx, false, zero.clone());
+        }
+        (0..24000).fold(0f32, |acc, _| {
+            let a = T::sample_to_float(lfo.next(ctx, false, zero.clone()));
+            let b = T::sample_to_float(shifted.next(ctx, false, offset.clone()));
+            acc.max((a - b).abs())
+        })
+    }
+
+    #[test]
+    fn lfo_phase_offset() {
+        let ctx = Context::new(48000f32);
+        assert!(offset_error::<f32>(&ctx, 0.25, 12000) < 0.001);
+        assert!(offset_error::<f32>(&ctx, 0f32, 12000) > 0.5);
+        assert!(offset_error::<f32>(&ctx, 0.99, 47520) < 0.001);
+        let ctx = ContextFxP::new_480();
+        assert!(offset_error::<i16>(&ctx, ScalarFxP::lit("0.25"), 12000) < 0.01);
+        // Offsets near a full cycle must wrap rather than overflow the phase
+        assert!(offset_error::<i16>(&ctx, ScalarFxP::lit("0.99"), 47520) < 0.01);
+        assert!(offset_error::<i16>(&ctx, ScalarFxP::MAX, 48000) < 0.01);
+    }
+
+    #[test]
+    fn sample_hold_external_clock() {
+        let ctx = Context::new(48000f32);
+        let mut lfo = Lfo::<f32>::default();
+        let params = LfoParams::<f32> {
+            freq: 20f32,
+            depth: 1f32,
+            opts: LfoOptions::new(LfoWave::SampleHold, true, false).with_external_clock(true),
+            phase_offset: 0f32,
+        };
+        let mut run = |clock: bool| {
+            let first = lfo.next_with_clock(&ctx, false, clock, params.clone());
+            // Several LFO cycles with no clock edge should not change the value
+            for _ in 0..10000 {
+                assert_eq!(
+                    lfo.next_with_clock(&ctx, false, clock, params.clone()),
+                    first
+                );
+            }
+            first
+        };
+        let initial = run(false);
+        let latched = run(true);
+        assert_ne!(latched, initial);
+        assert_eq!(run(false), latched);
+        let relatched = run(true);
+        assert_ne!(relatched, latched);
+    }
+
+    /// Run a 10Hz sine LFO for one second, retriggering it partway through,
+    /// and return the index of and LFO output at each trigger
+    fn sine_triggers<T: DspFormat>(ctx: &T::Context, freq: T::LfoFreq) -> [(usize, f32); 11] {
+        let mut lfo = Lfo::<T>::default();
+        let params = LfoParams::<T> {
+            freq,
+            depth: T::Scalar::one(),
+            opts: LfoOptions::new(LfoWave::Sine, true, true),
+            phase_offset: T::Scalar::zero(),
+        };
+        let mut triggers = [(0, 0f32); 11];
+        let mut found = 0;
+        for i in 0..48000 {
+            let (value, trigger) = lfo.next_with_trigger(ctx, i >= 21000, false, params.clone());
+            if trigger {
+                triggers[found] = (i, T::sample_to_float(value));
+                found += 1;
+            }
+        }
+        assert_eq!(found, triggers.len());
+        triggers
+    }
+
+    #[test]
+    fn one_trigger_per_cycle() {
+        let float = sine_triggers::<f32>(&Context::new(48000f32), 10f32);
+        let fixed = sine_triggers::<i16>(&ContextFxP::new_480(), crate::LfoFreqFxP::lit("10"));
+        for triggers in [float, fixed] {
+            // Five cycles, then the retrigger, then five more cycles
+            assert_eq!(triggers[0].0, 0);
+            assert_eq!(triggers[5].0, 21000);
+            for (i, w) in triggers.windows(2).enumerate() {
+                if i != 4 {
+                    assert!(w[1].0.abs_diff(w[0].0 + 4800) < 5, "{triggers:?}");
+                }
+            }
+            // Each trigger is at the rising zero crossing
+            assert!(triggers.iter().all(|(_, x)| x.abs() < 0.01), "{triggers:?}");
+        }
+    }
+
+    /// The largest difference between a 1Hz sine LFO and an exact sine wave
+    /// over one cycle
+    fn sine_error<T: DspFormat>(ctx: &T::Context) -> f32 {
+        let mut lfo = Lfo::<T>::default();
+        let params = LfoParams::<T> {
+            freq: T::LfoFreq::one(),
+            depth: T::Scalar::one(),
+            opts: LfoOptions::new(LfoWave::Sine, true, false),
+            phase_offset: T::Scalar::zero(),
+        };
+        (0..48000).fold(0f32, |acc, i| {
+            let value = T::sample_to_float(lfo.next(ctx, false, params.clone()));
+            let expected = (core::f32::consts::TAU * i as f32 / 48000f32).sin();
+            acc.max((value - expected).abs())
+        })
+    }
+
+    #[test]
+    fn sine_tracks_sin_over_full_cycle() {
+        let error = sine_error::<f32>(&Context::new(48000f32));
+        assert!(error < 0.01, "{error}");
+        let error = sine_error::<i16>(&ContextFxP::new_480());
+        assert!(error < 0.01, "{error}");
+    }
 }
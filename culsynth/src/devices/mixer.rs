@@ -26,6 +26,14 @@ impl<T: DspFormat, const N: usize> Device<T> for Mixer<T, N> {
         input: Self::Input,
         params: Self::Params,
     ) -> T::Sample {
+        Self::mix(input, params)
+    }
+}
+
+impl<T: DspFormat, const N: usize> Mixer<T, N> {
+    /// Mix the signals together with the provided gains.  The mixer is
+    /// stateless, so this is equivalent to calling [Device::next].
+    pub fn mix(input: [T::Sample; N], params: [T::Scalar; N]) -> T::Sample {
         T::narrow_sample(
             zip(input.iter(), params.iter())
                 .fold(T::WideSample::default(), |acc, (signal, scale_factor)| {
@@ -1,7 +1,7 @@
 use super::*;
 
 /// A parameter pack for [MixOsc].
-#[derive(Clone, Default)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct MixOscParams<T: DspFormatBase> {
     /// The tuning offset, in semitones offset from 12TET/A440
     pub tune: T::NoteOffset,
@@ -16,8 +16,39 @@ pub struct MixOscParams<T: DspFormatBase> {
     pub tri: T::Scalar,
     /// Sawtooth wave gain
     pub saw: T::Scalar,
+    /// Overall output level, applied after the waveforms are mixed
+    pub osc_level: T::Scalar,
+    /// If set, this single control replaces the individual waveform gains,
+    /// continuously blending from sine (0) through triangle (1/3) and
+    /// sawtooth (2/3) to square (1)
+    pub wave_morph: Option<T::Scalar>,
+    /// Invert the polarity of the mixed output
+    pub invert: bool,
+    /// If true, reset the phase of this oscillator on each note on
+    /// (key-sync), so every note starts with the same attack transient.  If
+    /// false (the default), the oscillator runs freely between notes
+    /// (free-run).  This is applied by [crate::voice::Voice].
+    pub key_sync: bool,
+}
+
+impl<T: DspFormatBase> Default for MixOscParams<T> {
+    fn default() -> Self {
+        Self {
+            tune: T::NoteOffset::zero(),
+            shape: T::Scalar::zero(),
+            sin: T::Scalar::zero(),
+            sq: T::Scalar::zero(),
+            tri: T::Scalar::zero(),
+            saw: T::Scalar::zero(),
+            osc_level: T::Scalar::one(),
+            wave_morph: None,
+            invert: false,
+            key_sync: false,
+        }
+    }
 }
 
+#[cfg(feature = "float")]
 impl<T: DspFloat> From<&MixOscParams<i16>> for MixOscParams<T> {
     fn from(value: &MixOscParams<i16>) -> Self {
         MixOscParams::<T> {
@@ -27,6 +58,10 @@ impl<T: DspFloat> From<&MixOscParams<i16>> for MixOscParams<T> {
             sq: value.sq.to_num(),
             tri: value.tri.to_num(),
             saw: value.saw.to_num(),
+            osc_level: value.osc_level.to_num(),
+            wave_morph: value.wave_morph.map(|x| x.to_num()),
+            invert: value.invert,
+            key_sync: value.key_sync,
         }
     }
 }
@@ -50,7 +85,6 @@ impl<T: DspFormatBase> MixOscParams<T> {
 /// waveforms scaled by their respective gains.
 #[derive(Clone, Default)]
 pub struct MixOsc<T: DspFormat> {
-    mixer: Mixer<T, 4>,
     osc: Osc<T>,
 }
 
@@ -59,39 +93,38 @@ impl<T: DspFormat> Device<T> for MixOsc<T> {
     type Params = MixOscParams<T>;
     type Output = T::Sample;
     fn next(&mut self, context: &T::Context, note: T::Note, params: MixOscParams<T>) -> T::Sample {
-        let osc_out = self.osc.next(context, note, params.to_osc_params());
-        self.mixer.next(
-            context,
-            [osc_out.sin, osc_out.sq, osc_out.tri, osc_out.saw],
-            [params.sin, params.sq, params.tri, params.saw],
-        )
+        self.osc.next(context, note, params.to_osc_params()).mix(&params)
     }
 }
 
 /// This struct contains parameters for a synced oscillator pair
-#[derive(Clone, Default)]
+#[derive(Clone, Default, Debug, PartialEq)]
 pub struct SyncedMixOscsParams<T: DspFormatBase> {
     /// Parameters for the primary oscillator
     pub primary: MixOscParams<T>,
     /// Parameters for the secondary (synced) oscillator
     pub secondary: MixOscParams<T>,
-    /// True if oscillator sync has been enabled - when false, both oscillators
+    /// The oscillator sync mode - when [OscSyncMode::Off], both oscillators
     /// will run independently
-    pub sync: bool,
+    pub sync: OscSyncMode,
+    /// The phase modulation (linear FM) index (see [SyncedOscsParams::fm_index])
+    pub fm_index: T::Scalar,
 }
 
+#[cfg(feature = "float")]
 impl<T: DspFloat> From<&SyncedMixOscsParams<i16>> for SyncedMixOscsParams<T> {
     fn from(value: &SyncedMixOscsParams<i16>) -> Self {
         Self {
             primary: (&value.primary).into(),
             secondary: (&value.secondary).into(),
             sync: value.sync,
+            fm_index: value.fm_index.to_num(),
         }
     }
 }
 
 /// The output of a [SyncedMixOscs] device.
-#[derive(Clone, Default)]
+#[derive(Clone, Default, Debug, PartialEq)]
 pub struct SyncedMixOscsOutput<T: DspFormatBase> {
     /// The output of the primary oscillator
     pub primary: T::Sample,
@@ -110,8 +143,17 @@ pub struct SyncedMixOscsOutput<T: DspFormatBase> {
 #[derive(Clone, Default)]
 pub struct SyncedMixOscs<T: DspFormat> {
     oscs: SyncedOscs<T>,
-    mixer_pri: Mixer<T, 4>,
-    mixer_sec: Mixer<T, 4>,
+}
+
+impl<T: DspFormat> SyncedMixOscs<T> {
+    /// Reset the phase of the primary oscillator (see [Osc::reset_phase])
+    pub fn reset_primary_phase(&mut self) {
+        self.oscs.reset_primary_phase();
+    }
+    /// Reset the phase of the secondary oscillator (see [Osc::reset_phase])
+    pub fn reset_secondary_phase(&mut self) {
+        self.oscs.reset_secondary_phase();
+    }
 }
 
 impl<T: DspFormat> Device<T> for SyncedMixOscs<T> {
@@ -128,34 +170,193 @@ impl<T: DspFormat> Device<T> for SyncedMixOscs<T> {
             primary: params.primary.to_osc_params(),
             secondary: params.secondary.to_osc_params(),
             sync: params.sync,
+            fm_index: params.fm_index,
         };
         let SyncedOscsOutput {
             primary: p,
             secondary: s,
         } = self.oscs.next(context, note, inputs);
-        let pri_out = self.mixer_pri.next(
-            context,
-            [p.sin, p.sq, p.tri, p.saw],
-            [
-                params.primary.sin,
-                params.primary.sq,
-                params.primary.tri,
-                params.primary.saw,
-            ],
-        );
-        let sec_out = self.mixer_sec.next(
-            context,
-            [s.sin, s.sq, s.tri, s.saw],
-            [
-                params.secondary.sin,
-                params.secondary.sq,
-                params.secondary.tri,
-                params.secondary.saw,
-            ],
-        );
+        let pri_out = p.mix(&params.primary);
+        let sec_out = s.mix(&params.secondary);
         SyncedMixOscsOutput {
             primary: pri_out,
             secondary: sec_out,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Run two identical oscillators at full and half `osc_level` and check
+    /// that the second always outputs half of the first (within `tol`)
+    #[cfg(feature = "float")]
+    fn check_half_level<T: DspFormat>(
+        ctx: &T::Context,
+        note: T::Note,
+        params: MixOscParams<T>,
+        half: T::Scalar,
+        tol: f32,
+    ) {
+        let mut full_osc = MixOsc::<T>::default();
+        let mut half_osc = MixOsc::<T>::default();
+        let half_params = MixOscParams {
+            osc_level: half,
+            ..params.clone()
+        };
+        let mut peak = 0f32;
+        for _ in 0..1000 {
+            let full = T::sample_to_float(full_osc.next(ctx, note, params.clone()));
+            let half = T::sample_to_float(half_osc.next(ctx, note, half_params.clone()));
+            assert!((full / 2f32 - half).abs() <= tol, "{full} / 2 != {half}");
+            peak = peak.max(full.abs());
+        }
+        assert!(peak > 0.5f32);
+    }
+
+    /// Run a [MixOsc] with the wave morph set to `position` alongside a bare
+    /// [Osc] for one cycle, returning the index (in sine, square, triangle,
+    /// saw order) of the waveform best correlated with the mixed output
+    #[cfg(feature = "float")]
+    fn dominant_wave<T: DspFormat>(ctx: &T::Context, position: T::Scalar) -> usize {
+        let params = MixOscParams::<T> {
+            wave_morph: Some(position),
+            ..Default::default()
+        };
+        let note = T::note_from_fixed(NoteFxP::lit("57"));
+        let mut osc = Osc::<T>::new();
+        let mut mixosc = MixOsc::<T>::default();
+        let (mut dot, mut norm) = ([0f32; 4], [0f32; 4]);
+        // A220 at 48kHz is a little over 218 samples per cycle
+        for _ in 0..218 {
+            let out = T::sample_to_float(mixosc.next(ctx, note, params.clone()));
+            let waves = osc.next(ctx, note, params.to_osc_params());
+            for (i, wave) in [waves.sin, waves.sq, waves.tri, waves.saw].into_iter().enumerate() {
+                let wave = T::sample_to_float(wave);
+                dot[i] += out * wave;
+                norm[i] += wave * wave;
+            }
+        }
+        let corr = core::array::from_fn::<f32, 4, _>(|i| dot[i] / norm[i].sqrt());
+        (0..4).max_by(|a, b| corr[*a].total_cmp(&corr[*b])).unwrap()
+    }
+
+    #[cfg(feature = "float")]
+    #[test]
+    fn wave_morph_dominant_waveform() {
+        // sine -> triangle -> saw -> square
+        let expected = [0, 2, 3, 1];
+        let ctx = Context::new(48000f32);
+        let positions = [0f32, 0.34, 0.66, 1.0];
+        assert_eq!(positions.map(|p| dominant_wave::<f32>(&ctx, p)), expected);
+        let ctx = ContextFxP::new_480();
+        let positions = [
+            ScalarFxP::ZERO,
+            ScalarFxP::lit("0.34"),
+            ScalarFxP::lit("0.66"),
+            ScalarFxP::MAX,
+        ];
+        assert_eq!(positions.map(|p| dominant_wave::<i16>(&ctx, p)), expected);
+    }
+
+    #[cfg(feature = "float")]
+    #[test]
+    fn half_osc_level_halves_output() {
+        check_half_level::<f32>(
+            &Context::new(48000f32),
+            60f32,
+            MixOscParams {
+                sin: 0.5f32,
+                saw: 0.75f32,
+                ..Default::default()
+            },
+            0.5f32,
+            1e-6,
+        );
+        check_half_level::<i16>(
+            &ContextFxP::new_480(),
+            NoteFxP::lit("60"),
+            MixOscParams {
+                sin: ScalarFxP::lit("0.5"),
+                saw: ScalarFxP::lit("0.75"),
+                ..Default::default()
+            },
+            ScalarFxP::lit("0.5"),
+            1f32 / 1024f32,
+        );
+    }
+
+    #[test]
+    fn osc_output_mix_matches_mixosc() {
+        let ctx = ContextFxP::new_480();
+        let params = MixOscParams::<i16> {
+            shape: ScalarFxP::lit("0.25"),
+            sin: ScalarFxP::lit("0.5"),
+            sq: ScalarFxP::lit("0.25"),
+            tri: ScalarFxP::MAX,
+            saw: ScalarFxP::lit("0.75"),
+            ..Default::default()
+        };
+        let note = NoteFxP::lit("60");
+        let mut osc = Osc::<i16>::new();
+        let mut mixosc = MixOsc::<i16>::default();
+        for _ in 0..1000 {
+            let mixed = osc.next(&ctx, note, params.to_osc_params()).mix(&params);
+            assert_eq!(mixed, mixosc.next(&ctx, note, params.clone()));
+        }
+    }
+
+    /// Sum an inverted and a non-inverted oscillator with otherwise identical
+    /// parameters, returning the peak of one oscillator and of the sum
+    #[cfg(feature = "float")]
+    fn inverted_sum_peak<T: DspFormat>(
+        ctx: &T::Context,
+        note: T::Note,
+        params: MixOscParams<T>,
+    ) -> (f32, f32) {
+        let inv_params = MixOscParams {
+            invert: true,
+            ..params.clone()
+        };
+        let mut osc = MixOsc::<T>::default();
+        let mut inv_osc = MixOsc::<T>::default();
+        let (mut peak, mut sum_peak) = (0f32, 0f32);
+        for _ in 0..1000 {
+            let out = osc.next(ctx, note, params.clone());
+            let inv = inv_osc.next(ctx, note, inv_params.clone());
+            peak = peak.max(T::sample_to_float(out).abs());
+            sum_peak = sum_peak.max(T::sample_to_float(out.dsp_saturating_add(inv)).abs());
+        }
+        (peak, sum_peak)
+    }
+
+    #[cfg(feature = "float")]
+    #[test]
+    fn inverted_osc_cancels() {
+        let (peak, sum_peak) = inverted_sum_peak::<f32>(
+            &Context::new(48000f32),
+            60f32,
+            MixOscParams {
+                sin: 0.5f32,
+                sq: 0.5f32,
+                saw: 0.75f32,
+                ..Default::default()
+            },
+        );
+        assert!(peak > 0.5f32, "{peak}");
+        assert_eq!(sum_peak, 0f32);
+        let (peak, sum_peak) = inverted_sum_peak::<i16>(
+            &ContextFxP::new_480(),
+            NoteFxP::lit("60"),
+            MixOscParams {
+                sin: ScalarFxP::lit("0.5"),
+                sq: ScalarFxP::lit("0.5"),
+                saw: ScalarFxP::lit("0.75"),
+                ..Default::default()
+            },
+        );
+        assert!(peak > 0.5f32, "{peak}");
+        assert_eq!(sum_peak, 0f32);
+    }
+}
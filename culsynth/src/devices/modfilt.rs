@@ -1,6 +1,15 @@
 use super::*;
+use crate::util::Ramp;
+use rand::{rngs::SmallRng, RngCore, SeedableRng};
+
+/// Default random seed to use if not provided a seed
+const RANDOM_SEED: u64 = 0x5b1d4e3f08c6a297u64; //random 64 bit integer
+
+/// The number of samples between each new random value of the cutoff drift
+const DRIFT_PERIOD: usize = 512;
 
 /// Input for a [ModFilt]
+#[derive(Clone, Debug, PartialEq)]
 pub struct ModFiltInput<T: DspFormatBase> {
     /// The signal being filtered
     pub signal: T::Sample,
@@ -13,7 +22,7 @@ pub struct ModFiltInput<T: DspFormatBase> {
 }
 
 /// A parameter pack for a [ModFiltFxP]
-#[derive(Clone, Default)]
+#[derive(Clone, Default, Debug, PartialEq)]
 pub struct ModFiltParams<T: DspFormatBase> {
     /// The amount of envelope modulation, from 0 (none) to 1 (the envelope
     /// will, at peak, fully open the filter)
@@ -21,6 +30,11 @@ pub struct ModFiltParams<T: DspFormatBase> {
     /// The amount of velocity modulation, from 0 (none) to 1 (max velocity
     /// will fully open the filter)
     pub vel_mod: T::Scalar,
+    /// The amount that the envelope modulation is scaled by the note on
+    /// velocity, from 0 (the full `env_mod` is applied regardless of
+    /// velocity) to 1 (`env_mod` is multiplied by the velocity).  This is
+    /// applied by the voice's modulation section.
+    pub env_vel: T::Scalar,
     /// The amount of keyboard tracking, from 0 (none) to 1 (1:1)
     pub kbd_tracking: T::Scalar,
     /// The cutoff frequency of the filter, as a MIDI note number
@@ -33,24 +47,107 @@ pub struct ModFiltParams<T: DspFormatBase> {
     pub band_mix: T::Scalar,
     /// The mix of the high-pass output of the filter
     pub high_mix: T::Scalar,
+    /// The amount of input drive, from 0 (the input is passed to the filter
+    /// unchanged) to 1 (the input is boosted by 4x and soft clipped).  This
+    /// saturates the filter input like an analog filter, which fattens up
+    /// resonant sounds.
+    pub drive: T::Scalar,
+    /// The number of poles, i.e. the slope of the filter (see [FilterPoles])
+    pub poles: FilterPoles,
 }
 
+#[cfg(feature = "float")]
 impl<T: DspFloat> From<&ModFiltParams<i16>> for ModFiltParams<T> {
     fn from(value: &ModFiltParams<i16>) -> Self {
         Self {
             env_mod: value.env_mod.to_num(),
             vel_mod: value.vel_mod.to_num(),
+            env_vel: value.env_vel.to_num(),
             kbd_tracking: value.kbd_tracking.to_num(),
             cutoff: value.cutoff.to_num(),
             resonance: value.resonance.to_num(),
             low_mix: value.low_mix.to_num(),
             band_mix: value.band_mix.to_num(),
             high_mix: value.high_mix.to_num(),
+            drive: value.drive.to_num(),
+            poles: value.poles,
+        }
+    }
+}
+
+/// A traditional "filter type" selection, as an alternative to continuously
+/// mixing the low, band, and high-pass outputs of a [ModFilt] (see
+/// [ModFiltParams::set_filter_type])
+#[derive(Default, Clone, Copy, PartialEq, Debug, Eq)]
+#[repr(u8)]
+pub enum FilterType {
+    /// Low-pass is default
+    #[default]
+    LowPass,
+    /// Band-pass
+    BandPass,
+    /// High-pass
+    HighPass,
+    /// Notch (band-reject), the sum of the low and high-pass outputs
+    Notch,
+}
+
+impl FilterType {
+    const ELEM: [FilterType; 4] = [Self::LowPass, Self::BandPass, Self::HighPass, Self::Notch];
+    /// Returns a slice to all of the possible FilterTypes
+    pub const fn types() -> &'static [FilterType] {
+        &Self::ELEM
+    }
+    /// Provides the name of the filter type
+    pub const fn to_str(&self) -> &'static str {
+        ["Low Pass", "Band Pass", "High Pass", "Notch"][*self as usize]
+    }
+    /// The (low, band, high) mix that implements this filter type
+    pub fn mix<T: DspFormatBase>(&self) -> (T::Scalar, T::Scalar, T::Scalar) {
+        let (zero, one) = (T::Scalar::zero(), T::Scalar::one());
+        match self {
+            Self::LowPass => (one, zero, zero),
+            Self::BandPass => (zero, one, zero),
+            Self::HighPass => (zero, zero, one),
+            Self::Notch => (one, zero, one),
         }
     }
 }
 
+/// The number of poles of a [ModFilt], which sets how steeply it rolls off
+/// outside of its passband (see [ModFiltParams::poles])
+#[derive(Default, Clone, Copy, PartialEq, Debug, Eq)]
+#[repr(u8)]
+pub enum FilterPoles {
+    /// One pole, rolling off at 6dB/octave.  A single pole cannot resonate,
+    /// so the resonance is ignored, and the band-pass output is the product of
+    /// a one pole low-pass and high-pass.
+    One,
+    /// Two poles, rolling off at 12dB/octave.  This is the default.
+    #[default]
+    Two,
+    /// Four poles, rolling off at 24dB/octave.  This cascades a second filter
+    /// stage, and only the second stage is resonant.
+    Four,
+}
+
+impl FilterPoles {
+    const ELEM: [FilterPoles; 3] = [Self::One, Self::Two, Self::Four];
+    /// Returns a slice to all of the possible FilterPoles
+    pub const fn poles() -> &'static [FilterPoles] {
+        &Self::ELEM
+    }
+    /// Provides the name of the slope
+    pub const fn to_str(&self) -> &'static str {
+        ["6dB/oct", "12dB/oct", "24dB/oct"][*self as usize]
+    }
+}
+
 impl<T: DspFormatBase> ModFiltParams<T> {
+    /// Set the low, band, and high-pass mix to select a single `filter_type`
+    pub fn set_filter_type(&mut self, filter_type: FilterType) {
+        (self.low_mix, self.band_mix, self.high_mix) = filter_type.mix::<T>();
+    }
     /// Extract the [FiltParams] from this parameter pack, taking into account
     /// any modulation from the [ModFiltInput].
     pub fn to_filt_params(&self, input: &ModFiltInput<T>) -> FiltParams<T> {
@@ -71,10 +168,101 @@ impl<T: DspFormatBase> ModFiltParams<T> {
 /// low, band, and high-pass gain, and mixes the outputs together, and adds
 /// parameters to modulate the cutoff frequency with keyboard tracking,
 /// envelope, and velocity modulation.
-#[derive(Clone, Default)]
+///
+/// An optional input drive stage (see [ModFiltParams::drive]) saturates the
+/// signal with a [Waveshaper] before it reaches the filter.
+///
+/// The slope of the filter may be selected at runtime (see [FilterPoles]).
+/// The one pole responses are derived from the outputs of the non-resonant
+/// state-variable filter, and the four pole responses pass the mixed output
+/// of a non-resonant stage through a second, resonant stage with the same
+/// mix, so e.g. the low-pass output is a 4 pole low-pass.
+///
+/// The cutoff frequency may also be set to drift randomly by a small amount,
+/// emulating the instability of an analog filter (see [ModFilt::set_drift]).
+#[derive(Clone)]
 pub struct ModFilt<T: DspFormat> {
     filter: Filt<T>,
+    /// The second stage of a four pole filter
+    filter2: Filt<T>,
+    shaper: Waveshaper<T>,
     mixer: Mixer<T, 3>,
+    seed: u64,
+    rng: SmallRng,
+    /// A leaky random walk (i.e. brown noise), from -1 to 1 in units of
+    /// [ScalarFxP::DELTA], updated every [DRIFT_PERIOD] samples
+    walk: i32,
+    drift_depth: T::NoteOffset,
+    drift: Ramp<T, T::NoteOffset>,
+    drift_counter: usize,
+}
+
+impl<T: DspFormat> ModFilt<T> {
+    /// Constructor, seeding the random number generator for the cutoff drift
+    /// with `seed`
+    pub fn new_with_seed(seed: u64) -> Self {
+        Self {
+            filter: Default::default(),
+            filter2: Default::default(),
+            shaper: Default::default(),
+            mixer: Default::default(),
+            seed,
+            rng: SmallRng::seed_from_u64(seed),
+            walk: 0,
+            drift_depth: T::NoteOffset::zero(),
+            drift: Ramp::new(T::NoteOffset::zero()),
+            drift_counter: 0,
+        }
+    }
+    /// Reset the filters, the input drive stage, and the cutoff drift to their
+    /// initial state, reseeding the random number generator so the drift
+    /// repeats the same sequence as a newly constructed filter.  The drift
+    /// depth (see [ModFilt::set_drift]) is unchanged.
+    pub fn reset(&mut self) {
+        *self = Self {
+            drift_depth: self.drift_depth,
+            ..Self::new_with_seed(self.seed)
+        };
+    }
+    /// Set the maximum amount (in semitones) that the cutoff frequency will
+    /// randomly drift away from its nominal value.  A few cents is enough for
+    /// a subtle "analog" wobble.  Zero (the default) disables the drift.
+    pub fn set_drift(&mut self, depth: T::NoteOffset) {
+        self.drift_depth = depth;
+    }
+    /// Advance the cutoff drift by one sample, returning the current offset
+    fn next_drift(&mut self) -> T::NoteOffset {
+        if self.drift_counter == 0 {
+            let step = (self.rng.next_u32() as i16 as i32) >> 1;
+            self.walk = (self.walk - self.walk / 16 + step).clamp(-0xffff, 0xffff);
+            let mag = T::scalar_from_fixed(ScalarFxP::from_bits(self.walk.unsigned_abs() as u16));
+            let target = self.drift_depth.scale(mag);
+            let target = if self.walk < 0 {
+                T::NoteOffset::zero() - target
+            } else {
+                target
+            };
+            self.drift.ramp_to(target, DRIFT_PERIOD);
+        }
+        self.drift_counter = (self.drift_counter + 1) % DRIFT_PERIOD;
+        self.drift.next().unwrap_or(T::NoteOffset::zero())
+    }
+    /// Apply the input drive stage (see [ModFiltParams::drive])
+    fn drive(&mut self, context: &T::Context, signal: T::Sample, drive: T::Scalar) -> T::Sample {
+        let boost = signal.scale(drive);
+        let driven = signal
+            .dsp_saturating_add(boost)
+            .dsp_saturating_add(boost)
+            .dsp_saturating_add(boost);
+        let threshold = T::Scalar::one() - drive;
+        self.shaper.next(context, driven, WaveshaperParams { threshold })
+    }
+}
+
+impl<T: DspFormat> Default for ModFilt<T> {
+    fn default() -> Self {
+        Self::new_with_seed(RANDOM_SEED)
+    }
 }
 
 impl<T: DspFormat> Device<T> for ModFilt<T> {
@@ -87,11 +275,277 @@ impl<T: DspFormat> Device<T> for ModFilt<T> {
         input: ModFiltInput<T>,
         params: ModFiltParams<T>,
     ) -> T::Sample {
-        let filt_out = self.filter.next(context, input.signal, params.to_filt_params(&input));
-        self.mixer.next(
-            context,
-            [filt_out.low, filt_out.band, filt_out.high],
-            [params.low_mix, params.band_mix, params.high_mix],
-        )
+        let mut filt_params = params.to_filt_params(&input);
+        if self.drift_depth != T::NoteOffset::zero() {
+            filt_params.cutoff = T::apply_note_offset(filt_params.cutoff, self.next_drift());
+        }
+        let signal = if params.drive == T::Scalar::zero() {
+            input.signal
+        } else {
+            self.drive(context, input.signal, params.drive)
+        };
+        let gains = [params.low_mix, params.band_mix, params.high_mix];
+        match params.poles {
+            FilterPoles::One => {
+                // With no resonance, low + band and high + band are the one
+                // pole low-pass and high-pass responses
+                let filt_params = FiltParams {
+                    resonance: T::Scalar::zero(),
+                    ..filt_params
+                };
+                let out = self.filter.next(context, signal, filt_params);
+                let low = out.low.dsp_saturating_add(out.band);
+                let high = out.high.dsp_saturating_add(out.band);
+                self.mixer.next(context, [low, out.band, high], gains)
+            }
+            FilterPoles::Two => {
+                let out = self.filter.next(context, signal, filt_params);
+                self.mixer.next(context, [out.low, out.band, out.high], gains)
+            }
+            FilterPoles::Four => {
+                let first_params = FiltParams {
+                    cutoff: filt_params.cutoff,
+                    resonance: T::Scalar::zero(),
+                };
+                let out = self.filter.next(context, signal, first_params);
+                let first = self.mixer.next(context, [out.low, out.band, out.high], gains);
+                let out = self.filter2.next(context, first, filt_params);
+                self.mixer.next(context, [out.low, out.band, out.high], gains)
+            }
+        }
+    }
+}
+
+#[cfg(all(test, feature = "float"))]
+mod tests {
+    use super::*;
+
+    /// Check that a [ModFilt] set to [FilterType::HighPass] outputs only the
+    /// high-pass output of the underlying [Filt] (within `tol`)
+    fn check_high_pass<T: DspFormat>(ctx: &T::Context, mut params: ModFiltParams<T>, tol: f32) {
+        params.set_filter_type(FilterType::HighPass);
+        assert!(params.low_mix == T::Scalar::zero() && params.band_mix == T::Scalar::zero());
+        let mut modfilt = ModFilt::<T>::default();
+        let mut filt = Filt::<T>::default();
+        let mut osc = Osc::<T>::default();
+        let note = T::note_from_fixed(crate::NoteFxP::lit("48"));
+        for _ in 0..1000 {
+            let signal = osc.next(ctx, note, Default::default()).saw;
+            let input = ModFiltInput {
+                signal,
+                env: T::Scalar::zero(),
+                vel: T::Scalar::zero(),
+                kbd: T::Note::zero(),
+            };
+            let expected = filt.next(ctx, signal, params.to_filt_params(&input)).high;
+            let out = modfilt.next(ctx, input, params.clone());
+            let (out, expected) = (T::sample_to_float(out), T::sample_to_float(expected));
+            assert!((out - expected).abs() <= tol, "{out} != {expected}");
+        }
+    }
+
+    #[test]
+    fn high_pass_excludes_low_and_band() {
+        let params = ModFiltParams::<f32> {
+            cutoff: 60f32,
+            resonance: 0.5f32,
+            low_mix: 1f32,
+            band_mix: 1f32,
+            ..Default::default()
+        };
+        check_high_pass::<f32>(&Context::new(48000f32), params, 0f32);
+        let params = ModFiltParams::<i16> {
+            cutoff: crate::NoteFxP::lit("60"),
+            resonance: ScalarFxP::lit("0.5"),
+            low_mix: ScalarFxP::MAX,
+            band_mix: ScalarFxP::MAX,
+            ..Default::default()
+        };
+        check_high_pass::<i16>(&ContextFxP::new_480(), params, 1f32 / 1024f32);
+    }
+
+    /// Filter a saw wave with `modfilt`, returning the output and the largest
+    /// magnitude of the cutoff drift (converted to semitones by `to_float`)
+    fn drifted_saw<T: DspFormat>(
+        ctx: &T::Context,
+        modfilt: &mut ModFilt<T>,
+        to_float: fn(T::NoteOffset) -> f32,
+    ) -> ([f32; 4096], f32) {
+        let mut osc = Osc::<T>::default();
+        let note = T::note_from_fixed(crate::NoteFxP::lit("48"));
+        let mut params = ModFiltParams::<T> {
+            cutoff: T::note_from_fixed(crate::NoteFxP::lit("72")),
+            resonance: T::scalar_from_fixed(ScalarFxP::lit("0.5")),
+            ..Default::default()
+        };
+        params.set_filter_type(FilterType::LowPass);
+        let mut max_drift = 0f32;
+        let out = core::array::from_fn(|_| {
+            let input = ModFiltInput {
+                signal: osc.next(ctx, note, Default::default()).saw,
+                env: T::Scalar::zero(),
+                vel: T::Scalar::zero(),
+                kbd: T::Note::zero(),
+            };
+            let out = modfilt.next(ctx, input, params.clone());
+            let drift = to_float(modfilt.drift.value());
+            max_drift = max_drift.max(drift.abs());
+            T::sample_to_float(out)
+        });
+        (out, max_drift)
+    }
+
+    fn check_drift<T: DspFormat>(
+        ctx: &T::Context,
+        depth: T::NoteOffset,
+        to_float: fn(T::NoteOffset) -> f32,
+    ) {
+        let (reference, _) = drifted_saw(ctx, &mut ModFilt::<T>::default(), to_float);
+        let mut modfilt = ModFilt::<T>::new_with_seed(1);
+        modfilt.set_drift(T::NoteOffset::zero());
+        let (out, max_drift) = drifted_saw(ctx, &mut modfilt, to_float);
+        assert_eq!(max_drift, 0f32);
+        assert!(out == reference);
+
+        let mut modfilt = ModFilt::<T>::new_with_seed(1);
+        modfilt.set_drift(depth);
+        let (out, max_drift) = drifted_saw(ctx, &mut modfilt, to_float);
+        let depth = to_float(depth);
+        assert!(max_drift <= depth, "{max_drift} > {depth}");
+        // The drift should wander over a good part of its range
+        assert!(max_drift >= depth / 4f32, "{max_drift}");
+        assert!(out != reference);
+    }
+
+    #[test]
+    fn cutoff_drift_is_bounded() {
+        check_drift::<f32>(&Context::new(48000f32), 0.05f32, |x| x);
+        let depth = crate::SignedNoteFxP::lit("0.05");
+        check_drift::<i16>(&ContextFxP::new_480(), depth, |x| x.to_num());
+    }
+
+    #[test]
+    fn reset_keeps_drift_depth() {
+        let ctx = Context::new(48000f32);
+        let mut modfilt = ModFilt::<f32>::new_with_seed(1);
+        modfilt.set_drift(0.05f32);
+        let (first, _) = drifted_saw(&ctx, &mut modfilt, |x| x);
+        modfilt.reset();
+        let (second, max_drift) = drifted_saw(&ctx, &mut modfilt, |x| x);
+        assert!(max_drift > 0f32);
+        assert!(first == second);
+    }
+
+    /// Low-pass filter a 100Hz sine wave with the filter wide open and the
+    /// given `drive`, returning the ratio of the amplitude of the third
+    /// harmonic to the fundamental, and the largest difference from the
+    /// output of the underlying [Filt] without any drive
+    fn drive_harmonics<T: DspFormat>(ctx: &T::Context, drive: T::Scalar) -> (f32, f32) {
+        let mut modfilt = ModFilt::<T>::default();
+        let mut filt = Filt::<T>::default();
+        let mut params = ModFiltParams::<T> {
+            cutoff: T::note_from_fixed(crate::NoteFxP::lit("127")),
+            drive,
+            ..Default::default()
+        };
+        params.set_filter_type(FilterType::LowPass);
+        let (mut fund, mut third, mut max_diff) = ((0f32, 0f32), (0f32, 0f32), 0f32);
+        for i in 0..9600 {
+            let phase = core::f32::consts::TAU * (i as f32) / 480f32;
+            let sin = crate::IScalarFxP::from_num(0.9 * phase.sin());
+            let input = ModFiltInput {
+                signal: T::sample_from_fixed(sin),
+                env: T::Scalar::zero(),
+                vel: T::Scalar::zero(),
+                kbd: T::Note::zero(),
+            };
+            let dry = filt.next(ctx, input.signal, params.to_filt_params(&input)).low;
+            let out = T::sample_to_float(modfilt.next(ctx, input, params.clone()));
+            max_diff = max_diff.max((out - T::sample_to_float(dry)).abs());
+            // Correlate the second half (after the filter settles) with the
+            // fundamental and third harmonic
+            if i >= 4800 {
+                fund.0 += out * phase.sin();
+                fund.1 += out * phase.cos();
+                third.0 += out * (3f32 * phase).sin();
+                third.1 += out * (3f32 * phase).cos();
+            }
+        }
+        let mag = |(re, im): (f32, f32)| (re * re + im * im).sqrt();
+        (mag(third) / mag(fund), max_diff)
+    }
+
+    #[test]
+    fn drive_adds_harmonics() {
+        let ctx = Context::new(48000f32);
+        let (clean, diff) = drive_harmonics::<f32>(&ctx, 0f32);
+        assert!(clean < 0.001, "{clean}");
+        assert_eq!(diff, 0f32);
+        let (driven, diff) = drive_harmonics::<f32>(&ctx, 0.5f32);
+        assert!(driven > 0.02, "{driven}");
+        assert!(diff > 0.1, "{diff}");
+
+        let ctx = ContextFxP::new_480();
+        let (clean, diff) = drive_harmonics::<i16>(&ctx, ScalarFxP::ZERO);
+        assert!(clean < 0.001, "{clean}");
+        assert!(diff <= 1f32 / 1024f32, "{diff}");
+        let (driven, _) = drive_harmonics::<i16>(&ctx, ScalarFxP::lit("0.5"));
+        assert!(driven > 0.02, "{driven}");
+    }
+
+    /// The gain, in dB, of a low-pass [ModFilt] with a cutoff of C3 and the
+    /// given number of `poles` for a sine wave at the MIDI note `note`
+    fn low_pass_gain_db<T: DspFormat>(ctx: &T::Context, poles: FilterPoles, note: f32) -> f32 {
+        let mut modfilt = ModFilt::<T>::default();
+        let mut params = ModFiltParams::<T> {
+            cutoff: T::note_from_fixed(crate::NoteFxP::lit("48")),
+            poles,
+            ..Default::default()
+        };
+        params.set_filter_type(FilterType::LowPass);
+        let freq = 440f32 * ((note - 69f32) / 12f32).exp2();
+        let (mut re, mut im) = (0f32, 0f32);
+        for i in 0..9600 {
+            let phase = core::f32::consts::TAU * freq * (i as f32) / 48000f32;
+            let input = ModFiltInput {
+                signal: T::sample_from_fixed(crate::IScalarFxP::from_num(0.5 * phase.sin())),
+                env: T::Scalar::zero(),
+                vel: T::Scalar::zero(),
+                kbd: T::Note::zero(),
+            };
+            let out = T::sample_to_float(modfilt.next(ctx, input, params.clone()));
+            // Correlate the second half, after the filter settles
+            if i >= 4800 {
+                re += out * phase.sin();
+                im += out * phase.cos();
+            }
+        }
+        // The correlation of a unit sine over 4800 samples is 2400
+        20f32 * ((re * re + im * im).sqrt() / (0.5 * 2400f32)).log10()
+    }
+
+    #[test]
+    fn poles_set_rolloff_slope() {
+        let ctx = Context::new(48000f32);
+        for (poles, slope) in [
+            (FilterPoles::One, -6f32),
+            (FilterPoles::Two, -12f32),
+            (FilterPoles::Four, -24f32),
+        ] {
+            // Measure the octave from 3 to 4 octaves above the cutoff
+            let near = low_pass_gain_db::<f32>(&ctx, poles, 84f32);
+            let far = low_pass_gain_db::<f32>(&ctx, poles, 96f32);
+            let measured = far - near;
+            assert!((measured - slope).abs() < 0.5, "{poles:?}: {measured}");
+        }
+        // The fixed point noise floor is too high to measure four poles this
+        // far from the cutoff
+        let ctx = ContextFxP::new_480();
+        for (poles, slope) in [(FilterPoles::One, -6f32), (FilterPoles::Two, -12f32)] {
+            let near = low_pass_gain_db::<i16>(&ctx, poles, 84f32);
+            let far = low_pass_gain_db::<i16>(&ctx, poles, 96f32);
+            let measured = far - near;
+            assert!((measured - slope).abs() < 1f32, "{poles:?}: {measured}");
+        }
     }
 }
@@ -1,10 +1,11 @@
 use super::*;
 
+#[cfg(feature = "float")]
 use crate::Float;
-use crate::{FrequencyFxP, PhaseFxP};
+use crate::{FrequencyFxP, IScalarFxP, PhaseFxP};
 
 /// Parameters for an [Osc]
-#[derive(Clone, Default)]
+#[derive(Clone, Default, Debug, PartialEq)]
 pub struct OscParams<T: DspFormatBase> {
     /// Tuning, as an offset in MIDI note number
     pub tune: T::NoteOffset,
@@ -12,6 +13,7 @@ pub struct OscParams<T: DspFormatBase> {
     pub shape: T::Scalar,
 }
 
+#[cfg(feature = "float")]
 impl<T: DspFloat> From<&OscParams<i16>> for OscParams<T> {
     fn from(value: &OscParams<i16>) -> Self {
         Self {
@@ -22,28 +24,35 @@ impl<T: DspFloat> From<&OscParams<i16>> for OscParams<T> {
 }
 
 /// Parameters for [SyncedOscs]
-#[derive(Clone, Default)]
+#[derive(Clone, Default, Debug, PartialEq)]
 pub struct SyncedOscsParams<T: DspFormatBase> {
     /// Parameters for the primary oscillator
     pub primary: OscParams<T>,
     /// Parameters for the secondary oscillator
     pub secondary: OscParams<T>,
-    /// True if oscillator sync has been enabled - when false, both oscillators
+    /// The oscillator sync mode - when [OscSyncMode::Off], both oscillators
     /// will run independently
-    pub sync: bool,
+    pub sync: OscSyncMode,
+    /// The phase modulation (linear FM) index, from 0 to 1.  The sine output
+    /// of the primary oscillator modulates the phase of the secondary
+    /// oscillator with a peak deviation of `fm_index * pi` radians.  A value
+    /// of zero disables phase modulation.
+    pub fm_index: T::Scalar,
 }
 
+#[cfg(feature = "float")]
 impl<T: DspFloat> From<&SyncedOscsParams<i16>> for SyncedOscsParams<T> {
     fn from(value: &SyncedOscsParams<i16>) -> Self {
         Self {
             primary: (&value.primary).into(),
             secondary: (&value.secondary).into(),
             sync: value.sync,
+            fm_index: value.fm_index.to_num(),
         }
     }
 }
 /// The output of an oscillator.
-#[derive(Clone, Default)]
+#[derive(Clone, Default, Debug, PartialEq)]
 pub struct OscOutput<T: DspFormatBase> {
     /// The sine wave output
     pub sin: T::Sample,
@@ -55,8 +64,28 @@ pub struct OscOutput<T: DspFormatBase> {
     pub saw: T::Sample,
 }
 
+impl<T: DspFormat> OscOutput<T> {
+    /// Mix the waveforms together using the gains in `params` (or the gains
+    /// given by [MixOscParams::wave_morph], if set), saturating rather than
+    /// overflowing, then apply [MixOscParams::osc_level] and
+    /// [MixOscParams::invert].  This is the same mix as performed by [MixOsc].
+    pub fn mix(&self, params: &MixOscParams<T>) -> T::Sample {
+        let gains = match params.wave_morph {
+            Some(position) => T::morph_gains(position),
+            None => [params.sin, params.sq, params.tri, params.saw],
+        };
+        let out = Mixer::<T, 4>::mix([self.sin, self.sq, self.tri, self.saw], gains)
+            .scale(params.osc_level);
+        if params.invert {
+            T::Sample::zero() - out
+        } else {
+            out
+        }
+    }
+}
+
 /// Output from [SyncedOscs]
-#[derive(Clone, Default)]
+#[derive(Clone, Default, Debug, PartialEq)]
 pub struct SyncedOscsOutput<T: DspFormatBase> {
     /// Output from the primary oscillator
     pub primary: OscOutput<T>,
@@ -64,6 +93,38 @@ pub struct SyncedOscsOutput<T: DspFormatBase> {
     pub secondary: OscOutput<T>,
 }
 
+/// How the secondary oscillator of [SyncedOscs] follows the primary
+#[derive(Eq, PartialEq, Clone, Copy, Default, Debug)]
+pub enum OscSyncMode {
+    /// The oscillators run independently
+    #[default]
+    Off,
+    /// Hard sync: the secondary oscillator resets its phase whenever the
+    /// primary crosses zero phase
+    Hard,
+    /// Soft sync: the secondary oscillator reverses direction whenever the
+    /// primary crosses zero phase.  Since the output does not jump, this has
+    /// a smoother timbre than hard sync.
+    Soft,
+}
+
+/// The oscillator sync signal passed between a pair of [Osc]s (see
+/// [Osc::next_with_sync])
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum OscSync<T: DspFormatBase> {
+    /// No sync behavior - do not calculate
+    Off,
+    /// This is the primary oscillator, and sync is enabled
+    Primary,
+    /// This is the secondary oscillator, sync is enabled, and the primary
+    /// crossed zero phase at some portion through this sample
+    Secondary(T::Scalar),
+    /// This is the secondary oscillator, soft sync is enabled, and the
+    /// primary crossed zero phase at some portion through this sample, so
+    /// the secondary reverses direction
+    Reverse(T::Scalar),
+}
+
 /// A variable-frequency, audio-rate oscillator
 ///
 /// This models an oscillator with Sine, Square, Triangle, and Sawtooth wave
@@ -74,6 +135,28 @@ pub struct SyncedOscsOutput<T: DspFormatBase> {
 /// positive and negative phase portions of the waveform while maintaining the
 /// same overall fundamental frequency.
 ///
+/// The triangle output is normally computed directly from the phase, which is
+/// cheap but aliases at high notes.  A band-limited triangle, derived by
+/// integrating a PolyBLEP-corrected square wave, may be enabled instead (see
+/// [Osc::set_band_limited_tri]).
+///
+/// The fixed point sine output uses polynomial approximations, which are
+/// cheap but leave some harmonic distortion.  For uses that need a purer
+/// sine (e.g. FM operators or test tones), an interpolated lookup table may
+/// be used instead (see [Osc::set_sine_table]).
+///
+/// Oscillator sync abruptly resets the phase of the secondary oscillator,
+/// which aliases heavily.  The reset may optionally be band-limited with a
+/// MinBLEP correction (see [Osc::set_sync_blep]).  Soft sync (see
+/// [OscSync::Reverse]) instead runs the waveform backwards from where it
+/// was, which keeps the output continuous.  While running backwards, any
+/// phase distortion is mirrored, i.e. the two halves of the waveform swap
+/// durations.
+///
+/// Notes whose fundamental approaches the Nyquist frequency (e.g. the top of
+/// the MIDI range at low sample rates) can only alias, so the oscillator may
+/// optionally fade them out (see [Osc::set_nyquist_mute]).
+///
 /// This device returns each individual waveform as a separate output.  For
 /// convenience, devices are provided that premix these waveforms into a single
 /// output with parameterized gains (see [MixOsc] and [SyncedMixOscs]).
@@ -84,25 +167,323 @@ pub struct SyncedOscsOutput<T: DspFormatBase> {
 #[derive(Clone, Default)]
 pub struct Osc<T: DspFormat> {
     phase: T::Phase,
+    anti_click: bool,
+    reset_pending: bool,
+    fade_count: u8,
+    fade_offset: OscOutput<T>,
+    fade_step: OscOutput<T>,
+    last: OscOutput<T>,
+    band_limited_tri: bool,
+    tri_state: Option<T::TriState>,
+    last_phase: T::Phase,
+    nyquist_mute: bool,
+    sine_table: bool,
+    sync_phase: T::Scalar,
+    sync_blep: bool,
+    blep: [[T::Sample; 4]; BLEP_LEN],
+    blep_idx: usize,
+    /// True if soft sync has reversed the oscillator, in which case the
+    /// waveform is output at the mirror image of `phase`
+    reversed: bool,
 }
 
+/// The length of the anti-click fade is `1 << FADE_SHIFT` samples
+const FADE_SHIFT: u8 = 3;
+
+/// The length of the MinBLEP sync correction, in samples
+const BLEP_LEN: usize = 12;
+
+/// [SYNC_BLEP] has `1 << BLEP_SHIFT` points per sample
+const BLEP_SHIFT: u32 = 4;
+
+/// The raw bits of the MinBLEP residual (the band-limited step minus an ideal
+/// unit step) as an [IScalarFxP], at 16 points per sample for [BLEP_LEN]
+/// samples after the step.  This was generated from the minimum phase version
+/// (computed via the real cepstrum) of a Blackman-windowed sinc spanning 8
+/// zero crossings either side, integrated and normalized to a unit step.  The
+/// final point is set to exactly zero.
+const SYNC_BLEP: [i16; (BLEP_LEN << BLEP_SHIFT) + 1] = [
+    -32768, -32768, -32768, -32768, -32767, -32766, -32764, -32760, -32754, -32744, -32730, -32710,
+    -32681, -32641, -32586, -32514, -32420, -32299, -32146, -31955, -31720, -31434, -31090, -30682,
+    -30202, -29643, -29000, -28265, -27435, -26505, -25474, -24339, -23102, -21765, -20333, -18813,
+    -17214, -15547, -13825, -12064, -10279, -8491, -6717, -4980, -3298, -1693, -185, 1208, 2468,
+    3579, 4530, 5309, 5910, 6330, 6569, 6631, 6523, 6255, 5843, 5303, 4655, 3920, 3121, 2283, 1430,
+    585, -229, -990, -1680, -2283, -2785, -3176, -3450, -3604, -3640, -3561, -3376, -3095, -2732,
+    -2302, -1821, -1307, -779, -254, 252, 722, 1143, 1504, 1795, 2011, 2147, 2204, 2183, 2089,
+    1929, 1711, 1448, 1149, 827, 494, 163, -154, -448, -709, -930, -1105, -1231, -1306, -1329,
+    -1304, -1233, -1123, -979, -809, -621, -422, -220, -24, 160, 327, 470, 586, 672, 727, 752, 747,
+    714, 657, 579, 485, 379, 267, 153, 41, -63, -157, -238, -304, -353, -384, -398, -396, -378,
+    -347, -306, -256, -200, -141, -82, -25, 28, 75, 114, 146, 168, 181, 186, 182, 172, 155, 133,
+    108, 81, 53, 26, 0, -23, -43, -58, -70, -77, -80, -80, -76, -69, -59, -48, -36, -24, -12, -1,
+    9, 17, 24, 29, 32, 33, 32, 30, 27, 23, 18, 0,
+];
+
 impl<T: DspFormat> Osc<T> {
     /// Constructor
     pub fn new() -> Self {
-        Self {
-            phase: T::Phase::zero(),
+        Default::default()
+    }
+    /// Reset the phase of the oscillator to zero (e.g. on note retrigger).
+    ///
+    /// If anti-click is enabled (see [Osc::set_anti_click]), the output will
+    /// fade from its previous value over a few samples rather than jumping.
+    pub fn reset_phase(&mut self) {
+        self.phase = T::Phase::zero();
+        self.reversed = false;
+        self.reset_pending = true;
+        self.tri_state = None;
+    }
+    /// Reverse the direction of the oscillator, leaving the output phase
+    /// unchanged.  The phase is mirrored, so it may keep advancing as normal.
+    fn reverse(&mut self) {
+        let phase = T::Phase::zero() - self.phase;
+        self.phase = if phase >= T::Phase::PI {
+            phase - T::Phase::TAU
+        } else {
+            phase
+        };
+        self.reversed = !self.reversed;
+        self.tri_state = None;
+    }
+    /// The phase of the waveform being output
+    fn output_phase(&self) -> T::Phase {
+        if self.reversed {
+            let phase = T::Phase::zero() - self.phase;
+            if phase >= T::Phase::PI {
+                phase - T::Phase::TAU
+            } else {
+                phase
+            }
+        } else {
+            self.phase
         }
     }
-    fn next_with_sync(
+    /// Enable or disable the anti-click fade.  When enabled, any phase reset
+    /// (from [Osc::reset_phase] or oscillator sync) will crossfade from the
+    /// previous output over 8 samples to avoid an audible discontinuity.
+    /// This is disabled by default.
+    pub fn set_anti_click(&mut self, enable: bool) {
+        self.anti_click = enable;
+        self.fade_count = 0;
+    }
+    /// Enable or disable the band-limited triangle output.  When enabled, the
+    /// triangle is derived by integrating a PolyBLEP-corrected square wave,
+    /// which greatly reduces aliasing at high notes at the cost of some extra
+    /// processing.  This is disabled by default.
+    ///
+    /// The integrator leaks slightly to remove any DC offset, so the band
+    /// limited triangle is very slightly rounded compared to the naive one.
+    pub fn set_band_limited_tri(&mut self, enable: bool) {
+        self.band_limited_tri = enable;
+        self.tri_state = None;
+    }
+    /// Enable or disable muting of ultrasonic notes.  When enabled, the output
+    /// is faded out linearly as the fundamental rises from half the Nyquist
+    /// frequency, and is silent at or above the Nyquist frequency.  This is
+    /// disabled by default.
+    pub fn set_nyquist_mute(&mut self, enable: bool) {
+        self.nyquist_mute = enable;
+    }
+    /// Enable or disable the lookup table sine output.  When enabled, the
+    /// sine is linearly interpolated from a 1024 point table, which is more
+    /// accurate than the default approximation at the cost of some extra
+    /// processing and memory.  This is disabled by default.
+    pub fn set_sine_table(&mut self, enable: bool) {
+        self.sine_table = enable;
+    }
+    /// Set the phase that this oscillator resets to when it is synced to
+    /// another oscillator, as a fraction of a cycle from 0 to 1.  Changing the
+    /// phase at which the waveform restarts gives different sync timbres.
+    /// This defaults to zero, the start of the waveform.
+    pub fn set_sync_phase(&mut self, phase: T::Scalar) {
+        self.sync_phase = phase;
+    }
+    /// Enable or disable the anti-aliased hard sync.  When enabled, the
+    /// discontinuity in each waveform when this oscillator is reset by sync
+    /// is band-limited with a MinBLEP (minimum phase band-limited step)
+    /// correction spread over the following 12 samples.  This greatly reduces
+    /// aliasing from sync, at the cost of some extra processing on each sync
+    /// reset.  This is disabled by default.
+    pub fn set_sync_blep(&mut self, enable: bool) {
+        self.sync_blep = enable;
+        self.blep = Default::default();
+        self.blep_idx = 0;
+    }
+    /// Calculate the raw waveforms at `phase`
+    fn waveforms(&self, context: &T::Context, phase: T::Phase) -> OscOutput<T> {
+        let mut out = T::calc_waveforms(context, phase);
+        if self.sine_table {
+            out.sin = T::sin_lookup(phase);
+        }
+        out
+    }
+    /// Add the MinBLEP correction for a sync reset `frac` of a sample ago to
+    /// the upcoming samples.  `unsynced` is the phase the oscillator would
+    /// have had without the reset, and the height of each step is found by
+    /// winding both phases back to the moment of the reset.
+    fn add_sync_blep(
+        &mut self,
+        context: &T::Context,
+        unsynced: T::Phase,
+        pm: (T::Sample, T::Scalar),
+        frac: T::Scalar,
+    ) {
+        let (zero, pi, tau) = (T::Phase::zero(), T::Phase::PI, T::Phase::TAU);
+        let reset = if self.sync_phase == T::Scalar::zero() {
+            zero
+        } else {
+            <T as lfo::detail::LfoOps>::offset_phase(zero, self.sync_phase)
+        };
+        let mut since = self.phase - reset;
+        if since < zero {
+            since = since + tau;
+        }
+        let mut before = unsynced - since;
+        if before < zero - pi {
+            before = before + tau;
+        }
+        let waves = |o: OscOutput<T>| [o.sin, o.sq, o.tri, o.saw];
+        let synced = waves(self.waveforms(context, T::phase_modulate(reset, pm.0, pm.1)));
+        let unsynced = waves(self.waveforms(context, T::phase_modulate(before, pm.0, pm.1)));
+        for k in 0..BLEP_LEN {
+            let residual = T::sync_blep(frac, k);
+            let slot = &mut self.blep[(self.blep_idx + k) % BLEP_LEN];
+            for (acc, (new, old)) in slot.iter_mut().zip(synced.iter().zip(unsynced.iter())) {
+                *acc = acc.dsp_saturating_add((*new - *old).multiply(residual));
+            }
+        }
+    }
+    /// Apply (and consume) the pending MinBLEP correction for this sample
+    fn apply_sync_blep(&mut self, out: OscOutput<T>) -> OscOutput<T> {
+        let [sin, sq, tri, saw] = core::mem::take(&mut self.blep[self.blep_idx]);
+        self.blep_idx = (self.blep_idx + 1) % BLEP_LEN;
+        OscOutput {
+            sin: out.sin.dsp_saturating_add(sin),
+            sq: out.sq.dsp_saturating_add(sq),
+            tri: out.tri.dsp_saturating_add(tri),
+            saw: out.saw.dsp_saturating_add(saw),
+        }
+    }
+    /// Replace the naive triangle output `naive` at `phase` with the
+    /// band-limited version.  The integrator is (re)started from the naive
+    /// output after any phase discontinuity.
+    fn band_limit_tri(&mut self, phase: T::Phase, naive: T::Sample) -> T::Sample {
+        let last_phase = core::mem::replace(&mut self.last_phase, phase);
+        match self.tri_state.as_mut() {
+            Some(state) => T::calc_tri_blep(phase, last_phase, state),
+            None => {
+                self.tri_state = Some(T::tri_state_from(naive));
+                naive
+            }
+        }
+    }
+    /// Apply the anti-click fade to the raw output `out`
+    fn fade(&mut self, out: OscOutput<T>) -> OscOutput<T> {
+        let waves = |o: &OscOutput<T>| [o.sin, o.sq, o.tri, o.saw];
+        let from_waves = |[sin, sq, tri, saw]: [T::Sample; 4]| OscOutput { sin, sq, tri, saw };
+        if self.reset_pending {
+            // Start a linear ramp from the last output to the new output
+            let (last, new) = (waves(&self.last), waves(&out));
+            let offset: [T::Sample; 4] = core::array::from_fn(|i| last[i] - new[i]);
+            self.fade_step =
+                from_waves(offset.map(|x| (0..FADE_SHIFT).fold(x, |acc, _| acc.divide_by_two())));
+            self.fade_offset = from_waves(offset);
+            self.fade_count = 1 << FADE_SHIFT;
+        }
+        let mut out = waves(&out);
+        if self.fade_count > 0 {
+            let (offset, step) = (waves(&self.fade_offset), waves(&self.fade_step));
+            out = core::array::from_fn(|i| out[i] + offset[i]);
+            self.fade_offset = from_waves(core::array::from_fn(|i| offset[i] - step[i]));
+            self.fade_count -= 1;
+        }
+        self.last = from_waves(out);
+        self.last.clone()
+    }
+    /// Process one sample, participating in oscillator sync.
+    ///
+    /// To hard sync a secondary oscillator to a primary oscillator, call this
+    /// on the primary first with [OscSync::Primary], then pass the returned
+    /// [OscSync] to the secondary for the *same* sample.  The primary must
+    /// always be processed before the secondary, or the secondary will reset
+    /// one sample late.  The [OscSync] returned by a secondary oscillator (or
+    /// one called with [OscSync::Off]) is always [OscSync::Off].
+    ///
+    /// To soft sync instead, pass [OscSync::Reverse] to the secondary in place
+    /// of the returned [OscSync::Secondary].  The secondary then reverses
+    /// direction from the next sample.
+    ///
+    /// [SyncedOscs] wraps this logic up for a pair of oscillators.
+    pub fn next_with_sync(
+        &mut self,
+        context: &T::Context,
+        note: T::Note,
+        params: OscParams<T>,
+        sync: OscSync<T>,
+    ) -> (OscOutput<T>, OscSync<T>) {
+        let no_pm = (T::Sample::zero(), T::Scalar::zero());
+        self.next_with_sync_pm(context, note, params, sync, no_pm)
+    }
+    fn next_with_sync_pm(
         &mut self,
         context: &T::Context,
         note: T::Note,
         params: OscParams<T>,
         mut sync: OscSync<T>,
+        pm: (T::Sample, T::Scalar),
     ) -> (OscOutput<T>, OscSync<T>) {
-        let freq = T::note_to_freq(T::apply_note_offset(note, params.tune));
-        let out = T::calc_waveforms(self.phase);
+        let freq = T::note_to_freq(context, T::apply_note_offset(note, params.tune));
+        let phase = T::phase_modulate(self.output_phase(), pm.0, pm.1);
+        let mut out = self.waveforms(context, phase);
+        if self.band_limited_tri {
+            out.tri = self.band_limit_tri(phase, out.tri);
+            if matches!(sync, OscSync::Secondary(_)) {
+                self.tri_state = None;
+            }
+        }
+        if self.sync_blep {
+            out = self.apply_sync_blep(out);
+        }
+        if let Some(gain) = T::nyquist_gain(context, freq).filter(|_| self.nyquist_mute) {
+            out = OscOutput {
+                sin: out.sin.scale(gain),
+                sq: out.sq.scale(gain),
+                tri: out.tri.scale(gain),
+                saw: out.saw.scale(gain),
+            };
+        }
+        if self.anti_click {
+            out = self.fade(out);
+            // A sync pulse will reset the phase partway through this sample,
+            // so the discontinuity will show up in the next sample
+            self.reset_pending = matches!(sync, OscSync::Secondary(_));
+        } else {
+            self.reset_pending = false;
+        }
+        let sync_frac = match sync {
+            OscSync::Secondary(frac) => Some(frac),
+            OscSync::Reverse(_) => {
+                self.reverse();
+                sync = OscSync::Off;
+                None
+            }
+            _ => None,
+        };
+        if sync_frac.is_some() {
+            self.reversed = false;
+        }
+        let last_phase = self.phase;
         (self.phase, sync) = T::advance_phase(context, freq, self.phase, params.shape, sync);
+        if let Some(frac) = sync_frac {
+            if self.sync_phase != T::Scalar::zero() {
+                self.phase = <T as lfo::detail::LfoOps>::offset_phase(self.phase, self.sync_phase);
+            }
+            if self.sync_blep {
+                let (unsynced, _) =
+                    T::advance_phase(context, freq, last_phase, params.shape, OscSync::Off);
+                self.add_sync_blep(context, unsynced, pm, frac);
+            }
+        }
         (out, sync)
     }
 }
@@ -118,11 +499,48 @@ impl<T: DspFormat> Device<T> for Osc<T> {
 }
 
 /// A synced pair of [Osc]s.  The secondary oscillator will be synced
-/// to the primary oscillator.
+/// to the primary oscillator, and its phase may be modulated by the primary
+/// oscillator (see [SyncedOscsParams::fm_index]).
 ///
 /// This implements [Device], taking a Note as input and a [SyncedOscsParams]
 /// as parameters.  It outputs a [SyncedOscsOutput], which contains the output
-/// signals from both underlying oscillators.
+/// signals from both underlying oscillators.  The primary oscillator is always
+/// processed first, so the secondary resets within the same sample that the
+/// primary crosses zero phase (see [Osc::next_with_sync] to sync a pair of
+/// oscillators manually).
+///
+/// With sync enabled, sweeping the tuning of the secondary oscillator changes
+/// its timbre but not its pitch, producing the characteristic sync sweep:
+///
+/// ```
+/// # #[cfg(feature = "float")]
+/// # fn main() {
+/// use culsynth::context::Context;
+/// use culsynth::devices::*;
+///
+/// let ctx = Context::new(48000f32);
+/// let mut oscs = SyncedOscs::<f32>::new();
+/// // Sweep the secondary oscillator up two octaves above the primary
+/// let secondary = (0..4800).map(|i| OscParams {
+///     tune: 24f32 * (i as f32) / 4800f32,
+///     shape: 0f32,
+/// });
+/// let params = new_synced_oscs_param_iter()
+///     .with_secondary(secondary)
+///     .with_sync(core::iter::repeat(OscSyncMode::Hard));
+/// let mut last_primary = 0f32;
+/// for out in oscs.process(&ctx, core::iter::repeat(48f32), params) {
+///     // Every time the primary crosses zero phase, the secondary restarts
+///     // its waveform along with it
+///     if last_primary < 0f32 && out.primary.saw >= 0f32 {
+///         assert!(out.secondary.saw.abs() < 0.05);
+///     }
+///     last_primary = out.primary.saw;
+/// }
+/// # }
+/// # #[cfg(not(feature = "float"))]
+/// # fn main() {}
+/// ```
 #[derive(Clone, Default)]
 pub struct SyncedOscs<T: DspFormat> {
     primary: Osc<T>,
@@ -134,6 +552,48 @@ impl<T: DspFormat> SyncedOscs<T> {
     pub fn new() -> Self {
         Default::default()
     }
+    /// Reset the phase of the primary oscillator (see [Osc::reset_phase])
+    pub fn reset_primary_phase(&mut self) {
+        self.primary.reset_phase();
+    }
+    /// Reset the phase of the secondary oscillator (see [Osc::reset_phase])
+    pub fn reset_secondary_phase(&mut self) {
+        self.secondary.reset_phase();
+    }
+    /// Enable or disable the anti-click fade for both oscillators (see
+    /// [Osc::set_anti_click])
+    pub fn set_anti_click(&mut self, enable: bool) {
+        self.primary.set_anti_click(enable);
+        self.secondary.set_anti_click(enable);
+    }
+    /// Enable or disable the band-limited triangle output for both
+    /// oscillators (see [Osc::set_band_limited_tri])
+    pub fn set_band_limited_tri(&mut self, enable: bool) {
+        self.primary.set_band_limited_tri(enable);
+        self.secondary.set_band_limited_tri(enable);
+    }
+    /// Enable or disable muting of ultrasonic notes for both oscillators (see
+    /// [Osc::set_nyquist_mute])
+    pub fn set_nyquist_mute(&mut self, enable: bool) {
+        self.primary.set_nyquist_mute(enable);
+        self.secondary.set_nyquist_mute(enable);
+    }
+    /// Enable or disable the lookup table sine output for both oscillators
+    /// (see [Osc::set_sine_table])
+    pub fn set_sine_table(&mut self, enable: bool) {
+        self.primary.set_sine_table(enable);
+        self.secondary.set_sine_table(enable);
+    }
+    /// Set the phase that the secondary oscillator resets to when synced (see
+    /// [Osc::set_sync_phase])
+    pub fn set_sync_phase(&mut self, phase: T::Scalar) {
+        self.secondary.set_sync_phase(phase);
+    }
+    /// Enable or disable the anti-aliased hard sync for the secondary
+    /// oscillator (see [Osc::set_sync_blep])
+    pub fn set_sync_blep(&mut self, enable: bool) {
+        self.secondary.set_sync_blep(enable);
+    }
 }
 
 impl<T: DspFormat> Device<T> for SyncedOscs<T> {
@@ -146,13 +606,25 @@ impl<T: DspFormat> Device<T> for SyncedOscs<T> {
         note: T::Note,
         params: SyncedOscsParams<T>,
     ) -> Self::Output {
-        let sync = if params.sync {
-            OscSync::<T>::Primary
-        } else {
-            OscSync::<T>::Off
+        let sync = match params.sync {
+            OscSyncMode::Off => OscSync::<T>::Off,
+            OscSyncMode::Hard | OscSyncMode::Soft => OscSync::<T>::Primary,
         };
         let (pri_out, sync) = self.primary.next_with_sync(context, note, params.primary, sync);
-        let (sec_out, _) = self.secondary.next_with_sync(context, note, params.secondary, sync);
+        let sync = match (params.sync, sync) {
+            (OscSyncMode::Soft, OscSync::Secondary(frac)) => OscSync::Reverse(frac),
+            (OscSyncMode::Soft, sync) => sync,
+            (_, sync) => {
+                // Run forwards again if soft sync has been switched off
+                if self.secondary.reversed {
+                    self.secondary.reverse();
+                }
+                sync
+            }
+        };
+        let pm = (pri_out.sin, params.fm_index);
+        let (sec_out, _) =
+            self.secondary.next_with_sync_pm(context, note, params.secondary, sync, pm);
         SyncedOscsOutput {
             primary: pri_out,
             secondary: sec_out,
@@ -163,19 +635,9 @@ impl<T: DspFormat> Device<T> for SyncedOscs<T> {
 pub(crate) mod detail {
     use super::*;
 
-    #[derive(PartialEq, Clone, Copy)]
-    pub enum OscSync<T: DspFormatBase> {
-        /// No sync behavior - do not calculate
-        Off,
-        /// This is the primary oscillator, and sync is enabled
-        Primary,
-        /// This is the secondary oscillator, sync is enabled, and the master
-        /// completed a full phase at some portion through this sample
-        Secondary(T::Scalar),
-    }
-
     pub trait OscOps: crate::DspFormatBase {
         const FRAC_2_PI: Self::Scalar;
+        type TriState: Copy + Default + Send;
         fn advance_phase(
             context: &Self::Context,
             freq: Self::Frequency,
@@ -183,17 +645,89 @@ pub(crate) mod detail {
             shape: Self::Scalar,
             sync: OscSync<Self>,
         ) -> (Self::Phase, OscSync<Self>);
-        fn calc_waveforms(phase: Self::Phase) -> OscOutput<Self>;
+        fn calc_waveforms(context: &Self::Context, phase: Self::Phase) -> OscOutput<Self>;
+        /// The sine of `phase`, using [crate::fixedmath::sin_lookup]
+        fn sin_lookup(phase: Self::Phase) -> Self::Sample;
+        /// The MinBLEP residual (see [SYNC_BLEP]) `frac + k` samples after
+        /// a step, linearly interpolated
+        fn sync_blep(frac: Self::Scalar, k: usize) -> Self::Sample;
+        fn phase_modulate(
+            phase: Self::Phase,
+            signal: Self::Sample,
+            index: Self::Scalar,
+        ) -> Self::Phase;
+        fn tri_state_from(tri: Self::Sample) -> Self::TriState;
+        fn calc_tri_blep(
+            phase: Self::Phase,
+            last_phase: Self::Phase,
+            state: &mut Self::TriState,
+        ) -> Self::Sample;
+        fn morph_gains(position: Self::Scalar) -> [Self::Scalar; 4];
+        /// The gain to apply to an oscillator at `freq` to fade it out as it
+        /// approaches the Nyquist frequency, or `None` if it is below half
+        /// the Nyquist frequency and should not be attenuated
+        fn nyquist_gain(context: &Self::Context, freq: Self::Frequency) -> Option<Self::Scalar>;
+    }
+}
+
+/// The PolyBLEP residual for a unit step at `t = 0`, where `t` is the phase as
+/// a fraction of a cycle and `dt` is the phase increment per sample
+#[cfg(feature = "float")]
+fn polyblep<T: DspFloat>(t: T, dt: T) -> T {
+    if t < dt {
+        let x = t / dt;
+        x + x - x * x - T::ONE
+    } else if t > T::ONE - dt {
+        let x = (t - T::ONE) / dt;
+        x * x + x + x + T::ONE
+    } else {
+        T::ZERO
+    }
+}
+
+/// The reciprocal of the phase increment `dt` for [polyblep_fixed], as
+/// `y * 2^(24 - shift)`, or None if `dt` is too small to need any correction
+fn polyblep_recip(dt: PhaseFxP) -> Option<(fixedmath::U1F15, u32)> {
+    use fixedmath::{one_over_one_plus_refined, U28F4};
+    // Reinterpret the increment with 4 fractional bits to scale it up by
+    // 2^24, so it is at least one for any nonzero frequency
+    let scaled = U28F4::from_bits(dt.to_bits() as u32);
+    if dt <= PhaseFxP::ZERO || scaled <= U28F4::ONE {
+        return None;
     }
+    Some(one_over_one_plus_refined(scaled - U28F4::ONE))
 }
 
-use detail::OscSync;
+/// The fixed point equivalent of [polyblep], where `recip` is the
+/// [polyblep_recip] of `dt`
+fn polyblep_fixed(t: PhaseFxP, dt: PhaseFxP, recip: Option<(fixedmath::U1F15, u32)>) -> PhaseFxP {
+    let (y, shift) = match recip {
+        Some(recip) => recip,
+        None => return PhaseFxP::ZERO,
+    };
+    // x / dt, where x has 28 fractional bits and y has 15, so shift the
+    // product back to 28 fractional bits (accounting for the 2^24 above)
+    let over_dt = |x: PhaseFxP| {
+        PhaseFxP::from_bits((((x.to_bits() as i64 * y.to_bits() as i64) << 9) >> shift) as i32)
+    };
+    if t < dt {
+        let x = over_dt(t);
+        x + x - x * x - PhaseFxP::ONE
+    } else if t > PhaseFxP::ONE - dt {
+        let x = over_dt(t - PhaseFxP::ONE);
+        x * x + x + x + PhaseFxP::ONE
+    } else {
+        PhaseFxP::ZERO
+    }
+}
 
 // This section contains the actual DSP logic for both fixed and floating point
 
+#[cfg(feature = "float")]
 impl<T: DspFloat> detail::OscOps for T {
     const FRAC_2_PI: T = <T as Float>::FRAC_2_PI;
-    fn calc_waveforms(phase: Self::Phase) -> OscOutput<Self> {
+    type TriState = T;
+    fn calc_waveforms(context: &Context<T>, phase: Self::Phase) -> OscOutput<Self> {
         let mut out = osc::OscOutput::<T>::default();
         //generate waveforms (piecewise defined)
         let frac_2phase_pi = phase * <Self as detail::OscOps>::FRAC_2_PI;
@@ -203,12 +737,12 @@ impl<T: DspFloat> detail::OscOps for T {
             if phase < T::FRAC_PI_2.neg() {
                 // phase in [-pi, pi/2)
                 // sin(x) = -cos(x+pi/2)
-                out.sin = (phase + T::FRAC_PI_2).fcos().neg();
+                out.sin = context.cos(phase + T::FRAC_PI_2).neg();
                 // Subtract (1+1) because traits :eyeroll:
                 out.tri = frac_2phase_pi.neg() - T::TWO;
             } else {
                 // phase in [-pi/2, 0)
-                out.sin = phase.fsin();
+                out.sin = context.sin(phase);
                 //triangle
                 out.tri = frac_2phase_pi;
             }
@@ -216,17 +750,110 @@ impl<T: DspFloat> detail::OscOps for T {
             out.sq = T::ONE;
             if phase < T::FRAC_PI_2 {
                 // phase in [0, pi/2)
-                out.sin = phase.fsin();
+                out.sin = context.sin(phase);
                 out.tri = frac_2phase_pi;
             } else {
                 // phase in [pi/2, pi)
                 // sin(x) = cos(x-pi/2)
-                out.sin = (phase - T::FRAC_PI_2).fcos();
+                out.sin = context.cos(phase - T::FRAC_PI_2);
                 out.tri = T::TWO - frac_2phase_pi;
             }
         }
         out
     }
+    fn sin_lookup(phase: T) -> T {
+        // Convert the phase to a fraction of a cycle in [0, 1)
+        let mut x = phase / T::TAU;
+        if x < T::ZERO {
+            x = x + T::ONE;
+        }
+        let scale = <T as num_traits::NumCast>::from(1u64 << 32).unwrap_or(T::ZERO);
+        let bits = <u64 as num_traits::NumCast>::from(x * scale).unwrap_or(0);
+        // Rounding may give exactly one full cycle, which wraps to zero
+        fixedmath::sin_lookup(fixedmath::U0F32::from_bits(bits as u32)).to_num()
+    }
+    fn sync_blep(frac: T, k: usize) -> T {
+        let points = T::from_u16(1 << BLEP_SHIFT);
+        let pos = (frac + T::from_u16(k as u16)) * points;
+        let index = <usize as num_traits::NumCast>::from(pos).unwrap_or(0);
+        let index = index.min(SYNC_BLEP.len() - 2);
+        let frac = pos - T::from_u16(index as u16);
+        let [lo, hi] = [SYNC_BLEP[index], SYNC_BLEP[index + 1]]
+            .map(|bits| IScalarFxP::from_bits(bits).to_num::<T>());
+        lo + (hi - lo) * frac
+    }
+    fn phase_modulate(phase: T, signal: T, index: T) -> T {
+        if index == T::ZERO {
+            return phase;
+        }
+        let signal = signal.max(T::ONE.neg()).min(T::ONE);
+        let pm_phase = phase + signal * index * T::PI;
+        // The offset is at most pi, so wrapping once is sufficient
+        if pm_phase >= T::PI {
+            pm_phase - T::TAU
+        } else if pm_phase < T::PI.neg() {
+            pm_phase + T::TAU
+        } else {
+            pm_phase
+        }
+    }
+    fn tri_state_from(tri: T) -> T {
+        tri
+    }
+    fn morph_gains(position: T) -> [T; 4] {
+        let x = position.max(T::ZERO).min(T::ONE) * T::THREE;
+        // Split into the sine-triangle, triangle-saw, and saw-square segments
+        let (segment, frac) = if x < T::ONE {
+            (0, x)
+        } else if x < T::TWO {
+            (1, x - T::ONE)
+        } else {
+            (2, x - T::TWO)
+        };
+        let (a, b) = (T::ONE - frac, frac);
+        match segment {
+            0 => [a, T::ZERO, b, T::ZERO],
+            1 => [T::ZERO, T::ZERO, a, b],
+            _ => [T::ZERO, b, T::ZERO, a],
+        }
+    }
+    fn calc_tri_blep(phase: T, last_phase: T, state: &mut T) -> T {
+        // Phase modulation may move the phase backwards, so keep the sign
+        let mut dp = phase - last_phase;
+        if dp >= T::PI {
+            dp = dp - T::TAU;
+        } else if dp < T::PI.neg() {
+            dp = dp + T::TAU;
+        }
+        let dt = (dp / T::TAU).abs();
+        // The derivative of the triangle is a square wave rising at -pi/2 and
+        // falling at pi/2.  Normalize the phase to a fraction of a cycle
+        // starting at the rising edge:
+        let mut t = (phase + T::FRAC_PI_2) / T::TAU;
+        if t < T::ZERO {
+            t = t + T::ONE;
+        }
+        let mut t_fall = t + T::ONE_HALF;
+        if t_fall >= T::ONE {
+            t_fall = t_fall - T::ONE;
+        }
+        let naive = if t < T::ONE_HALF {
+            T::ONE
+        } else {
+            T::ONE.neg()
+        };
+        let sq = naive + polyblep(t, dt) - polyblep(t_fall, dt);
+        // Integrate (the triangle's slope is 2/pi per radian), leaking
+        // slightly to remove any DC offset
+        let leak = *state * dt / T::from_u16(32);
+        *state = *state - leak + sq * dp * <T as Float>::FRAC_2_PI;
+        *state
+    }
+    fn nyquist_gain(ctx: &Context<T>, freq: T) -> Option<T> {
+        // The frequency as a fraction of the Nyquist frequency
+        let x = freq * T::TWO / ctx.sample_rate;
+        (x > T::ONE_HALF).then(|| (T::TWO - x - x).max(T::ZERO))
+    }
     fn advance_phase(
         ctx: &Self::Context,
         freq: Self::Frequency,
@@ -252,14 +879,16 @@ impl<T: DspFloat> detail::OscOps for T {
         };
         let old_phase = phase;
         match sync {
-            OscSync::Off => {
+            // The oscillator has already been reversed (see Osc::reverse)
+            OscSync::Off | OscSync::Reverse(_) => {
                 phase = phase + phase_per_smp_adj;
             }
             OscSync::Primary => {
                 phase = phase + phase_per_smp_adj;
                 // calculate what time in this sampling period the phase crossed zero:
                 if old_phase < T::ZERO && phase >= T::ZERO {
-                    sync_out = OscSync::Secondary(phase / phase_per_smp_adj);
+                    let frac = phase / phase_per_smp_adj;
+                    sync_out = OscSync::Secondary(clamp_sync_fraction::<T>(frac));
                 }
             }
             OscSync::Secondary(primary_xpt) => {
@@ -292,7 +921,8 @@ impl<T: DspFloat> detail::OscOps for T {
 
 impl detail::OscOps for i16 {
     const FRAC_2_PI: ScalarFxP = ScalarFxP::lit("0x0.a2fa");
-    fn calc_waveforms(phase: Self::Phase) -> OscOutput<Self> {
+    type TriState = PhaseFxP;
+    fn calc_waveforms(_: &ContextFxP, phase: Self::Phase) -> OscOutput<Self> {
         use crate::fixed_traits::Fixed16;
         use fixedmath::{cos_fixed, sin_fixed};
         const TWO: SampleFxP = SampleFxP::lit("2");
@@ -331,6 +961,84 @@ impl detail::OscOps for i16 {
         }
         ret
     }
+    fn sin_lookup(phase: PhaseFxP) -> SampleFxP {
+        const FRAC_1_TAU: fixedmath::I1F31 = fixedmath::I1F31::lit("0.1591549430918953");
+        // Convert the phase to a fraction of a cycle, with 32 fractional bits
+        // (truncating to 32 bits wraps negative phases into [0.5, 1))
+        let bits = phase.wide_mul(FRAC_1_TAU).to_bits() >> 27;
+        SampleFxP::from_num(fixedmath::sin_lookup(fixedmath::U0F32::from_bits(
+            bits as u32,
+        )))
+    }
+    fn sync_blep(frac: ScalarFxP, k: usize) -> SampleFxP {
+        const FRAC_BITS: u32 = 16 - BLEP_SHIFT;
+        let bits = frac.to_bits() as usize;
+        let index = (k << BLEP_SHIFT) + (bits >> FRAC_BITS);
+        let frac = (bits & ((1 << FRAC_BITS) - 1)) as i32;
+        let (lo, hi) = (SYNC_BLEP[index] as i32, SYNC_BLEP[index + 1] as i32);
+        let residual = lo + (((hi - lo) * frac) >> FRAC_BITS);
+        SampleFxP::from_num(IScalarFxP::from_bits(residual as i16))
+    }
+    fn phase_modulate(phase: PhaseFxP, signal: SampleFxP, index: ScalarFxP) -> PhaseFxP {
+        use crate::fixed_traits::Fixed16;
+        if index == ScalarFxP::ZERO {
+            return phase;
+        }
+        let signal = signal.clamp(SampleFxP::NEG_ONE, SampleFxP::ONE);
+        let offset = PhaseFxP::from_num(signal.scale_fixed(index)) * PhaseFxP::PI;
+        fixedmath::wrap_phase(phase + offset)
+    }
+    fn tri_state_from(tri: SampleFxP) -> PhaseFxP {
+        PhaseFxP::from_num(tri)
+    }
+    fn morph_gains(position: ScalarFxP) -> [ScalarFxP; 4] {
+        // See the floating point implementation for details
+        let x = position.to_bits() as u32 * 3;
+        let frac = ScalarFxP::from_bits(x as u16);
+        let (a, b) = (ScalarFxP::MAX - frac, frac);
+        match x >> 16 {
+            0 => [a, ScalarFxP::ZERO, b, ScalarFxP::ZERO],
+            1 => [ScalarFxP::ZERO, ScalarFxP::ZERO, a, b],
+            _ => [ScalarFxP::ZERO, b, ScalarFxP::ZERO, a],
+        }
+    }
+    fn calc_tri_blep(phase: PhaseFxP, last_phase: PhaseFxP, state: &mut PhaseFxP) -> SampleFxP {
+        // See the floating point implementation for details
+        const FRAC_1_TAU: PhaseFxP = PhaseFxP::lit("0.1591549431");
+        const FRAC_2_PI: PhaseFxP = PhaseFxP::lit("0.6366197724");
+        const ONE_HALF: PhaseFxP = PhaseFxP::lit("0.5");
+        let dp = fixedmath::wrap_phase(phase - last_phase);
+        let dt = (dp * FRAC_1_TAU).abs();
+        let mut t = (phase + PhaseFxP::FRAC_PI_2) * FRAC_1_TAU;
+        if t < PhaseFxP::ZERO {
+            t += PhaseFxP::ONE;
+        }
+        let mut t_fall = t + ONE_HALF;
+        if t_fall >= PhaseFxP::ONE {
+            t_fall -= PhaseFxP::ONE;
+        }
+        let naive = if t < ONE_HALF {
+            PhaseFxP::ONE
+        } else {
+            PhaseFxP::NEG_ONE
+        };
+        let recip = polyblep_recip(dt);
+        let sq = naive + polyblep_fixed(t, dt, recip) - polyblep_fixed(t_fall, dt, recip);
+        let leak = (*state * dt).unwrapped_shr(5);
+        *state = (*state - leak).saturating_add(sq * dp * FRAC_2_PI);
+        SampleFxP::saturating_from_num(*state)
+    }
+    fn nyquist_gain(ctx: &ContextFxP, freq: FrequencyFxP) -> Option<ScalarFxP> {
+        use fixedmath::{scale_fixedfloat, U4F28};
+        // The phase per sample is pi at the Nyquist frequency
+        let phase_per_sample = phase_per_sample_fixed(ctx, freq);
+        if phase_per_sample <= U4F28::FRAC_PI_2 {
+            return None;
+        }
+        let remaining = U4F28::PI.saturating_sub(phase_per_sample);
+        let gain = scale_fixedfloat(remaining, Self::FRAC_2_PI);
+        Some(ScalarFxP::saturating_from_num(gain))
+    }
     fn advance_phase(
         ctx: &ContextFxP,
         freq: FrequencyFxP,
@@ -341,41 +1049,34 @@ impl detail::OscOps for i16 {
         // perform shape clipping:
         let shape = ShapeFxP::new(shape);
         let mut sync_out = OscSync::<i16>::Off;
-        use fixedmath::{one_over_one_plus_highacc, scale_fixedfloat, U1F15, U3F13, U4F28};
-        // we need to divide by 2^12 here, but we're increasing the fractional part by 10
-        // bits so we'll only actually shift by 2 places and then use a bitcast for the
-        // remaining logical 10 bits:
-        let phase_per_sample = U4F28::from_bits(
-            scale_fixedfloat(freq, ctx.sample_rate.frac_2pi4096_sr())
-                .unwrapped_shr(2)
-                .to_bits(),
-        );
+        use fixedmath::{one_over_one_plus_highacc, scale_fixedfloat, U1F15};
+        let phase_per_sample = phase_per_sample_fixed(ctx, freq);
         // Handle slave oscillator resetting phase if master crosses:
         if let OscSync::Secondary(_) = sync {
             phase = PhaseFxP::ZERO;
         }
-        // Adjust phase per sample for the shape parameter:
-        let phase_per_smp_adj = PhaseFxP::from_num(if phase < PhaseFxP::ZERO {
+        // Adjust phase per sample for the shape parameter.  This saturates at
+        // pi (i.e. the Nyquist frequency) so the phase can't overflow - any
+        // higher frequency would alias anyways:
+        let phase_per_smp_adj = PhaseFxP::saturating_from_num(if phase < PhaseFxP::ZERO {
             let (x, s) = one_over_one_plus_highacc(*shape);
             scale_fixedfloat(phase_per_sample, x).unwrapped_shr(s)
         } else {
             scale_fixedfloat(phase_per_sample, one_over_one_minus_x(shape))
-        });
+        })
+        .min(PhaseFxP::PI);
         // Advance the oscillator's phase, and handle oscillator sync logic:
         let old_phase = phase;
         match sync {
-            OscSync::Off => {
+            // The oscillator has already been reversed (see Osc::reverse)
+            OscSync::Off | OscSync::Reverse(_) => {
                 phase += phase_per_smp_adj;
             }
             OscSync::Primary => {
                 phase += phase_per_smp_adj;
                 // calculate what time in this sampling period the phase crossed zero:
                 if old_phase < PhaseFxP::ZERO && phase >= PhaseFxP::ZERO {
-                    // we need to calculate 1 - (phase / phase_per_sample_adj)
-                    let adj_s = ScalarFxP::from_num(phase_per_smp_adj.unwrapped_shr(2));
-                    let x = U3F13::from_num(phase).wide_mul(inverse(adj_s));
-                    let proportion = ScalarFxP::saturating_from_num(x.unwrapped_shr(2));
-                    sync_out = OscSync::Secondary(proportion);
+                    sync_out = OscSync::Secondary(sync_fraction(phase, phase_per_smp_adj));
                 }
             }
             OscSync::Secondary(primary_xpt) => {
@@ -389,13 +1090,13 @@ impl detail::OscOps for i16 {
             // where k is the shape, so no work required if shape is 0
             let scaled = scale_fixedfloat(phase.unsigned_abs(), one_over_one_minus_x(shape));
             let one_plus_shape = U1F15::from_num(*shape) + U1F15::ONE;
-            phase = PhaseFxP::from_num(scale_fixedfloat(scaled, one_plus_shape));
+            phase = PhaseFxP::saturating_from_num(scale_fixedfloat(scaled, one_plus_shape));
         }
         // Check if we've crossed from positive phase back to negative:
         if phase >= PhaseFxP::PI {
             // if we're a symmetric wave this is as simple as just subtract 2pi
             if *shape == ScalarFxP::ZERO {
-                phase -= PhaseFxP::TAU;
+                phase = fixedmath::wrap_phase(phase);
             } else {
                 // if assymmetric we have to multiply residual phase i.e. phase - pi
                 // by (1-k)/(1+k) where k is the shape:
@@ -406,19 +1107,31 @@ impl detail::OscOps for i16 {
                 let (x, s) = one_over_one_plus_highacc(*shape);
                 let delta = scale_fixedfloat(scaled, x).unwrapped_shr(s);
                 // add new change in phase to our baseline, -pi:
-                phase = (-PhaseFxP::PI).add_unsigned(delta);
+                phase = fixedmath::wrap_phase((-PhaseFxP::PI).saturating_add_unsigned(delta));
             }
         }
         (phase, sync_out)
     }
 }
 
+/// The phase increment per sample, in radians, of an oscillator at `freq`
+fn phase_per_sample_fixed(ctx: &ContextFxP, freq: FrequencyFxP) -> fixedmath::U4F28 {
+    // we need to divide by 2^12 here, but we're increasing the fractional part by 10
+    // bits so we'll only actually shift by 2 places and then use a bitcast for the
+    // remaining logical 10 bits:
+    fixedmath::U4F28::from_bits(
+        fixedmath::scale_fixedfloat(freq, ctx.sample_rate.frac_2pi4096_sr())
+            .unwrapped_shr(2)
+            .to_bits(),
+    )
+}
+
 // Newtype around ScalarFxP with the invariant that clip_shape() was called
 #[derive(Default, Clone, Copy)]
 struct ShapeFxP(ScalarFxP);
 
 impl ShapeFxP {
-    pub const fn new(value: ScalarFxP) -> Self {
+    pub fn new(value: ScalarFxP) -> Self {
         Self(clip_shape(value))
     }
     pub const fn value(self) -> ScalarFxP {
@@ -433,89 +1146,39 @@ impl core::ops::Deref for ShapeFxP {
     }
 }
 
-const fn clip_shape(x: ScalarFxP) -> ScalarFxP {
+fn clip_shape(x: ScalarFxP) -> ScalarFxP {
     const CLIP_MAX: ScalarFxP = ScalarFxP::lit("0x0.F");
-    // equivalent to x > CLIP_MAX, but callable in const context
-    if CLIP_MAX.checked_sub(x).is_none() {
-        CLIP_MAX
-    } else {
-        x
-    }
+    x.dsp_clamp(ScalarFxP::ZERO, CLIP_MAX)
 }
 
-fn inverse(x: ScalarFxP) -> crate::fixedmath::U8F8 {
-    // For brevity in defining the lookup table:
-    const fn lit(x: &str) -> crate::fixedmath::U8F8 {
-        crate::fixedmath::U8F8::lit(x)
+/// The fraction of the last sample since the phase crossed zero, i.e.
+/// `phase / per_smp`, where `phase` is the phase just after the crossing and
+/// `per_smp` is the phase increment for that sample.  This is used to time
+/// oscillator sync resets, so it must stay accurate for low frequencies.
+fn sync_fraction(phase: PhaseFxP, per_smp: PhaseFxP) -> ScalarFxP {
+    use crate::fixedmath::{one_over_one_plus_refined, U16F16};
+    // Reinterpret the increment with 16 fractional bits to scale it up by
+    // 2^12, so it is at least one for any audible frequency
+    let scaled = U16F16::from_bits(per_smp.to_bits() as u32);
+    if phase <= PhaseFxP::ZERO || scaled <= U16F16::ONE {
+        return ScalarFxP::ZERO;
     }
-    #[rustfmt::skip]
-    const LOOKUP_TABLE: [crate::fixedmath::U8F8; 256] = [
-        lit("0xff.ff"), lit("0xff.ff"), lit("0x80.00"), lit("0x55.55"),
-        lit("0x40.00"), lit("0x33.33"), lit("0x2a.aa"), lit("0x24.92"),
-        lit("0x20.00"), lit("0x1c.71"), lit("0x19.99"), lit("0x17.45"),
-        lit("0x15.55"), lit("0x13.b1"), lit("0x12.49"), lit("0x11.11"),
-        lit("0x10.00"), lit("0xf.0f"), lit("0xe.38"), lit("0xd.79"),
-        lit("0xc.cc"), lit("0xc.30"), lit("0xb.a2"), lit("0xb.21"),
-        lit("0xa.aa"), lit("0xa.3d"), lit("0x9.d8"), lit("0x9.7b"),
-        lit("0x9.24"), lit("0x8.d3"), lit("0x8.88"), lit("0x8.42"),
-        lit("0x8.00"), lit("0x7.c1"), lit("0x7.87"), lit("0x7.50"),
-        lit("0x7.1c"), lit("0x6.eb"), lit("0x6.bc"), lit("0x6.90"),
-        lit("0x6.66"), lit("0x6.3e"), lit("0x6.18"), lit("0x5.f4"),
-        lit("0x5.d1"), lit("0x5.b0"), lit("0x5.90"), lit("0x5.72"),
-        lit("0x5.55"), lit("0x5.39"), lit("0x5.1e"), lit("0x5.05"),
-        lit("0x4.ec"), lit("0x4.d4"), lit("0x4.bd"), lit("0x4.a7"),
-        lit("0x4.92"), lit("0x4.7d"), lit("0x4.69"), lit("0x4.56"),
-        lit("0x4.44"), lit("0x4.32"), lit("0x4.21"), lit("0x4.10"),
-        lit("0x4.00"), lit("0x3.f0"), lit("0x3.e0"), lit("0x3.d2"),
-        lit("0x3.c3"), lit("0x3.b5"), lit("0x3.a8"), lit("0x3.9b"),
-        lit("0x3.8e"), lit("0x3.81"), lit("0x3.75"), lit("0x3.69"),
-        lit("0x3.5e"), lit("0x3.53"), lit("0x3.48"), lit("0x3.3d"),
-        lit("0x3.33"), lit("0x3.29"), lit("0x3.1f"), lit("0x3.15"),
-        lit("0x3.0c"), lit("0x3.03"), lit("0x2.fa"), lit("0x2.f1"),
-        lit("0x2.e8"), lit("0x2.e0"), lit("0x2.d8"), lit("0x2.d0"),
-        lit("0x2.c8"), lit("0x2.c0"), lit("0x2.b9"), lit("0x2.b1"),
-        lit("0x2.aa"), lit("0x2.a3"), lit("0x2.9c"), lit("0x2.95"),
-        lit("0x2.8f"), lit("0x2.88"), lit("0x2.82"), lit("0x2.7c"),
-        lit("0x2.76"), lit("0x2.70"), lit("0x2.6a"), lit("0x2.64"),
-        lit("0x2.5e"), lit("0x2.59"), lit("0x2.53"), lit("0x2.4e"),
-        lit("0x2.49"), lit("0x2.43"), lit("0x2.3e"), lit("0x2.39"),
-        lit("0x2.34"), lit("0x2.30"), lit("0x2.2b"), lit("0x2.26"),
-        lit("0x2.22"), lit("0x2.1d"), lit("0x2.19"), lit("0x2.14"),
-        lit("0x2.10"), lit("0x2.0c"), lit("0x2.08"), lit("0x2.04"),
-        lit("0x2.00"), lit("0x1.fc"), lit("0x1.f8"), lit("0x1.f4"),
-        lit("0x1.f0"), lit("0x1.ec"), lit("0x1.e9"), lit("0x1.e5"),
-        lit("0x1.e1"), lit("0x1.de"), lit("0x1.da"), lit("0x1.d7"),
-        lit("0x1.d4"), lit("0x1.d0"), lit("0x1.cd"), lit("0x1.ca"),
-        lit("0x1.c7"), lit("0x1.c3"), lit("0x1.c0"), lit("0x1.bd"),
-        lit("0x1.ba"), lit("0x1.b7"), lit("0x1.b4"), lit("0x1.b2"),
-        lit("0x1.af"), lit("0x1.ac"), lit("0x1.a9"), lit("0x1.a6"),
-        lit("0x1.a4"), lit("0x1.a1"), lit("0x1.9e"), lit("0x1.9c"),
-        lit("0x1.99"), lit("0x1.97"), lit("0x1.94"), lit("0x1.92"),
-        lit("0x1.8f"), lit("0x1.8d"), lit("0x1.8a"), lit("0x1.88"),
-        lit("0x1.86"), lit("0x1.83"), lit("0x1.81"), lit("0x1.7f"),
-        lit("0x1.7d"), lit("0x1.7a"), lit("0x1.78"), lit("0x1.76"),
-        lit("0x1.74"), lit("0x1.72"), lit("0x1.70"), lit("0x1.6e"),
-        lit("0x1.6c"), lit("0x1.6a"), lit("0x1.68"), lit("0x1.66"),
-        lit("0x1.64"), lit("0x1.62"), lit("0x1.60"), lit("0x1.5e"),
-        lit("0x1.5c"), lit("0x1.5a"), lit("0x1.58"), lit("0x1.57"),
-        lit("0x1.55"), lit("0x1.53"), lit("0x1.51"), lit("0x1.50"),
-        lit("0x1.4e"), lit("0x1.4c"), lit("0x1.4a"), lit("0x1.49"),
-        lit("0x1.47"), lit("0x1.46"), lit("0x1.44"), lit("0x1.42"),
-        lit("0x1.41"), lit("0x1.3f"), lit("0x1.3e"), lit("0x1.3c"),
-        lit("0x1.3b"), lit("0x1.39"), lit("0x1.38"), lit("0x1.36"),
-        lit("0x1.35"), lit("0x1.33"), lit("0x1.32"), lit("0x1.30"),
-        lit("0x1.2f"), lit("0x1.2e"), lit("0x1.2c"), lit("0x1.2b"),
-        lit("0x1.29"), lit("0x1.28"), lit("0x1.27"), lit("0x1.25"),
-        lit("0x1.24"), lit("0x1.23"), lit("0x1.21"), lit("0x1.20"),
-        lit("0x1.1f"), lit("0x1.1e"), lit("0x1.1c"), lit("0x1.1b"),
-        lit("0x1.1a"), lit("0x1.19"), lit("0x1.18"), lit("0x1.16"),
-        lit("0x1.15"), lit("0x1.14"), lit("0x1.13"), lit("0x1.12"),
-        lit("0x1.11"), lit("0x1.0f"), lit("0x1.0e"), lit("0x1.0d"),
-        lit("0x1.0c"), lit("0x1.0b"), lit("0x1.0a"), lit("0x1.09"),
-        lit("0x1.08"), lit("0x1.07"), lit("0x1.06"), lit("0x1.05"),
-        lit("0x1.04"), lit("0x1.03"), lit("0x1.02"), lit("0x1.01"),
-    ];
-    LOOKUP_TABLE[(x.to_bits() >> 8) as usize]
+    // y * 2^-shift == 2^-12 / per_smp
+    let (y, shift) = one_over_one_plus_refined(scaled - U16F16::ONE);
+    // phase has 28 fractional bits and y has 15, so shift the product back to
+    // 16 fractional bits (accounting for the 2^-12 above)
+    let x = (phase.to_bits() as u64 * y.to_bits() as u64) >> (15 + shift);
+    // phase < per_smp, so this is at most about one, but the reciprocal may
+    // round it up past the largest Scalar
+    let x = U16F16::from_bits(x as u32).dsp_clamp(U16F16::ZERO, U16F16::from_num(ScalarFxP::MAX));
+    ScalarFxP::from_num(x)
+}
+
+/// Clamp an oscillator sync fraction to `[0, 1]`, as rounding may push it
+/// just outside of that range
+#[cfg(feature = "float")]
+fn clamp_sync_fraction<T: DspFormatBase>(frac: T::Scalar) -> T::Scalar {
+    frac.dsp_clamp(T::Scalar::zero(), T::Scalar::one())
 }
 
 fn one_over_one_minus_x(x: ShapeFxP) -> crate::fixedmath::USample {
@@ -605,3 +1268,499 @@ fn one_over_one_minus_x(x: ShapeFxP) -> crate::fixedmath::USample {
         .wide_mul(crate::fixedmath::U8F8::from_bits(x_bits & 0xFF));
     lookup_val + crate::fixedmath::USample::from_num(interp)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SignedNoteFxP;
+
+    /// The magnitude of the single bin DFT of `signal` (sampled at `sr` Hz)
+    /// at `freq` Hz
+    #[cfg(feature = "float")]
+    fn dft_magnitude(signal: &[f32], freq: f32, sr: f32) -> f32 {
+        let (mut re, mut im) = (0f64, 0f64);
+        for (i, smp) in signal.iter().enumerate() {
+            // Wrap to a single cycle so the phase stays accurate for long signals
+            let cycles = (freq as f64 * i as f64 / sr as f64).fract();
+            let phase = core::f64::consts::TAU * cycles;
+            re += *smp as f64 * phase.cos();
+            im += *smp as f64 * phase.sin();
+        }
+        (re * re + im * im).sqrt() as f32
+    }
+
+    /// A Hann window of length `n`, evaluated at sample `i`
+    #[cfg(feature = "float")]
+    fn hann(i: usize, n: usize) -> f32 {
+        0.5 - 0.5 * (core::f32::consts::TAU * i as f32 / n as f32).cos()
+    }
+
+    /// Relative magnitudes (to the carrier at A440) of the spectrum of the
+    /// secondary oscillator at the given frequencies, with the primary
+    /// oscillator an octave below the secondary (at 220Hz)
+    #[cfg(feature = "float")]
+    fn pm_spectrum(fm_index: f32, freqs: &[f32; 4]) -> [f32; 4] {
+        const SR: f32 = 48000f32;
+        let ctx = Context::new(SR);
+        let mut oscs = SyncedOscs::<f32>::new();
+        let params = SyncedOscsParams {
+            primary: OscParams {
+                tune: -12f32,
+                shape: 0f32,
+            },
+            secondary: Default::default(),
+            sync: OscSyncMode::Off,
+            fm_index,
+        };
+        let mut signal = [0f32; 48000];
+        for smp in signal.iter_mut() {
+            *smp = oscs.next(&ctx, 69f32, params.clone()).secondary.sin;
+        }
+        let carrier = dft_magnitude(&signal, 440f32, SR);
+        freqs.map(|f| dft_magnitude(&signal, f, SR) / carrier)
+    }
+
+    #[cfg(feature = "float")]
+    #[test]
+    fn phase_mod_sidebands() {
+        // Sidebands should appear at 440 +/- 220*k Hz, but not in between
+        let freqs = [220f32, 660f32, 880f32, 550f32];
+        let unmodulated = pm_spectrum(0f32, &freqs);
+        assert!(unmodulated.iter().all(|x| *x < 0.01));
+        let modulated = pm_spectrum(0.25, &freqs);
+        assert!(modulated[0] > 0.1 && modulated[1] > 0.1 && modulated[2] > 0.01);
+        assert!(modulated[3] < 0.01);
+        let more_modulated = pm_spectrum(0.5, &freqs);
+        assert!(more_modulated[2] > modulated[2]);
+        assert!(more_modulated[3] < 0.01);
+    }
+
+    /// Magnitudes of the triangle output at note 100 (with a fundamental of
+    /// `f0` Hz), returning the fundamental and the first four aliased
+    /// harmonics (the 11th, 13th, 15th and 17th, which alias down to about
+    /// 19kHz, 13.7kHz, 8.4kHz and 3.2kHz respectively)
+    #[cfg(feature = "float")]
+    fn tri_aliasing<T: DspFormat>(
+        ctx: &T::Context,
+        f0: f32,
+        band_limited: bool,
+    ) -> (f32, [f32; 4]) {
+        const SR: f32 = 48000f32;
+        const N: usize = 9600;
+        let mut osc = Osc::<T>::new();
+        osc.set_band_limited_tri(band_limited);
+        let note = T::note_from_fixed(NoteFxP::lit("100"));
+        // Let the integrator settle before measuring
+        for _ in 0..4800 {
+            osc.next(ctx, note, Default::default());
+        }
+        let mut signal = [0f32; N];
+        for (i, smp) in signal.iter_mut().enumerate() {
+            let tri = T::sample_to_float(osc.next(ctx, note, Default::default()).tri);
+            *smp = tri * hann(i, N);
+        }
+        let magnitude = |freq: f32| dft_magnitude(&signal, freq, SR);
+        let aliases = [11f32, 13f32, 15f32, 17f32].map(|k| magnitude(SR - k * f0));
+        (magnitude(f0), aliases)
+    }
+
+    #[cfg(feature = "float")]
+    fn check_tri_aliasing<T: DspFormat>(ctx: &T::Context, f0: f32) {
+        let (naive_f0, naive_alias) = tri_aliasing::<T>(ctx, f0, false);
+        let (blep_f0, blep_alias) = tri_aliasing::<T>(ctx, f0, true);
+        // The fundamental should be (nearly) unchanged...
+        assert!(
+            (blep_f0 / naive_f0 - 1f32).abs() < 0.05,
+            "{blep_f0} vs {naive_f0}"
+        );
+        // ...but the aliasing should be significantly reduced, especially
+        // for the harmonics furthest above Nyquist
+        let blep_total: f32 = blep_alias.iter().sum();
+        let naive_total: f32 = naive_alias.iter().sum();
+        assert!(
+            blep_total < naive_total / 2f32,
+            "{blep_total} vs {naive_total}"
+        );
+        assert!(
+            blep_alias[3] < naive_alias[3] / 8f32,
+            "{blep_alias:?} vs {naive_alias:?}"
+        );
+    }
+
+    #[cfg(feature = "float")]
+    #[test]
+    fn band_limited_tri_reduces_aliasing() {
+        let f0 = 440f32 * 2f32.powf(31f32 / 12f32);
+        check_tri_aliasing::<f32>(&Context::new(48000f32), f0);
+        let f0 = fixedmath::midi_note_to_frequency(NoteFxP::lit("100")).to_num();
+        check_tri_aliasing::<i16>(&ContextFxP::new_480(), f0);
+    }
+
+    #[cfg(feature = "float")]
+    #[test]
+    fn polyblep_fixed_matches_float() {
+        for dt in [0.00001f64, 0.001, 0.02, 0.3] {
+            let dt_fxp = PhaseFxP::from_num(dt);
+            let recip = polyblep_recip(dt_fxp);
+            for frac in [0f64, 0.1, 0.5, 0.9, -0.9, -0.5, -0.1] {
+                let t = if frac < 0. { 1. + frac * dt } else { frac * dt };
+                let t_fxp = PhaseFxP::from_num(t);
+                let fixed: f64 = polyblep_fixed(t_fxp, dt_fxp, recip).to_num();
+                let float = polyblep(t_fxp.to_num::<f64>(), dt_fxp.to_num::<f64>());
+                assert!(
+                    (fixed - float).abs() < 0.001,
+                    "{dt} {t}: {fixed} vs {float}"
+                );
+            }
+        }
+    }
+
+    /// Total harmonic distortion (the 2nd through 8th harmonics, relative to
+    /// the fundamental) of `signal`, which has a fundamental of `f0` Hz
+    #[cfg(feature = "float")]
+    fn thd(signal: impl Fn(usize) -> f32, f0: f32) -> f32 {
+        const SR: f32 = 48000f32;
+        const N: usize = 9600;
+        let windowed: [f32; N] = core::array::from_fn(|i| signal(i) * hann(i, N));
+        let magnitude = |freq: f32| dft_magnitude(&windowed, freq, SR);
+        let harmonics: f32 = (2..=8).map(|k| magnitude(k as f32 * f0).powi(2)).sum();
+        harmonics.sqrt() / magnitude(f0)
+    }
+
+    /// The THD of the sine output of an oscillator at note 69 (with a
+    /// fundamental of `f0` Hz)
+    #[cfg(feature = "float")]
+    fn sine_thd<T: DspFormat>(ctx: &T::Context, f0: f32, table: bool) -> f32 {
+        let mut osc = Osc::<T>::new();
+        osc.set_sine_table(table);
+        let note = T::note_from_fixed(NoteFxP::lit("69"));
+        let signal: [f32; 9600] = core::array::from_fn(|_| {
+            T::sample_to_float(osc.next(ctx, note, Default::default()).sin)
+        });
+        thd(|i| signal[i], f0)
+    }
+
+    /// Check the lookup table sine against the float reference, and (if
+    /// `approximated`, i.e. not using libm) against the default sine
+    #[cfg(feature = "float")]
+    fn check_sine_thd<T: DspFormat>(ctx: &T::Context, f0: f32, approximated: bool) {
+        let reference = thd(
+            |i| (core::f32::consts::TAU * f0 * i as f32 / 48000f32).sin(),
+            f0,
+        );
+        let approx = sine_thd::<T>(ctx, f0, false);
+        let table = sine_thd::<T>(ctx, f0, true);
+        // The table should be close to the float reference, which only shows
+        // spectral leakage from the window, to within about -100dB
+        assert!(table < reference + 1e-5, "{table} vs {reference}");
+        if approximated {
+            assert!(table < approx / 8f32, "{table} vs {approx}");
+        }
+    }
+
+    #[cfg(feature = "float")]
+    #[test]
+    fn sine_table_reduces_thd() {
+        let ctx = Context::new(48000f32);
+        check_sine_thd::<f32>(&ctx, 440f32, !ctx.use_libm());
+        let f0 = fixedmath::midi_note_to_frequency(NoteFxP::lit("69")).to_num();
+        check_sine_thd::<i16>(&ContextFxP::new_480(), f0, true);
+    }
+
+    /// Run an oscillator for a few periods at the given note and tuning,
+    /// returning the sum of the saw output
+    #[cfg(feature = "float")]
+    fn saw_sum<T: DspFormat>(ctx: &T::Context, note: T::Note, tune: T::NoteOffset) -> f32 {
+        let mut osc = Osc::<T>::new();
+        let params = OscParams {
+            tune,
+            shape: T::Scalar::zero(),
+        };
+        (0..1000)
+            .map(|_| T::sample_to_float(osc.next(ctx, note, params.clone()).saw))
+            .sum()
+    }
+
+    #[cfg(feature = "float")]
+    #[test]
+    fn extreme_tune_clamps() {
+        let ctx = ContextFxP::new_480();
+        let top = saw_sum::<i16>(&ctx, NoteFxP::MAX, SignedNoteFxP::ZERO);
+        let bottom = saw_sum::<i16>(&ctx, NoteFxP::ZERO, SignedNoteFxP::ZERO);
+        assert_eq!(
+            saw_sum::<i16>(&ctx, NoteFxP::lit("120"), SignedNoteFxP::MAX),
+            top
+        );
+        assert_eq!(
+            saw_sum::<i16>(&ctx, NoteFxP::lit("5"), SignedNoteFxP::MIN),
+            bottom
+        );
+
+        let ctx = Context::new(48000f32);
+        let top = saw_sum::<f32>(&ctx, NoteFxP::MAX.to_num(), 0f32);
+        let bottom = saw_sum::<f32>(&ctx, 0f32, 0f32);
+        assert_eq!(saw_sum::<f32>(&ctx, 120f32, 64f32), top);
+        assert_eq!(saw_sum::<f32>(&ctx, 5f32, -64f32), bottom);
+    }
+
+    /// The largest sample-to-sample change in the saw output around a phase
+    /// reset partway through the waveform
+    #[cfg(feature = "float")]
+    fn max_reset_delta<T: DspFormat>(ctx: &T::Context, anti_click: bool) -> f32 {
+        let mut osc = Osc::<T>::new();
+        osc.set_anti_click(anti_click);
+        let note = T::note_from_fixed(NoteFxP::lit("60"));
+        let next =
+            |osc: &mut Osc<T>| T::sample_to_float(osc.next(ctx, note, Default::default()).saw);
+        let mut last = 0f32;
+        for _ in 0..80 {
+            last = next(&mut osc);
+        }
+        osc.reset_phase();
+        (0..16).fold(0f32, |acc, _| {
+            let smp = next(&mut osc);
+            let delta = (smp - last).abs();
+            last = smp;
+            acc.max(delta)
+        })
+    }
+
+    #[cfg(feature = "float")]
+    #[test]
+    fn anti_click_fade() {
+        let ctx = Context::new(48000f32);
+        assert!(max_reset_delta::<f32>(&ctx, false) > 0.5);
+        assert!(max_reset_delta::<f32>(&ctx, true) < 0.15);
+        let ctx = ContextFxP::new_480();
+        assert!(max_reset_delta::<i16>(&ctx, false) > 0.5);
+        assert!(max_reset_delta::<i16>(&ctx, true) < 0.15);
+    }
+
+    #[test]
+    fn extreme_phase_increment_wraps() {
+        let ctx = ContextFxP::new_441();
+        let mut osc = Osc::<i16>::new();
+        let params = OscParams {
+            tune: SignedNoteFxP::ZERO,
+            shape: ScalarFxP::MAX,
+        };
+        for _ in 0..1000 {
+            let out = osc.next(&ctx, NoteFxP::MAX, params.clone());
+            // Allow for the rounding error in the saw output at exactly -pi:
+            assert!(out.saw.to_num::<f32>().abs() < 1.001);
+            assert!(osc.phase >= -PhaseFxP::PI && osc.phase < PhaseFxP::PI);
+        }
+    }
+
+    /// The ratio of the peak saw output with the Nyquist mute enabled to the
+    /// peak output without it
+    #[cfg(feature = "float")]
+    fn nyquist_ratio<T: DspFormat>(ctx: &T::Context, note: T::Note) -> f32 {
+        let peak = |mute| {
+            let mut osc = Osc::<T>::new();
+            osc.set_nyquist_mute(mute);
+            (0..480)
+                .map(|_| T::sample_to_float(osc.next(ctx, note, Default::default()).saw).abs())
+                .fold(0f32, f32::max)
+        };
+        peak(true) / peak(false)
+    }
+
+    #[cfg(feature = "float")]
+    #[test]
+    fn nyquist_mute_attenuates() {
+        let ctx = Context::new(16000f32);
+        assert_eq!(nyquist_ratio::<f32>(&ctx, 100f32), 1f32);
+        let near = nyquist_ratio::<f32>(&ctx, 110f32);
+        let nearer = nyquist_ratio::<f32>(&ctx, 115f32);
+        assert!(near < 0.95 && nearer < near, "{near} {nearer}");
+        // The fundamental is above the Nyquist frequency
+        assert_eq!(nyquist_ratio::<f32>(&ctx, 120f32), 0f32);
+
+        let ctx = ContextFxP::new_441();
+        assert_eq!(nyquist_ratio::<i16>(&ctx, NoteFxP::lit("100")), 1f32);
+        let top = nyquist_ratio::<i16>(&ctx, NoteFxP::lit("127"));
+        assert!(top > 0.8 && top < 0.95, "{top}");
+    }
+
+    /// The phase of the secondary of a synced pair of oscillators (tuned
+    /// `tune` above the primary) just after each of the first 8 times it is
+    /// synced, converted by `to_float`
+    #[cfg(feature = "float")]
+    fn sync_phases<T: DspFormat>(
+        ctx: &T::Context,
+        tune: T::NoteOffset,
+        sync_phase: T::Scalar,
+        to_float: fn(T::Phase) -> f32,
+    ) -> [f32; 8] {
+        let mut oscs = SyncedOscs::<T>::new();
+        oscs.set_sync_phase(sync_phase);
+        let note = T::note_from_fixed(NoteFxP::lit("48"));
+        let params = SyncedOscsParams::<T> {
+            secondary: OscParams {
+                tune,
+                shape: T::Scalar::zero(),
+            },
+            sync: OscSyncMode::Hard,
+            ..Default::default()
+        };
+        let mut phases = [0f32; 8];
+        let mut n = 0;
+        while n < phases.len() {
+            let before = oscs.primary.phase;
+            oscs.next(ctx, note, params.clone());
+            if before < T::Phase::zero() && oscs.primary.phase >= T::Phase::zero() {
+                phases[n] = to_float(oscs.secondary.phase);
+                n += 1;
+            }
+        }
+        phases
+    }
+
+    #[cfg(feature = "float")]
+    fn check_sync_phase<T: DspFormat>(
+        ctx: &T::Context,
+        tune: T::NoteOffset,
+        quarter: T::Scalar,
+        to_float: fn(T::Phase) -> f32,
+    ) {
+        let reference = sync_phases::<T>(ctx, tune, T::Scalar::zero(), to_float);
+        let offset = sync_phases::<T>(ctx, tune, quarter, to_float);
+        for (a, b) in reference.iter().zip(offset.iter()) {
+            // The secondary restarts a quarter cycle later in its waveform
+            let diff = (b - a).rem_euclid(core::f32::consts::TAU);
+            assert!(
+                (diff - core::f32::consts::FRAC_PI_2).abs() < 1e-3,
+                "{a} {b}"
+            );
+        }
+    }
+
+    #[cfg(feature = "float")]
+    #[test]
+    fn sync_phase_offsets_reset() {
+        // Tune the secondary a fifth above the primary
+        check_sync_phase::<f32>(&Context::new(48000f32), 7f32, 0.25f32, |x| x);
+        let (tune, quarter) = (SignedNoteFxP::lit("7"), ScalarFxP::lit("0.25"));
+        check_sync_phase::<i16>(&ContextFxP::new_480(), tune, quarter, |x| x.to_num());
+    }
+
+    /// The summed magnitude of the aliases of harmonics 125 to 200 in the
+    /// sine output of the secondary of a synced pair (with the primary at
+    /// 220Hz) tuned `tune` above the primary.  The sine has no discontinuities
+    /// of its own, so all of the aliasing is caused by the sync resets.
+    #[cfg(feature = "float")]
+    fn sync_aliasing<T: DspFormat>(ctx: &T::Context, tune: T::NoteOffset, blep: bool) -> f32 {
+        const SR: f32 = 48000f32;
+        const N: usize = 9600;
+        let mut oscs = SyncedOscs::<T>::new();
+        oscs.set_sync_blep(blep);
+        let note = T::note_from_fixed(NoteFxP::lit("57"));
+        let params = SyncedOscsParams::<T> {
+            secondary: OscParams {
+                tune,
+                shape: T::Scalar::zero(),
+            },
+            sync: OscSyncMode::Hard,
+            ..Default::default()
+        };
+        let signal: [f32; N] = core::array::from_fn(|i| {
+            let out = oscs.next(ctx, note, params.clone()).secondary.sin;
+            T::sample_to_float(out) * hann(i, N)
+        });
+        let magnitude = |freq: f32| dft_magnitude(&signal, freq, SR);
+        (125..=200).map(|k| magnitude(SR - k as f32 * 220f32)).sum()
+    }
+
+    #[cfg(feature = "float")]
+    #[test]
+    fn sync_blep_reduces_aliasing() {
+        let ctx = Context::new(48000f32);
+        for tune in [17.5f32, 21f32, 26.5f32] {
+            let naive = sync_aliasing::<f32>(&ctx, tune, false);
+            let blep = sync_aliasing::<f32>(&ctx, tune, true);
+            assert!(blep < naive / 8f32, "{tune}: {blep} vs {naive}");
+        }
+        let ctx = ContextFxP::new_480();
+        for tune in ["17.5", "21", "26.5"].map(SignedNoteFxP::lit) {
+            let naive = sync_aliasing::<i16>(&ctx, tune, false);
+            let blep = sync_aliasing::<i16>(&ctx, tune, true);
+            assert!(blep < naive / 8f32, "{tune}: {blep} vs {naive}");
+        }
+    }
+
+    #[test]
+    fn shape_clips_at_boundaries() {
+        let clip = ScalarFxP::lit("0x0.F");
+        assert_eq!(clip_shape(ScalarFxP::ZERO), ScalarFxP::ZERO);
+        assert_eq!(clip_shape(clip - ScalarFxP::DELTA), clip - ScalarFxP::DELTA);
+        assert_eq!(clip_shape(clip), clip);
+        assert_eq!(clip_shape(clip + ScalarFxP::DELTA), clip);
+        assert_eq!(clip_shape(ScalarFxP::MAX), clip);
+    }
+
+    #[test]
+    fn sync_fraction_clamps_at_boundaries() {
+        let per_smp = PhaseFxP::lit("0.05");
+        assert_eq!(sync_fraction(PhaseFxP::ZERO, per_smp), ScalarFxP::ZERO);
+        let half = sync_fraction(per_smp / 2, per_smp).to_num::<f32>();
+        assert!((half - 0.5).abs() < 0.001, "{half}");
+        let whole = sync_fraction(per_smp, per_smp);
+        assert!(whole > ScalarFxP::lit("0.9999"), "{whole}");
+        // Anything past a whole sample saturates at the largest Scalar
+        assert_eq!(sync_fraction(per_smp * 2, per_smp), ScalarFxP::MAX);
+    }
+
+    #[cfg(feature = "float")]
+    #[test]
+    fn float_sync_fraction_clamps() {
+        assert_eq!(clamp_sync_fraction::<f32>(1.0001), 1f32);
+        assert_eq!(clamp_sync_fraction::<f32>(-0.0001), 0f32);
+    }
+
+    /// The summed magnitude of the components from 4.4kHz to 8.8kHz (at
+    /// multiples of 110Hz, as soft sync repeats every other cycle of the
+    /// primary at 220Hz) in the sine output of the secondary of a pair synced
+    /// with `mode` and tuned `tune` above the primary
+    #[cfg(feature = "float")]
+    fn sync_harmonics<T: DspFormat>(
+        ctx: &T::Context,
+        tune: T::NoteOffset,
+        mode: OscSyncMode,
+    ) -> f32 {
+        const SR: f32 = 48000f32;
+        const N: usize = 9600;
+        let mut oscs = SyncedOscs::<T>::new();
+        let note = T::note_from_fixed(NoteFxP::lit("57"));
+        let params = SyncedOscsParams::<T> {
+            secondary: OscParams {
+                tune,
+                shape: T::Scalar::zero(),
+            },
+            sync: mode,
+            ..Default::default()
+        };
+        let signal: [f32; N] = core::array::from_fn(|i| {
+            let out = oscs.next(ctx, note, params.clone()).secondary.sin;
+            T::sample_to_float(out) * hann(i, N)
+        });
+        let magnitude = |freq: f32| dft_magnitude(&signal, freq, SR);
+        (40..=80).map(|k| magnitude(k as f32 * 110f32)).sum()
+    }
+
+    #[cfg(feature = "float")]
+    fn check_soft_sync<T: DspFormat>(ctx: &T::Context, tune: T::NoteOffset) {
+        let hard = sync_harmonics::<T>(ctx, tune, OscSyncMode::Hard);
+        let soft = sync_harmonics::<T>(ctx, tune, OscSyncMode::Soft);
+        assert!(soft < hard / 4f32, "{soft} vs {hard}");
+    }
+
+    #[cfg(feature = "float")]
+    #[test]
+    fn soft_sync_is_smoother() {
+        // Tune the secondary a fourth above the primary, so the hard sync
+        // resets are far from the zero crossings of the secondary
+        check_soft_sync::<f32>(&Context::new(48000f32), 5f32);
+        check_soft_sync::<i16>(&ContextFxP::new_480(), SignedNoteFxP::lit("5"));
+    }
+}
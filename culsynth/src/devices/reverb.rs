@@ -0,0 +1,198 @@
+use super::delay::{dry_wet, DelayLine};
+use super::*;
+
+/// Parameters for a [Reverb]
+#[derive(Clone, Default, Debug, PartialEq)]
+pub struct ReverbParams<T: DspFormatBase> {
+    /// The room size, as the length of the longest delay line in samples.
+    /// This is clamped between 1 and the length of the delay buffers.
+    pub size: usize,
+    /// The amount of the mixed signal fed back into the delay lines, which
+    /// sets the decay time
+    pub decay: T::Scalar,
+    /// The amount of high frequency damping in the feedback path, from 0 (no
+    /// damping) to 1 (all high frequencies are removed)
+    pub damping: T::Scalar,
+    /// The dry/wet mix, from 0 (only the input) to 1 (only the reverberated
+    /// signal)
+    pub mix: T::Scalar,
+}
+
+#[cfg(feature = "float")]
+impl<T: DspFloat> From<&ReverbParams<i16>> for ReverbParams<T> {
+    fn from(value: &ReverbParams<i16>) -> Self {
+        Self {
+            size: value.size,
+            decay: value.decay.to_num(),
+            damping: value.damping.to_num(),
+            mix: value.mix.to_num(),
+        }
+    }
+}
+
+/// The lengths of each delay line relative to [ReverbParams::size], as a
+/// fraction of 65536.  These are chosen so the echoes from each line do not
+/// line up with each other.
+const LINE_RATIOS: [u64; 4] = [65535, 56563, 48563, 41091];
+
+/// Apply `op` (which must not change the sign of its argument) to the
+/// magnitude of `x`, so that any fixed point truncation rounds towards zero.
+/// Otherwise, negative values can get stuck at -1 LSB in the feedback path,
+/// sustaining a limit cycle.
+fn toward_zero<T: DspFormatBase>(x: T::Sample, op: impl Fn(T::Sample) -> T::Sample) -> T::Sample {
+    if x < T::Sample::zero() {
+        T::Sample::zero() - op(T::Sample::zero() - x)
+    } else {
+        op(x)
+    }
+}
+
+/// A stereo reverb
+///
+/// This is a small feedback delay network: four delay lines, whose outputs
+/// are lowpass filtered (see [ReverbParams::damping]), mixed with each other
+/// by an orthogonal (Hadamard) matrix, and fed back into their inputs.  The
+/// left input feeds the first two lines and the right input the last two,
+/// while each output channel takes one line fed by each input, so even a
+/// mono input produces a decorrelated stereo tail.
+///
+/// The buffers are statically sized to `N` samples per delay line, which
+/// sets the maximum room size.
+///
+/// This implements [Device] taking a tuple of (left, right) Samples as input
+/// and [ReverbParams] as parameters, and outputting a tuple of (left, right)
+/// Samples.
+#[derive(Clone, Default)]
+pub struct Reverb<T: DspFormat, const N: usize> {
+    lines: [DelayLine<T, N>; 4],
+    damped: [T::Sample; 4],
+    idx: usize,
+}
+
+impl<T: DspFormat, const N: usize> Reverb<T, N> {
+    /// Constructor
+    pub fn new() -> Self {
+        Default::default()
+    }
+}
+
+impl<T: DspFormat, const N: usize> Device<T> for Reverb<T, N> {
+    type Input = (T::Sample, T::Sample);
+    type Params = ReverbParams<T>;
+    type Output = (T::Sample, T::Sample);
+    fn next(
+        &mut self,
+        _: &T::Context,
+        (in_l, in_r): (T::Sample, T::Sample),
+        params: ReverbParams<T>,
+    ) -> (T::Sample, T::Sample) {
+        let size = params.size.clamp(1, N) as u64;
+        let taps: [T::Sample; 4] = core::array::from_fn(|i| {
+            let time = ((size * LINE_RATIOS[i]) >> 16) as usize;
+            self.lines[i].read(self.idx, time)
+        });
+        for (damped, tap) in self.damped.iter_mut().zip(taps) {
+            let held = toward_zero::<T>(*damped, |x| x.scale(params.damping));
+            let fresh = tap.scale(T::Scalar::one() - params.damping);
+            *damped = T::flush_denormal(fresh.dsp_saturating_add(held));
+        }
+        // Apply the Hadamard matrix (scaled by 1/2 to keep it orthogonal),
+        // halving first so the intermediate sums cannot overflow
+        let [a, b, c, d] = self.damped.map(|x| toward_zero::<T>(x, |x| x.divide_by_two()));
+        let (sum_ab, diff_ab) = (a + b, a - b);
+        let (sum_cd, diff_cd) = (c + d, c - d);
+        let mixed = [
+            sum_ab.dsp_saturating_add(sum_cd),
+            diff_ab.dsp_saturating_add(diff_cd),
+            sum_ab.dsp_saturating_sub(sum_cd),
+            diff_ab.dsp_saturating_sub(diff_cd),
+        ];
+        let inputs = [in_l, in_l, in_r, in_r].map(|x| x.divide_by_two());
+        for ((line, input), fb) in self.lines.iter_mut().zip(inputs).zip(mixed) {
+            let fb = toward_zero::<T>(fb, |x| x.scale(params.decay));
+            line.buf[self.idx] = T::flush_denormal(input.dsp_saturating_add(fb));
+        }
+        self.idx = (self.idx + 1) % N;
+        let wet_l = taps[0].divide_by_two() + taps[2].divide_by_two();
+        let wet_r = taps[1].divide_by_two() + taps[3].divide_by_two();
+        (
+            dry_wet::<T>(in_l, wet_l, params.mix),
+            dry_wet::<T>(in_r, wet_r, params.mix),
+        )
+    }
+}
+
+#[cfg(all(test, feature = "float"))]
+mod tests {
+    use super::*;
+
+    const SIZE: usize = 512;
+
+    /// Feed an impulse into the left channel of a reverb with the given decay,
+    /// returning the output energy in each window of `SIZE` samples and the
+    /// fraction of nonzero output samples in the fourth window
+    fn impulse_windows<T: DspFormat>(ctx: &T::Context, decay: T::Scalar) -> ([f32; 32], f32) {
+        let mut reverb = Reverb::<T, SIZE>::new();
+        let params = ReverbParams {
+            size: SIZE,
+            decay,
+            damping: T::Scalar::one().divide_by_two().divide_by_two(),
+            mix: T::Scalar::one(),
+        };
+        let impulse = T::sample_from_fixed(crate::IScalarFxP::lit("0.5"));
+        let mut windows = [0f32; 32];
+        let mut nonzero = 0;
+        for (i, window) in (0..SIZE * windows.len()).map(|i| (i, i / SIZE)) {
+            let input = if i == 0 { impulse } else { T::Sample::zero() };
+            let (l, r) = reverb.next(ctx, (input, T::Sample::zero()), params.clone());
+            let (l, r) = (T::sample_to_float(l), T::sample_to_float(r));
+            windows[window] += l * l + r * r;
+            if window == 3 && l != 0f32 && r != 0f32 {
+                nonzero += 1;
+            }
+        }
+        (windows, nonzero as f32 / SIZE as f32)
+    }
+
+    /// The first window with less than 1% of the energy of the loudest
+    fn decay_time(windows: &[f32]) -> usize {
+        let peak = windows.iter().fold(0f32, |acc, x| acc.max(*x));
+        windows.iter().rposition(|x| *x >= peak * 0.01).unwrap() + 1
+    }
+
+    #[test]
+    fn impulse_tail_decays() {
+        let ctx = Context::new(48000f32);
+        let (short, _) = impulse_windows::<f32>(&ctx, 0.6f32);
+        let (long, density) = impulse_windows::<f32>(&ctx, 0.9f32);
+        assert!(density > 0.9, "{density}");
+        // After the first pass through the network, the tail only decays
+        assert!(short[2..].windows(2).all(|w| w[1] < w[0]), "{short:?}");
+        let (short_time, long_time) = (decay_time(&short), decay_time(&long));
+        assert!(long_time >= 2 * short_time, "{short_time} vs {long_time}");
+
+        // Fixed point truncation leaves gaps between echoes, so only check
+        // the decay time
+        let ctx = ContextFxP::new_480();
+        let (short, _) = impulse_windows::<i16>(&ctx, ScalarFxP::lit("0.6"));
+        let (long, _) = impulse_windows::<i16>(&ctx, ScalarFxP::lit("0.9"));
+        let (short_time, long_time) = (decay_time(&short), decay_time(&long));
+        assert!(long_time >= 2 * short_time, "{short_time} vs {long_time}");
+    }
+
+    #[test]
+    fn float_state_flushes_denormals() {
+        let ctx = Context::new(48000f32);
+        let mut reverb = Reverb::<f32, 64>::new();
+        let params = ReverbParams {
+            size: 64,
+            decay: 0.7f32,
+            damping: 0.5f32,
+            mix: 0.5f32,
+        };
+        crate::devices::tests::decays_cleanly(|x| {
+            reverb.next(&ctx, (x, x), params.clone());
+            reverb.damped
+        });
+    }
+}
@@ -1,7 +1,7 @@
 use super::*;
 
 /// Input for a [RingMod].
-#[derive(Clone, Default)]
+#[derive(Clone, Default, Debug, PartialEq)]
 pub struct RingModInput<T: DspFormatBase> {
     /// The first (carrier) input signal
     pub signal_a: T::Sample,
@@ -9,8 +9,18 @@ pub struct RingModInput<T: DspFormatBase> {
     pub signal_b: T::Sample,
 }
 
+#[cfg(feature = "float")]
+impl<T: DspFloat> From<&RingModInput<i16>> for RingModInput<T> {
+    fn from(value: &RingModInput<i16>) -> Self {
+        Self {
+            signal_a: value.signal_a.to_num(),
+            signal_b: value.signal_b.to_num(),
+        }
+    }
+}
+
 /// Params for a [RingMod]
-#[derive(Clone, Default)]
+#[derive(Clone, Default, Debug, PartialEq)]
 pub struct RingModParams<T: DspFormatBase> {
     /// Gain of the original first (carrier) signal, to be mixed
     /// back into the device's output.
@@ -20,14 +30,20 @@ pub struct RingModParams<T: DspFormatBase> {
     pub mix_b: T::Scalar,
     /// Gain of the modulated result to be mixed into the device's output
     pub mix_mod: T::Scalar,
+    /// The amount of stereo spread applied by a [StereoRingMod], from 0 (both
+    /// channels identical) to 1 (the right channel's modulator is offset by
+    /// 16 samples).  This is ignored by the mono [RingMod].
+    pub spread: T::Scalar,
 }
 
+#[cfg(feature = "float")]
 impl<T: DspFloat> From<&RingModParams<i16>> for RingModParams<T> {
     fn from(value: &RingModParams<i16>) -> Self {
         Self {
             mix_a: value.mix_a.to_num(),
             mix_b: value.mix_b.to_num(),
             mix_mod: value.mix_mod.to_num(),
+            spread: value.spread.to_num(),
         }
     }
 }
@@ -54,7 +70,7 @@ impl<T: DspFormat> Device<T> for RingMod<T> {
         input: RingModInput<T>,
         params: RingModParams<T>,
     ) -> T::Sample {
-        let ring = input.signal_a.multiply(input.signal_b);
+        let ring = input.signal_a.dsp_saturating_multiply(input.signal_b);
         self.mixer.next(
             context,
             [input.signal_a, input.signal_b, ring],
@@ -62,3 +78,195 @@ impl<T: DspFormat> Device<T> for RingMod<T> {
         )
     }
 }
+
+/// The maximum offset, in samples, between the modulator signals of the left
+/// and right channels of a [StereoRingMod] (see [RingModParams::spread])
+const SPREAD_SAMPLES: usize = 16;
+
+/// A stereo Ring Modulator and Mixer
+///
+/// This is identical to a [RingMod], except that the modulator used for the
+/// right channel's ring modulation is offset in phase from the left channel's,
+/// widening the modulated signal.  The offset is created by crossfading the
+/// modulator with a copy of itself delayed by 16 samples,
+/// according to [RingModParams::spread].  The unmodulated input signals are
+/// mixed identically into both channels.
+///
+/// This implements [Device], taking a [RingModInput] as input and
+/// [RingModParams] as parameters and outputting a tuple of (left, right)
+/// Samples.
+#[derive(Clone)]
+pub struct StereoRingMod<T: DspFormat> {
+    mixer: Mixer<T, 3>,
+    history: [T::Sample; SPREAD_SAMPLES],
+    idx: usize,
+}
+
+impl<T: DspFormat> Default for StereoRingMod<T> {
+    fn default() -> Self {
+        Self {
+            mixer: Default::default(),
+            history: [T::Sample::zero(); SPREAD_SAMPLES],
+            idx: 0,
+        }
+    }
+}
+
+impl<T: DspFormat> StereoRingMod<T> {
+    /// Constructor
+    pub fn new() -> Self {
+        Default::default()
+    }
+}
+
+impl<T: DspFormat> Device<T> for StereoRingMod<T> {
+    type Input = RingModInput<T>;
+    type Params = RingModParams<T>;
+    type Output = (T::Sample, T::Sample);
+    fn next(
+        &mut self,
+        context: &T::Context,
+        input: RingModInput<T>,
+        params: RingModParams<T>,
+    ) -> (T::Sample, T::Sample) {
+        // The oldest sample in the history is SPREAD_SAMPLES samples old
+        let delayed = self.history[self.idx];
+        self.history[self.idx] = input.signal_b;
+        self.idx = (self.idx + 1) % SPREAD_SAMPLES;
+        // Crossfade this way so that zero spread is exactly the modulator.
+        // The difference is halved first so it cannot overflow at full scale.
+        let half_diff =
+            (delayed.divide_by_two() - input.signal_b.divide_by_two()).scale(params.spread);
+        let offset = input.signal_b.dsp_saturating_add(half_diff).dsp_saturating_add(half_diff);
+        let gains = [params.mix_a, params.mix_b, params.mix_mod];
+        let ring_l = input.signal_a.dsp_saturating_multiply(input.signal_b);
+        let ring_r = input.signal_a.dsp_saturating_multiply(offset);
+        (
+            self.mixer.next(context, [input.signal_a, input.signal_b, ring_l], gains),
+            self.mixer.next(context, [input.signal_a, input.signal_b, ring_r], gains),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Sum of squared differences between the left and right channels when
+    /// ring modulating two sine waves
+    #[cfg(feature = "float")]
+    fn channel_difference<T: DspFormat>(
+        ctx: &T::Context,
+        params: RingModParams<T>,
+        to_float: impl Fn(T::Sample) -> f32,
+    ) -> f32 {
+        let mut ringmod = StereoRingMod::<T>::new();
+        let sine = |freq: f32, i: usize| {
+            let phase = core::f32::consts::TAU * freq * (i as f32) / 48000f32;
+            T::sample_from_fixed(crate::IScalarFxP::from_num(phase.sin() / 2f32))
+        };
+        (0..960)
+            .map(|i| {
+                let input = RingModInput {
+                    signal_a: sine(440f32, i),
+                    signal_b: sine(1250f32, i),
+                };
+                let (l, r) = ringmod.next(ctx, input, params.clone());
+                let diff = to_float(l) - to_float(r);
+                diff * diff
+            })
+            .sum()
+    }
+
+    #[cfg(feature = "float")]
+    #[test]
+    fn spread_decorrelates_channels() {
+        let ctx = Context::new(48000f32);
+        let params = |spread| RingModParams::<f32> {
+            mix_a: 0f32,
+            mix_b: 0f32,
+            mix_mod: 1f32,
+            spread,
+        };
+        assert_eq!(channel_difference(&ctx, params(0f32), |x| x), 0f32);
+        assert!(channel_difference(&ctx, params(1f32), |x| x) > 1f32);
+        let ctx = ContextFxP::new_480();
+        let params = |spread| RingModParams::<i16> {
+            mix_a: ScalarFxP::ZERO,
+            mix_b: ScalarFxP::ZERO,
+            mix_mod: ScalarFxP::MAX,
+            spread,
+        };
+        let to_float = |x: SampleFxP| x.to_num();
+        assert_eq!(
+            channel_difference(&ctx, params(ScalarFxP::ZERO), to_float),
+            0f32
+        );
+        assert!(channel_difference(&ctx, params(ScalarFxP::MAX), to_float) > 1f32);
+    }
+
+    #[test]
+    fn full_scale_saturates() {
+        let ctx = ContextFxP::new_480();
+        let mut ringmod = RingMod::<i16>::default();
+        let mut stereo = StereoRingMod::<i16>::new();
+        let params = RingModParams::<i16> {
+            mix_a: ScalarFxP::MAX,
+            mix_b: ScalarFxP::MAX,
+            mix_mod: ScalarFxP::MAX,
+            spread: ScalarFxP::MAX,
+        };
+        // The reference result, clipping the ring modulator and the output
+        let expected = |a: f32, b: f32| (a + b + (a * b).clamp(-8f32, 8f32)).clamp(-8f32, 8f32);
+        let full = [SampleFxP::MAX, SampleFxP::MIN];
+        let mut history = [0f32; SPREAD_SAMPLES];
+        // Step through every combination of full scale inputs, for long
+        // enough that the delayed modulator is also at full scale
+        for i in 0..(4 * SPREAD_SAMPLES + 1) {
+            let (a, b) = (full[i % 2], full[(i / 3) % 2]);
+            let (fa, fb) = (a.to_num::<f32>(), b.to_num::<f32>());
+            let delayed = history[i % SPREAD_SAMPLES];
+            history[i % SPREAD_SAMPLES] = fb;
+            let input = RingModInput {
+                signal_a: a,
+                signal_b: b,
+            };
+            let out = ringmod.next(&ctx, input.clone(), params.clone());
+            assert!(
+                (out.to_num::<f32>() - expected(fa, fb)).abs() < 0.01,
+                "{a} {b}: {out}"
+            );
+            let (l, r) = stereo.next(&ctx, input, params.clone());
+            assert!(
+                (l.to_num::<f32>() - expected(fa, fb)).abs() < 0.01,
+                "{a} {b}: {l}"
+            );
+            // At full spread, the right channel is modulated by the delayed
+            // modulator
+            let r_expected = (fa + fb + (fa * delayed).clamp(-8f32, 8f32)).clamp(-8f32, 8f32);
+            assert!((r.to_num::<f32>() - r_expected).abs() < 0.01, "{i}: {r}");
+        }
+    }
+
+    #[cfg(feature = "float")]
+    #[test]
+    fn fixed_matches_float() {
+        use crate::devices::tests::assert_formats_agree;
+        let sine = |freq: f32, i: usize| {
+            SampleFxP::from_num((core::f32::consts::TAU * freq * i as f32 / 48000f32).sin())
+        };
+        assert_formats_agree!(RingMod, 4, 4800, |i| {
+            let input = RingModInput {
+                signal_a: sine(440f32, i),
+                signal_b: sine(1250f32, i),
+            };
+            let params = RingModParams {
+                mix_a: ScalarFxP::lit("0.25"),
+                mix_b: ScalarFxP::lit("0.5"),
+                mix_mod: ScalarFxP::from_bits((i * 13) as u16),
+                spread: ScalarFxP::ZERO,
+            };
+            (input, params)
+        });
+    }
+}
@@ -0,0 +1,201 @@
+use super::*;
+use crate::fixedmath::{one_over_one_plus_refined, I16F16, U1F15, U32F0};
+
+pub(crate) mod detail {
+    pub trait WaveshaperOps: crate::DspFormatBase {
+        /// Precalculated data for [WaveshaperOps::soft_clip] at a given
+        /// threshold, so it need not divide by the knee every sample
+        type KneeScale: Copy + Default + Send;
+        fn knee_scale(threshold: Self::Scalar) -> Self::KneeScale;
+        fn soft_clip(
+            signal: Self::Sample,
+            threshold: Self::Scalar,
+            scale: Self::KneeScale,
+        ) -> Self::Sample;
+    }
+}
+
+/// Parameters for a [Waveshaper]
+#[derive(Clone, Default, Debug, PartialEq)]
+pub struct WaveshaperParams<T: DspFormatBase> {
+    /// The level (between 0 and 1) above which the signal starts to saturate.
+    /// Signals below this level pass through unchanged.
+    pub threshold: T::Scalar,
+}
+
+#[cfg(feature = "float")]
+impl<T: DspFloat> From<&WaveshaperParams<i16>> for WaveshaperParams<T> {
+    fn from(value: &WaveshaperParams<i16>) -> Self {
+        Self {
+            threshold: value.threshold.to_num(),
+        }
+    }
+}
+
+/// A soft-saturation waveshaper
+///
+/// Below the threshold the signal is passed through unchanged.  Above it, a
+/// quadratic knee smoothly bends the signal towards a ceiling of 1, which it
+/// reaches at an input level of `2 - threshold`.  The transfer curve and its
+/// slope are continuous, which gives a "warm" overdrive rather than the harsh
+/// clipping of fixed-point saturation.
+///
+/// This implements [Device] taking a Sample as input and [WaveshaperParams]
+/// as parameters and outputting a Sample.
+#[derive(Clone, Default)]
+pub struct Waveshaper<T: DspFormat> {
+    /// The threshold that `knee` was calculated for
+    threshold: Option<T::Scalar>,
+    knee: T::KneeScale,
+}
+
+impl<T: DspFormat> Device<T> for Waveshaper<T> {
+    type Input = T::Sample;
+    type Params = WaveshaperParams<T>;
+    type Output = T::Sample;
+    fn next(
+        &mut self,
+        _: &T::Context,
+        signal: T::Sample,
+        params: WaveshaperParams<T>,
+    ) -> T::Sample {
+        if self.threshold != Some(params.threshold) {
+            self.threshold = Some(params.threshold);
+            self.knee = T::knee_scale(params.threshold);
+        }
+        T::soft_clip(signal, params.threshold, self.knee)
+    }
+}
+
+#[cfg(feature = "float")]
+impl<T: DspFloat> detail::WaveshaperOps for T {
+    type KneeScale = T;
+    fn knee_scale(threshold: T) -> T {
+        // 1 / (4 * knee).  This is infinite for a threshold of 1, but then the
+        // knee is never reached
+        T::ONE / ((T::ONE - threshold) * T::TWO * T::TWO)
+    }
+    fn soft_clip(signal: T, threshold: T, scale: T) -> T {
+        let mag = signal.abs();
+        if mag <= threshold {
+            return signal;
+        }
+        let knee = T::ONE - threshold;
+        let excess = mag - threshold;
+        let shaped = if excess < knee * T::TWO {
+            threshold + excess - excess * excess * scale
+        } else {
+            T::ONE
+        };
+        if signal < T::ZERO {
+            -shaped
+        } else {
+            shaped
+        }
+    }
+}
+
+impl detail::WaveshaperOps for i16 {
+    type KneeScale = (U1F15, u32);
+    fn knee_scale(threshold: ScalarFxP) -> (U1F15, u32) {
+        // Reinterpret the knee as an integer to scale it up by 2^16, so it is
+        // at least one for any threshold
+        let knee = U32F0::from_bits((1u32 << 16) - threshold.to_bits() as u32);
+        // y * 2^-shift == 2^-16 / knee
+        one_over_one_plus_refined(knee - U32F0::ONE)
+    }
+    fn soft_clip(signal: SampleFxP, threshold: ScalarFxP, scale: (U1F15, u32)) -> SampleFxP {
+        let thresh = I16F16::from_num(threshold);
+        let mag = I16F16::from_num(signal.unsigned_abs());
+        if mag <= thresh {
+            return signal;
+        }
+        // threshold < 1 for a U0F16, so the knee is always nonzero
+        let knee = I16F16::ONE - thresh;
+        let excess = mag - thresh;
+        let shaped = if excess < knee * 2 {
+            // excess^2 has 32 fractional bits and y has 15, so shift the
+            // product back to 16 fractional bits (accounting for the 2^16
+            // above and the factor of 4)
+            let (y, shift) = scale;
+            let sq = excess.to_bits() as i64 * excess.to_bits() as i64;
+            thresh + excess - I16F16::from_bits(((sq * y.to_bits() as i64) >> (17 + shift)) as i32)
+        } else {
+            I16F16::ONE
+        };
+        let shaped = SampleFxP::saturating_from_num(shaped);
+        if signal < SampleFxP::ZERO {
+            -shaped
+        } else {
+            shaped
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "float")]
+    #[test]
+    fn waveshaper_float() {
+        let ctx = Context::new(48000f32);
+        let mut shaper = Waveshaper::<f32>::default();
+        let params = WaveshaperParams { threshold: 0.5 };
+        for x in [0f32, 0.1, -0.25, 0.5, -0.5] {
+            assert_eq!(shaper.next(&ctx, x, params.clone()), x);
+        }
+        let y = shaper.next(&ctx, 0.75, params.clone());
+        assert!(y > 0.5 && y < 0.75);
+        assert_eq!(shaper.next(&ctx, -0.75, params.clone()), -y);
+        assert_eq!(shaper.next(&ctx, 1.5, params.clone()), 1.0);
+        assert_eq!(shaper.next(&ctx, -4.0, params.clone()), -1.0);
+    }
+    #[test]
+    fn waveshaper_fixed() {
+        let ctx = ContextFxP::new_480();
+        let mut shaper = Waveshaper::<i16>::default();
+        let params = WaveshaperParams {
+            threshold: ScalarFxP::lit("0.5"),
+        };
+        for x in [0f32, 0.1, -0.25, 0.5, -0.5] {
+            let x = SampleFxP::from_num(x);
+            assert_eq!(shaper.next(&ctx, x, params.clone()), x);
+        }
+        let x = SampleFxP::lit("0.75");
+        let y = shaper.next(&ctx, x, params.clone());
+        assert!(y > SampleFxP::lit("0.5") && y < x);
+        assert_eq!(shaper.next(&ctx, -x, params.clone()), -y);
+        assert_eq!(
+            shaper.next(&ctx, SampleFxP::lit("1.5"), params.clone()),
+            SampleFxP::ONE
+        );
+        assert_eq!(
+            shaper.next(&ctx, SampleFxP::MIN, params.clone()),
+            -SampleFxP::ONE
+        );
+    }
+    #[cfg(feature = "float")]
+    #[test]
+    fn fixed_knee_matches_float() {
+        let ctx = Context::new(48000f32);
+        let ctx_fxp = ContextFxP::new_480();
+        let mut shaper = Waveshaper::<f32>::default();
+        let mut shaper_fxp = Waveshaper::<i16>::default();
+        for threshold in [0f32, 0.25, 0.5, 0.9, 0.999] {
+            let params = WaveshaperParams {
+                threshold: ScalarFxP::from_num(threshold),
+            };
+            let float_params = WaveshaperParams::<f32>::from(&params);
+            for x in [0.1f32, 0.5, 0.95, 1.0, 1.2, 1.9, -0.95] {
+                let x = SampleFxP::from_num(x);
+                let fixed: f32 = shaper_fxp.next(&ctx_fxp, x, params.clone()).to_num();
+                let float = shaper.next(&ctx, x.to_num(), float_params.clone());
+                assert!(
+                    (fixed - float).abs() < 0.001,
+                    "{threshold} {x}: {fixed} vs {float}"
+                );
+            }
+        }
+    }
+}
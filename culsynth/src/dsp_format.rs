@@ -1,5 +1,6 @@
 use super::*;
 use core::ops::{Add, Neg, Sub};
+#[cfg(feature = "float")]
 use fixed::traits::FromFixed;
 use fixedmath::scale_fixedfloat;
 
@@ -7,10 +8,14 @@ use fixedmath::scale_fixedfloat;
 /// 32 bit float, etc).
 pub trait DspFormat:
     DspFormatBase
+    + devices::biquad::detail::BiquadOps
     + devices::osc::detail::OscOps
     + devices::env::detail::EnvOps
     + devices::filt::detail::FiltOps
+    + devices::follower::detail::FollowerOps
+    + devices::glide::detail::GlideOps
     + devices::lfo::detail::LfoOps
+    + devices::waveshaper::detail::WaveshaperOps
     + voice::modulation::detail::ModulatorOps
 {
 }
@@ -45,10 +50,17 @@ pub trait DspFormatBase: Sized + Copy + Default + Send {
     /// Provide a value of the default note, definied as A440 (MIDI NN #69)
     fn default_note() -> Self::Note;
     /// Convert a midi Note into a Frequency
-    fn note_to_freq(note: Self::Note) -> Self::Frequency;
+    fn note_to_freq(context: &Self::Context, note: Self::Note) -> Self::Frequency;
     /// Convert a signed scalar to a Sample
     fn sample_from_fixed(value: crate::IScalarFxP) -> Self::Sample;
+    /// Convert a Sample to a signed scalar, saturating outside of [-1, 1)
+    fn sample_to_fixed(value: Self::Sample) -> crate::IScalarFxP;
+    /// Convert a 16 bit fixed point note to a Note
+    fn note_from_fixed(value: crate::NoteFxP) -> Self::Note;
+    /// Convert a 16 bit fixed point scalar to a Scalar
+    fn scalar_from_fixed(value: crate::ScalarFxP) -> Self::Scalar;
     /// Convert a sample to a 32 bit float
+    #[cfg(feature = "float")]
     fn sample_to_float(value: Self::Sample) -> f32;
     /// Widen a sample to a WideSample
     fn widen_sample(smp: Self::Sample) -> Self::WideSample;
@@ -57,10 +69,17 @@ pub trait DspFormatBase: Sized + Copy + Default + Send {
     /// Convert a Scalar to a Note (where 0 maps to the lowest representable
     /// note, and 1 maps to the highest)
     fn note_from_scalar(scalar: Self::Scalar) -> Self::Note;
-    /// Apply a note offset
+    /// Apply a note offset, saturating at the bounds of the representable
+    /// note range (so extreme modulation cannot wrap the pitch)
     fn apply_note_offset(note: Self::Note, offset: Self::NoteOffset) -> Self::Note;
+    /// Flush a denormal sample to zero (see [Float::flush_denormal]).  This
+    /// is a no-op for fixed point types, which have no denormals.
+    fn flush_denormal(smp: Self::Sample) -> Self::Sample {
+        smp
+    }
 }
 
+#[cfg(feature = "float")]
 ///Helper trait to make constraint bounds less painful for floating point types
 pub trait DspFloat:
     crate::Float
@@ -86,7 +105,13 @@ pub trait DspFloat:
 /// A trait to simplify common operations on DSP Types.  This is used to
 /// maximize the amount of code that can be agnostic to fixed and floating point
 pub trait DspType<T: DspFormatBase>:
-    Copy + Default + Send + Add<Self, Output = Self> + Sub<Self, Output = Self> + PartialOrd
+    Copy
+    + Default
+    + Send
+    + core::fmt::Debug
+    + Add<Self, Output = Self>
+    + Sub<Self, Output = Self>
+    + PartialOrd
 {
     /// A constant representing the value PI (3.14159...)
     const PI: Self;
@@ -101,9 +126,28 @@ pub trait DspType<T: DspFormatBase>:
     ///
     /// Used for when saturation is desired to avoid overflows, not correctness
     fn dsp_saturating_add(self, rhs: Self) -> Self;
+    /// This function will perform a saturating subtraction for fixed-point
+    /// types, and a normal subtraction for floating-point types
+    fn dsp_saturating_sub(self, rhs: Self) -> Self;
+    /// Clamp this value to the range `[min, max]`, which must not be empty.
+    /// For floating point types, NaN is passed through unchanged.
+    fn dsp_clamp(self, min: Self, max: Self) -> Self {
+        if self < min {
+            min
+        } else if self > max {
+            max
+        } else {
+            self
+        }
+    }
     /// Multiply this type with itself.  This trait does not provide any
     /// specified behavior for fixed-point overflow.
     fn multiply(self, rhs: Self) -> Self;
+    /// This function will perform a saturating multiplication for 16 and 32
+    /// bit fixed-point types, and a normal multiplication otherwise
+    fn dsp_saturating_multiply(self, rhs: Self) -> Self {
+        self.multiply(rhs)
+    }
     /// Divide a value by two
     fn divide_by_two(self) -> Self;
     /// Multiply this type by a Scalar.  This will never overflow
@@ -113,8 +157,10 @@ pub trait DspType<T: DspFormatBase>:
 
 // Floating-point implementation:
 
+#[cfg(feature = "float")]
 impl<T: DspFloat> DspFormat for T {}
 
+#[cfg(feature = "float")]
 impl<T: Float + Send> DspFormatBase for T
 where
     T: From<crate::IScalarFxP> + From<crate::NoteFxP>,
@@ -134,12 +180,22 @@ where
     fn default_note() -> Self::Note {
         Self::from_u16(69)
     }
-    fn note_to_freq(note: Self::Note) -> Self::Frequency {
-        T::midi_to_freq(note)
+    fn note_to_freq(context: &Self::Context, note: Self::Note) -> Self::Frequency {
+        context.midi_to_freq(note)
     }
     fn sample_from_fixed(value: IScalarFxP) -> Self::Sample {
         value.into()
     }
+    fn sample_to_fixed(value: Self::Sample) -> IScalarFxP {
+        IScalarFxP::saturating_from_num(value.as_f32())
+    }
+    fn note_from_fixed(value: NoteFxP) -> Self::Note {
+        value.into()
+    }
+    fn scalar_from_fixed(value: ScalarFxP) -> Self::Scalar {
+        <T as From<u16>>::from(value.to_bits()) / (<T as From<u16>>::from(u16::MAX) + T::ONE)
+    }
+    #[cfg(feature = "float")]
     fn sample_to_float(value: Self::Sample) -> f32 {
         value.as_f32()
     }
@@ -154,13 +210,20 @@ where
         note_max * scalar
     }
     fn apply_note_offset(note: Self::Note, offset: Self::NoteOffset) -> Self::Note {
-        note + offset
+        let note_max: Self = NoteFxP::MAX.into();
+        (note + offset).max(T::ZERO).min(note_max)
+    }
+    fn flush_denormal(smp: T) -> T {
+        smp.flush_denormal()
     }
 }
 
+#[cfg(feature = "float")]
 impl DspFloat for f32 {}
+#[cfg(feature = "float")]
 impl DspFloat for f64 {}
 
+#[cfg(feature = "float")]
 impl<T: Float + Send> DspType<T> for T
 where
     T: From<crate::IScalarFxP> + From<crate::NoteFxP>,
@@ -177,6 +240,9 @@ where
     fn dsp_saturating_add(self, rhs: Self) -> Self {
         self + rhs
     }
+    fn dsp_saturating_sub(self, rhs: Self) -> Self {
+        self - rhs
+    }
     fn multiply(self, rhs: Self) -> Self {
         self * rhs
     }
@@ -209,12 +275,22 @@ impl DspFormatBase for i16 {
         const DEFAULT: NoteFxP = NoteFxP::lit("69");
         DEFAULT
     }
-    fn note_to_freq(note: NoteFxP) -> FrequencyFxP {
+    fn note_to_freq(_: &Self::Context, note: NoteFxP) -> FrequencyFxP {
         crate::fixedmath::midi_note_to_frequency(note)
     }
     fn sample_from_fixed(value: IScalarFxP) -> Self::Sample {
         SampleFxP::from_num(value)
     }
+    fn sample_to_fixed(value: Self::Sample) -> IScalarFxP {
+        IScalarFxP::saturating_from_num(value)
+    }
+    fn note_from_fixed(value: NoteFxP) -> Self::Note {
+        value
+    }
+    fn scalar_from_fixed(value: ScalarFxP) -> Self::Scalar {
+        value
+    }
+    #[cfg(feature = "float")]
     fn sample_to_float(value: Self::Sample) -> f32 {
         value.into()
     }
@@ -244,9 +320,15 @@ impl<T: Fixed16 + Send> DspType<i16> for T {
     fn dsp_saturating_add(self, rhs: Self) -> Self {
         self.saturating_add(rhs)
     }
+    fn dsp_saturating_sub(self, rhs: Self) -> Self {
+        self.saturating_sub(rhs)
+    }
     fn multiply(self, rhs: Self) -> Self {
         self.multiply_fixed(rhs)
     }
+    fn dsp_saturating_multiply(self, rhs: Self) -> Self {
+        self.saturating_multiply_fixed(rhs)
+    }
     fn divide_by_two(self) -> Self {
         self.unwrapped_shr(1)
     }
@@ -267,6 +349,9 @@ impl DspType<i16> for FrequencyFxP {
     fn dsp_saturating_add(self, rhs: Self) -> Self {
         self.saturating_add(rhs)
     }
+    fn dsp_saturating_sub(self, rhs: Self) -> Self {
+        self.saturating_sub(rhs)
+    }
     fn multiply(self, rhs: Self) -> Self {
         Self::from_num(self.wide_mul(rhs))
     }
@@ -290,6 +375,9 @@ impl DspType<i16> for PhaseFxP {
     fn dsp_saturating_add(self, rhs: Self) -> Self {
         self.saturating_add(rhs)
     }
+    fn dsp_saturating_sub(self, rhs: Self) -> Self {
+        self.saturating_sub(rhs)
+    }
     fn multiply(self, rhs: Self) -> Self {
         Self::from_num(self.wide_mul(rhs))
     }
@@ -319,6 +407,9 @@ impl DspType<i16> for WideSampleFxP {
     fn dsp_saturating_add(self, rhs: Self) -> Self {
         self.saturating_add(rhs)
     }
+    fn dsp_saturating_sub(self, rhs: Self) -> Self {
+        self.saturating_sub(rhs)
+    }
     fn multiply(self, rhs: Self) -> Self {
         Self::from_num(self.wide_mul(rhs))
     }
@@ -335,3 +426,152 @@ impl DspType<i16> for WideSampleFxP {
     }
 }
 */
+
+// 32-bit fixed point:
+//
+// This provides higher precision (lower quantization noise) than the 16 bit
+// format at the cost of 64 bit multiplies, so it is intended for targets with
+// DSP extensions.  Like the 16 bit format, it avoids division in the per-sample
+// path (a 32 bit fixed point division needs a 128 bit intermediate).  Only the
+// [DspFormatBase] types are provided, along with the operations for
+// [devices::Filt] and [devices::Env], so `i32` does not (yet) implement
+// [DspFormat].
+
+impl DspFormatBase for i32 {
+    type Sample = SampleFxP32;
+    type Note = NoteFxP32;
+    type NoteOffset = SignedNoteFxP32;
+    type Frequency = FrequencyFxP;
+    type Scalar = ScalarFxP32;
+    type IScalar = IScalarFxP32;
+    type EnvParam = EnvParamFxP32;
+    type EnvSignal = devices::env::detail::EnvSignalFxP32;
+    type Phase = PhaseFxP;
+    type LfoFreq = LfoFreqFxP32;
+    type WideSample = fixedmath::I36F28;
+    type Context = context::ContextFxP;
+    fn default_note() -> Self::Note {
+        const DEFAULT: NoteFxP32 = NoteFxP32::lit("69");
+        DEFAULT
+    }
+    fn note_to_freq(_: &Self::Context, note: NoteFxP32) -> FrequencyFxP {
+        crate::fixedmath::midi_note_to_frequency(NoteFxP::from_num(note))
+    }
+    fn sample_from_fixed(value: IScalarFxP) -> Self::Sample {
+        SampleFxP32::from_num(value)
+    }
+    fn sample_to_fixed(value: Self::Sample) -> IScalarFxP {
+        IScalarFxP::saturating_from_num(value)
+    }
+    fn note_from_fixed(value: NoteFxP) -> Self::Note {
+        NoteFxP32::from_num(value)
+    }
+    fn scalar_from_fixed(value: ScalarFxP) -> Self::Scalar {
+        ScalarFxP32::from_num(value)
+    }
+    #[cfg(feature = "float")]
+    fn sample_to_float(value: Self::Sample) -> f32 {
+        value.to_num()
+    }
+    fn widen_sample(smp: Self::Sample) -> Self::WideSample {
+        fixedmath::I36F28::from_num(smp)
+    }
+    fn narrow_sample(wide_smp: Self::WideSample) -> SampleFxP32 {
+        SampleFxP32::saturating_from_num(wide_smp)
+    }
+    fn note_from_scalar(scalar: ScalarFxP32) -> NoteFxP32 {
+        NoteFxP32::from_bits(scalar.to_bits())
+    }
+    fn apply_note_offset(note: NoteFxP32, offset: SignedNoteFxP32) -> NoteFxP32 {
+        note.saturating_add_signed(offset)
+    }
+}
+
+impl<T: Fixed32 + Send> DspType<i32> for T {
+    const PI: Self = T::PI;
+    const TAU: Self = T::TAU;
+    fn zero() -> Self {
+        Self::ZERO
+    }
+    fn one() -> Self {
+        Self::ONE_OR_MAX
+    }
+    fn dsp_saturating_add(self, rhs: Self) -> Self {
+        self.saturating_add(rhs)
+    }
+    fn dsp_saturating_sub(self, rhs: Self) -> Self {
+        self.saturating_sub(rhs)
+    }
+    fn multiply(self, rhs: Self) -> Self {
+        self.multiply_fixed(rhs)
+    }
+    fn dsp_saturating_multiply(self, rhs: Self) -> Self {
+        self.saturating_multiply_fixed(rhs)
+    }
+    fn divide_by_two(self) -> Self {
+        self.unwrapped_shr(1)
+    }
+    fn scale(self, rhs: ScalarFxP32) -> Self {
+        self.scale_fixed(rhs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fixed_clamp_and_saturate_boundaries() {
+        let (lo, hi) = (SampleFxP::NEG_ONE, SampleFxP::ONE);
+        assert_eq!(SampleFxP::MIN.dsp_clamp(lo, hi), lo);
+        assert_eq!(lo.dsp_clamp(lo, hi), lo);
+        assert_eq!(
+            (lo + SampleFxP::DELTA).dsp_clamp(lo, hi),
+            lo + SampleFxP::DELTA
+        );
+        assert_eq!(hi.dsp_clamp(lo, hi), hi);
+        assert_eq!(SampleFxP::MAX.dsp_clamp(lo, hi), hi);
+        // An empty range clamps to its (only) value
+        assert_eq!(SampleFxP::ZERO.dsp_clamp(hi, hi), hi);
+        assert_eq!(
+            ScalarFxP::MAX.dsp_clamp(ScalarFxP::ZERO, ScalarFxP::MAX),
+            ScalarFxP::MAX
+        );
+        // Saturating arithmetic stops at the ends of the range
+        assert_eq!(
+            SampleFxP::MIN.dsp_saturating_sub(SampleFxP::DELTA),
+            SampleFxP::MIN
+        );
+        assert_eq!(
+            SampleFxP::MAX.dsp_saturating_add(SampleFxP::DELTA),
+            SampleFxP::MAX
+        );
+        assert_eq!(
+            ScalarFxP::ZERO.dsp_saturating_sub(ScalarFxP::MAX),
+            ScalarFxP::ZERO
+        );
+        assert_eq!(
+            DspType::<i16>::dsp_saturating_sub(FrequencyFxP::ZERO, FrequencyFxP::ONE),
+            FrequencyFxP::ZERO
+        );
+        assert_eq!(
+            DspType::<i16>::dsp_saturating_sub(PhaseFxP::MIN, PhaseFxP::PI),
+            PhaseFxP::MIN
+        );
+        assert_eq!(
+            ScalarFxP32::ZERO.dsp_saturating_sub(ScalarFxP32::DELTA),
+            ScalarFxP32::ZERO
+        );
+    }
+
+    #[cfg(feature = "float")]
+    #[test]
+    fn float_clamp_boundaries() {
+        assert_eq!((-2f32).dsp_clamp(-1f32, 1f32), -1f32);
+        assert_eq!(1f32.dsp_clamp(-1f32, 1f32), 1f32);
+        assert_eq!(0.5f32.dsp_clamp(-1f32, 1f32), 0.5f32);
+        assert_eq!(f32::INFINITY.dsp_clamp(-1f32, 1f32), 1f32);
+        assert!(f32::NAN.dsp_clamp(-1f32, 1f32).is_nan());
+        assert_eq!((-1f32).dsp_saturating_sub(f32::MAX), -1f32 - f32::MAX);
+    }
+}
@@ -1,6 +1,6 @@
-use super::ScalarFxP;
+use super::{ScalarFxP, ScalarFxP32};
 use core::ops::Add;
-use fixed::types::extra::{LeEqU16, LeEqU32, Sum, Unsigned, U16};
+use fixed::types::extra::{LeEqU16, LeEqU32, LeEqU64, Sum, Unsigned, U16, U32};
 use fixed::{traits::Fixed, FixedI32};
 
 /// A trait encompassing 16 bit fixed point numbers along with a couple of
@@ -15,6 +15,8 @@ pub trait Fixed16: Fixed {
     };
     /// Multiply two fixed point numbers
     fn multiply_fixed(self, rhs: Self) -> Self;
+    /// Multiply two fixed point numbers, saturating on overflow
+    fn saturating_multiply_fixed(self, rhs: Self) -> Self;
     /// Scale a fixed point number
     fn scale_fixed(self, rhs: ScalarFxP) -> Self;
     /// A 32 bit fixed point number with the same number of fractional bits
@@ -34,6 +36,9 @@ where
     fn multiply_fixed(self, rhs: Self) -> Self {
         Self::from_num(self.wide_mul(rhs))
     }
+    fn saturating_multiply_fixed(self, rhs: Self) -> Self {
+        crate::fixedmath::saturating_scale(self, rhs)
+    }
     fn scale_fixed(self, rhs: ScalarFxP) -> Self {
         Self::from_num(self.wide_mul_unsigned(rhs))
     }
@@ -55,6 +60,9 @@ where
     fn multiply_fixed(self, rhs: Self) -> Self {
         Self::from_num(self.wide_mul(rhs))
     }
+    fn saturating_multiply_fixed(self, rhs: Self) -> Self {
+        Self::saturating_from_num(self.wide_mul(rhs))
+    }
     fn scale_fixed(self, rhs: ScalarFxP) -> Self {
         Self::from_num(self.wide_mul(rhs))
     }
@@ -66,3 +74,56 @@ where
         Self::Widened::from_bits(bits)
     }
 }
+
+/// A trait encompassing 32 bit fixed point numbers along with a couple of
+/// convenience methods for the type.  This is the 32 bit analogue of
+/// [Fixed16], used by the `i32` [DspFormatBase](crate::DspFormatBase).
+pub trait Fixed32: Fixed {
+    /// The value one, or if one is not representable, the maximum representable
+    /// by the type
+    const ONE_OR_MAX: Self = if let Some(val) = Self::TRY_ONE {
+        val
+    } else {
+        Self::MAX
+    };
+    /// Multiply two fixed point numbers
+    fn multiply_fixed(self, rhs: Self) -> Self;
+    /// Multiply two fixed point numbers, saturating on overflow
+    fn saturating_multiply_fixed(self, rhs: Self) -> Self;
+    /// Scale a fixed point number
+    fn scale_fixed(self, rhs: ScalarFxP32) -> Self;
+}
+
+impl<N> Fixed32 for fixed::FixedI32<N>
+where
+    N: Unsigned + LeEqU32 + LeEqU64 + Add<N> + Add<U32>,
+    Sum<N, N>: Unsigned + LeEqU64,
+    Sum<N, U32>: Unsigned + LeEqU64,
+{
+    fn multiply_fixed(self, rhs: Self) -> Self {
+        Self::from_num(self.wide_mul(rhs))
+    }
+    fn saturating_multiply_fixed(self, rhs: Self) -> Self {
+        Self::saturating_from_num(self.wide_mul(rhs))
+    }
+    fn scale_fixed(self, rhs: ScalarFxP32) -> Self {
+        Self::from_num(self.wide_mul_unsigned(rhs))
+    }
+}
+
+impl<N> Fixed32 for fixed::FixedU32<N>
+where
+    N: Unsigned + LeEqU32 + LeEqU64 + Add<N> + Add<U32>,
+    Sum<N, N>: Unsigned + LeEqU64,
+    Sum<N, U32>: Unsigned + LeEqU64,
+{
+    fn multiply_fixed(self, rhs: Self) -> Self {
+        Self::from_num(self.wide_mul(rhs))
+    }
+    fn saturating_multiply_fixed(self, rhs: Self) -> Self {
+        Self::saturating_from_num(self.wide_mul(rhs))
+    }
+    fn scale_fixed(self, rhs: ScalarFxP32) -> Self {
+        Self::from_num(self.wide_mul(rhs))
+    }
+}
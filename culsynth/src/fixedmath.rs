@@ -5,9 +5,11 @@
 //! you have been warned!
 
 use core::ops::Add;
-use fixed::types::extra::{IsLessOrEqual, LeEqU16, LeEqU32, True, Unsigned, U16, U31};
+use fixed::types::extra::{
+    IsLessOrEqual, LeEqU16, LeEqU32, LeEqU64, Sum, True, Unsigned, U16, U31, U63,
+};
 pub use fixed::types::*;
-use fixed::{FixedI16, FixedI32, FixedU16, FixedU32};
+use fixed::{FixedI16, FixedI32, FixedU16, FixedU32, FixedU64};
 
 /// A fixed point number representing a sample or otherwise generic piece of data
 /// within the synthesizer.  These are 16 bit signed fixed point numbers with 12
@@ -64,6 +66,9 @@ where
     FracA: Unsigned + LeEqU32,
     FracB: Unsigned + LeEqU16 + Add<U16> + IsLessOrEqual<FracA>,
 {
+    if a == FixedU32::<FracA>::ZERO {
+        return a;
+    }
     let bbits = FixedU16::<FracB>::INT_NBITS;
     let shift = a.leading_zeros();
     let a_shifted = U0F32::from_bits(a.unwrapped_shl(shift).to_bits());
@@ -84,6 +89,16 @@ where
     FixedI32::<Frac>::from_num(a)
 }
 
+/// Multiply two 16 bit signed fixed point numbers, saturating at the bounds
+/// of the type rather than wrapping (or panicking) if the product overflows
+pub fn saturating_scale<Frac>(a: FixedI16<Frac>, b: FixedI16<Frac>) -> FixedI16<Frac>
+where
+    Frac: Unsigned + LeEqU16 + Add<Frac>,
+    Sum<Frac, Frac>: Unsigned + LeEqU32,
+{
+    FixedI16::<Frac>::saturating_from_num(a.wide_mul(b))
+}
+
 fn one_over_one_plus_helper<Frac>(n: FixedU32<Frac>) -> (U1F31, u32)
 where
     Frac: Unsigned + IsLessOrEqual<U31, Output = True> + LeEqU32,
@@ -121,15 +136,64 @@ where
     Frac: Unsigned + IsLessOrEqual<U31, Output = True> + LeEqU32,
 {
     let (x_shifted, shift) = one_over_one_plus_helper(x);
-    let x_shifted_trunc = U1F15::from_num(x_shifted);
-    let x2 = I3F29::from_num(x_shifted_trunc.wide_mul(x_shifted_trunc));
-    let one_minus_x = I3F29::ONE - I3F29::from_num(x_shifted);
+    (reciprocal_quadratic(x_shifted), shift)
+}
+
+/// Approximate 1/x for x in [sqrt(2)/2, sqrt(2)) (see [one_over_one_plus])
+fn reciprocal_quadratic(x: U1F31) -> U1F15 {
+    let x_trunc = U1F15::from_num(x);
+    let x2 = I3F29::from_num(x_trunc.wide_mul(x_trunc));
+    let one_minus_x = I3F29::ONE - I3F29::from_num(x);
+    U1F15::from_num(x2 + one_minus_x + one_minus_x.unwrapped_shl(1))
+}
+
+/// Perform the same calculation as [one_over_one_plus], but refine the result
+/// with two Newton-Raphson iterations.  This reduces the worst case error from
+/// a few percent to about the precision of the output, at the cost of an
+/// extra four 64-bit multiplies.
+pub fn one_over_one_plus_refined<Frac>(x: FixedU32<Frac>) -> (U1F15, u32)
+where
+    Frac: Unsigned + IsLessOrEqual<U31, Output = True> + LeEqU32,
+{
+    let (x_shifted, shift) = one_over_one_plus_helper(x);
+    (U1F15::from_num(refine_reciprocal(x_shifted, 2)), shift)
+}
+
+/// Perform the same calculation as [one_over_one_plus_refined], but with a 64
+/// bit fixed point number as the input and a 32 bit result.  This uses a third
+/// Newton-Raphson iteration to reach the precision of the output, and is how
+/// the 32 bit format avoids a (128 bit) division every sample.
+pub fn one_over_one_plus_refined32<Frac>(x: FixedU64<Frac>) -> (U1F31, u32)
+where
+    Frac: Unsigned + IsLessOrEqual<U63, Output = True> + LeEqU64,
+{
+    let nbits = FixedU64::<Frac>::INT_NBITS;
+    let x = x + FixedU64::<Frac>::ONE;
+    let mut shift = x.leading_zeros();
+    let mut x_shifted = U1F63::from_bits(x.to_bits()).unwrapped_shl(shift);
+    shift += 1;
+    if x_shifted >= U1F63::SQRT_2 {
+        shift -= 1;
+        x_shifted = x_shifted.unwrapped_shr(1);
+    }
     (
-        U1F15::from_num(x2 + one_minus_x + one_minus_x.unwrapped_shl(1)),
-        shift,
+        refine_reciprocal(U1F31::from_num(x_shifted), 3),
+        nbits - shift,
     )
 }
 
+/// Approximate 1/x for x in [sqrt(2)/2, sqrt(2)), refining the quadratic
+/// approximation with `iterations` Newton-Raphson iterations
+fn refine_reciprocal(x: U1F31, iterations: usize) -> U1F31 {
+    let mut y = U1F31::from_num(reciprocal_quadratic(x));
+    // Each iteration squares the relative error: y' = y * (2 - x*y)
+    for _ in 0..iterations {
+        let err = U2F30::from_num(U2F62::lit("2") - x.wide_mul(y));
+        y = U1F31::saturating_from_num(y.wide_mul(err));
+    }
+    y
+}
+
 /// Perform the same calculation as [one_over_one_plus], but with a 16 bit
 /// fixed point number as the input, and use a quartic taylor series instead
 /// of a quadratic one.  This is significantly more accurate at the cost of an
@@ -215,6 +279,22 @@ pub fn tan_fixed(x: U0F16) -> U1F15 {
     U1F15::from_num(res_over_x.wide_mul(x))
 }
 
+/// Wrap a phase into the range `[-pi, pi)`.  This handles phases any distance
+/// outside of that range (up to the limits of the type), so a large phase
+/// increment can never skip the wrap and leave the phase out of range.
+pub fn wrap_phase(mut phase: I4F28) -> I4F28 {
+    // Use exactly twice PI (rather than TAU, which may differ by an ulp) so
+    // a phase of PI wraps to exactly -PI and not one ulp past it
+    const TWO_PI: I4F28 = I4F28::from_bits(I4F28::PI.to_bits() * 2);
+    while phase >= I4F28::PI {
+        phase -= TWO_PI;
+    }
+    while phase < -I4F28::PI {
+        phase += TWO_PI;
+    }
+    phase
+}
+
 /// calculate e^x in the range [-0.5, 0.5) using an order 4 Taylor series
 fn exp_fixed_small(x: I0F16) -> U2F14 {
     // e^x ~= 1 + x + x^2/2! + x^3/3! + x^4/4!
@@ -306,6 +386,375 @@ pub fn midi_note_to_frequency(note: Note) -> Frequency {
     FREQ_E4 * U14F18::from_num(exp_fixed(power))
 }
 
+// The frequency of each MIDI note, generated using the following python
+// snippet:
+//
+// for n in range(128):
+//      print('Frequency::from_bits(0x%08x),' % round(440 * 2**((n - 69) / 12) * 2**18))
+const NOTE_FREQ_TABLE: [Frequency; 128] = [
+    Frequency::from_bits(0x0020b405),
+    Frequency::from_bits(0x0022a5d8),
+    Frequency::from_bits(0x0024b546),
+    Frequency::from_bits(0x0026e410),
+    Frequency::from_bits(0x00293415),
+    Frequency::from_bits(0x002ba74e),
+    Frequency::from_bits(0x002e3fd2),
+    Frequency::from_bits(0x0030ffdb),
+    Frequency::from_bits(0x0033e9c0),
+    Frequency::from_bits(0x00370000),
+    Frequency::from_bits(0x003a453e),
+    Frequency::from_bits(0x003dbc44),
+    Frequency::from_bits(0x00416809),
+    Frequency::from_bits(0x00454bb0),
+    Frequency::from_bits(0x00496a8c),
+    Frequency::from_bits(0x004dc821),
+    Frequency::from_bits(0x0052682a),
+    Frequency::from_bits(0x00574e9b),
+    Frequency::from_bits(0x005c7fa5),
+    Frequency::from_bits(0x0061ffb5),
+    Frequency::from_bits(0x0067d380),
+    Frequency::from_bits(0x006e0000),
+    Frequency::from_bits(0x00748a7b),
+    Frequency::from_bits(0x007b7888),
+    Frequency::from_bits(0x0082d013),
+    Frequency::from_bits(0x008a9760),
+    Frequency::from_bits(0x0092d517),
+    Frequency::from_bits(0x009b9041),
+    Frequency::from_bits(0x00a4d054),
+    Frequency::from_bits(0x00ae9d37),
+    Frequency::from_bits(0x00b8ff49),
+    Frequency::from_bits(0x00c3ff6a),
+    Frequency::from_bits(0x00cfa700),
+    Frequency::from_bits(0x00dc0000),
+    Frequency::from_bits(0x00e914f6),
+    Frequency::from_bits(0x00f6f110),
+    Frequency::from_bits(0x0105a025),
+    Frequency::from_bits(0x01152ec1),
+    Frequency::from_bits(0x0125aa2e),
+    Frequency::from_bits(0x01372082),
+    Frequency::from_bits(0x0149a0a8),
+    Frequency::from_bits(0x015d3a6d),
+    Frequency::from_bits(0x0171fe92),
+    Frequency::from_bits(0x0187fed5),
+    Frequency::from_bits(0x019f4e01),
+    Frequency::from_bits(0x01b80000),
+    Frequency::from_bits(0x01d229ec),
+    Frequency::from_bits(0x01ede220),
+    Frequency::from_bits(0x020b404a),
+    Frequency::from_bits(0x022a5d82),
+    Frequency::from_bits(0x024b545c),
+    Frequency::from_bits(0x026e4104),
+    Frequency::from_bits(0x0293414f),
+    Frequency::from_bits(0x02ba74db),
+    Frequency::from_bits(0x02e3fd25),
+    Frequency::from_bits(0x030ffdaa),
+    Frequency::from_bits(0x033e9c01),
+    Frequency::from_bits(0x03700000),
+    Frequency::from_bits(0x03a453d9),
+    Frequency::from_bits(0x03dbc440),
+    Frequency::from_bits(0x04168094),
+    Frequency::from_bits(0x0454bb04),
+    Frequency::from_bits(0x0496a8b9),
+    Frequency::from_bits(0x04dc8208),
+    Frequency::from_bits(0x0526829e),
+    Frequency::from_bits(0x0574e9b6),
+    Frequency::from_bits(0x05c7fa4a),
+    Frequency::from_bits(0x061ffb54),
+    Frequency::from_bits(0x067d3803),
+    Frequency::from_bits(0x06e00000),
+    Frequency::from_bits(0x0748a7b1),
+    Frequency::from_bits(0x07b78880),
+    Frequency::from_bits(0x082d0128),
+    Frequency::from_bits(0x08a97607),
+    Frequency::from_bits(0x092d5172),
+    Frequency::from_bits(0x09b90410),
+    Frequency::from_bits(0x0a4d053d),
+    Frequency::from_bits(0x0ae9d36b),
+    Frequency::from_bits(0x0b8ff494),
+    Frequency::from_bits(0x0c3ff6a7),
+    Frequency::from_bits(0x0cfa7005),
+    Frequency::from_bits(0x0dc00000),
+    Frequency::from_bits(0x0e914f62),
+    Frequency::from_bits(0x0f6f1100),
+    Frequency::from_bits(0x105a0251),
+    Frequency::from_bits(0x1152ec0e),
+    Frequency::from_bits(0x125aa2e4),
+    Frequency::from_bits(0x13720820),
+    Frequency::from_bits(0x149a0a79),
+    Frequency::from_bits(0x15d3a6d6),
+    Frequency::from_bits(0x171fe928),
+    Frequency::from_bits(0x187fed4e),
+    Frequency::from_bits(0x19f4e00b),
+    Frequency::from_bits(0x1b800000),
+    Frequency::from_bits(0x1d229ec4),
+    Frequency::from_bits(0x1ede2200),
+    Frequency::from_bits(0x20b404a2),
+    Frequency::from_bits(0x22a5d81d),
+    Frequency::from_bits(0x24b545c7),
+    Frequency::from_bits(0x26e41040),
+    Frequency::from_bits(0x293414f2),
+    Frequency::from_bits(0x2ba74dac),
+    Frequency::from_bits(0x2e3fd250),
+    Frequency::from_bits(0x30ffda9d),
+    Frequency::from_bits(0x33e9c015),
+    Frequency::from_bits(0x37000000),
+    Frequency::from_bits(0x3a453d89),
+    Frequency::from_bits(0x3dbc4401),
+    Frequency::from_bits(0x41680943),
+    Frequency::from_bits(0x454bb03a),
+    Frequency::from_bits(0x496a8b8f),
+    Frequency::from_bits(0x4dc82080),
+    Frequency::from_bits(0x526829e4),
+    Frequency::from_bits(0x574e9b58),
+    Frequency::from_bits(0x5c7fa49f),
+    Frequency::from_bits(0x61ffb539),
+    Frequency::from_bits(0x67d3802a),
+    Frequency::from_bits(0x6e000000),
+    Frequency::from_bits(0x748a7b12),
+    Frequency::from_bits(0x7b788802),
+    Frequency::from_bits(0x82d01286),
+    Frequency::from_bits(0x8a976074),
+    Frequency::from_bits(0x92d5171d),
+    Frequency::from_bits(0x9b904101),
+    Frequency::from_bits(0xa4d053c9),
+    Frequency::from_bits(0xae9d36b0),
+    Frequency::from_bits(0xb8ff493e),
+    Frequency::from_bits(0xc3ff6a72),
+];
+
+/// Look up the frequency in Hz of the MIDI note `n` (which should be at most
+/// 127, and is clamped otherwise).  This is faster (and more accurate) than
+/// [midi_note_to_frequency] for integer notes.
+pub fn note_frequency(n: u8) -> Frequency {
+    NOTE_FREQ_TABLE[n.min(127) as usize]
+}
+
+/// Convert a MIDI note number to a frequency in Hz by linear interpolation
+/// between adjacent entries of the note frequency table (see
+/// [note_frequency]).  This is exact for integer notes and accurate to about
+/// 1 cent for fractional notes.
+pub fn note_frequency_interp(note: Note) -> Frequency {
+    let index = (note.to_bits() >> 9) as usize;
+    let frac = (note.to_bits() & 0x1FF) as u64;
+    let lo = NOTE_FREQ_TABLE[index];
+    if index == 127 {
+        return lo;
+    }
+    let step = (NOTE_FREQ_TABLE[index + 1] - lo).to_bits() as u64;
+    lo + Frequency::from_bits(((step * frac) >> 9) as u32)
+}
+
+// The following tables each hold 17 points of a function over one octave,
+// for linear interpolation by interp_table.  They were generated using the
+// following python snippet (with f replaced by the function tabulated):
+//
+// for i in range(17):
+//      print('U2F30::from_bits(0x%08x),' % round(f(i / 16) * 2**30))
+
+/// sqrt(1 + x) for x in [0, 1]
+const SQRT_TABLE: [U2F30; 17] = [
+    U2F30::from_bits(0x40000000),
+    U2F30::from_bits(0x41f83d9b),
+    U2F30::from_bits(0x43e1db33),
+    U2F30::from_bits(0x45be0cd2),
+    U2F30::from_bits(0x478dde6e),
+    U2F30::from_bits(0x49523ae4),
+    U2F30::from_bits(0x4b0bf165),
+    U2F30::from_bits(0x4cbbb9d6),
+    U2F30::from_bits(0x4e623850),
+    U2F30::from_bits(0x50000000),
+    U2F30::from_bits(0x5195957c),
+    U2F30::from_bits(0x532370b9),
+    U2F30::from_bits(0x54a9fea7),
+    U2F30::from_bits(0x5629a293),
+    U2F30::from_bits(0x57a2b749),
+    U2F30::from_bits(0x59159016),
+    U2F30::from_bits(0x5a82799a),
+];
+
+/// log2(1 + x) for x in [0, 1]
+const LOG2_TABLE: [U2F30; 17] = [
+    U2F30::from_bits(0x00000000),
+    U2F30::from_bits(0x0598fdbf),
+    U2F30::from_bits(0x0ae00d1d),
+    U2F30::from_bits(0x0fde0b5d),
+    U2F30::from_bits(0x149a784c),
+    U2F30::from_bits(0x191bba89),
+    U2F30::from_bits(0x1d6753e0),
+    U2F30::from_bits(0x21820a02),
+    U2F30::from_bits(0x2570068e),
+    U2F30::from_bits(0x2934f098),
+    U2F30::from_bits(0x2cd4011d),
+    U2F30::from_bits(0x305013ab),
+    U2F30::from_bits(0x33abb3fb),
+    U2F30::from_bits(0x36e9291f),
+    U2F30::from_bits(0x3a0a7eda),
+    U2F30::from_bits(0x3d118d67),
+    U2F30::from_bits(0x40000000),
+];
+
+/// 2^x for x in [0, 1]
+const EXP2_TABLE: [U2F30; 17] = [
+    U2F30::from_bits(0x40000000),
+    U2F30::from_bits(0x42d561b4),
+    U2F30::from_bits(0x45cae0f2),
+    U2F30::from_bits(0x48e1e9ba),
+    U2F30::from_bits(0x4c1bf829),
+    U2F30::from_bits(0x4f7a9930),
+    U2F30::from_bits(0x52ff6b55),
+    U2F30::from_bits(0x56ac1f75),
+    U2F30::from_bits(0x5a82799a),
+    U2F30::from_bits(0x5e8451d0),
+    U2F30::from_bits(0x62b39509),
+    U2F30::from_bits(0x6712460b),
+    U2F30::from_bits(0x6ba27e65),
+    U2F30::from_bits(0x70666f76),
+    U2F30::from_bits(0x75606374),
+    U2F30::from_bits(0x7a92be8b),
+    U2F30::from_bits(0x80000000),
+];
+
+const SQRT_2: U2F30 = U2F30::from_bits(0x5a82799a);
+
+/// The raw bits of sin(x) as a U2F30 for x in [0, pi/2], in 256 equal steps.
+/// This is one quadrant of a 1024 point table for a full cycle, generated in
+/// the same way as the tables above (but with `i / 256` and 257 points).
+const SIN_TABLE: [u32; 257] = [
+    0x00000000, 0x006487c4, 0x00c90e90, 0x012d936c, 0x0192155f, 0x01f69373, 0x025b0caf, 0x02bf801a,
+    0x0323ecbe, 0x038851a2, 0x03ecadcf, 0x0451004d, 0x04b54825, 0x0519845e, 0x057db403, 0x05e1d61b,
+    0x0645e9af, 0x06a9edc9, 0x070de172, 0x0771c3b3, 0x07d59396, 0x08395024, 0x089cf867, 0x09008b6a,
+    0x09640837, 0x09c76dd8, 0x0a2abb59, 0x0a8defc3, 0x0af10a22, 0x0b540982, 0x0bb6ecef, 0x0c19b374,
+    0x0c7c5c1e, 0x0cdee5f9, 0x0d415013, 0x0da39978, 0x0e05c135, 0x0e67c65a, 0x0ec9a7f3, 0x0f2b650f,
+    0x0f8cfcbe, 0x0fee6e0d, 0x104fb80e, 0x10b0d9d0, 0x1111d263, 0x1172a0d7, 0x11d3443f, 0x1233bbac,
+    0x1294062f, 0x12f422db, 0x135410c3, 0x13b3cefa, 0x14135c94, 0x1472b8a5, 0x14d1e242, 0x1530d881,
+    0x158f9a76, 0x15ee2738, 0x164c7ddd, 0x16aa9d7e, 0x17088531, 0x1766340f, 0x17c3a931, 0x1820e3b0,
+    0x187de2a7, 0x18daa52f, 0x19372a64, 0x19937161, 0x19ef7944, 0x1a4b4128, 0x1aa6c82b, 0x1b020d6c,
+    0x1b5d100a, 0x1bb7cf23, 0x1c1249d8, 0x1c6c7f4a, 0x1cc66e99, 0x1d2016e9, 0x1d79775c, 0x1dd28f15,
+    0x1e2b5d38, 0x1e83e0eb, 0x1edc1953, 0x1f340596, 0x1f8ba4dc, 0x1fe2f64c, 0x2039f90f, 0x2090ac4d,
+    0x20e70f32, 0x213d20e8, 0x2192e09b, 0x21e84d76, 0x223d66a8, 0x22922b5e, 0x22e69ac8, 0x233ab414,
+    0x238e7673, 0x23e1e117, 0x2434f332, 0x2487abf7, 0x24da0a9a, 0x252c0e4f, 0x257db64c, 0x25cf01c8,
+    0x261feffa, 0x2670801a, 0x26c0b162, 0x2710830c, 0x275ff452, 0x27af0472, 0x27fdb2a7, 0x284bfe2f,
+    0x2899e64a, 0x28e76a37, 0x29348937, 0x2981428c, 0x29cd9578, 0x2a19813f, 0x2a650525, 0x2ab02071,
+    0x2afad269, 0x2b451a55, 0x2b8ef77d, 0x2bd8692b, 0x2c216eaa, 0x2c6a0746, 0x2cb2324c, 0x2cf9ef09,
+    0x2d413ccd, 0x2d881ae8, 0x2dce88aa, 0x2e148566, 0x2e5a1070, 0x2e9f291b, 0x2ee3cebe, 0x2f2800af,
+    0x2f6bbe45, 0x2faf06da, 0x2ff1d9c7, 0x30343667, 0x30761c18, 0x30b78a36, 0x30f8801f, 0x3138fd35,
+    0x317900d6, 0x31b88a66, 0x31f79948, 0x32362ce0, 0x32744493, 0x32b1dfc9, 0x32eefdea, 0x332b9e5e,
+    0x3367c090, 0x33a363ec, 0x33de87de, 0x34192bd5, 0x34534f41, 0x348cf190, 0x34c61236, 0x34feb0a5,
+    0x3536cc52, 0x356e64b2, 0x35a5793c, 0x35dc0968, 0x361214b0, 0x36479a8e, 0x367c9a7e, 0x36b113fd,
+    0x36e5068a, 0x371871a5, 0x374b54ce, 0x377daf89, 0x37af8159, 0x37e0c9c3, 0x3811884d, 0x3841bc7f,
+    0x387165e3, 0x38a08402, 0x38cf1669, 0x38fd1ca4, 0x392a9642, 0x395782d3, 0x3983e1e8, 0x39afb313,
+    0x39daf5e8, 0x3a05a9fd, 0x3a2fcee8, 0x3a596442, 0x3a8269a3, 0x3aaadea6, 0x3ad2c2e8, 0x3afa1605,
+    0x3b20d79e, 0x3b470753, 0x3b6ca4c4, 0x3b91af97, 0x3bb6276e, 0x3bda0bf0, 0x3bfd5cc4, 0x3c201994,
+    0x3c42420a, 0x3c63d5d1, 0x3c84d496, 0x3ca53e09, 0x3cc511d9, 0x3ce44fb7, 0x3d02f757, 0x3d21086c,
+    0x3d3e82ae, 0x3d5b65d2, 0x3d77b192, 0x3d9365a8, 0x3dae81cf, 0x3dc905c5, 0x3de2f148, 0x3dfc4418,
+    0x3e14fdf7, 0x3e2d1ea8, 0x3e44a5ef, 0x3e5b9392, 0x3e71e759, 0x3e87a10c, 0x3e9cc076, 0x3eb14563,
+    0x3ec52fa0, 0x3ed87efc, 0x3eeb3347, 0x3efd4c54, 0x3f0ec9f5, 0x3f1fabff, 0x3f2ff24a, 0x3f3f9cab,
+    0x3f4eaafe, 0x3f5d1d1d, 0x3f6af2e3, 0x3f782c30, 0x3f84c8e2, 0x3f90c8da, 0x3f9c2bfb, 0x3fa6f228,
+    0x3fb11b48, 0x3fbaa740, 0x3fc395f9, 0x3fcbe75e, 0x3fd39b5a, 0x3fdab1d9, 0x3fe12acb, 0x3fe7061f,
+    0x3fec43c7, 0x3ff0e3b6, 0x3ff4e5e0, 0x3ff84a3c, 0x3ffb10c1, 0x3ffd3969, 0x3ffec42d, 0x3fffb10b,
+    0x40000000,
+];
+
+/// Linearly interpolate an increasing function tabulated in 16 equal steps
+/// across `[0, 1]` (see [SQRT_TABLE], etc.) at the point `x`
+fn interp_table(table: &[U2F30; 17], x: U0F32) -> U2F30 {
+    let index = (x.to_bits() >> 28) as usize;
+    let frac = U0F32::from_bits(x.to_bits() << 4);
+    let (lo, hi) = (table[index], table[index + 1]);
+    lo + U2F30::from_num((hi - lo).wide_mul(frac))
+}
+
+/// Split a nonzero `x` into an exponent and the fractional part of its
+/// mantissa, such that `x = (1 + mantissa) * 2^exponent`
+fn normalize(x: U16F16) -> (i32, U0F32) {
+    let lz = x.leading_zeros();
+    // Shift out the leading one as well, leaving only the fractional bits
+    let mantissa = U0F32::from_bits(x.to_bits().checked_shl(lz + 1).unwrap_or(0));
+    (15 - lz as i32, mantissa)
+}
+
+/// Shift the bits of `x` left by `shift` (or right, if negative), saturating
+/// if the result does not fit in 32 bits
+fn shift_bits_saturating(x: U2F30, shift: i32) -> u32 {
+    let bits = x.to_bits() as u64;
+    let bits = if shift >= 0 {
+        bits << shift
+    } else {
+        bits >> (-shift).min(63)
+    };
+    u32::try_from(bits).unwrap_or(u32::MAX)
+}
+
+/// Calculate the square root of a 32 bit unsigned fixed point number with 16
+/// fractional bits, returning a unsigned 32 bit number with 24 fractional
+/// bits (so the result has at least as much relative precision as the input).
+/// Relative error is less than about 0.1% across the input range.
+pub fn sqrt_fixed(x: U16F16) -> U8F24 {
+    if x == U16F16::ZERO {
+        return U8F24::ZERO;
+    }
+    // sqrt(m * 2^e) = sqrt(m) * 2^(e/2), with an extra sqrt(2) for odd e
+    let (exponent, mantissa) = normalize(x);
+    let mut root = interp_table(&SQRT_TABLE, mantissa);
+    if exponent % 2 != 0 {
+        root = U2F30::from_num(root.wide_mul(SQRT_2));
+    }
+    // Move from 30 fractional bits to 24 while applying the exponent
+    U8F24::from_bits(shift_bits_saturating(root, exponent.div_euclid(2) - 6))
+}
+
+/// Calculate the base 2 logarithm of a 32 bit unsigned fixed point number with
+/// 16 fractional bits (so the result is in the range `[-16, 16)`).  Zero
+/// returns the most negative representable value.  Absolute error is less
+/// than about 0.001.
+pub fn log2_fixed(x: U16F16) -> I6F26 {
+    if x == U16F16::ZERO {
+        return I6F26::MIN;
+    }
+    let (exponent, mantissa) = normalize(x);
+    I6F26::from_num(exponent) + I6F26::from_num(interp_table(&LOG2_TABLE, mantissa))
+}
+
+/// Calculate 2^x, returning a 32 bit unsigned fixed point number with 16
+/// fractional bits.  Results too large to represent saturate, and results too
+/// small to represent are zero.  Relative error is less than about 0.1% for
+/// results of at least 1/16 (below that, the output's resolution dominates).
+pub fn exp2_fixed(x: I6F26) -> U16F16 {
+    // Note: x.int() rounds towards -inf, not zero for fixed point numbers
+    let int = x.int().to_num::<i32>();
+    let frac = U0F32::from_num(x.frac());
+    // Move from 30 fractional bits to 16 while applying the integral part
+    U16F16::from_bits(shift_bits_saturating(
+        interp_table(&EXP2_TABLE, frac),
+        int - 14,
+    ))
+}
+
+/// Calculate sin(2 * pi * x) by linear interpolation of a 1024 point lookup
+/// table, where `x` is the phase as a fraction of a cycle.  This is slower
+/// than [sin_fixed] (and uses 1kB for the table), but is more accurate, with
+/// an absolute error of less than about 5e-6 across the entire cycle.
+pub fn sin_lookup(x: U0F32) -> I2F30 {
+    const QUADRANT: u32 = 0x3fff_ffff;
+    let bits = x.to_bits();
+    // Reflect the second and fourth quadrants back into the first (this is
+    // off by one ulp, which is far below the error of the interpolation)
+    let pos = if bits & (1 << 30) == 0 {
+        bits & QUADRANT
+    } else {
+        !bits & QUADRANT
+    };
+    let index = (pos >> 22) as usize;
+    let frac = U0F32::from_bits(pos << 10);
+    let [lo, hi] = [SIN_TABLE[index], SIN_TABLE[index + 1]].map(U2F30::from_bits);
+    let mag = I2F30::from_num(lo + U2F30::from_num((hi - lo).wide_mul(frac)));
+    if bits & (1 << 31) == 0 {
+        mag
+    } else {
+        -mag
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::super::util::calculate_cents;
@@ -379,6 +828,85 @@ mod tests {
         let _b = cos_fixed(Sample::lit("-3.2"));
     }
     #[test]
+    fn wrap_phase_far_out_of_range() {
+        for x in [
+            I4F28::MAX,
+            I4F28::lit("5"),
+            I4F28::PI,
+            I4F28::MIN,
+            -I4F28::lit("4"),
+        ] {
+            let wrapped = wrap_phase(x);
+            assert!(wrapped >= -I4F28::PI && wrapped < I4F28::PI);
+            // Should only differ by a multiple of 2*pi:
+            let cycles = (x - wrapped).to_num::<f32>() / core::f32::consts::TAU;
+            assert!((cycles - cycles.round()).abs() < 1e-6);
+        }
+        assert_eq!(wrap_phase(I4F28::ONE), I4F28::ONE);
+        assert_eq!(wrap_phase(-I4F28::PI), -I4F28::PI);
+    }
+    #[test]
+    fn one_over_one_plus_refined_error() {
+        for i in 0..2000 {
+            let x = U16F16::from_num(2f64.powf(i as f64 / 100f64 - 4f64));
+            let expected = 1f64 / (1f64 + x.to_num::<f64>());
+            let (y, shift) = one_over_one_plus_refined(x);
+            let err = y.to_num::<f64>() / (1u64 << shift) as f64 / expected - 1f64;
+            assert!(err.abs() < 0.0001, "1/(1+{x}): {err}");
+        }
+    }
+    #[test]
+    fn one_over_one_plus_refined32_error() {
+        for i in 0..3200 {
+            let x = U32F32::from_num(2f64.powf(i as f64 / 100f64 - 8f64));
+            let expected = 1f64 / (1f64 + x.to_num::<f64>());
+            let (y, shift) = one_over_one_plus_refined32(x);
+            let err = y.to_num::<f64>() / (1u64 << shift) as f64 / expected - 1f64;
+            assert!(err.abs() < 1e-8, "1/(1+{x}): {err}");
+        }
+    }
+    //
+    //SQRT/LOG/EXP TESTS:
+    //
+    /// Inputs spanning the range of a U16F16 logarithmically
+    fn log_sweep() -> impl Iterator<Item = f64> {
+        (0..=3200).map(|i| 2f64.powf(i as f64 / 100f64 - 16f64))
+    }
+    #[test]
+    fn sqrt_fixed_relative_error() {
+        assert_eq!(sqrt_fixed(U16F16::ZERO), U8F24::ZERO);
+        for x in log_sweep().filter(|x| *x < 65536f64) {
+            let fixed = U16F16::from_num(x);
+            let expected = fixed.to_num::<f64>().sqrt();
+            let err = sqrt_fixed(fixed).to_num::<f64>() / expected - 1f64;
+            assert!(err.abs() < 0.001, "sqrt({x}): {err}");
+        }
+    }
+    #[test]
+    fn log2_fixed_error() {
+        assert_eq!(log2_fixed(U16F16::ZERO), I6F26::MIN);
+        for x in log_sweep().filter(|x| *x < 65536f64) {
+            let fixed = U16F16::from_num(x);
+            let expected = fixed.to_num::<f64>().log2();
+            let err = log2_fixed(fixed).to_num::<f64>() - expected;
+            assert!(err.abs() < 0.001, "log2({x}): {err}");
+        }
+    }
+    #[test]
+    fn exp2_fixed_relative_error() {
+        // Only check results with at least 12 significant bits
+        for i in -400..1600 {
+            let x = I6F26::from_num(i as f64 / 100f64);
+            let expected = 2f64.powf(x.to_num::<f64>());
+            let err = exp2_fixed(x).to_num::<f64>() / expected - 1f64;
+            assert!(err.abs() < 0.001, "exp2({x}): {err}");
+        }
+        assert_eq!(exp2_fixed(I6F26::lit("16")), U16F16::MAX);
+        assert_eq!(exp2_fixed(I6F26::MAX), U16F16::MAX);
+        assert_eq!(exp2_fixed(I6F26::lit("-17")), U16F16::ZERO);
+        assert_eq!(exp2_fixed(I6F26::MIN), U16F16::ZERO);
+    }
+    #[test]
     fn midi_pitch_calculations() {
         for i in 0..=127 {
             let pitch = 440.0 * f32::powf(2.0, ((i - 69) as f32) / 12.0);
@@ -387,4 +915,20 @@ mod tests {
             assert!(error < 1.0); //less than one cent per note
         }
     }
+    #[test]
+    fn note_frequency_table() {
+        for i in 0..=127u8 {
+            let computed = midi_note_to_frequency(i.to_fixed()).to_num::<f32>();
+            let table = note_frequency(i).to_num::<f32>();
+            assert!(calculate_cents(computed, table).abs() < 1.0, "note {i}");
+            let interp = note_frequency_interp(i.to_fixed()).to_num::<f32>();
+            assert_eq!(interp, table);
+        }
+        for i in 0..1270 {
+            let note = i as f32 / 10.0;
+            let pitch = 440.0 * f32::powf(2.0, (note - 69.0) / 12.0);
+            let interp = note_frequency_interp(note.to_fixed()).to_num::<f32>();
+            assert!(calculate_cents(pitch, interp).abs() < 1.0, "note {note}");
+        }
+    }
 }
@@ -2,7 +2,6 @@
 //!
 //! Benchmark me!
 
-#[cfg(any(test, doc, not(feature = "libm")))]
 mod detail {
     use crate::Float;
     use num_traits::AsPrimitive;
@@ -111,7 +110,6 @@ mod detail {
     }
 }
 
-#[cfg(any(test, doc, not(feature = "libm")))]
 pub use detail::*;
 
 #[cfg(test)]
@@ -5,7 +5,7 @@ use num_traits::Float as NumTraitsFloat;
 
 /// Types must implement this trait to instantiate any of the generic devices
 /// in this module.  Implementations are provided for `f32` and `f64`.
-pub trait Float: NumTraitsFloat + From<u16> + Default + Copy {
+pub trait Float: NumTraitsFloat + From<u16> + Default + Copy + core::fmt::Debug {
     /// 0
     const ZERO: Self;
     /// 1
@@ -18,6 +18,8 @@ pub trait Float: NumTraitsFloat + From<u16> + Default + Copy {
     const ONE_HALF: Self;
     /// 0.1
     const POINT_ONE: Self;
+    /// 0.02
+    const POINT_ZERO_TWO: Self;
     /// 0.98
     const POINT_NINE_EIGHT: Self;
     /// 0xF000 / 0xFFFF
@@ -26,6 +28,8 @@ pub trait Float: NumTraitsFloat + From<u16> + Default + Copy {
     const NOTE_MAX: Self;
     /// 0.9375
     const SHAPE_CLIP: Self;
+    /// 0.0001 (-80dB)
+    const ENV_IDLE: Self;
     /// 2 / pi
     const FRAC_2_PI: Self;
     /// pi / 2
@@ -47,8 +51,21 @@ pub trait Float: NumTraitsFloat + From<u16> + Default + Copy {
     fn ftan(self) -> Self;
     /// Convert a MIDI note number to a frequency
     fn midi_to_freq(self) -> Self;
+    /// Convert a MIDI note number to a frequency, always using the internal
+    /// approximation (even when using libm)
+    fn midi_to_freq_approx(self) -> Self;
     /// Convert to a f32
     fn as_f32(self) -> f32;
+    /// Returns zero if self is denormal (subnormal), or self otherwise.  This
+    /// is used in recursive feedback paths, as decaying into the denormal
+    /// range can be very slow on some hardware.
+    fn flush_denormal(self) -> Self {
+        if self.classify() == core::num::FpCategory::Subnormal {
+            Self::ZERO
+        } else {
+            self
+        }
+    }
 }
 
 impl Float for f32 {
@@ -58,6 +75,7 @@ impl Float for f32 {
     const THREE: f32 = 3.0f32;
     const ONE_HALF: f32 = 0.5f32;
     const POINT_ONE: f32 = 0.1f32;
+    const POINT_ZERO_TWO: f32 = 0.02f32;
     const POINT_NINE_EIGHT: f32 = 0.98f32;
     const FRAC_PI_2: f32 = core::f32::consts::FRAC_PI_2;
     const FRAC_2_PI: f32 = core::f32::consts::FRAC_2_PI;
@@ -66,6 +84,7 @@ impl Float for f32 {
     const RES_MAX: f32 = 0xF000 as f32 / 0xFFFF as f32;
     const NOTE_MAX: f32 = 127.0f32 * (0xFFFF as f32 / 0x10000 as f32);
     const SHAPE_CLIP: f32 = 0.9375f32;
+    const ENV_IDLE: f32 = 0.0001f32;
     fn fsin(self) -> Self {
         #[cfg(not(feature = "libm"))]
         let ret = crate::float_approx::sin_approx(self);
@@ -94,6 +113,9 @@ impl Float for f32 {
         let ret = 440f32 * ((self - 69f32) / 12f32).exp2();
         ret
     }
+    fn midi_to_freq_approx(self) -> Self {
+        crate::float_approx::midi_note_to_frequency(self)
+    }
     fn as_f32(self) -> f32 {
         self
     }
@@ -106,6 +128,7 @@ impl Float for f64 {
     const THREE: f64 = 3.0f64;
     const ONE_HALF: f64 = 0.5f64;
     const POINT_ONE: f64 = 0.1f64;
+    const POINT_ZERO_TWO: f64 = 0.02f64;
     const POINT_NINE_EIGHT: f64 = 0.98f64;
     const RES_MAX: f64 = 0xF000 as f64 / 0xFFFF as f64;
     const FRAC_PI_2: f64 = core::f64::consts::FRAC_PI_2;
@@ -114,6 +137,7 @@ impl Float for f64 {
     const TAU: f64 = core::f64::consts::TAU;
     const NOTE_MAX: f64 = 127.0f64 * (0xFFFF as f64 / 0x10000 as f64);
     const SHAPE_CLIP: f64 = 0.9375f64;
+    const ENV_IDLE: f64 = 0.0001f64;
     fn fsin(self) -> Self {
         #[cfg(not(feature = "libm"))]
         let ret = crate::float_approx::sin_approx(self);
@@ -142,6 +166,9 @@ impl Float for f64 {
         let ret = 440f64 * ((self - 69f64) / 12f64).exp2();
         ret
     }
+    fn midi_to_freq_approx(self) -> Self {
+        crate::float_approx::midi_note_to_frequency(self)
+    }
     fn as_f32(self) -> f32 {
         self as f32
     }
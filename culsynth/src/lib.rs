@@ -8,6 +8,13 @@
 //!
 //! Most of the relevant code for users can be found in the [devices] module.
 //!
+//! Floating-point support is provided by the `float` feature, which is enabled
+//! by default.  For targets without an FPU, disable the default features to
+//! build only the fixed-point (`i16`) path.  This also removes the helpers that
+//! are implemented in floating point, such as biquad coefficient design, the
+//! voice [Meter](voice::meter::Meter), and the
+//! [VoiceParamsBuilder](voice::builder::VoiceParamsBuilder).
+//!
 //! This crate uses the (somewhat regrettably hungarian-style) convention of
 //! having all fixed-point structs and traits be the same as their floating-point
 //! counterparts with the FxP suffix to denote fixed point operation.  This is
@@ -21,11 +28,13 @@
 use fixed::{traits::Fixed, FixedI32};
 
 mod fixedmath;
+#[cfg(feature = "float")]
 mod float_approx;
 pub mod util;
 
 /// True if using libm for floating-point math, false if using internal
-/// approximation functions
+/// approximation functions.  With libm, the choice may also be made at runtime
+/// for each [Context](context::Context) (see `Context::set_use_libm`).
 pub const USE_LIBM: bool = cfg!(feature = "libm");
 
 /// This module contains `u8` constants for MIDI note numbers, using standard
@@ -38,6 +47,11 @@ pub const USE_LIBM: bool = cfg!(feature = "libm");
 /// flat.
 pub mod midi_const;
 
+/// This module contains a fixed-capacity, `no_std` queue of timestamped MIDI
+/// events, for buffering the events of a block and applying them at the
+/// correct sample
+pub mod midi_queue;
+
 pub mod context;
 pub mod devices;
 
@@ -50,6 +64,8 @@ pub use fixedmath::Sample as SampleFxP;
 pub use fixedmath::Scalar as ScalarFxP;
 pub use fixedmath::SignedNote as SignedNoteFxP;
 pub use fixedmath::USample as USampleFxP;
+pub use fixedmath::{exp2_fixed, log2_fixed, sqrt_fixed};
+pub use fixedmath::{note_frequency, note_frequency_interp};
 /// An envelope rise/fall time parameter, represented in seconds as an unsigned
 /// 16 bit fixed point number with 13 fractional bits and 3 integral bits.  This
 /// yields a range of 0 to 8 seconds - though as implemented this timing is not
@@ -65,13 +81,74 @@ pub type IScalarFxP = fixedmath::I1F15;
 /// A 32-bit fixed point number representing a sinusoid's phase.  
 type PhaseFxP = fixedmath::I4F28;
 
+/// A sample of audio data for the 32 bit fixed point format, with 28
+/// fractional bits (see [SampleFxP])
+pub type SampleFxP32 = fixedmath::I4F28;
+/// A MIDI note number for the 32 bit fixed point format, with 25 fractional
+/// bits (see [NoteFxP])
+pub type NoteFxP32 = fixedmath::U7F25;
+/// A signed offset to a [NoteFxP32]
+pub type SignedNoteFxP32 = fixedmath::I7F25;
+/// A value in the range `[0, 1)` for the 32 bit fixed point format
+pub type ScalarFxP32 = fixedmath::U0F32;
+/// A signed value in the range `[-1, 1)` for the 32 bit fixed point format
+pub type IScalarFxP32 = fixedmath::I1F31;
+/// An envelope rise/fall time parameter for the 32 bit fixed point format (see
+/// [EnvParamFxP])
+pub type EnvParamFxP32 = fixedmath::U3F29;
+/// A frequency parameter for a LFO, in Hertz, for the 32 bit fixed point format
+pub type LfoFreqFxP32 = fixedmath::U7F25;
+
 mod fixed_traits;
-pub use fixed_traits::Fixed16;
+pub use fixed_traits::{Fixed16, Fixed32};
 
+#[cfg(feature = "float")]
 mod float_traits;
+#[cfg(feature = "float")]
 pub use float_traits::Float;
 
 mod dsp_format;
-pub use dsp_format::{DspFloat, DspFormat, DspFormatBase, DspType};
+#[cfg(feature = "float")]
+pub use dsp_format::DspFloat;
+pub use dsp_format::{DspFormat, DspFormatBase, DspType};
 
 type WideSampleFxP = FixedI32<<SampleFxP as Fixed>::Frac>;
+
+#[cfg(all(test, not(feature = "float")))]
+mod tests {
+    use super::*;
+    use crate::context::ContextFxP;
+    use crate::voice::{Voice, VoiceInput, VoiceParams};
+
+    /// Guards the fixed-point only build: a voice must still build and play
+    /// without the `float` feature
+    #[test]
+    fn fixed_only_voice_plays() {
+        let ctx = ContextFxP::new_480();
+        let mut params = VoiceParams::<i16>::default();
+        params.oscs_p.primary.saw = ScalarFxP::MAX;
+        params.ring_p.mix_a = ScalarFxP::MAX;
+        params.filt_p.low_mix = ScalarFxP::MAX;
+        params.filt_p.cutoff = NoteFxP::MAX;
+        let input = VoiceInput::<i16> {
+            gate: true,
+            ..Default::default()
+        };
+        let mut voice = Voice::<i16>::new_with_seed(0);
+        let mut matrix = Some(Default::default());
+        let peak = (0..4800)
+            .map(|_| {
+                let out = voice.next(
+                    &ctx,
+                    matrix.take().as_ref(),
+                    &input,
+                    &Default::default(),
+                    params.clone(),
+                );
+                out.unsigned_abs()
+            })
+            .max()
+            .unwrap();
+        assert!(peak > USampleFxP::lit("0.1"), "{peak}");
+    }
+}
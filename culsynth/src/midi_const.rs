@@ -120,3 +120,68 @@ mod detail {
 }
 
 pub use detail::*;
+
+/// The names of the notes in an octave, starting from C and using flats for
+/// the black keys (matching the constants in this module)
+pub const NOTE_NAMES: [&str; 12] = [
+    "C", "Db", "D", "Eb", "E", "F", "Gb", "G", "Ab", "A", "Bb", "B",
+];
+
+/// A MIDI note number, which is displayed as its note name and octave (e.g.
+/// `NoteName(C4)` displays as `"C4"`)
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct NoteName(pub u8);
+
+impl NoteName {
+    /// The name of the note, without the octave (see [NOTE_NAMES])
+    pub const fn name(&self) -> &'static str {
+        NOTE_NAMES[(self.0 % 12) as usize]
+    }
+    /// The octave of the note, where octave 4 starts at middle C
+    pub const fn octave(&self) -> i8 {
+        (self.0 / 12) as i8 - 1
+    }
+}
+
+impl core::fmt::Display for NoteName {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}{}", self.name(), self.octave())
+    }
+}
+
+/// Quantize `note` to the nearest MIDI note for display, returning that note
+/// and the offset from it in cents, from -49 to 50 (so a note exactly halfway
+/// between two semitones is displayed as the lower note, +50 cents).  Notes
+/// above G9 are displayed relative to G9 (the highest MIDI note), so the
+/// offset may reach +100 cents.
+pub fn note_name(note: crate::NoteFxP) -> (NoteName, i8) {
+    const HALF: u32 = 1 << (crate::NoteFxP::FRAC_NBITS - 1);
+    let bits = note.to_bits() as u32;
+    let nearest = core::cmp::min((bits + HALF - 1) >> crate::NoteFxP::FRAC_NBITS, 127);
+    let offset = bits as i32 - (nearest << crate::NoteFxP::FRAC_NBITS) as i32;
+    let cents = (offset * 100 + HALF as i32).div_euclid(2 * HALF as i32);
+    (NoteName(nearest as u8), cents as i8)
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+    use super::*;
+    use crate::NoteFxP;
+    use std::format;
+
+    #[test]
+    fn note_names_with_cents() {
+        let display = |note: NoteFxP| {
+            let (name, cents) = note_name(note);
+            format!("{name} {cents:+} cents")
+        };
+        assert_eq!(display(NoteFxP::lit("60.5")), "C4 +50 cents");
+        assert_eq!(display(NoteFxP::lit("69")), "A4 +0 cents");
+        assert_eq!(display(NoteFxP::lit("60.75")), "Db4 -25 cents");
+        assert_eq!(display(NoteFxP::ZERO), "C-1 +0 cents");
+        assert_eq!(display(NoteFxP::lit("127.75")), "G9 +75 cents");
+        assert_eq!(display(NoteFxP::MAX), "G9 +100 cents");
+        assert_eq!(note_name(NoteFxP::from_num(Bb3)).0, NoteName(Bb3));
+    }
+}
@@ -0,0 +1,139 @@
+use arrayvec::ArrayVec;
+
+/// A MIDI event, as stored in a [MidiQueue]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MidiEvent {
+    /// A note on event, with the note number and velocity
+    NoteOn {
+        /// The MIDI note number
+        note: u8,
+        /// The note on velocity, from 0 to 127
+        velocity: u8,
+    },
+    /// A note off event, with the note number and release velocity
+    NoteOff {
+        /// The MIDI note number
+        note: u8,
+        /// The release velocity, from 0 to 127
+        velocity: u8,
+    },
+    /// A control change event
+    ControlChange {
+        /// The controller number, from 0 to 127
+        cc: u8,
+        /// The controller value, from 0 to 127
+        value: u8,
+    },
+    /// A channel pressure (aftertouch) event, from 0 to 127
+    ChannelPressure(u8),
+    /// A pitch bend event, as a signed value centered on zero
+    PitchBend(i16),
+}
+
+/// A fixed-capacity queue of timestamped [MidiEvent]s for one block of audio
+///
+/// Each event is tagged with the index of the sample in the block at which it
+/// takes effect.  Events may be pushed in any order and are drained in order
+/// of their timestamps, with events at the same sample kept in the order they
+/// were pushed.  Up to `N` events may be stored without allocating.
+#[derive(Clone, Default)]
+pub struct MidiQueue<const N: usize> {
+    events: ArrayVec<(u32, MidiEvent), N>,
+}
+
+impl<const N: usize> MidiQueue<N> {
+    /// Constructor
+    pub fn new() -> Self {
+        Default::default()
+    }
+    /// Add `event` at sample index `time`.  If the queue is full, the event is
+    /// dropped and returned as the error, leaving the queued events unchanged.
+    pub fn push(&mut self, time: u32, event: MidiEvent) -> Result<(), MidiEvent> {
+        if self.events.is_full() {
+            return Err(event);
+        }
+        let idx = self.events.partition_point(|(t, _)| *t <= time);
+        self.events.insert(idx, (time, event));
+        Ok(())
+    }
+    /// Remove and iterate over all of the events at or before sample index
+    /// `time`, in order, as `(time, event)` pairs.  Call this once per sample
+    /// to apply each event on the sample it was timestamped with.
+    pub fn drain_until(&mut self, time: u32) -> impl Iterator<Item = (u32, MidiEvent)> + '_ {
+        let end = self.events.partition_point(|(t, _)| *t <= time);
+        self.events.drain(..end)
+    }
+    /// Remove all queued events
+    pub fn clear(&mut self) {
+        self.events.clear();
+    }
+    /// The number of queued events
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+    /// True if there are no queued events
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+    /// True if no more events may be queued
+    pub fn is_full(&self) -> bool {
+        self.events.is_full()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const fn note_on(note: u8) -> MidiEvent {
+        MidiEvent::NoteOn {
+            note,
+            velocity: 100,
+        }
+    }
+
+    #[test]
+    fn events_drain_in_order() {
+        let mut queue = MidiQueue::<8>::new();
+        let cc = MidiEvent::ControlChange { cc: 1, value: 64 };
+        queue.push(10, note_on(60)).unwrap();
+        queue.push(3, cc).unwrap();
+        queue.push(10, MidiEvent::PitchBend(-5)).unwrap();
+        queue.push(0, note_on(62)).unwrap();
+        let expected = [
+            (0, note_on(62)),
+            (3, cc),
+            (10, note_on(60)),
+            (10, MidiEvent::PitchBend(-5)),
+        ];
+        assert!(queue.drain_until(u32::MAX).eq(expected));
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn overflow_drops_new_events() {
+        let mut queue = MidiQueue::<2>::new();
+        queue.push(5, note_on(60)).unwrap();
+        queue.push(1, note_on(61)).unwrap();
+        assert!(queue.is_full());
+        assert_eq!(queue.push(0, note_on(62)), Err(note_on(62)));
+        assert_eq!(queue.len(), 2);
+        assert!(queue.drain_until(10).eq([(1, note_on(61)), (5, note_on(60))]));
+        // Draining frees up space for new events
+        assert_eq!(queue.push(0, note_on(62)), Ok(()));
+    }
+
+    #[test]
+    fn drain_until_sample_index() {
+        let mut queue = MidiQueue::<8>::new();
+        for (time, note) in [(0, 60), (4, 61), (4, 62), (7, 63)] {
+            queue.push(time, note_on(note)).unwrap();
+        }
+        assert!(queue.drain_until(0).eq([(0, note_on(60))]));
+        assert_eq!(queue.drain_until(3).count(), 0);
+        assert!(queue.drain_until(5).eq([(4, note_on(61)), (4, note_on(62))]));
+        assert_eq!(queue.len(), 1);
+        queue.clear();
+        assert!(queue.is_empty());
+    }
+}
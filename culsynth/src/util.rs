@@ -1,6 +1,13 @@
 //! Various utility functions and helpful constants
 
+#[cfg(feature = "float")]
+use crate::fixedmath::{I6F26, U16F16};
+#[cfg(feature = "float")]
+use crate::{exp2_fixed, log2_fixed, Float, SampleFxP, ScalarFxP};
+use crate::{DspFormatBase, DspType};
+
 // currently the only users of this function are unit tests... shut up dead code warning
+/// Calculate the distance between two frequencies, in cents
 #[cfg(test)]
 pub fn calculate_cents(base: f32, freq: f32) -> f32 {
     1200.0 * f32::log2(freq / base)
@@ -17,6 +24,235 @@ pub fn midi_note_pretty(note: i8) -> String {
 }
 */
 
+/// An edge in a gate signal, as reported by [GateDetect]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum GateEdge {
+    /// The gate transitioned from low to high
+    Rising,
+    /// The gate transitioned from high to low
+    Falling,
+}
+
+/// A helper to detect rising and falling edges in a per-sample gate signal.
+///
+/// This tracks the gate state from the previous sample, so one instance
+/// should be used per gate signal and updated exactly once per sample.
+#[derive(Default, Clone, Copy)]
+pub struct GateDetect {
+    last: bool,
+}
+
+impl GateDetect {
+    /// Constructor.  The gate is initially assumed to be low.
+    pub const fn new() -> Self {
+        Self { last: false }
+    }
+    /// Update the detector with the gate for this sample, returning the edge
+    /// (if any) since the last sample
+    pub fn next(&mut self, gate: bool) -> Option<GateEdge> {
+        let last = core::mem::replace(&mut self.last, gate);
+        match (last, gate) {
+            (false, true) => Some(GateEdge::Rising),
+            (true, false) => Some(GateEdge::Falling),
+            _ => None,
+        }
+    }
+    /// The gate state as of the last call to [GateDetect::next]
+    pub fn gate(&self) -> bool {
+        self.last
+    }
+}
+
+/// A linear ramp of a parameter value over a fixed number of samples.
+///
+/// Use this as a parameter source (e.g. for the `with_*` methods of the
+/// parameter iterator builders in [crate::devices]) to avoid stepped
+/// parameter changes.  As an iterator, this yields one value per sample and
+/// never ends: after [Ramp::ramp_to] with a length of `N` samples, the `N`th
+/// value yielded is exactly the target, which is then held.
+#[derive(Clone)]
+pub struct Ramp<T: DspFormatBase, V: DspType<T>> {
+    start: V,
+    target: V,
+    len: usize,
+    idx: usize,
+    /// The progress through the ramp, with 32 fractional bits
+    frac: u32,
+    /// The per-sample increment of `frac`, so it need not divide every sample
+    step: u32,
+    phantom: core::marker::PhantomData<T>,
+}
+
+impl<T: DspFormatBase, V: DspType<T>> Ramp<T, V> {
+    /// Constructor.  The ramp initially holds `value`.
+    pub fn new(value: V) -> Self {
+        Self {
+            start: value,
+            target: value,
+            len: 0,
+            idx: 0,
+            frac: 0,
+            step: 0,
+            phantom: Default::default(),
+        }
+    }
+    /// Start ramping from the current value to `target` over `len` samples.
+    /// A length of zero jumps to `target` immediately.
+    pub fn ramp_to(&mut self, target: V, len: usize) {
+        self.start = self.value();
+        self.target = target;
+        self.len = len;
+        self.idx = 0;
+        self.frac = 0;
+        self.step = if len > 1 {
+            ((1u64 << 32) / len as u64) as u32
+        } else {
+            0
+        };
+    }
+    /// The current value of the ramp (i.e. the last value yielded)
+    pub fn value(&self) -> V {
+        if self.idx >= self.len {
+            return self.target;
+        }
+        let frac = T::scalar_from_fixed(crate::ScalarFxP::from_bits((self.frac >> 16) as u16));
+        // Halve both ends first so the difference cannot overflow
+        if self.target >= self.start {
+            let step = (self.target.divide_by_two() - self.start.divide_by_two()).scale(frac);
+            self.start.dsp_saturating_add(step).dsp_saturating_add(step)
+        } else {
+            let step = (self.start.divide_by_two() - self.target.divide_by_two()).scale(frac);
+            self.start - step - step
+        }
+    }
+    /// The value this ramp is moving towards
+    pub fn target(&self) -> V {
+        self.target
+    }
+    /// Returns true if the ramp has reached its target
+    pub fn is_done(&self) -> bool {
+        self.idx >= self.len
+    }
+}
+
+impl<T: DspFormatBase, V: DspType<T>> Iterator for Ramp<T, V> {
+    type Item = V;
+    fn next(&mut self) -> Option<V> {
+        self.idx = core::cmp::min(self.idx + 1, self.len);
+        // Once idx reaches len the ramp snaps to its target, so frac stays
+        // below one (i.e. cannot overflow)
+        if self.idx < self.len {
+            self.frac += self.step;
+        }
+        Some(self.value())
+    }
+}
+
+/// Approximate square root (initial guess from the float representation,
+/// refined with two Newton-Raphson iterations)
+#[cfg(feature = "float")]
+pub(crate) fn sqrt_approx(x: f32) -> f32 {
+    if x <= 0f32 {
+        return 0f32;
+    }
+    let mut y = f32::from_bits((x.to_bits() >> 1) + 0x1fbd_1df5);
+    y = 0.5f32 * (y + x / y);
+    0.5f32 * (y + x / y)
+}
+
+/// The level measurement used by [normalize_table] and
+/// [normalize_table_float]
+#[cfg(feature = "float")]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum NormalizeMode {
+    /// Normalize so the largest absolute sample value hits the target
+    Peak,
+    /// Normalize so the RMS level hits the target
+    Rms,
+}
+
+/// Measure the level of `table` as specified by `mode`
+#[cfg(feature = "float")]
+fn table_level(table: impl Iterator<Item = f32> + Clone, mode: NormalizeMode) -> f32 {
+    match mode {
+        NormalizeMode::Peak => table.fold(0f32, |acc, x| acc.max(x.abs())),
+        NormalizeMode::Rms => {
+            let len = table.clone().count();
+            let sum_sq: f32 = table.map(|x| x * x).sum();
+            sqrt_approx(sum_sq / core::cmp::max(len, 1) as f32)
+        }
+    }
+}
+
+/// Scale a (e.g. wavetable) `table` in place so that its peak or RMS level
+/// (as selected by `mode`) is equal to `target`, saturating any samples that
+/// would overflow.  Returns the gain that was applied.  A silent table is left
+/// unchanged (with a gain of 1).
+///
+/// This is intended to be used when loading tables, not in real time.
+#[cfg(feature = "float")]
+pub fn normalize_table(table: &mut [SampleFxP], mode: NormalizeMode, target: SampleFxP) -> f32 {
+    let level = table_level(table.iter().map(|x| x.to_num::<f32>()), mode);
+    if level == 0f32 {
+        return 1f32;
+    }
+    let gain = target.to_num::<f32>() / level;
+    for smp in table.iter_mut() {
+        *smp = SampleFxP::saturating_from_num(smp.to_num::<f32>() * gain);
+    }
+    gain
+}
+
+/// The floating-point version of [normalize_table]
+#[cfg(feature = "float")]
+pub fn normalize_table_float<T: Float>(table: &mut [T], mode: NormalizeMode, target: T) -> T {
+    let level = table_level(table.iter().map(|x| x.as_f32()), mode);
+    if level == 0f32 {
+        return T::ONE;
+    }
+    let gain = match mode {
+        // Avoid the error of the square root approximation for peaks:
+        NormalizeMode::Peak => target / table.iter().fold(T::ZERO, |acc, x| acc.max(x.abs())),
+        NormalizeMode::Rms => target / <T as num_traits::NumCast>::from(level).unwrap_or(T::ONE),
+    };
+    for smp in table.iter_mut() {
+        *smp = *smp * gain;
+    }
+    gain
+}
+
+/// Decibels per doubling of amplitude (20 * log10(2))
+#[cfg(feature = "float")]
+const DB_PER_OCTAVE: f32 = 6.020_6;
+
+/// The level of the smallest nonzero [ScalarFxP], in decibels (about -96.3dB).
+/// [scalar_to_db] never returns less than this.
+#[cfg(feature = "float")]
+pub const SCALAR_DB_MIN: f32 = -16f32 * DB_PER_OCTAVE;
+
+/// Convert a gain to decibels (so [ScalarFxP::MAX] is approximately 0dB and
+/// 0.5 is approximately -6dB), e.g. for displaying mix and level controls.
+/// Zero is treated as the smallest nonzero gain, returning [SCALAR_DB_MIN].
+#[cfg(feature = "float")]
+pub fn scalar_to_db(gain: ScalarFxP) -> f32 {
+    let gain = core::cmp::max(gain, ScalarFxP::DELTA);
+    log2_fixed(U16F16::from_num(gain)).to_num::<f32>() * DB_PER_OCTAVE
+}
+
+/// Convert a level in decibels to a gain (the inverse of [scalar_to_db]).
+/// Levels of 0dB or above saturate to [ScalarFxP::MAX], and levels below
+/// [SCALAR_DB_MIN] (or NaN) are zero.
+#[cfg(feature = "float")]
+pub fn db_to_scalar(db: f32) -> ScalarFxP {
+    if db >= 0f32 {
+        return ScalarFxP::MAX;
+    } else if db < SCALAR_DB_MIN || db.is_nan() {
+        return ScalarFxP::ZERO;
+    }
+    let gain = exp2_fixed(I6F26::saturating_from_num(db / DB_PER_OCTAVE));
+    ScalarFxP::saturating_from_num(gain)
+}
+
 /// A character depicting a sine wave (∿)
 pub const SIN_CHARSTR: &str = "\u{223F}";
 /// A character depicting a square wave (⎍).
@@ -27,3 +263,104 @@ pub const SQ_CHARSTR: &str = "\u{238D}";
 pub const TRI_CHARSTR: &str = "\u{039B}";
 /// A character depicting a sawtooth wave (⩘).  This is the "sloping large and".
 pub const SAW_CHARSTR: &str = "\u{2A58}";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "float")]
+    #[test]
+    fn sqrt_approx_error() {
+        for x in [1e-6f32, 0.01, 0.25, 0.5, 1.0, 2.0, 64.0] {
+            let err = (sqrt_approx(x) - x.sqrt()).abs() / x.sqrt();
+            assert!(err < 1e-4);
+        }
+    }
+    #[cfg(feature = "float")]
+    #[test]
+    fn normalize_quiet_table() {
+        let mut table = [0f32; 256];
+        for (i, smp) in table.iter_mut().enumerate() {
+            *smp = 0.1 * (core::f32::consts::TAU * i as f32 / 256f32).sin();
+        }
+        let mut fixed = table.map(SampleFxP::from_num);
+        let gain = normalize_table_float(&mut table, NormalizeMode::Peak, 0.8);
+        assert!(gain > 7.9 && gain < 8.1);
+        assert_eq!(table.iter().fold(0f32, |acc, x| acc.max(x.abs())), 0.8);
+
+        let target = SampleFxP::lit("0.8");
+        normalize_table(&mut fixed, NormalizeMode::Peak, target);
+        assert_eq!(fixed.iter().map(|x| x.abs()).max(), Some(target));
+
+        let mut rms = fixed;
+        normalize_table(&mut rms, NormalizeMode::Rms, SampleFxP::lit("0.5"));
+        let level = table_level(rms.iter().map(|x| x.to_num::<f32>()), NormalizeMode::Rms);
+        assert!((level - 0.5).abs() < 0.001);
+    }
+    #[cfg(feature = "float")]
+    #[test]
+    fn db_anchor_points() {
+        assert!(scalar_to_db(ScalarFxP::MAX).abs() < 0.01);
+        assert!((scalar_to_db(ScalarFxP::lit("0.5")) + 6.0206).abs() < 0.01);
+        assert_eq!(scalar_to_db(ScalarFxP::ZERO), SCALAR_DB_MIN);
+        assert_eq!(db_to_scalar(0f32), ScalarFxP::MAX);
+        assert_eq!(db_to_scalar(6f32), ScalarFxP::MAX);
+        let half = db_to_scalar(-6.0206);
+        assert!((half.to_num::<f32>() - 0.5).abs() < 0.001, "{half}");
+        assert_eq!(db_to_scalar(-120f32), ScalarFxP::ZERO);
+        assert_eq!(db_to_scalar(f32::NEG_INFINITY), ScalarFxP::ZERO);
+        for db in [-0.1f32, -3.0, -12.0, -24.0, -48.0] {
+            let round_trip = scalar_to_db(db_to_scalar(db));
+            assert!((round_trip - db).abs() < 0.05, "{db}: {round_trip}");
+        }
+    }
+    #[test]
+    fn gate_detect_edges() {
+        let gates = [false, true, true, false, false, true, false];
+        let expected = [
+            None,
+            Some(GateEdge::Rising),
+            None,
+            Some(GateEdge::Falling),
+            None,
+            Some(GateEdge::Rising),
+            Some(GateEdge::Falling),
+        ];
+        let mut detect = GateDetect::new();
+        for (gate, edge) in core::iter::zip(gates, expected) {
+            assert_eq!(detect.next(gate), edge);
+            assert_eq!(detect.gate(), gate);
+        }
+    }
+    #[cfg(feature = "float")]
+    #[test]
+    fn ramp_reaches_target() {
+        let mut ramp = Ramp::<f32, f32>::new(1f32);
+        ramp.ramp_to(-1f32, 100);
+        let vals: [f32; 101] = core::array::from_fn(|_| ramp.next().unwrap());
+        assert!(vals.windows(2).all(|w| w[1] <= w[0]));
+        assert!((vals[49] - 0f32).abs() < 0.001, "{}", vals[49]);
+        assert_eq!(vals[99], -1f32);
+        assert_eq!(vals[100], -1f32);
+        assert!(ramp.is_done());
+
+        // Unsigned fixed point, in both directions, from the middle of a ramp
+        let mut ramp = Ramp::<i16, crate::NoteFxP>::new(crate::NoteFxP::ZERO);
+        ramp.ramp_to(crate::NoteFxP::MAX, 480);
+        let mid = ramp.nth(239).unwrap();
+        assert!((mid.to_num::<f32>() - 64f32).abs() < 0.1, "{mid}");
+        let target = crate::NoteFxP::lit("60");
+        ramp.ramp_to(target, 7);
+        let vals: [_; 8] = core::array::from_fn(|_| ramp.next().unwrap());
+        assert!(vals[..6].iter().all(|v| *v > target && *v < mid));
+        assert_eq!(vals[6], target);
+        assert_eq!(vals[7], target);
+
+        // A power of two length steps exactly
+        ramp.ramp_to(crate::NoteFxP::ZERO, 4);
+        let vals: [_; 5] = core::array::from_fn(|_| ramp.next().unwrap());
+        assert_eq!(vals[1], crate::NoteFxP::lit("30"));
+        assert_eq!(vals[3], crate::NoteFxP::ZERO);
+        assert_eq!(vals[4], crate::NoteFxP::ZERO);
+    }
+}
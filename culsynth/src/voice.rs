@@ -1,16 +1,29 @@
 //! This module contains a struct composing various devices together as a
 //! single voice unit for a basic subtractive synthesizer.
 
-use crate::{devices::*, DspFloat, DspFormat};
+use crate::util::{GateDetect, GateEdge};
+#[cfg(feature = "float")]
+use crate::DspFloat;
+use crate::{devices::*, DspFormat, DspType};
+use rand::{rngs::SmallRng, RngCore, SeedableRng};
 
+#[cfg(feature = "float")]
+use self::meter::{Meter, MeterLevels};
+#[cfg(feature = "float")]
+use self::modulation::{detail::ModValue, ModDest};
 use self::modulation::{ModMatrix, ModSection};
 
+pub mod arp;
+#[cfg(feature = "float")]
+pub mod builder;
 pub mod cc;
+#[cfg(feature = "float")]
+pub mod meter;
 pub mod modulation;
 pub mod nrpn;
 
 /// A parameter pack for a [Voice]
-#[derive(Clone, Default)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct VoiceParams<T: DspFormat> {
     /// Oscillator section parameters
     pub oscs_p: SyncedMixOscsParams<T>,
@@ -30,8 +43,29 @@ pub struct VoiceParams<T: DspFormat> {
     pub env1_p: EnvParams<T>,
     /// Modulation Envelope 2
     pub env2_p: EnvParams<T>,
+    /// Portamento
+    pub glide_p: GlideParams<T>,
+    /// Output soft-saturation, or `None` (the default) to disable it
+    pub sat_p: Option<WaveshaperParams<T>>,
+    /// The overall output gain of the voice, applied at the VCA, e.g. to
+    /// match the loudness of different patches.  Defaults to unity (or the
+    /// largest representable gain for fixed point).
+    pub master_gain: T::Scalar,
+    /// If true, reset the voice (see [Voice::reset]) on each note on, so
+    /// repeated notes sound identical regardless of what was played before,
+    /// e.g. for rhythmic parts.  If false (the default), the oscillators,
+    /// LFOs and envelopes run freely between notes (subject to each
+    /// oscillator's key-sync option and each LFO's retrigger option, see
+    /// [MixOscParams::key_sync] and [LfoOptions::retrigger]).
+    pub note_sync: bool,
+    /// The index of the output bus this voice is routed to (e.g. dry vs.
+    /// send).  This is ignored by the voice itself, and is provided for voice
+    /// allocators to group voice outputs (see [mix_into_bus]).  Zero (the
+    /// default) is the main bus.
+    pub bus: u8,
 }
 
+#[cfg(feature = "float")]
 impl<T: DspFloat> From<&VoiceParams<i16>> for VoiceParams<T> {
     fn from(value: &VoiceParams<i16>) -> Self {
         Self {
@@ -44,10 +78,89 @@ impl<T: DspFloat> From<&VoiceParams<i16>> for VoiceParams<T> {
             lfo2_p: (&value.lfo2_p).into(),
             env1_p: (&value.env1_p).into(),
             env2_p: (&value.env2_p).into(),
+            glide_p: (&value.glide_p).into(),
+            sat_p: value.sat_p.as_ref().map(|p| p.into()),
+            master_gain: value.master_gain.to_num(),
+            note_sync: value.note_sync,
+            bus: value.bus,
+        }
+    }
+}
+
+impl<T: DspFormat> Default for VoiceParams<T> {
+    fn default() -> Self {
+        Self {
+            oscs_p: Default::default(),
+            ring_p: Default::default(),
+            filt_p: Default::default(),
+            filt_env_p: Default::default(),
+            amp_env_p: Default::default(),
+            lfo1_p: Default::default(),
+            lfo2_p: Default::default(),
+            env1_p: Default::default(),
+            env2_p: Default::default(),
+            glide_p: Default::default(),
+            sat_p: None,
+            master_gain: T::Scalar::one(),
+            note_sync: false,
+            bus: 0,
         }
     }
 }
 
+#[cfg(feature = "float")]
+impl<T: DspFormat> VoiceParams<T> {
+    /// Read the value of the parameter targeted by `dest`, or `None` if `dest`
+    /// is not a parameter of the voice itself (i.e. it targets one of the
+    /// modulation LFOs or envelopes, or a disabled wave morph).  Both the course and fine tune
+    /// destinations read the total oscillator tuning.
+    fn get(&self, dest: ModDest) -> Option<ModValue<T>> {
+        use ModValue::*;
+        let (osc1, osc2) = (&self.oscs_p.primary, &self.oscs_p.secondary);
+        let (filt, env_filt, env_amp) = (&self.filt_p, &self.filt_env_p, &self.amp_env_p);
+        Some(match dest {
+            ModDest::Osc1Course | ModDest::Osc1Fine => NoteOffset(osc1.tune),
+            ModDest::Osc1Shape => Scalar(osc1.shape),
+            ModDest::Osc1Sin => Scalar(osc1.sin),
+            ModDest::Osc1Sq => Scalar(osc1.sq),
+            ModDest::Osc1Tri => Scalar(osc1.tri),
+            ModDest::Osc1Saw => Scalar(osc1.saw),
+            ModDest::Osc1Level => Scalar(osc1.osc_level),
+            ModDest::Osc1Morph => Scalar(osc1.wave_morph?),
+            ModDest::Osc2Course | ModDest::Osc2Fine => NoteOffset(osc2.tune),
+            ModDest::Osc2Shape => Scalar(osc2.shape),
+            ModDest::Osc2Sin => Scalar(osc2.sin),
+            ModDest::Osc2Sq => Scalar(osc2.sq),
+            ModDest::Osc2Tri => Scalar(osc2.tri),
+            ModDest::Osc2Saw => Scalar(osc2.saw),
+            ModDest::Osc2Level => Scalar(osc2.osc_level),
+            ModDest::Osc2Morph => Scalar(osc2.wave_morph?),
+            ModDest::RingOsc1 => Scalar(self.ring_p.mix_a),
+            ModDest::RingOsc2 => Scalar(self.ring_p.mix_b),
+            ModDest::RingMod => Scalar(self.ring_p.mix_mod),
+            ModDest::FiltCutoff => Note(filt.cutoff),
+            ModDest::FiltRes => Scalar(filt.resonance),
+            ModDest::FiltEnv => Scalar(filt.env_mod),
+            ModDest::FiltKbd => Scalar(filt.kbd_tracking),
+            ModDest::FiltVel => Scalar(filt.vel_mod),
+            ModDest::FiltLow => Scalar(filt.low_mix),
+            ModDest::FiltBand => Scalar(filt.band_mix),
+            ModDest::FiltHigh => Scalar(filt.high_mix),
+            ModDest::FiltDrive => Scalar(filt.drive),
+            ModDest::EnvFiltA => EnvParam(env_filt.attack),
+            ModDest::EnvFiltD => EnvParam(env_filt.decay),
+            ModDest::EnvFiltS => Scalar(env_filt.sustain),
+            ModDest::EnvFiltR => EnvParam(env_filt.release),
+            ModDest::EnvAmpA => EnvParam(env_amp.attack),
+            ModDest::EnvAmpD => EnvParam(env_amp.decay),
+            ModDest::EnvAmpS => Scalar(env_amp.sustain),
+            ModDest::EnvAmpR => EnvParam(env_amp.release),
+            ModDest::MasterGain => Scalar(self.master_gain),
+            _ => return None,
+        })
+    }
+}
+
 impl From<&VoiceParams<i16>> for VoiceParams<i16> {
     fn from(value: &VoiceParams<i16>) -> Self {
         value.clone()
@@ -55,22 +168,38 @@ impl From<&VoiceParams<i16>> for VoiceParams<i16> {
 }
 
 /// Inputs for a [Voice] that are note-specific
-#[derive(Clone, Default)]
+#[derive(Clone, Default, Debug, PartialEq)]
 pub struct VoiceInput<T: DspFormat> {
     /// The note itself, as a MIDI note number
     pub note: T::Note,
     /// The velocity this note was played with
     pub velocity: T::Scalar,
+    /// The velocity this note was released with (MIDI Note Off velocity).
+    /// Only meaningful once the gate is low, and zero if not supported by
+    /// the controller.
+    pub release_velocity: T::Scalar,
     /// The gate signal
     pub gate: bool,
+    /// A per-note pitch bend (e.g. from an MPE controller), which is added
+    /// to `note`
+    pub bend: T::NoteOffset,
+    /// Per-note pressure (e.g. MPE channel pressure)
+    pub pressure: T::Scalar,
+    /// Per-note "slide" (e.g. MPE CC #74)
+    pub slide: T::Scalar,
 }
 
+#[cfg(feature = "float")]
 impl<T: DspFloat> From<&VoiceInput<i16>> for VoiceInput<T> {
     fn from(value: &VoiceInput<i16>) -> Self {
         Self {
             note: value.note.to_num(),
             gate: value.gate,
             velocity: value.velocity.to_num(),
+            release_velocity: value.release_velocity.to_num(),
+            bend: value.bend.to_num(),
+            pressure: value.pressure.to_num(),
+            slide: value.slide.to_num(),
         }
     }
 }
@@ -82,19 +211,25 @@ impl From<&VoiceInput<i16>> for VoiceInput<i16> {
 }
 
 /// Channel-wide (i.e. affecting all notes) inputs for a given [Voice]
-#[derive(Clone, Default)]
+#[derive(Clone, Default, Debug, PartialEq)]
 pub struct VoiceChannelInput<T: DspFormat> {
     /// Aftertouch (e.g. for a MIDI Channel Pressure Message)
     pub aftertouch: T::Scalar,
     /// Modulation Wheel (MIDI CC #1)
     pub modwheel: T::Scalar,
+    /// The output of an LFO shared by all voices.  If this is not `None`, it
+    /// replaces the output of each voice's own LFO 1, so every voice is
+    /// modulated in phase (see [modulation::ModSectionParams::global_lfo1])
+    pub global_lfo1: Option<T::Sample>,
 }
 
+#[cfg(feature = "float")]
 impl<T: DspFloat> From<&VoiceChannelInput<i16>> for VoiceChannelInput<T> {
     fn from(value: &VoiceChannelInput<i16>) -> Self {
         Self {
             aftertouch: value.aftertouch.to_num(),
             modwheel: value.modwheel.to_num(),
+            global_lfo1: value.global_lfo1.map(|x| x.to_num()),
         }
     }
 }
@@ -107,16 +242,26 @@ impl From<&VoiceChannelInput<i16>> for VoiceChannelInput<i16> {
 
 /// This struct encapsulates a single voice unit, containing a single oscillator,
 /// a single VCF (with modulation inputs and mixing of low/band/high pass outputs),
-/// a VCA, and two envelopes (one for the VCA and one for the VCF).
+/// a VCA, and two envelopes (one for the VCA and one for the VCF), followed by
+/// an optional soft-saturation output stage.
 #[derive(Clone, Default)]
 pub struct Voice<T: DspFormat> {
+    glide: Glide<T>,
     oscs: SyncedMixOscs<T>,
     ringmod: RingMod<T>,
     filt: ModFilt<T>,
     env_amp: Env<T>,
     env_filt: Env<T>,
     vca: Amp<T>,
+    shaper: Waveshaper<T>,
     modsection: ModSection<T>,
+    gate: GateDetect,
+    #[cfg(feature = "float")]
+    meter: Option<Meter>,
+    #[cfg(feature = "float")]
+    modulated: Option<VoiceParams<T>>,
+    patch: Option<(VoiceParams<T>, ModMatrix<T>)>,
+    patch_changed: bool,
 }
 
 impl<T: DspFormat> Voice<T> {
@@ -124,13 +269,139 @@ impl<T: DspFormat> Voice<T> {
     pub fn new() -> Self {
         Default::default()
     }
-    /// Constructor
+    /// Constructor, deterministically seeding all of the random sources in
+    /// this voice (e.g. S+H LFOs) with distinct seeds derived from `seed`
+    pub fn new_with_seed(seed: u64) -> Self {
+        let mut rng = SmallRng::seed_from_u64(seed);
+        let (seeda, seedb) = (rng.next_u64(), rng.next_u64());
+        Self {
+            filt: ModFilt::new_with_seed(rng.next_u64()),
+            ..Self::new_with_seeds(seeda, seedb)
+        }
+    }
+    /// Constructor, seeding the modulation LFOs with `seeda` and `seedb`
     pub fn new_with_seeds(seeda: u64, seedb: u64) -> Self {
         Self {
             modsection: ModSection::new_with_seeds(seeda, seedb),
             ..Default::default()
         }
     }
+    /// Enable or disable smoothing of mod matrix depth changes (see
+    /// [ModSection::set_depth_smoothing])
+    pub fn set_depth_smoothing(&mut self, enabled: bool) {
+        self.modsection.set_depth_smoothing(enabled);
+    }
+    /// Set the triggering behavior of the filter envelope (see [EnvMode]).
+    /// Setting this to [EnvMode::Loop] turns the filter envelope into an
+    /// extra cyclic modulation source while the note is held.
+    pub fn set_filt_env_mode(&mut self, mode: EnvMode) {
+        self.env_filt.set_mode(mode);
+    }
+    /// Start tracking the peak and RMS output levels over a window of
+    /// `window` samples (see [Voice::meter])
+    #[cfg(feature = "float")]
+    pub fn enable_meter(&mut self, window: u32) {
+        self.meter = Some(Meter::new(window));
+    }
+    /// Stop tracking the output levels
+    #[cfg(feature = "float")]
+    pub fn disable_meter(&mut self) {
+        self.meter = None;
+    }
+    /// The current output levels, or `None` if metering is not enabled
+    #[cfg(feature = "float")]
+    pub fn meter(&self) -> Option<MeterLevels> {
+        self.meter.as_ref().map(Meter::levels)
+    }
+    /// Enable or disable recording of the parameters after modulation is
+    /// applied (see [Voice::last_modulated]).  This is off by default, since
+    /// it requires copying the parameters on every sample.
+    #[cfg(feature = "float")]
+    pub fn set_record_modulation(&mut self, enabled: bool) {
+        if enabled != self.modulated.is_some() {
+            self.modulated = enabled.then(Default::default);
+        }
+    }
+    /// The value of the parameter targeted by `dest` for the last sample,
+    /// after modulation was applied, in the same units as the parameter
+    /// itself (e.g. a MIDI note number for [ModDest::FiltCutoff], or seconds
+    /// for an envelope time).  The tuning destinations return the total tuning
+    /// offset of the oscillator, in semitones.
+    ///
+    /// Returns `None` if recording is not enabled (see
+    /// [Voice::set_record_modulation]) or if `dest` targets one of the
+    /// modulation LFOs or envelopes rather than the voice itself (or a wave
+    /// morph that is not enabled).
+    #[cfg(feature = "float")]
+    pub fn last_modulated(&self, dest: ModDest) -> Option<f32> {
+        let value = self.modulated.as_ref()?.get(dest)?;
+        Some(T::mod_value_to_float(value))
+    }
+    /// Atomically replace the stored patch (the parameters and modulation
+    /// matrix used by [Voice::next_patch]) with copies of `params` and
+    /// `matrix`.  The new patch takes effect in full on the next sample, so
+    /// no parameter is ever processed alongside a stale matrix (or vice
+    /// versa).  Note that if depth smoothing is enabled (see
+    /// [Voice::set_depth_smoothing]), the new depths are still ramped to.
+    pub fn load_patch(&mut self, params: &VoiceParams<T>, matrix: &ModMatrix<T>) {
+        self.patch = Some((params.clone(), matrix.clone()));
+        self.patch_changed = true;
+    }
+    /// Retrieve a copy of the stored patch, or `None` if no patch has been
+    /// loaded (see [Voice::load_patch])
+    pub fn save_patch(&self) -> Option<(VoiceParams<T>, ModMatrix<T>)> {
+        self.patch.clone()
+    }
+    /// Get the next sample from this voice using the stored patch (see
+    /// [Voice::load_patch]), or the default parameters if no patch has been
+    /// loaded.  This is otherwise the same as [Voice::next].
+    pub fn next_patch(
+        &mut self,
+        ctx: &T::Context,
+        input: &VoiceInput<T>,
+        ch_input: &VoiceChannelInput<T>,
+    ) -> T::Sample {
+        let patch = self.patch.take();
+        let changed = core::mem::take(&mut self.patch_changed);
+        let out = match patch.as_ref() {
+            Some((params, matrix)) => {
+                let matrix = changed.then_some(matrix);
+                self.next(ctx, matrix, input, ch_input, params.clone())
+            }
+            None => self.next(ctx, None, input, ch_input, Default::default()),
+        };
+        self.patch = patch;
+        out
+    }
+    /// Reset the portamento, oscillators, filter, envelopes, and modulation
+    /// LFOs and envelopes to their initial state, as if the voice were newly
+    /// constructed with the same seed.  The settings of the voice (e.g.
+    /// [Voice::set_filt_env_mode]), the stored patch, and the modulation
+    /// matrix are unchanged.  This will cause a discontinuity in the output
+    /// if the voice is currently active.
+    pub fn reset(&mut self) {
+        self.glide.reset();
+        self.oscs = Default::default();
+        self.filt.reset();
+        self.env_amp.reset();
+        self.env_filt.reset();
+        self.modsection.reset();
+    }
+    /// Returns false once the amp envelope has been released and has decayed
+    /// to idle.  The output of the voice is then below -80dB relative to the
+    /// filter output, so it can be safely culled or reused by an allocator.
+    pub fn is_active(&self) -> bool {
+        !self.env_amp.is_idle()
+    }
+    /// The delay, in samples, between an input to this voice and the
+    /// corresponding output, for hosts that compensate for plugin latency.
+    ///
+    /// Every device in the voice is causal and processes one sample at a
+    /// time, so this is currently always zero.  Any future feature needing
+    /// lookahead or a block-based filter will report its delay here.
+    pub fn latency_samples(&self) -> usize {
+        0
+    }
     /// Get the next sample from this voice.
     ///
     /// If matrix is not `None`, this will update the internal modulation
@@ -145,15 +416,30 @@ impl<T: DspFormat> Voice<T> {
         ch_input: &VoiceChannelInput<T>,
         mut params: VoiceParams<T>,
     ) -> T::Sample {
+        if self.gate.next(input.gate) == Some(GateEdge::Rising) {
+            if params.note_sync {
+                self.reset();
+            }
+            if params.oscs_p.primary.key_sync {
+                self.oscs.reset_primary_phase();
+            }
+            if params.oscs_p.secondary.key_sync {
+                self.oscs.reset_secondary_phase();
+            }
+        }
         // Build the ModMatrix
         let modparams = modulation::ModSectionParams::<T> {
             velocity: input.velocity,
+            release_velocity: input.release_velocity,
+            pressure: input.pressure,
+            slide: input.slide,
             aftertouch: ch_input.aftertouch,
             modwheel: ch_input.modwheel,
             lfo1_params: params.lfo1_p,
             lfo2_params: params.lfo2_p,
             env1_params: params.env1_p,
             env2_params: params.env2_p,
+            global_lfo1: ch_input.global_lfo1,
         };
         let m = self.modsection.next(ctx, input.gate, modparams, matrix);
         // Modulate all the parameters
@@ -163,8 +449,22 @@ impl<T: DspFormat> Voice<T> {
         m.modulate_env(&mut params.filt_env_p, &modulation::ENV_FILT_MOD_DEST);
         m.modulate_env(&mut params.amp_env_p, &modulation::ENV_AMP_MOD_DEST);
         m.modulate_mod_filt(&mut params.filt_p);
+        m.modulate_scalar(&mut params.master_gain, modulation::ModDest::MasterGain);
+        #[cfg(feature = "float")]
+        if let Some(modulated) = self.modulated.as_mut() {
+            // The modulation LFO and envelope parameters have already been
+            // moved into the mod section, so only record the voice's own
+            modulated.oscs_p = params.oscs_p.clone();
+            modulated.ring_p = params.ring_p.clone();
+            modulated.filt_p = params.filt_p.clone();
+            modulated.filt_env_p = params.filt_env_p.clone();
+            modulated.amp_env_p = params.amp_env_p.clone();
+            modulated.master_gain = params.master_gain;
+        }
 
-        let oscs_out = self.oscs.next(ctx, input.note, params.oscs_p);
+        let glide_note = self.glide.next(ctx, input.note, params.glide_p);
+        let note = T::apply_note_offset(glide_note, input.bend);
+        let oscs_out = self.oscs.next(ctx, note, params.oscs_p);
 
         let ring_mod_out = self.ringmod.next(
             ctx,
@@ -175,18 +475,733 @@ impl<T: DspFormat> Voice<T> {
             params.ring_p,
         );
 
-        let filt_env_out = self.env_filt.next(ctx, input.gate, params.filt_env_p);
+        let filt_env_out = self.env_filt.next_with_note(
+            ctx,
+            input.gate,
+            note,
+            input.velocity,
+            input.release_velocity,
+            params.filt_env_p,
+        );
         let filt_out = self.filt.next(
             ctx,
             ModFiltInput {
                 signal: ring_mod_out,
                 env: filt_env_out,
-                kbd: input.note,
+                kbd: note,
                 vel: input.velocity,
             },
             params.filt_p,
         );
-        let vca_env_out = self.env_amp.next(ctx, input.gate, params.amp_env_p);
-        self.vca.next(ctx, filt_out, vca_env_out)
+        let vca_env_out = self.env_amp.next_with_note(
+            ctx,
+            input.gate,
+            note,
+            input.velocity,
+            input.release_velocity,
+            params.amp_env_p,
+        );
+        // Unity gain is not representable in fixed point, so bypass it there
+        let vca_gain = if params.master_gain == T::Scalar::one() {
+            vca_env_out
+        } else {
+            vca_env_out.scale(params.master_gain)
+        };
+        let vca_out = self.vca.next(ctx, filt_out, vca_gain);
+        let out = match params.sat_p {
+            Some(sat_p) => self.shaper.next(ctx, vca_out, sat_p),
+            None => vca_out,
+        };
+        #[cfg(feature = "float")]
+        if let Some(meter) = self.meter.as_mut() {
+            meter.next(T::sample_to_float(out));
+        }
+        out
+    }
+    /// Fill `out` with samples from this voice, modulated by `matrix`.
+    ///
+    /// Each sample takes the next item from each of `input`, `ch_input` and
+    /// `params`, exactly as if [Voice::next] had been called once per sample
+    /// (with `matrix` passed for the first sample only).  Returns the part of
+    /// `out` that was filled, which is shorter than `out` if any of the
+    /// iterators run out first.
+    pub fn process_block<'a, InputIt, ChInputIt, ParamIt>(
+        &mut self,
+        ctx: &T::Context,
+        matrix: &ModMatrix<T>,
+        input: InputIt,
+        ch_input: ChInputIt,
+        params: ParamIt,
+        out: &'a mut [T::Sample],
+    ) -> &'a [T::Sample]
+    where
+        InputIt: Iterator<Item = VoiceInput<T>>,
+        ChInputIt: Iterator<Item = VoiceChannelInput<T>>,
+        ParamIt: Iterator<Item = VoiceParams<T>>,
+    {
+        let mut matrix = Some(matrix);
+        let mut len = 0;
+        for (smp, ((input, ch_input), params)) in
+            out.iter_mut().zip(input.zip(ch_input).zip(params))
+        {
+            *smp = self.next(ctx, matrix.take(), &input, &ch_input, params);
+            len += 1;
+        }
+        &out[..len]
+    }
+}
+
+#[cfg(all(feature = "float", any(test, feature = "diagnostics")))]
+impl Voice<i16> {
+    /// Estimate the numerical error of the fixed point implementation for the
+    /// patch `params`, relative to the floating point implementation, to help
+    /// choose between the two.
+    ///
+    /// This renders 0.1s of a held middle C through a fixed point and a single
+    /// precision voice with the same seed and the default modulation matrix,
+    /// and returns the RMS difference of their outputs.  Patches with high
+    /// resonance amplify the rounding error of the fixed point filter, so
+    /// they will have a larger estimate.
+    ///
+    /// This is only available with the `diagnostics` feature.
+    pub fn fixed_float_error_estimate(params: &VoiceParams<i16>) -> f32 {
+        use crate::context::{Context, ContextFxP};
+        const SAMPLES: u16 = 4800;
+        let ctx_fxp = ContextFxP::new_480();
+        let ctx_flt = Context::new(48000f32);
+        let input = VoiceInput::<i16> {
+            note: crate::NoteFxP::lit("60"),
+            velocity: crate::ScalarFxP::MAX,
+            gate: true,
+            ..Default::default()
+        };
+        let input_flt = VoiceInput::<f32>::from(&input);
+        let params_flt = VoiceParams::<f32>::from(params);
+        let matrix = ModMatrix::<i16>::default();
+        let matrix_flt = ModMatrix::<f32>::from(&matrix);
+        let (mut fxp, mut flt) = (
+            Voice::<i16>::new_with_seed(0),
+            Voice::<f32>::new_with_seed(0),
+        );
+        let ch_input = Default::default();
+        let mut sum_sq = 0f32;
+        for i in 0..SAMPLES {
+            let (matrix, matrix_flt) = if i == 0 {
+                (Some(&matrix), Some(&matrix_flt))
+            } else {
+                (None, None)
+            };
+            let x = fxp.next(&ctx_fxp, matrix, &input, &ch_input, params.clone());
+            let y = flt.next(
+                &ctx_flt,
+                matrix_flt,
+                &input_flt,
+                &Default::default(),
+                params_flt.clone(),
+            );
+            let err = x.to_num::<f32>() - y;
+            sum_sq += err * err;
+        }
+        crate::util::sqrt_approx(sum_sq / SAMPLES as f32)
+    }
+}
+
+/// Accumulate `block` into the output bus `bus` of `buses` (see
+/// [VoiceParams::bus]), saturating on overflow.  Output routed to a bus that
+/// does not exist is dropped.
+pub fn mix_into_bus<T: DspFormat>(buses: &mut [&mut [T::Sample]], bus: u8, block: &[T::Sample]) {
+    if let Some(out) = buses.get_mut(bus as usize) {
+        for (o, smp) in out.iter_mut().zip(block) {
+            *o = o.dsp_saturating_add(*smp);
+        }
+    }
+}
+
+#[cfg(all(test, feature = "float"))]
+mod tests {
+    use super::*;
+    use crate::context::{Context, ContextFxP};
+    use crate::DspType;
+
+    fn active_after_release<T: DspFormat>(ctx: &T::Context) {
+        let mut voice = Voice::<T>::new();
+        assert!(!voice.is_active());
+        let mut input = VoiceInput::<T> {
+            gate: true,
+            ..Default::default()
+        };
+        let ch_input = Default::default();
+        let matrix = ModMatrix::default();
+        voice.next(ctx, Some(&matrix), &input, &ch_input, Default::default());
+        assert!(voice.is_active());
+        // Hold for 0.5s (well past the default 0.1s attack/decay)
+        for _ in 0..24000 {
+            voice.next(ctx, None, &input, &ch_input, Default::default());
+        }
+        assert!(voice.is_active());
+        input.gate = false;
+        voice.next(ctx, None, &input, &ch_input, Default::default());
+        assert!(voice.is_active());
+        // The default release is 0.1s, so this should be idle after 0.5s
+        for _ in 0..24000 {
+            voice.next(ctx, None, &input, &ch_input, Default::default());
+        }
+        assert!(!voice.is_active());
+    }
+
+    fn sample_hold_output(seed: u64) -> [f32; 4800] {
+        let ctx = Context::new(48000f32);
+        let mut params = VoiceParams::<f32>::default();
+        params.oscs_p.primary.saw = 1f32;
+        params.ring_p.mix_a = 1f32;
+        params.filt_p.cutoff = 127f32;
+        params.filt_p.low_mix = 1f32;
+        params.lfo1_p = LfoParams {
+            freq: 20f32,
+            depth: 1f32,
+            opts: LfoOptions::new(LfoWave::SampleHold, true, false),
+            phase_offset: 0f32,
+        };
+        let mut matrix = ModMatrix::<f32>::default();
+        matrix.rows[modulation::ModSrc::Lfo1 as usize].1[0] =
+            (modulation::ModDest::Osc1Course, 0.5f32);
+        let input = VoiceInput::<f32> {
+            note: 60f32,
+            velocity: 1f32,
+            release_velocity: 0f32,
+            gate: true,
+            ..Default::default()
+        };
+        let mut voice = Voice::<f32>::new_with_seed(seed);
+        let mut matrix = Some(&matrix);
+        let mut out = [0f32; 4800];
+        for smp in out.iter_mut() {
+            *smp = voice.next(
+                &ctx,
+                matrix.take(),
+                &input,
+                &Default::default(),
+                params.clone(),
+            );
+        }
+        out
+    }
+
+    /// The number of rising zero crossings in 0.1s of a saw wave voice
+    /// playing A3 with a per-note pitch bend of `bend` semitones
+    fn bent_crossings(bend: f32) -> usize {
+        let ctx = Context::new(48000f32);
+        let mut params = VoiceParams::<f32>::default();
+        params.oscs_p.primary.saw = 1f32;
+        params.ring_p.mix_a = 1f32;
+        params.filt_p.cutoff = 127f32;
+        params.filt_p.low_mix = 1f32;
+        let input = VoiceInput::<f32> {
+            note: 57f32,
+            gate: true,
+            bend,
+            ..Default::default()
+        };
+        let mut voice = Voice::<f32>::new_with_seed(0);
+        let mut matrix = Some(Default::default());
+        let mut last = 0f32;
+        let mut crossings = 0;
+        for _ in 0..4800 {
+            let out = voice.next(
+                &ctx,
+                matrix.take().as_ref(),
+                &input,
+                &Default::default(),
+                params.clone(),
+            );
+            if last < 0f32 && out >= 0f32 {
+                crossings += 1;
+            }
+            last = out;
+        }
+        crossings
+    }
+
+    #[test]
+    fn per_note_bend() {
+        // Each voice tracks its own bend: 220Hz, and an octave up
+        let (unbent, bent) = (bent_crossings(0f32), bent_crossings(12f32));
+        assert!((21..=23).contains(&unbent), "{unbent}");
+        assert!((43..=45).contains(&bent), "{bent}");
+    }
+
+    /// Route a 5Hz triangle LFO1 to the filter cutoff, returning the range of the
+    /// recorded cutoff and the number of times it crosses the base value
+    fn recorded_cutoff<T: DspFormat>(ctx: &T::Context, params: VoiceParams<T>) -> (f32, usize) {
+        let mut matrix = ModMatrix::<T>::default();
+        matrix.rows[modulation::ModSrc::Lfo1 as usize].1[0] =
+            (ModDest::FiltCutoff, T::IScalar::one().divide_by_two());
+        let input = VoiceInput::<T> {
+            gate: true,
+            ..Default::default()
+        };
+        let mut voice = Voice::<T>::new();
+        assert!(voice.last_modulated(ModDest::FiltCutoff).is_none());
+        voice.set_record_modulation(true);
+        let mut matrix = Some(&matrix);
+        let (mut min, mut max, mut crossings) = (f32::MAX, f32::MIN, 0);
+        let mut above = false;
+        for _ in 0..24000 {
+            voice.next(
+                ctx,
+                matrix.take(),
+                &input,
+                &Default::default(),
+                params.clone(),
+            );
+            let cutoff = voice.last_modulated(ModDest::FiltCutoff).unwrap();
+            (min, max) = (min.min(cutoff), max.max(cutoff));
+            if (cutoff > 64f32) != above {
+                above = !above;
+                crossings += 1;
+            }
+        }
+        assert!(voice.last_modulated(ModDest::Lfo1Rate).is_none());
+        (max - min, crossings)
+    }
+
+    #[test]
+    fn last_modulated_cutoff_oscillates() {
+        let mut params = VoiceParams::<f32>::default();
+        params.filt_p.cutoff = 64f32;
+        params.lfo1_p = LfoParams {
+            freq: 5f32,
+            depth: 1f32,
+            opts: LfoOptions::new(LfoWave::Triangle, true, false),
+            phase_offset: 0f32,
+        };
+        let ctx = Context::new(48000f32);
+        let (range, crossings) =
+            recorded_cutoff::<f32>(&ctx, (&VoiceParams::<i16>::default()).into());
+        assert!(range < 0.01 && crossings <= 1);
+        let (range, crossings) = recorded_cutoff::<f32>(&ctx, params);
+        assert!(range > 60f32, "{range}");
+        assert!((4..=6).contains(&crossings), "{crossings}");
+
+        let mut params = VoiceParams::<i16>::default();
+        params.filt_p.cutoff = crate::NoteFxP::lit("64");
+        params.lfo1_p = LfoParams {
+            freq: crate::LfoFreqFxP::lit("5"),
+            depth: crate::ScalarFxP::MAX,
+            opts: LfoOptions::new(LfoWave::Triangle, true, false),
+            phase_offset: crate::ScalarFxP::ZERO,
+        };
+        let (range, crossings) = recorded_cutoff::<i16>(&ContextFxP::new_480(), params);
+        assert!(range > 60f32, "{range}");
+        assert!((4..=6).contains(&crossings), "{crossings}");
+    }
+
+    /// Play a note with LFO1 modulating the oscillator shape through both
+    /// [Voice::process_block] and [Voice::next], checking the outputs match
+    fn block_matches_next<T: DspFormat>(ctx: &T::Context, mut params: VoiceParams<T>) {
+        params.oscs_p.primary.saw = T::Scalar::one();
+        params.oscs_p.primary.sq = T::Scalar::one();
+        params.ring_p.mix_a = T::Scalar::one();
+        params.filt_p.low_mix = T::Scalar::one();
+        let mut matrix = ModMatrix::<T>::default();
+        matrix.rows[modulation::ModSrc::Lfo1 as usize].1[0] =
+            (ModDest::Osc1Shape, T::IScalar::one().divide_by_two());
+        let gate = |i: usize| VoiceInput::<T> {
+            gate: i < 600,
+            ..Default::default()
+        };
+        let mut block_voice = Voice::<T>::new_with_seed(42);
+        let mut block = [T::Sample::zero(); 1000];
+        let filled = block_voice.process_block(
+            ctx,
+            &matrix,
+            (0..800).map(gate),
+            core::iter::repeat(Default::default()),
+            core::iter::repeat(params.clone()),
+            &mut block,
+        );
+        assert_eq!(filled.len(), 800);
+        let mut voice = Voice::<T>::new_with_seed(42);
+        let mut matrix = Some(&matrix);
+        for (i, smp) in filled.iter().enumerate() {
+            let out = voice.next(
+                ctx,
+                matrix.take(),
+                &gate(i),
+                &Default::default(),
+                params.clone(),
+            );
+            assert_eq!(T::sample_to_float(out), T::sample_to_float(*smp));
+        }
+        assert!(filled.iter().any(|smp| T::sample_to_float(*smp).abs() > 0.1));
+    }
+
+    #[test]
+    fn process_block_matches_next() {
+        let mut params = VoiceParams::<f32>::default();
+        params.filt_p.cutoff = 100f32;
+        params.lfo1_p.freq = 30f32;
+        params.lfo1_p.depth = 1f32;
+        block_matches_next::<f32>(&Context::new(48000f32), params);
+        let mut params = VoiceParams::<i16>::default();
+        params.filt_p.cutoff = crate::NoteFxP::lit("100");
+        params.lfo1_p.freq = crate::LfoFreqFxP::lit("30");
+        params.lfo1_p.depth = crate::ScalarFxP::MAX;
+        block_matches_next::<i16>(&ContextFxP::new_480(), params);
+    }
+
+    /// Play two voices (the second starting 7000 samples after the first)
+    /// with LFO1 routed to the filter cutoff, returning the largest difference
+    /// in the modulated cutoff once both are playing.  If `global`, LFO1 is
+    /// shared between the voices via [VoiceChannelInput::global_lfo1].
+    fn two_voice_cutoff_diff<T: DspFormat>(
+        ctx: &T::Context,
+        params: VoiceParams<T>,
+        global: bool,
+    ) -> f32 {
+        let mut matrix = ModMatrix::<T>::default();
+        matrix.rows[modulation::ModSrc::Lfo1 as usize].1[0] =
+            (ModDest::FiltCutoff, T::IScalar::one().divide_by_two());
+        let mut lfo = Lfo::<T>::default();
+        let mut voices = [Voice::<T>::new(), Voice::<T>::new()];
+        for voice in voices.iter_mut() {
+            voice.set_record_modulation(true);
+        }
+        let mut matrix = Some(&matrix);
+        let mut max_diff = 0f32;
+        for i in 0..24000 {
+            let ch_input = VoiceChannelInput::<T> {
+                global_lfo1: global.then(|| lfo.next(ctx, false, params.lfo1_p.clone())),
+                ..Default::default()
+            };
+            let matrix = matrix.take();
+            for (voice, start) in voices.iter_mut().zip([0, 7000]) {
+                let input = VoiceInput::<T> {
+                    gate: i >= start,
+                    ..Default::default()
+                };
+                voice.next(ctx, matrix, &input, &ch_input, params.clone());
+            }
+            if i >= 7000 {
+                let [a, b] = voices.each_ref().map(|v| v.last_modulated(ModDest::FiltCutoff));
+                max_diff = max_diff.max((a.unwrap() - b.unwrap()).abs());
+            }
+        }
+        max_diff
+    }
+
+    #[test]
+    fn global_lfo_modulates_in_phase() {
+        let mut params = VoiceParams::<f32>::default();
+        params.filt_p.cutoff = 64f32;
+        params.lfo1_p = LfoParams {
+            freq: 5f32,
+            depth: 1f32,
+            opts: LfoOptions::new(LfoWave::Triangle, true, true),
+            phase_offset: 0f32,
+        };
+        let ctx = Context::new(48000f32);
+        assert!(two_voice_cutoff_diff::<f32>(&ctx, params.clone(), false) > 10f32);
+        assert_eq!(two_voice_cutoff_diff::<f32>(&ctx, params, true), 0f32);
+
+        let mut params = VoiceParams::<i16>::default();
+        params.filt_p.cutoff = crate::NoteFxP::lit("64");
+        params.lfo1_p = LfoParams {
+            freq: crate::LfoFreqFxP::lit("5"),
+            depth: crate::ScalarFxP::MAX,
+            opts: LfoOptions::new(LfoWave::Triangle, true, true),
+            phase_offset: crate::ScalarFxP::ZERO,
+        };
+        let ctx = ContextFxP::new_480();
+        assert!(two_voice_cutoff_diff::<i16>(&ctx, params.clone(), false) > 10f32);
+        assert_eq!(two_voice_cutoff_diff::<i16>(&ctx, params, true), 0f32);
+    }
+
+    #[test]
+    fn voice_seeding_is_deterministic() {
+        assert_eq!(sample_hold_output(1234), sample_hold_output(1234));
+        assert_ne!(sample_hold_output(1234), sample_hold_output(5678));
+    }
+
+    #[test]
+    fn voice_inactive_after_release() {
+        active_after_release::<f32>(&Context::new(48000.0));
+        active_after_release::<i16>(&ContextFxP::new_480());
+    }
+
+    /// Render `block.len()` samples of a voice playing `note`
+    fn voice_block(note: f32, block: &mut [f32]) {
+        voice_block_gain(note, 1f32, block);
+    }
+
+    /// [voice_block] with the given master gain
+    fn voice_block_gain(note: f32, master_gain: f32, block: &mut [f32]) {
+        let mut params = VoiceParams::<f32> {
+            master_gain,
+            ..Default::default()
+        };
+        params.oscs_p.primary.saw = 1f32;
+        params.ring_p.mix_a = 1f32;
+        params.filt_p.low_mix = 1f32;
+        params.filt_p.cutoff = 127f32;
+        let input = VoiceInput::<f32> {
+            note,
+            gate: true,
+            ..Default::default()
+        };
+        Voice::<f32>::new_with_seed(1).process_block(
+            &Context::new(48000f32),
+            &Default::default(),
+            core::iter::repeat(input),
+            core::iter::repeat(Default::default()),
+            core::iter::repeat(params),
+            block,
+        );
+    }
+
+    #[test]
+    fn buses_accumulate_separately() {
+        let (mut low, mut high) = ([0f32; 480], [0f32; 480]);
+        voice_block(48f32, &mut low);
+        voice_block(72f32, &mut high);
+        let (mut dry, mut send) = ([0f32; 480], [0f32; 480]);
+        let mut buses = [&mut dry[..], &mut send[..]];
+        mix_into_bus::<f32>(&mut buses, 0, &low);
+        mix_into_bus::<f32>(&mut buses, 1, &high);
+        // There is no third bus, so this is dropped
+        mix_into_bus::<f32>(&mut buses, 2, &high);
+        assert_eq!(dry, low);
+        assert_eq!(send, high);
+        assert!(low.iter().any(|x| x.abs() > 0.1));
+        assert!(high.iter().any(|x| x.abs() > 0.1));
+        mix_into_bus::<f32>(&mut [&mut dry[..]], 0, &high);
+        for ((d, l), h) in dry.iter().zip(low).zip(high) {
+            assert_eq!(*d, l + h);
+        }
+    }
+
+    /// Load a patch with the given cutoff and LFO1 to cutoff depth, and
+    /// return the recorded cutoff after `n` samples with [Voice::next_patch]
+    fn patch_cutoff(voice: &mut Voice<f32>, cutoff: f32, depth: f32, n: usize) -> f32 {
+        let mut params = VoiceParams::<f32>::default();
+        params.filt_p.cutoff = cutoff;
+        params.lfo1_p.freq = 5f32;
+        params.lfo1_p.depth = 1f32;
+        let mut matrix = ModMatrix::<f32>::default();
+        matrix.rows[modulation::ModSrc::Lfo1 as usize].1[0] = (ModDest::FiltCutoff, depth);
+        voice.load_patch(&params, &matrix);
+        let input = VoiceInput::<f32> {
+            gate: true,
+            ..Default::default()
+        };
+        let ctx = Context::new(48000f32);
+        for _ in 0..n {
+            voice.next_patch(&ctx, &input, &Default::default());
+        }
+        voice.last_modulated(ModDest::FiltCutoff).unwrap()
+    }
+
+    #[test]
+    fn load_patch_replaces_all_params() {
+        let mut voice = Voice::<f32>::new_with_seed(3);
+        assert!(voice.save_patch().is_none());
+        voice.set_record_modulation(true);
+        voice.set_depth_smoothing(false);
+        let modulated = patch_cutoff(&mut voice, 40f32, 0.5, 2400);
+        assert!((modulated - 40f32).abs() > 1f32, "{modulated}");
+        // The new patch applies in full on the very next sample
+        let cutoff = patch_cutoff(&mut voice, 90f32, 0f32, 1);
+        assert_eq!(cutoff, 90f32);
+        let (params, matrix) = voice.save_patch().unwrap();
+        assert_eq!(params.filt_p.cutoff, 90f32);
+        let row = &matrix.rows[modulation::ModSrc::Lfo1 as usize];
+        assert_eq!(row.1[0].1, 0f32);
+    }
+
+    #[test]
+    fn master_gain_is_linear() {
+        assert_eq!(VoiceParams::<f32>::default().master_gain, 1f32);
+        assert_eq!(
+            VoiceParams::<i16>::default().master_gain,
+            crate::ScalarFxP::MAX
+        );
+        let mut full = [0f32; 480];
+        voice_block(60f32, &mut full);
+        assert!(full.iter().any(|x| x.abs() > 0.1));
+        for gain in [0.5f32, 0.25, 0.1] {
+            let mut scaled = [0f32; 480];
+            voice_block_gain(60f32, gain, &mut scaled);
+            for (s, f) in scaled.iter().zip(full) {
+                assert!((s - f * gain).abs() < 1e-5, "{gain}: {s} vs {f}");
+            }
+        }
+    }
+
+    /// Play the same note twice on a fixed point voice, with a short release
+    /// in between (so the voice is still active when it is retriggered), and
+    /// return the output of each note
+    fn repeated_notes(note_sync: bool) -> [[crate::SampleFxP; 2400]; 2] {
+        use crate::{IScalarFxP, LfoFreqFxP, NoteFxP, ScalarFxP};
+        let ctx = ContextFxP::new_480();
+        let mut params = VoiceParams::<i16>::default();
+        params.oscs_p.primary.saw = ScalarFxP::MAX;
+        params.ring_p.mix_a = ScalarFxP::MAX;
+        params.filt_p.cutoff = NoteFxP::lit("80");
+        params.filt_p.low_mix = ScalarFxP::MAX;
+        params.lfo1_p.freq = LfoFreqFxP::lit("7");
+        params.lfo1_p.depth = ScalarFxP::MAX;
+        params.lfo1_p.opts = LfoOptions::new(LfoWave::SampleGlide, true, false);
+        params.note_sync = note_sync;
+        let mut matrix = ModMatrix::<i16>::default();
+        matrix.rows[modulation::ModSrc::Lfo1 as usize].1[0] =
+            (ModDest::FiltCutoff, IScalarFxP::lit("0.25"));
+        let mut voice = Voice::<i16>::new_with_seed(5);
+        let mut matrix = Some(&matrix);
+        let mut play = |gate: bool, out: &mut [crate::SampleFxP]| {
+            let input = VoiceInput::<i16> {
+                note: NoteFxP::lit("60"),
+                gate,
+                velocity: ScalarFxP::MAX,
+                ..Default::default()
+            };
+            for smp in out.iter_mut() {
+                *smp = voice.next(
+                    &ctx,
+                    matrix.take(),
+                    &input,
+                    &Default::default(),
+                    params.clone(),
+                );
+            }
+        };
+        let mut notes = [[crate::SampleFxP::ZERO; 2400]; 2];
+        play(true, &mut notes[0]);
+        play(false, &mut [crate::SampleFxP::ZERO; 1000]);
+        play(true, &mut notes[1]);
+        notes
+    }
+
+    #[test]
+    fn note_sync_repeats_notes_exactly() {
+        let [first, second] = repeated_notes(true);
+        assert!(first.iter().any(|x| x.abs() > 0.1));
+        assert!(first == second);
+        // Without note sync, the oscillator and LFO phases carry over
+        let [first, second] = repeated_notes(false);
+        assert!(first != second);
+    }
+
+    #[test]
+    fn fixed_float_error_tracks_resonance() {
+        let mut sine = VoiceParams::<i16>::default();
+        sine.oscs_p.primary.sin = crate::ScalarFxP::MAX;
+        sine.ring_p.mix_a = crate::ScalarFxP::MAX;
+        sine.filt_p.cutoff = crate::NoteFxP::lit("127");
+        sine.filt_p.low_mix = crate::ScalarFxP::MAX;
+        let sine_err = Voice::fixed_float_error_estimate(&sine);
+        let mut resonant = sine.clone();
+        resonant.oscs_p.primary.sin = crate::ScalarFxP::ZERO;
+        resonant.oscs_p.primary.saw = crate::ScalarFxP::MAX;
+        resonant.filt_p.cutoff = crate::NoteFxP::lit("72");
+        resonant.filt_p.resonance = crate::ScalarFxP::MAX;
+        let resonant_err = Voice::fixed_float_error_estimate(&resonant);
+        assert!(sine_err < 0.02, "{sine_err}");
+        assert!(
+            resonant_err > 4f32 * sine_err,
+            "{resonant_err} vs {sine_err}"
+        );
+    }
+
+    /// Play a note on a fixed point voice for `held` samples, then after it
+    /// has been released and decayed, return the attack of a second note
+    fn second_attack(key_sync: bool, held: usize) -> [crate::SampleFxP; 480] {
+        use crate::{NoteFxP, ScalarFxP};
+        let ctx = ContextFxP::new_480();
+        let mut params = VoiceParams::<i16>::default();
+        params.oscs_p.primary.saw = ScalarFxP::MAX;
+        params.oscs_p.primary.key_sync = key_sync;
+        params.ring_p.mix_a = ScalarFxP::MAX;
+        params.filt_p.cutoff = NoteFxP::lit("127");
+        params.filt_p.low_mix = ScalarFxP::MAX;
+        let mut voice = Voice::<i16>::new_with_seed(0);
+        let mut matrix = Some(ModMatrix::<i16>::default());
+        let mut play = |gate: bool, out: &mut [crate::SampleFxP]| {
+            let input = VoiceInput::<i16> {
+                note: NoteFxP::lit("57"),
+                gate,
+                velocity: ScalarFxP::MAX,
+                ..Default::default()
+            };
+            for smp in out.iter_mut() {
+                *smp = voice.next(
+                    &ctx,
+                    matrix.take().as_ref(),
+                    &input,
+                    &Default::default(),
+                    params.clone(),
+                );
+            }
+        };
+        play(true, &mut [crate::SampleFxP::ZERO; 1000][..held]);
+        play(false, &mut [crate::SampleFxP::ZERO; 24000]);
+        let mut attack = [crate::SampleFxP::ZERO; 480];
+        play(true, &mut attack);
+        attack
+    }
+
+    /// The largest difference between two attacks from [second_attack]
+    fn attack_diff(key_sync: bool) -> f32 {
+        let first = second_attack(key_sync, 900);
+        let second = second_attack(key_sync, 937);
+        assert!(first.iter().any(|x| x.abs() > 0.1));
+        let diffs = first.iter().zip(second.iter()).map(|(a, b)| a.dist(*b));
+        diffs.max().unwrap().to_num()
+    }
+
+    #[test]
+    fn key_sync_repeats_attacks() {
+        // Allow for rounding noise left in the filter by the first note
+        let synced = attack_diff(true);
+        assert!(synced < 0.001, "{synced}");
+        // A free running oscillator starts each note at a different phase
+        let free = attack_diff(false);
+        assert!(free > 0.1, "{free}");
+    }
+
+    #[test]
+    fn default_voice_has_no_latency() {
+        let mut voice = Voice::<f32>::new_with_seed(0);
+        assert_eq!(voice.latency_samples(), 0);
+        voice.set_depth_smoothing(true);
+        voice.set_record_modulation(true);
+        assert_eq!(voice.latency_samples(), 0);
+    }
+
+    #[test]
+    fn params_debug_format() {
+        extern crate std;
+        use std::format;
+        let mut params = VoiceParams::<i16>::default();
+        params.filt_p.cutoff = crate::NoteFxP::lit("81.5");
+        params.filt_p.env_mod = crate::ScalarFxP::lit("0.25");
+        params.lfo1_p.opts = LfoOptions::new(LfoWave::Triangle, false, true);
+        let debug = format!("{params:?}");
+        // Fixed point values print as numbers, not as raw bits
+        assert!(debug.contains("cutoff: 81.5"), "{debug}");
+        assert!(debug.contains("env_mod: 0.25"), "{debug}");
+        assert!(debug.contains("wave: Some(Triangle)"), "{debug}");
+        for field in [
+            "oscs_p",
+            "ring_p",
+            "amp_env_p",
+            "sat_p",
+            "master_gain",
+            "note_sync",
+        ] {
+            assert!(debug.contains(field), "{field}");
+        }
+        let float = VoiceParams::<f32>::from(&params);
+        assert!(format!("{float:?}").contains("cutoff: 81.5"));
+        assert_eq!(VoiceParams::<f32>::from(&params), float);
     }
 }
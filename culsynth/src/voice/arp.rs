@@ -0,0 +1,305 @@
+//! This module contains an arpeggiator, which converts a set of held notes
+//! into a rhythmic sequence of note on/off events.
+
+use arrayvec::ArrayVec;
+use rand::{rngs::SmallRng, RngCore, SeedableRng};
+
+use crate::context::GetContext;
+use crate::ScalarFxP;
+
+/// The maximum number of notes that may be held in an [Arpeggiator] at once.
+/// Any notes beyond this are silently ignored.
+pub const ARP_MAX_NOTES: usize = 16;
+
+/// Default random seed to use if not provided a seed
+const RANDOM_SEED: u64 = 0x5a3c9e17d2b40f61u64; //random 64 bit integer
+
+/// The order in which an [Arpeggiator] steps through the held notes
+#[derive(Default, Clone, Copy, PartialEq, Eq, Debug)]
+#[repr(u8)]
+pub enum ArpPattern {
+    /// Lowest to highest
+    #[default]
+    Up,
+    /// Highest to lowest
+    Down,
+    /// Lowest to highest and back down, without repeating the end notes
+    UpDown,
+    /// A random held note on every step
+    Random,
+}
+
+impl ArpPattern {
+    const ELEM: [ArpPattern; 4] = [Self::Up, Self::Down, Self::UpDown, Self::Random];
+    /// Returns a slice to all of the possible patterns
+    pub const fn patterns() -> &'static [ArpPattern] {
+        &Self::ELEM
+    }
+    /// Provides the name of the pattern
+    pub const fn to_str(&self) -> &'static str {
+        ["Up", "Down", "Up/Down", "Random"][*self as usize]
+    }
+}
+
+impl TryFrom<u8> for ArpPattern {
+    type Error = &'static str;
+    fn try_from(value: u8) -> Result<Self, &'static str> {
+        Self::ELEM
+            .get(value as usize)
+            .copied()
+            .ok_or("Conversion of u8 to ArpPattern Overflowed")
+    }
+}
+
+/// Parameters for an [Arpeggiator]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ArpParams {
+    /// The order in which to play the held notes
+    pub pattern: ArpPattern,
+    /// The number of octaves to span (1 plays only the held notes).  A value
+    /// of zero is treated as one.  Notes transposed above the MIDI range are
+    /// clamped to 127.
+    pub octaves: u8,
+    /// The tempo, in beats per minute
+    pub tempo: u16,
+    /// The number of arpeggiator steps per beat (e.g. 4 for 16th notes)
+    pub steps_per_beat: u8,
+    /// The gate length, as a fraction of the step length
+    pub gate: ScalarFxP,
+}
+
+impl Default for ArpParams {
+    fn default() -> Self {
+        Self {
+            pattern: ArpPattern::Up,
+            octaves: 1,
+            tempo: 120,
+            steps_per_beat: 4,
+            gate: ScalarFxP::lit("0.5"),
+        }
+    }
+}
+
+/// An event emitted by an [Arpeggiator]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ArpEvent {
+    /// Start playing the given MIDI note number
+    NoteOn(u8),
+    /// Stop playing the given MIDI note number
+    NoteOff(u8),
+}
+
+/// An arpeggiator
+///
+/// Notes are added and removed with [Arpeggiator::note_on] and
+/// [Arpeggiator::note_off], and [Arpeggiator::next] is then called once per
+/// sample to generate note events.  The step clock is derived from the sample
+/// rate of the processing context and the tempo in [ArpParams].
+///
+/// This does not allocate - held notes are stored in a fixed-capacity set of
+/// up to [ARP_MAX_NOTES] notes.
+#[derive(Clone)]
+pub struct Arpeggiator {
+    rng: SmallRng,
+    held: ArrayVec<u8, ARP_MAX_NOTES>,
+    playing: Option<u8>,
+    step: usize,
+    counter: u32,
+    step_len: u32,
+    gate_len: u32,
+}
+
+impl Arpeggiator {
+    /// Constructor, seeding the RNG (for [ArpPattern::Random]) with `seed`
+    pub fn new(seed: u64) -> Self {
+        Self {
+            rng: SmallRng::seed_from_u64(seed),
+            held: ArrayVec::new(),
+            playing: None,
+            step: 0,
+            counter: 0,
+            step_len: 0,
+            gate_len: 0,
+        }
+    }
+    /// Add a note to the set of held notes
+    pub fn note_on(&mut self, note: u8) {
+        if let Err(idx) = self.held.binary_search(&note) {
+            let _ = self.held.try_insert(idx, note);
+        }
+    }
+    /// Remove a note from the set of held notes
+    pub fn note_off(&mut self, note: u8) {
+        if let Ok(idx) = self.held.binary_search(&note) {
+            self.held.remove(idx);
+        }
+    }
+    /// Remove all held notes.  The currently playing note (if any) will be
+    /// released on the next call to [Arpeggiator::next].
+    pub fn clear(&mut self) {
+        self.held.clear();
+    }
+    /// The currently held notes, in ascending order
+    pub fn held_notes(&self) -> &[u8] {
+        &self.held
+    }
+    fn sequence_len(&self, params: &ArpParams) -> usize {
+        let len = self.held.len() * core::cmp::max(params.octaves, 1) as usize;
+        if params.pattern == ArpPattern::UpDown && len > 1 {
+            2 * len - 2
+        } else {
+            len
+        }
+    }
+    fn note_at(&self, idx: usize) -> u8 {
+        let octave = (idx / self.held.len()) as u8;
+        let note = self.held[idx % self.held.len()].saturating_add(octave.saturating_mul(12));
+        core::cmp::min(note, 127)
+    }
+    fn next_note(&mut self, params: &ArpParams) -> u8 {
+        let len = self.held.len() * core::cmp::max(params.octaves, 1) as usize;
+        let seq_len = self.sequence_len(params);
+        self.step %= seq_len;
+        let idx = match params.pattern {
+            ArpPattern::Up => self.step,
+            ArpPattern::Down => len - 1 - self.step,
+            ArpPattern::UpDown => {
+                if self.step < len {
+                    self.step
+                } else {
+                    seq_len - self.step
+                }
+            }
+            ArpPattern::Random => self.rng.next_u32() as usize % len,
+        };
+        self.step = (self.step + 1) % seq_len;
+        self.note_at(idx)
+    }
+    /// Advance the arpeggiator by one sample, returning a note event if one
+    /// should occur at this sample.
+    pub fn next<C: GetContext>(&mut self, context: &C, params: &ArpParams) -> Option<ArpEvent> {
+        if self.held.is_empty() {
+            self.step = 0;
+            self.counter = 0;
+            return self.playing.take().map(ArpEvent::NoteOff);
+        }
+        if self.counter == 0 {
+            let sr = context.get_context().sample_rate();
+            let steps_per_min = core::cmp::max(params.tempo as u32, 1)
+                * core::cmp::max(params.steps_per_beat as u32, 1);
+            self.step_len = core::cmp::max((sr * 60) / steps_per_min, 2);
+            let gate = (self.step_len as u64 * params.gate.to_bits() as u64) >> 16;
+            self.gate_len = (gate as u32).clamp(1, self.step_len - 1);
+            if let Some(note) = self.playing.take() {
+                // Release the last note first, then start this step on the
+                // next sample
+                return Some(ArpEvent::NoteOff(note));
+            }
+            let note = self.next_note(params);
+            self.playing = Some(note);
+            self.counter = 1;
+            return Some(ArpEvent::NoteOn(note));
+        }
+        let mut ret = None;
+        if self.counter == self.gate_len {
+            ret = self.playing.take().map(ArpEvent::NoteOff);
+        }
+        self.counter += 1;
+        if self.counter >= self.step_len {
+            self.counter = 0;
+        }
+        ret
+    }
+}
+
+impl Default for Arpeggiator {
+    fn default() -> Self {
+        Self::new(RANDOM_SEED)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::ContextFxP;
+
+    fn note_ons(pattern: ArpPattern, steps: usize) -> ArrayVec<u8, 32> {
+        let ctx = ContextFxP::new_480();
+        let params = ArpParams {
+            pattern,
+            ..Default::default()
+        };
+        let mut arp = Arpeggiator::default();
+        for note in [64, 60, 67] {
+            arp.note_on(note);
+        }
+        let mut ret = ArrayVec::new();
+        while ret.len() < steps {
+            if let Some(ArpEvent::NoteOn(n)) = arp.next(&ctx, &params) {
+                ret.push(n);
+            }
+        }
+        ret
+    }
+
+    #[test]
+    fn arp_up() {
+        assert_eq!(
+            note_ons(ArpPattern::Up, 6).as_slice(),
+            &[60, 64, 67, 60, 64, 67]
+        );
+    }
+    #[test]
+    fn arp_down() {
+        assert_eq!(
+            note_ons(ArpPattern::Down, 6).as_slice(),
+            &[67, 64, 60, 67, 64, 60]
+        );
+    }
+    #[test]
+    fn arp_updown() {
+        assert_eq!(
+            note_ons(ArpPattern::UpDown, 6).as_slice(),
+            &[60, 64, 67, 64, 60, 64]
+        );
+    }
+    #[test]
+    fn arp_random() {
+        let notes = note_ons(ArpPattern::Random, 16);
+        assert!(notes.iter().all(|n| [60, 64, 67].contains(n)));
+    }
+    #[test]
+    fn arp_octaves_and_release() {
+        let ctx = ContextFxP::new_480();
+        let params = ArpParams {
+            octaves: 2,
+            ..Default::default()
+        };
+        let mut arp = Arpeggiator::default();
+        arp.note_on(60);
+        let mut ons = ArrayVec::<u8, 2>::new();
+        while !ons.is_full() {
+            if let Some(ArpEvent::NoteOn(n)) = arp.next(&ctx, &params) {
+                ons.push(n);
+            }
+        }
+        assert_eq!(ons.as_slice(), &[60, 72]);
+        arp.note_off(60);
+        assert_eq!(arp.next(&ctx, &params), Some(ArpEvent::NoteOff(72)));
+        assert_eq!(arp.next(&ctx, &params), None);
+
+        // Octaves above the MIDI range are clamped to the highest note
+        let params = ArpParams {
+            octaves: 4,
+            ..Default::default()
+        };
+        arp.note_on(120);
+        let mut ons = ArrayVec::<u8, 4>::new();
+        while !ons.is_full() {
+            if let Some(ArpEvent::NoteOn(n)) = arp.next(&ctx, &params) {
+                ons.push(n);
+            }
+        }
+        assert_eq!(ons.as_slice(), &[120, 127, 127, 127]);
+    }
+}
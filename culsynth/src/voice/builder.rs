@@ -0,0 +1,348 @@
+//! This module contains a builder for [VoiceParams], allowing parameters to
+//! be set in human-friendly units (percent, Hz, seconds, semitones) rather
+//! than by constructing the fixed point fields directly.
+
+use super::VoiceParams;
+use crate::devices::{
+    EnvParams, FilterPoles, FilterType, GlideMode, LfoOptions, LfoParams, MixOscParams,
+    OscSyncMode, WaveshaperParams,
+};
+use crate::fixedmath::{log2_fixed, U16F16};
+use crate::{EnvParamFxP, LfoFreqFxP, NoteFxP, ScalarFxP, SignedNoteFxP};
+
+/// Convert a percentage (0 to 100) to a [ScalarFxP], saturating at the ends
+/// of the range (so 100% is [ScalarFxP::MAX])
+fn percent(value: f32) -> ScalarFxP {
+    ScalarFxP::saturating_from_num(value / 100f32)
+}
+
+/// Convert a frequency in Hz to a MIDI note number, saturating to the range
+/// of a [NoteFxP]
+fn hz_to_note(hz: f32) -> NoteFxP {
+    let octaves = log2_fixed(U16F16::saturating_from_num(hz / 440f32));
+    NoteFxP::saturating_from_num(69f32 + 12f32 * octaves.to_num::<f32>())
+}
+
+/// Convert a time in seconds to an [EnvParamFxP], saturating to its range
+fn seconds(value: f32) -> EnvParamFxP {
+    EnvParamFxP::saturating_from_num(value)
+}
+
+/// A builder for a [`VoiceParams<i16>`], converting parameters from human
+/// units into their fixed point representations.  Any parameter that is not
+/// set keeps its default value.
+///
+/// ```
+/// use culsynth::voice::VoiceParams;
+///
+/// let params = VoiceParams::builder()
+///     .osc1_levels_percent(0.0, 0.0, 0.0, 100.0)
+///     .osc1_shape_percent(50.0)
+///     .filter_cutoff_hz(1000.0)
+///     .filter_resonance_percent(25.0)
+///     .amp_env_adsr(0.01, 0.2, 80.0, 0.5)
+///     .build();
+/// assert_eq!(params.oscs_p.primary.shape, culsynth::ScalarFxP::lit("0.5"));
+/// ```
+#[derive(Clone, Default)]
+pub struct VoiceParamsBuilder {
+    params: VoiceParams<i16>,
+}
+
+impl VoiceParamsBuilder {
+    /// Constructor, starting from the default parameters
+    pub fn new() -> Self {
+        Default::default()
+    }
+    /// Finish building, returning the parameters
+    pub fn build(self) -> VoiceParams<i16> {
+        self.params
+    }
+    fn osc(mut self, secondary: bool, f: impl FnOnce(&mut MixOscParams<i16>)) -> Self {
+        f(if secondary {
+            &mut self.params.oscs_p.secondary
+        } else {
+            &mut self.params.oscs_p.primary
+        });
+        self
+    }
+    /// Set the tuning of oscillator 1, in semitones
+    pub fn osc1_tune_semitones(self, tune: f32) -> Self {
+        self.osc(false, |p| p.tune = SignedNoteFxP::saturating_from_num(tune))
+    }
+    /// Set the tuning of oscillator 2, in semitones
+    pub fn osc2_tune_semitones(self, tune: f32) -> Self {
+        self.osc(true, |p| p.tune = SignedNoteFxP::saturating_from_num(tune))
+    }
+    /// Set the shape of oscillator 1, in percent
+    pub fn osc1_shape_percent(self, shape: f32) -> Self {
+        self.osc(false, |p| p.shape = percent(shape))
+    }
+    /// Set the shape of oscillator 2, in percent
+    pub fn osc2_shape_percent(self, shape: f32) -> Self {
+        self.osc(true, |p| p.shape = percent(shape))
+    }
+    /// Set the sine, square, triangle and saw levels of oscillator 1, in
+    /// percent
+    pub fn osc1_levels_percent(self, sin: f32, sq: f32, tri: f32, saw: f32) -> Self {
+        self.osc(false, |p| {
+            (p.sin, p.sq, p.tri, p.saw) = (percent(sin), percent(sq), percent(tri), percent(saw))
+        })
+    }
+    /// Set the sine, square, triangle and saw levels of oscillator 2, in
+    /// percent
+    pub fn osc2_levels_percent(self, sin: f32, sq: f32, tri: f32, saw: f32) -> Self {
+        self.osc(true, |p| {
+            (p.sin, p.sq, p.tri, p.saw) = (percent(sin), percent(sq), percent(tri), percent(saw))
+        })
+    }
+    /// Set the overall output level of oscillator 1, in percent
+    pub fn osc1_level_percent(self, level: f32) -> Self {
+        self.osc(false, |p| p.osc_level = percent(level))
+    }
+    /// Set the overall output level of oscillator 2, in percent
+    pub fn osc2_level_percent(self, level: f32) -> Self {
+        self.osc(true, |p| p.osc_level = percent(level))
+    }
+    /// Enable the wave morph for oscillator 1, setting its position in
+    /// percent (see [MixOscParams::wave_morph])
+    pub fn osc1_wave_morph_percent(self, position: f32) -> Self {
+        self.osc(false, |p| p.wave_morph = Some(percent(position)))
+    }
+    /// Enable the wave morph for oscillator 2, setting its position in
+    /// percent (see [MixOscParams::wave_morph])
+    pub fn osc2_wave_morph_percent(self, position: f32) -> Self {
+        self.osc(true, |p| p.wave_morph = Some(percent(position)))
+    }
+    /// Enable or disable resetting the phase of oscillator 1 on each note on
+    /// (see [MixOscParams::key_sync])
+    pub fn osc1_key_sync(self, sync: bool) -> Self {
+        self.osc(false, |p| p.key_sync = sync)
+    }
+    /// Enable or disable resetting the phase of oscillator 2 on each note on
+    /// (see [MixOscParams::key_sync])
+    pub fn osc2_key_sync(self, sync: bool) -> Self {
+        self.osc(true, |p| p.key_sync = sync)
+    }
+    /// Set how oscillator 2 is synced to oscillator 1 (see [OscSyncMode])
+    pub fn osc_sync(mut self, sync: OscSyncMode) -> Self {
+        self.params.oscs_p.sync = sync;
+        self
+    }
+    /// Set the FM index (oscillator 2 modulating oscillator 1), in percent
+    pub fn fm_index_percent(mut self, index: f32) -> Self {
+        self.params.oscs_p.fm_index = percent(index);
+        self
+    }
+    /// Set the levels of oscillator 1, oscillator 2, and the ring modulated
+    /// signal in the ring modulator's output, in percent
+    pub fn ring_mix_percent(mut self, a: f32, b: f32, ring: f32) -> Self {
+        self.params.ring_p.mix_a = percent(a);
+        self.params.ring_p.mix_b = percent(b);
+        self.params.ring_p.mix_mod = percent(ring);
+        self
+    }
+    /// Set the filter cutoff frequency, in Hz
+    pub fn filter_cutoff_hz(mut self, cutoff: f32) -> Self {
+        self.params.filt_p.cutoff = hz_to_note(cutoff);
+        self
+    }
+    /// Set the filter resonance, in percent
+    pub fn filter_resonance_percent(mut self, resonance: f32) -> Self {
+        self.params.filt_p.resonance = percent(resonance);
+        self
+    }
+    /// Set the amount of filter envelope, velocity, and keyboard tracking
+    /// modulation of the filter cutoff, in percent
+    pub fn filter_mod_percent(mut self, env: f32, vel: f32, kbd: f32) -> Self {
+        self.params.filt_p.env_mod = percent(env);
+        self.params.filt_p.vel_mod = percent(vel);
+        self.params.filt_p.kbd_tracking = percent(kbd);
+        self
+    }
+    /// Set how much the filter envelope modulation is scaled by the note on
+    /// velocity, in percent (see
+    /// [ModFiltParams::env_vel](crate::devices::ModFiltParams::env_vel))
+    pub fn filter_env_velocity_percent(mut self, env_vel: f32) -> Self {
+        self.params.filt_p.env_vel = percent(env_vel);
+        self
+    }
+    /// Set the levels of the low, band and high pass filter outputs, in
+    /// percent
+    pub fn filter_mix_percent(mut self, low: f32, band: f32, high: f32) -> Self {
+        self.params.filt_p.low_mix = percent(low);
+        self.params.filt_p.band_mix = percent(band);
+        self.params.filt_p.high_mix = percent(high);
+        self
+    }
+    /// Set the filter input drive, in percent (see
+    /// [ModFiltParams::drive](crate::devices::ModFiltParams::drive))
+    pub fn filter_drive_percent(mut self, drive: f32) -> Self {
+        self.params.filt_p.drive = percent(drive);
+        self
+    }
+    /// Set the number of filter poles, i.e. the slope (see [FilterPoles])
+    pub fn filter_poles(mut self, poles: FilterPoles) -> Self {
+        self.params.filt_p.poles = poles;
+        self
+    }
+    /// Select a single filter output (see [FilterType])
+    pub fn filter_type(mut self, filter_type: FilterType) -> Self {
+        self.params.filt_p.set_filter_type(filter_type);
+        self
+    }
+    fn adsr(env: &mut EnvParams<i16>, attack: f32, decay: f32, sustain: f32, release: f32) {
+        env.attack = seconds(attack);
+        env.decay = seconds(decay);
+        env.sustain = percent(sustain);
+        env.release = seconds(release);
+    }
+    /// Set the amp envelope attack, decay and release times (in seconds) and
+    /// sustain level (in percent)
+    pub fn amp_env_adsr(mut self, attack: f32, decay: f32, sustain: f32, release: f32) -> Self {
+        Self::adsr(&mut self.params.amp_env_p, attack, decay, sustain, release);
+        self
+    }
+    /// Set the filter envelope attack, decay and release times (in seconds)
+    /// and sustain level (in percent)
+    pub fn filter_env_adsr(mut self, attack: f32, decay: f32, sustain: f32, release: f32) -> Self {
+        Self::adsr(&mut self.params.filt_env_p, attack, decay, sustain, release);
+        self
+    }
+    /// Set the modulation envelope 1 attack, decay and release times (in
+    /// seconds) and sustain level (in percent)
+    pub fn env1_adsr(mut self, attack: f32, decay: f32, sustain: f32, release: f32) -> Self {
+        Self::adsr(&mut self.params.env1_p, attack, decay, sustain, release);
+        self
+    }
+    /// Set the modulation envelope 2 attack, decay and release times (in
+    /// seconds) and sustain level (in percent)
+    pub fn env2_adsr(mut self, attack: f32, decay: f32, sustain: f32, release: f32) -> Self {
+        Self::adsr(&mut self.params.env2_p, attack, decay, sustain, release);
+        self
+    }
+    fn lfo(lfo: &mut LfoParams<i16>, freq: f32, depth: f32, opts: LfoOptions) {
+        lfo.freq = LfoFreqFxP::saturating_from_num(freq);
+        lfo.depth = percent(depth);
+        lfo.opts = opts;
+    }
+    /// Set the LFO1 frequency (in Hz), depth (in percent), and options
+    pub fn lfo1(mut self, freq: f32, depth: f32, opts: LfoOptions) -> Self {
+        Self::lfo(&mut self.params.lfo1_p, freq, depth, opts);
+        self
+    }
+    /// Set the LFO2 frequency (in Hz), depth (in percent), and options
+    pub fn lfo2(mut self, freq: f32, depth: f32, opts: LfoOptions) -> Self {
+        Self::lfo(&mut self.params.lfo2_p, freq, depth, opts);
+        self
+    }
+    /// Set the portamento time (in seconds) and mode (see [GlideMode]).  A
+    /// time of zero disables portamento.
+    pub fn glide(mut self, time: f32, mode: GlideMode) -> Self {
+        self.params.glide_p.time = seconds(time);
+        self.params.glide_p.mode = mode;
+        self
+    }
+    /// Set the output soft-saturation parameters, or `None` to disable it
+    pub fn saturation(mut self, sat: Option<WaveshaperParams<i16>>) -> Self {
+        self.params.sat_p = sat;
+        self
+    }
+    /// Set the master gain, in decibels (0dB, the default, is unity)
+    pub fn master_gain_db(mut self, db: f32) -> Self {
+        self.params.master_gain = crate::util::db_to_scalar(db);
+        self
+    }
+    /// Enable or disable resetting the voice on each note on (see
+    /// [VoiceParams::note_sync])
+    pub fn note_sync(mut self, sync: bool) -> Self {
+        self.params.note_sync = sync;
+        self
+    }
+}
+
+impl VoiceParams<i16> {
+    /// Create a [VoiceParamsBuilder], to set parameters in human units
+    pub fn builder() -> VoiceParamsBuilder {
+        VoiceParamsBuilder::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::devices::LfoWave;
+
+    #[test]
+    fn builder_matches_manual() {
+        let opts = LfoOptions::new(LfoWave::Triangle, true, false);
+        let built = VoiceParams::builder()
+            .osc1_shape_percent(50.0)
+            .osc2_tune_semitones(-12.0)
+            .osc2_levels_percent(0.0, 25.0, 0.0, 100.0)
+            .filter_cutoff_hz(880.0)
+            .filter_resonance_percent(75.0)
+            .amp_env_adsr(0.5, 1.25, 50.0, 2.0)
+            .lfo1(2.5, 100.0, opts)
+            .build();
+        let mut manual = VoiceParams::<i16>::default();
+        manual.oscs_p.primary.shape = ScalarFxP::lit("0.5");
+        manual.oscs_p.secondary.tune = SignedNoteFxP::lit("-12");
+        manual.oscs_p.secondary.sq = ScalarFxP::lit("0.25");
+        manual.oscs_p.secondary.saw = ScalarFxP::MAX;
+        manual.filt_p.cutoff = NoteFxP::lit("81");
+        manual.filt_p.resonance = ScalarFxP::lit("0.75");
+        manual.amp_env_p.attack = EnvParamFxP::lit("0.5");
+        manual.amp_env_p.decay = EnvParamFxP::lit("1.25");
+        manual.amp_env_p.sustain = ScalarFxP::lit("0.5");
+        manual.amp_env_p.release = EnvParamFxP::lit("2");
+        manual.lfo1_p.freq = LfoFreqFxP::lit("2.5");
+        manual.lfo1_p.depth = ScalarFxP::MAX;
+        manual.lfo1_p.opts = opts;
+
+        let (b, m) = (&built.oscs_p, &manual.oscs_p);
+        assert_eq!(b.primary.shape.to_bits(), m.primary.shape.to_bits());
+        assert_eq!(b.secondary.tune.to_bits(), m.secondary.tune.to_bits());
+        assert_eq!(b.secondary.sin.to_bits(), m.secondary.sin.to_bits());
+        assert_eq!(b.secondary.sq.to_bits(), m.secondary.sq.to_bits());
+        assert_eq!(b.secondary.saw.to_bits(), m.secondary.saw.to_bits());
+        assert_eq!(
+            built.filt_p.cutoff.to_bits(),
+            manual.filt_p.cutoff.to_bits()
+        );
+        assert_eq!(
+            built.filt_p.resonance.to_bits(),
+            manual.filt_p.resonance.to_bits()
+        );
+        let (b, m) = (&built.amp_env_p, &manual.amp_env_p);
+        assert_eq!(b.attack.to_bits(), m.attack.to_bits());
+        assert_eq!(b.decay.to_bits(), m.decay.to_bits());
+        assert_eq!(b.sustain.to_bits(), m.sustain.to_bits());
+        assert_eq!(b.release.to_bits(), m.release.to_bits());
+        let (b, m) = (&built.lfo1_p, &manual.lfo1_p);
+        assert_eq!(b.freq.to_bits(), m.freq.to_bits());
+        assert_eq!(b.depth.to_bits(), m.depth.to_bits());
+        assert_eq!(
+            b.opts.wave().map(|w| w as u8),
+            m.opts.wave().map(|w| w as u8)
+        );
+        // Untouched parameters keep their defaults
+        let default = VoiceParams::<i16>::default();
+        assert_eq!(
+            built.filt_env_p.attack.to_bits(),
+            default.filt_env_p.attack.to_bits()
+        );
+    }
+
+    #[test]
+    fn cutoff_hz_round_trips() {
+        for hz in [55f32, 261.63, 1000.0, 4186.0] {
+            let note = hz_to_note(hz);
+            let back = crate::fixedmath::midi_note_to_frequency(note).to_num::<f32>();
+            assert!(
+                (back / hz - 1f32).abs() < 0.01,
+                "{hz}Hz -> {note} -> {back}Hz"
+            );
+        }
+    }
+}
@@ -7,9 +7,11 @@
 //! Bank 1:
 //!  - Knobs: Filter Params (in order) + Osc 1 Shape.
 //!  - Faders: VCF Envelope A/D/S/R + Osc 1 Sin/Sq/Tri/Saw/Vol
+//!
 //! Bank 2:
 //!  - Knobs: Ringmod Mix + Osc 2 Fine + LFOs Rate/Depth/Wave + Osc 2 Shape
 //!  - Faders: VCA Envelope A/D/S/R + Osc 2  Sin/Sq/Tri/Saw/Vol
+//!
 //! Buttons:
 //!  - LFO1 Retrigger/Bipolar, LFO2 Retrigger/Bipolar, Osc Sync
 
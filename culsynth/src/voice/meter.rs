@@ -0,0 +1,89 @@
+//! This module contains a simple level meter, used to provide peak and RMS
+//! output levels (e.g. for GUI level meters) without having to recompute them
+//! from the raw samples.
+
+use crate::util::sqrt_approx;
+
+/// A snapshot of the levels reported by a [Meter]
+#[derive(Clone, Copy, Default, PartialEq, Debug)]
+pub struct MeterLevels {
+    /// The largest absolute sample value seen over the last window
+    pub peak: f32,
+    /// The (approximate) RMS level over the last window
+    pub rms: f32,
+}
+
+/// A cheap peak/RMS level meter
+///
+/// The peak is a running maximum which is held for one window and then
+/// reset, and the RMS is computed from a leaky integrator of the squared
+/// signal with a time constant of one window.
+#[derive(Clone)]
+pub struct Meter {
+    coeff: f32,
+    window: u32,
+    count: u32,
+    peak_held: f32,
+    peak_running: f32,
+    mean_sq: f32,
+}
+
+impl Meter {
+    /// Constructor, averaging over `window` samples.  A window of zero is
+    /// treated as one.
+    pub fn new(window: u32) -> Self {
+        let window = core::cmp::max(window, 1);
+        Self {
+            coeff: 1f32 / window as f32,
+            window,
+            count: 0,
+            peak_held: 0f32,
+            peak_running: 0f32,
+            mean_sq: 0f32,
+        }
+    }
+    /// Feed one sample into the meter
+    pub fn next(&mut self, smp: f32) {
+        let mag = if smp < 0f32 { -smp } else { smp };
+        if mag > self.peak_running {
+            self.peak_running = mag;
+        }
+        self.count += 1;
+        if self.count >= self.window {
+            self.peak_held = self.peak_running;
+            self.peak_running = 0f32;
+            self.count = 0;
+        }
+        self.mean_sq += (smp * smp - self.mean_sq) * self.coeff;
+    }
+    /// Get the current levels
+    pub fn levels(&self) -> MeterLevels {
+        MeterLevels {
+            peak: if self.peak_running > self.peak_held {
+                self.peak_running
+            } else {
+                self.peak_held
+            },
+            rms: sqrt_approx(self.mean_sq),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn meter_sine() {
+        let amplitude = 0.5f32;
+        let mut meter = Meter::new(4800);
+        for i in 0..48000 {
+            let phase = core::f32::consts::TAU * 440f32 * (i as f32) / 48000f32;
+            meter.next(amplitude * phase.sin());
+        }
+        let levels = meter.levels();
+        let rms = amplitude * core::f32::consts::FRAC_1_SQRT_2;
+        assert!((levels.rms - rms).abs() < 0.01);
+        assert!((levels.peak - amplitude).abs() < 0.001);
+    }
+}
@@ -1,11 +1,15 @@
 //! This module contains data to allow modulation of a `Voice`
 use arrayvec::ArrayVec;
 
+#[cfg(feature = "float")]
+use crate::DspFloat;
 use crate::{devices::*, EnvParamFxP, LfoFreqFxP};
-use crate::{DspFloat, DspFormat, DspFormatBase, DspType};
-use crate::{ScalarFxP, SignedNoteFxP};
+use crate::{DspFormat, DspFormatBase, DspType};
+use crate::{IScalarFxP, ScalarFxP, SignedNoteFxP};
 
+mod info;
 mod types;
+pub use info::*;
 pub use types::*;
 
 /// The number of modulation slots per [ModSrc] in a [ModMatrix]
@@ -24,7 +28,7 @@ type ModMatrixEntry<T> = (ModSrc, ModMatrixRowEntries<T>);
 ///
 /// The implementation of `Default` creates a ModMatrix with rows initialized
 /// for each [ModSrc] and each slot mapped to [ModDest::Null] with a depth of 0.
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct ModMatrix<T: DspFormatBase> {
     /// The rows making up the modmatrix
     pub rows: [ModMatrixEntry<T>; ModSrc::numel()],
@@ -49,6 +53,7 @@ impl<T: DspFormatBase> ModMatrix<T> {
     }
 }
 
+#[cfg(feature = "float")]
 impl<T: DspFloat> From<&ModMatrix<i16>> for ModMatrix<T> {
     fn from(value: &ModMatrix<i16>) -> Self {
         Self {
@@ -65,11 +70,17 @@ impl From<&ModMatrix<i16>> for ModMatrix<i16> {
     }
 }
 
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq)]
 /// A parameter pack representing the different parameters to the [ModSection]
 pub struct ModSectionParams<T: DspFormatBase> {
     /// MIDI Velocity
     pub velocity: T::Scalar,
+    /// MIDI Note Off (release) velocity
+    pub release_velocity: T::Scalar,
+    /// Per-note pressure (e.g. MPE channel pressure)
+    pub pressure: T::Scalar,
+    /// Per-note "slide" (e.g. MPE CC #74)
+    pub slide: T::Scalar,
     /// MIDI Channel aftertouch
     pub aftertouch: T::Scalar,
     /// Modulation wheel (MIDI CC #1)
@@ -82,6 +93,11 @@ pub struct ModSectionParams<T: DspFormatBase> {
     pub env1_params: EnvParams<T>,
     /// Parameters for Envelope 2
     pub env2_params: EnvParams<T>,
+    /// If not `None`, this is used as the output of LFO 1 instead of the
+    /// section's own LFO.  This allows one LFO to be shared across several
+    /// voices so they are modulated in phase.  The shared LFO is not
+    /// modulated by LFO 2 or envelope 2, and `lfo1_params` is ignored.
+    pub global_lfo1: Option<T::Sample>,
 }
 
 #[derive(Clone)]
@@ -94,7 +110,7 @@ impl<T: DspFormatBase> From<&ModMatrix<T>> for ModMatrixExpanded<T> {
         let mut ret = Self::default();
         for (src, entries) in value.rows {
             for (dest, depth) in entries {
-                if dest == ModDest::Null {
+                if dest == ModDest::Null || !dest.is_valid_for(src) {
                     continue;
                 }
                 // FIXME: is it worth making these bigger for pathological
@@ -118,6 +134,9 @@ impl<T: DspFormatBase> Default for ModMatrixExpanded<T> {
 /// A struct containing all of the necessary information to modulate parameters
 pub struct Modulator<'a, T: DspFormatBase> {
     velocity: T::Scalar,
+    release_velocity: T::Scalar,
+    pressure: T::Scalar,
+    slide: T::Scalar,
     aftertouch: T::Scalar,
     modwheel: T::Scalar,
     env1: T::Scalar,
@@ -162,7 +181,7 @@ impl<'a, T: DspFormatBase + ModulatorOps> Modulator<'a, T> {
 
 /// The actual modulation section, containing the modulation LFOs and Envelopes and
 /// logic to build the [ModulatorFxP].
-#[derive(Clone, Default)]
+#[derive(Clone)]
 pub struct ModSection<
     T: DspFormatBase
         + ModulatorOps
@@ -173,7 +192,74 @@ pub struct ModSection<
     lfo2: Lfo<T>,
     env1: Env<T>,
     env2: Env<T>,
+    lfo2_last: T::Sample,
+    env2_last: T::Scalar,
     expanded_matrix: ModMatrixExpanded<T>,
+    smoothing: Option<DepthSmoothing<T>>,
+}
+
+/// The state of the mod matrix depth smoothing in a [ModSection]
+#[derive(Clone, Default)]
+struct DepthSmoothing<T: DspFormatBase> {
+    /// The matrix with the depths currently being applied
+    current: ModMatrix<T>,
+    /// True while the current depths are still ramping towards the target
+    ramping: bool,
+    /// The matrix most recently passed to [ModSection::next]
+    target: ModMatrix<T>,
+}
+
+impl<T: DspFormatBase + ModulatorOps> DepthSmoothing<T> {
+    /// Set a new target matrix.  Any slot with a new destination starts from
+    /// a depth of zero.
+    fn set_target(&mut self, matrix: &ModMatrix<T>) {
+        for (cur, new) in self.current.rows.iter_mut().zip(matrix.rows.iter()) {
+            for (cur_slot, new_slot) in cur.1.iter_mut().zip(new.1.iter()) {
+                if cur_slot.0 != new_slot.0 {
+                    *cur_slot = (new_slot.0, T::IScalar::zero());
+                }
+            }
+        }
+        self.target = matrix.clone();
+        self.ramping = true;
+    }
+    /// Move the current depths one sample towards the target, returning the
+    /// updated matrix if anything changed
+    fn step(&mut self) -> Option<&ModMatrix<T>> {
+        if !self.ramping {
+            return None;
+        }
+        let mut done = true;
+        for (cur, target) in self.current.rows.iter_mut().zip(self.target.rows.iter()) {
+            for (cur_slot, target_slot) in cur.1.iter_mut().zip(target.1.iter()) {
+                cur_slot.1 = T::smooth_depth(cur_slot.1, target_slot.1);
+                done &= cur_slot.1 == target_slot.1;
+            }
+        }
+        self.ramping = !done;
+        Some(&self.current)
+    }
+}
+
+impl<T> Default for ModSection<T>
+where
+    T: DspFormatBase
+        + ModulatorOps
+        + crate::devices::lfo::detail::LfoOps
+        + crate::devices::env::detail::EnvOps,
+{
+    fn default() -> Self {
+        Self {
+            lfo1: Default::default(),
+            lfo2: Default::default(),
+            env1: Default::default(),
+            env2: Default::default(),
+            lfo2_last: T::Sample::zero(),
+            env2_last: T::Scalar::zero(),
+            expanded_matrix: Default::default(),
+            smoothing: T::SMOOTH_DEPTH_DEFAULT.then(Default::default),
+        }
+    }
 }
 
 impl<T: DspFormat> ModSection<T> {
@@ -185,9 +271,32 @@ impl<T: DspFormat> ModSection<T> {
             lfo2: Lfo::new(seed2),
             env1: Default::default(),
             env2: Default::default(),
+            lfo2_last: T::Sample::zero(),
+            env2_last: T::Scalar::zero(),
             expanded_matrix: Default::default(),
+            smoothing: T::SMOOTH_DEPTH_DEFAULT.then(Default::default),
+        }
+    }
+    /// Enable or disable smoothing of changes to the modulation depths in the
+    /// [ModMatrix], so live edits to the matrix don't cause clicks.  When
+    /// enabled, depths ramp to their new values with a time constant of 256
+    /// samples.  This is on by default for floating point types, and off by
+    /// default for fixed point types to save processing time.
+    pub fn set_depth_smoothing(&mut self, enabled: bool) {
+        if enabled != self.smoothing.is_some() {
+            self.smoothing = enabled.then(Default::default);
         }
     }
+    /// Reset the modulation LFOs and envelopes to their initial state (see
+    /// [Lfo::reset] and [Env::reset]).  The modulation matrix is unchanged.
+    pub fn reset(&mut self) {
+        self.lfo1.reset();
+        self.lfo2.reset();
+        self.env1.reset();
+        self.env2.reset();
+        self.lfo2_last = T::Sample::zero();
+        self.env2_last = T::Scalar::zero();
+    }
     /// Build a [Modulator] from all the required data, to include the
     /// processing context, the gate signal, the [ModSectionParams], and
     /// the actual [ModMatrix].
@@ -198,21 +307,56 @@ impl<T: DspFormat> ModSection<T> {
         mut params: ModSectionParams<T>,
         entries: Option<&ModMatrix<T>>,
     ) -> Modulator<'a, T> {
-        let lfo1_out = self.lfo1.next(context, gate, params.lfo1_params);
-        let env1_out = self.env1.next(context, gate, params.env1_params);
-        if let Some(matrix) = entries {
-            self.expanded_matrix = matrix.into();
+        match (self.smoothing.as_mut(), entries) {
+            (Some(smoothing), matrix) => {
+                if let Some(matrix) = matrix {
+                    smoothing.set_target(matrix);
+                }
+                if let Some(current) = smoothing.step() {
+                    self.expanded_matrix = current.into();
+                }
+            }
+            (None, Some(matrix)) => self.expanded_matrix = matrix.into(),
+            (None, None) => {}
         }
-        // LFO2/ENV2 are default here, so empty slices.
+        // LFO1/ENV1 may be modulated by LFO2/ENV2 (and vice versa), so to
+        // break the cycle LFO1/ENV1 see the outputs of LFO2/ENV2 from the
+        // previous sample.  LFO1/ENV1 are zeroed since they are being computed.
         let modulator = Modulator {
             velocity: params.velocity,
+            release_velocity: params.release_velocity,
+            pressure: params.pressure,
+            slide: params.slide,
             aftertouch: params.aftertouch,
             modwheel: params.modwheel,
+            lfo1: T::Sample::zero(),
+            lfo2: self.lfo2_last,
+            env1: T::Scalar::zero(),
+            env2: self.env2_last,
+            matrix: &self.expanded_matrix,
+        };
+        T::modulate_lfo_freq(&modulator, &mut params.lfo1_params.freq, ModDest::Lfo1Rate);
+        T::modulate_scalar(
+            &modulator,
+            &mut params.lfo1_params.depth,
+            ModDest::Lfo1Depth,
+        );
+        T::modulate_env_param(&modulator, &mut params.env1_params.attack, ModDest::Env1A);
+        T::modulate_env_param(&modulator, &mut params.env1_params.decay, ModDest::Env1D);
+        T::modulate_scalar(&modulator, &mut params.env1_params.sustain, ModDest::Env1S);
+        T::modulate_env_param(&modulator, &mut params.env1_params.release, ModDest::Env1R);
+        let lfo1_out = match params.global_lfo1 {
+            Some(global) => global,
+            None => self.lfo1.next(context, gate, params.lfo1_params),
+        };
+        let env1_out = self.env1.next(context, gate, params.env1_params);
+        // LFO2/ENV2 are zeroed here since they are being computed.
+        let modulator = Modulator {
             lfo1: lfo1_out,
             lfo2: T::Sample::zero(),
             env1: env1_out,
             env2: T::Scalar::zero(),
-            matrix: &self.expanded_matrix,
+            ..modulator
         };
         T::modulate_lfo_freq(&modulator, &mut params.lfo2_params.freq, ModDest::Lfo2Rate);
         T::modulate_scalar(
@@ -224,11 +368,11 @@ impl<T: DspFormat> ModSection<T> {
         T::modulate_env_param(&modulator, &mut params.env2_params.decay, ModDest::Env2D);
         T::modulate_scalar(&modulator, &mut params.env2_params.sustain, ModDest::Env2S);
         T::modulate_env_param(&modulator, &mut params.env2_params.release, ModDest::Env2R);
-        let lfo2_out = self.lfo2.next(context, gate, params.lfo2_params);
-        let env2_out = self.env2.next(context, gate, params.env2_params);
+        self.lfo2_last = self.lfo2.next(context, gate, params.lfo2_params);
+        self.env2_last = self.env2.next(context, gate, params.env2_params);
         Modulator {
-            lfo2: lfo2_out,
-            env2: env2_out,
+            lfo2: self.lfo2_last,
+            env2: self.env2_last,
             ..modulator
         }
     }
@@ -236,7 +380,19 @@ impl<T: DspFormat> ModSection<T> {
 
 pub(crate) mod detail {
     use super::*;
+    /// A single (possibly modulated) parameter value, used to read back the
+    /// value of a [ModDest] from a [VoiceParams](crate::voice::VoiceParams)
+    #[cfg(feature = "float")]
+    #[derive(Clone, Copy)]
+    pub enum ModValue<T: DspFormatBase> {
+        Scalar(T::Scalar),
+        Note(T::Note),
+        NoteOffset(T::NoteOffset),
+        EnvParam(T::EnvParam),
+    }
     pub trait ModulatorOps: DspFormatBase {
+        const SMOOTH_DEPTH_DEFAULT: bool;
+        fn smooth_depth(current: Self::IScalar, target: Self::IScalar) -> Self::IScalar;
         fn modulate_env(
             modulator: &Modulator<Self>,
             params: &mut EnvParams<Self>,
@@ -256,6 +412,8 @@ pub(crate) mod detail {
         );
         fn modulate_scalar(modulator: &Modulator<Self>, scalar: &mut Self::Scalar, dest: ModDest);
         fn modulate_lfo_freq(modulator: &Modulator<Self>, freq: &mut Self::LfoFreq, dest: ModDest);
+        #[cfg(feature = "float")]
+        fn mod_value_to_float(value: ModValue<Self>) -> f32;
     }
     /// Apply all modulation to the parameter passed in `dest`
     ///
@@ -272,6 +430,9 @@ pub(crate) mod detail {
                 ModSrc::Env2 => modulator.env2.wide_mul_signed(depth),
                 ModSrc::Lfo1 => I1F31::saturating_from_num(modulator.lfo1.wide_mul(depth)),
                 ModSrc::Lfo2 => I1F31::saturating_from_num(modulator.lfo2.wide_mul(depth)),
+                ModSrc::ReleaseVelocity => modulator.release_velocity.wide_mul_signed(depth),
+                ModSrc::Pressure => modulator.pressure.wide_mul_signed(depth),
+                ModSrc::Slide => modulator.slide.wide_mul_signed(depth),
             };
             acc += T::widened_from_bits(if T::IS_SIGNED {
                 I17F15::from_num(mod_amt).to_bits()
@@ -281,6 +442,7 @@ pub(crate) mod detail {
         }
         T::saturating_from_num(acc)
     }
+    #[cfg(feature = "float")]
     pub fn coeff_from_fixed<T: crate::Fixed16, U: DspFloat>() -> U {
         let num_bits = if T::IS_SIGNED { 15 } else { 16 } - T::FRAC_NBITS as i32;
         if num_bits == -1 {
@@ -292,6 +454,7 @@ pub(crate) mod detail {
     /// Apply all modulation to the parameter passed in `dest`
     ///
     /// Returns true if any modulation was performed, or false otherwise
+    #[cfg(feature = "float")]
     pub fn modulate_float<T: DspFloat>(
         modulator: &Modulator<T>,
         dest: ModDest,
@@ -310,6 +473,9 @@ pub(crate) mod detail {
                         ModSrc::Env2 => modulator.env2,
                         ModSrc::Lfo1 => modulator.lfo1,
                         ModSrc::Lfo2 => modulator.lfo2,
+                        ModSrc::ReleaseVelocity => modulator.release_velocity,
+                        ModSrc::Pressure => modulator.pressure,
+                        ModSrc::Slide => modulator.slide,
                     });
         }
         acc = value + (acc * coeff);
@@ -323,6 +489,17 @@ pub(crate) mod detail {
 }
 
 impl detail::ModulatorOps for i16 {
+    const SMOOTH_DEPTH_DEFAULT: bool = false;
+    fn smooth_depth(current: IScalarFxP, target: IScalarFxP) -> IScalarFxP {
+        let diff = target.to_bits() as i32 - current.to_bits() as i32;
+        // Make sure we always make progress, even when diff >> 8 rounds to 0
+        let step = if diff.abs() < (1 << 8) {
+            diff.signum()
+        } else {
+            diff >> 8
+        };
+        IScalarFxP::from_bits((current.to_bits() as i32 + step) as i16)
+    }
     /// Modulate all of the parameters in `params` for the envelope specified by
     /// `dest`, which should be either [ENV_AMP_MOD_DEST] or [ENV_FILT_MOD_DEST]
     fn modulate_env(m: &Modulator<i16>, params: &mut EnvParams<i16>, dest: &EnvModDest) {
@@ -348,6 +525,10 @@ impl detail::ModulatorOps for i16 {
         params.sq = detail::modulate(m, dest.sq, params.sq);
         params.tri = detail::modulate(m, dest.tri, params.tri);
         params.saw = detail::modulate(m, dest.saw, params.saw);
+        params.osc_level = detail::modulate(m, dest.osc_level, params.osc_level);
+        if let Some(morph) = params.wave_morph.as_mut() {
+            *morph = detail::modulate(m, dest.wave_morph, *morph);
+        }
     }
     /// Modulate the ring modulator parameters
     fn modulate_ring(m: &Modulator<i16>, params: &mut RingModParams<i16>) {
@@ -358,6 +539,8 @@ impl detail::ModulatorOps for i16 {
     /// Modulate the filter parameters
     fn modulate_filt(m: &Modulator<i16>, params: &mut ModFiltParams<i16>) {
         params.env_mod = detail::modulate(m, ModDest::FiltEnv, params.env_mod);
+        let vel_cut = params.env_vel.scale(ScalarFxP::MAX - m.velocity);
+        params.env_mod -= params.env_mod.scale(vel_cut);
         params.vel_mod = detail::modulate(m, ModDest::FiltVel, params.vel_mod);
         params.kbd_tracking = detail::modulate(m, ModDest::FiltKbd, params.kbd_tracking);
         params.cutoff = detail::modulate(m, ModDest::FiltCutoff, params.cutoff);
@@ -365,6 +548,7 @@ impl detail::ModulatorOps for i16 {
         params.low_mix = detail::modulate(m, ModDest::FiltLow, params.low_mix);
         params.band_mix = detail::modulate(m, ModDest::FiltBand, params.band_mix);
         params.high_mix = detail::modulate(m, ModDest::FiltHigh, params.high_mix);
+        params.drive = detail::modulate(m, ModDest::FiltDrive, params.drive);
     }
     fn modulate_env_param(m: &Modulator<i16>, param: &mut EnvParamFxP, dest: ModDest) {
         *param = detail::modulate(m, dest, *param);
@@ -375,9 +559,29 @@ impl detail::ModulatorOps for i16 {
     fn modulate_scalar(m: &Modulator<i16>, scalar: &mut ScalarFxP, dest: ModDest) {
         *scalar = detail::modulate(m, dest, *scalar);
     }
+    #[cfg(feature = "float")]
+    fn mod_value_to_float(value: ModValue<i16>) -> f32 {
+        match value {
+            ModValue::Scalar(x) => x.to_num(),
+            ModValue::Note(x) => x.to_num(),
+            ModValue::NoteOffset(x) => x.to_num(),
+            ModValue::EnvParam(x) => x.to_num(),
+        }
+    }
 }
 
+#[cfg(feature = "float")]
 impl<T: DspFloat> detail::ModulatorOps for T {
+    const SMOOTH_DEPTH_DEFAULT: bool = true;
+    fn smooth_depth(current: T, target: T) -> T {
+        let diff = target - current;
+        // Snap to the target once within the resolution of IScalarFxP
+        if diff.abs() < T::ONE / T::from_u16(0x8000) {
+            target
+        } else {
+            current + diff / T::from_u16(256)
+        }
+    }
     /// Modulate all of the parameters in `params` for the envelope specified by
     /// `dest`, which should be either [ENV_AMP_MOD_DEST] or [ENV_FILT_MOD_DEST]
     fn modulate_env(m: &Modulator<T>, params: &mut EnvParams<T>, dest: &EnvModDest) {
@@ -398,6 +602,10 @@ impl<T: DspFloat> detail::ModulatorOps for T {
         params.sq = detail::modulate_float(m, dest.sq, params.sq, coeff);
         params.tri = detail::modulate_float(m, dest.tri, params.tri, coeff);
         params.saw = detail::modulate_float(m, dest.saw, params.saw, coeff);
+        params.osc_level = detail::modulate_float(m, dest.osc_level, params.osc_level, coeff);
+        if let Some(morph) = params.wave_morph.as_mut() {
+            *morph = detail::modulate_float(m, dest.wave_morph, *morph, coeff);
+        }
     }
     /// Modulate the ring modulator parameters
     fn modulate_ring(m: &Modulator<T>, params: &mut RingModParams<T>) {
@@ -411,6 +619,7 @@ impl<T: DspFloat> detail::ModulatorOps for T {
         let coeff = detail::coeff_from_fixed::<ScalarFxP, T>();
         let filt_coeff = detail::coeff_from_fixed::<crate::NoteFxP, T>();
         params.env_mod = detail::modulate_float(m, ModDest::FiltEnv, params.env_mod, coeff);
+        params.env_mod = params.env_mod * (T::ONE - params.env_vel * (T::ONE - m.velocity));
         params.vel_mod = detail::modulate_float(m, ModDest::FiltVel, params.vel_mod, coeff);
         params.kbd_tracking =
             detail::modulate_float(m, ModDest::FiltKbd, params.kbd_tracking, coeff);
@@ -419,6 +628,7 @@ impl<T: DspFloat> detail::ModulatorOps for T {
         params.low_mix = detail::modulate_float(m, ModDest::FiltLow, params.low_mix, coeff);
         params.band_mix = detail::modulate_float(m, ModDest::FiltBand, params.band_mix, coeff);
         params.high_mix = detail::modulate_float(m, ModDest::FiltHigh, params.high_mix, coeff);
+        params.drive = detail::modulate_float(m, ModDest::FiltDrive, params.drive, coeff);
     }
     fn modulate_env_param(m: &Modulator<T>, param: &mut T, dest: ModDest) {
         let coeff = detail::coeff_from_fixed::<EnvParamFxP, T>();
@@ -432,6 +642,178 @@ impl<T: DspFloat> detail::ModulatorOps for T {
         let coeff = detail::coeff_from_fixed::<ScalarFxP, T>();
         *scalar = detail::modulate_float(m, dest, *scalar, coeff);
     }
+    fn mod_value_to_float(value: ModValue<T>) -> f32 {
+        match value {
+            ModValue::Scalar(x)
+            | ModValue::Note(x)
+            | ModValue::NoteOffset(x)
+            | ModValue::EnvParam(x) => x.as_f32(),
+        }
+    }
 }
 
+#[cfg(feature = "float")]
+use detail::ModValue;
 use detail::ModulatorOps;
+
+#[cfg(all(test, feature = "float"))]
+mod tests {
+    use super::*;
+    use crate::context::{Context, ContextFxP};
+
+    /// Count the number of times LFO1 wraps around over one second with Env2
+    /// optionally routed to the LFO1 rate
+    fn lfo1_cycles<T: DspFormat>(context: &T::Context, modulate: bool) -> usize {
+        let mut matrix = ModMatrix::<T>::default();
+        if modulate {
+            matrix.rows[ModSrc::Env2 as usize].1[0] = (ModDest::Lfo1Rate, T::IScalar::one());
+        }
+        let params = ModSectionParams::<T> {
+            velocity: T::Scalar::zero(),
+            release_velocity: T::Scalar::zero(),
+            pressure: T::Scalar::zero(),
+            slide: T::Scalar::zero(),
+            aftertouch: T::Scalar::zero(),
+            modwheel: T::Scalar::zero(),
+            lfo1_params: LfoParams {
+                freq: T::LfoFreq::one(),
+                depth: T::Scalar::one(),
+                opts: LfoOptions::new(LfoWave::Saw, false, false),
+                phase_offset: T::Scalar::zero(),
+            },
+            lfo2_params: Default::default(),
+            env1_params: Default::default(),
+            env2_params: Default::default(),
+            global_lfo1: None,
+        };
+        let mut section = ModSection::<T>::default();
+        let mut entries = Some(&matrix);
+        let mut last = T::Sample::zero();
+        let mut cycles = 0;
+        for _ in 0..48000 {
+            let lfo1 = section.next(context, true, params.clone(), entries.take()).lfo1;
+            if lfo1 < last {
+                cycles += 1;
+            }
+            last = lfo1;
+        }
+        cycles
+    }
+
+    /// Route velocity to the filter resonance with a depth of 0, then change
+    /// the depth to 1/2, returning the modulated resonance for each sample
+    /// after the change
+    fn depth_change<T: DspFormat>(
+        context: &T::Context,
+        smoothing: bool,
+        to_float: impl Fn(T::Scalar) -> f32,
+    ) -> [f32; 2400] {
+        let mut section = ModSection::<T>::default();
+        section.set_depth_smoothing(smoothing);
+        let params = ModSectionParams::<T> {
+            velocity: T::Scalar::one(),
+            release_velocity: T::Scalar::zero(),
+            pressure: T::Scalar::zero(),
+            slide: T::Scalar::zero(),
+            aftertouch: T::Scalar::zero(),
+            modwheel: T::Scalar::zero(),
+            lfo1_params: Default::default(),
+            lfo2_params: Default::default(),
+            env1_params: Default::default(),
+            env2_params: Default::default(),
+            global_lfo1: None,
+        };
+        let mut matrix = ModMatrix::<T>::default();
+        matrix.rows[ModSrc::Velocity as usize].1[0] = (ModDest::FiltRes, T::IScalar::zero());
+        for _ in 0..100 {
+            section.next(context, true, params.clone(), Some(&matrix));
+        }
+        matrix.rows[ModSrc::Velocity as usize].1[0].1 = T::IScalar::one().divide_by_two();
+        let mut entries = Some(&matrix);
+        core::array::from_fn(|_| {
+            let m = section.next(context, true, params.clone(), entries.take());
+            let mut res = T::Scalar::zero();
+            m.modulate_scalar(&mut res, ModDest::FiltRes);
+            to_float(res)
+        })
+    }
+
+    #[test]
+    fn depth_smoothing_ramps() {
+        let ctx = Context::<f32>::new(48000.0);
+        let stepped = depth_change::<f32>(&ctx, false, |x| x);
+        assert!((stepped[0] - 0.5).abs() < 0.01);
+        let smoothed = depth_change::<f32>(&ctx, true, |x| x);
+        assert!(smoothed[0] < 0.01);
+        assert!(smoothed.windows(2).all(|w| w[1] >= w[0] && w[1] - w[0] < 0.01));
+        assert!((smoothed[2399] - 0.5).abs() < 0.01);
+
+        let ctx = ContextFxP::new_480();
+        let stepped = depth_change::<i16>(&ctx, false, |x| x.to_num());
+        assert!((stepped[0] - 0.5).abs() < 0.01);
+        let smoothed = depth_change::<i16>(&ctx, true, |x| x.to_num());
+        assert!(smoothed[0] < 0.01);
+        assert!(smoothed.windows(2).all(|w| w[1] >= w[0] && w[1] - w[0] < 0.01));
+        assert!((smoothed[2399] - 0.5).abs() < 0.01);
+    }
+
+    /// The filter envelope depth after modulation, for a full `env_mod` with
+    /// velocity scaling `env_vel` and the given note on `velocity`
+    fn filt_env_depth<T: DspFormat>(
+        context: &T::Context,
+        env_vel: T::Scalar,
+        velocity: T::Scalar,
+        to_float: impl Fn(T::Scalar) -> f32,
+    ) -> f32 {
+        let mut section = ModSection::<T>::default();
+        let params = ModSectionParams::<T> {
+            velocity,
+            release_velocity: T::Scalar::zero(),
+            pressure: T::Scalar::zero(),
+            slide: T::Scalar::zero(),
+            aftertouch: T::Scalar::zero(),
+            modwheel: T::Scalar::zero(),
+            lfo1_params: Default::default(),
+            lfo2_params: Default::default(),
+            env1_params: Default::default(),
+            env2_params: Default::default(),
+            global_lfo1: None,
+        };
+        let m = section.next(context, true, params, Some(&Default::default()));
+        let mut filt = ModFiltParams::<T> {
+            env_mod: T::Scalar::one(),
+            env_vel,
+            ..Default::default()
+        };
+        m.modulate_mod_filt(&mut filt);
+        to_float(filt.env_mod)
+    }
+
+    #[test]
+    fn velocity_scales_filt_env() {
+        let ctx = Context::<f32>::new(48000.0);
+        let depth = |env_vel, vel| filt_env_depth::<f32>(&ctx, env_vel, vel, |x| x);
+        assert!((depth(0.0, 0.5) - 1.0).abs() < 0.01);
+        assert!((depth(0.5, 1.0) - 1.0).abs() < 0.01);
+        assert!((depth(0.5, 0.5) - 0.75).abs() < 0.01);
+        assert!((depth(1.0, 0.25) - 0.25).abs() < 0.01);
+
+        let ctx = ContextFxP::new_480();
+        let depth = |env_vel, vel| filt_env_depth::<i16>(&ctx, env_vel, vel, |x| x.to_num());
+        let half = ScalarFxP::lit("0.5");
+        assert!((depth(ScalarFxP::ZERO, half) - 1.0).abs() < 0.01);
+        assert!((depth(half, ScalarFxP::MAX) - 1.0).abs() < 0.01);
+        assert!((depth(half, half) - 0.75).abs() < 0.01);
+        assert!((depth(ScalarFxP::MAX, ScalarFxP::lit("0.25")) - 0.25).abs() < 0.01);
+    }
+
+    #[test]
+    fn env2_modulates_lfo1_rate() {
+        let ctx = Context::<f32>::new(48000.0);
+        assert!(lfo1_cycles::<f32>(&ctx, false) <= 1);
+        assert!(lfo1_cycles::<f32>(&ctx, true) > 10);
+        let ctx = ContextFxP::new_480();
+        assert!(lfo1_cycles::<i16>(&ctx, false) <= 1);
+        assert!(lfo1_cycles::<i16>(&ctx, true) > 10);
+    }
+}
@@ -0,0 +1,158 @@
+use super::ModDest;
+use crate::devices::{EnvParams, LfoParams};
+use crate::voice::VoiceParams;
+use crate::{EnvParamFxP, LfoFreqFxP, NoteFxP, ScalarFxP, SignedNoteFxP};
+use fixed::traits::Fixed;
+
+/// The unit of the value of a parameter (see [ParamInfo])
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ParamUnit {
+    /// A level or amount from 0 to 1, usually displayed as a percentage
+    Ratio,
+    /// A pitch offset, in semitones
+    Semitones,
+    /// A pitch as a MIDI note number (e.g. the filter cutoff, which may be
+    /// displayed as a frequency instead)
+    Note,
+    /// A time, in seconds
+    Seconds,
+    /// A frequency, in Hz
+    Hertz,
+}
+
+/// Range and display metadata for the parameter targeted by a [ModDest], so
+/// that GUIs do not need to hardcode the range of each parameter.  All values
+/// are in the units given by `unit`.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct ParamInfo {
+    /// The smallest value of the parameter
+    pub min: f32,
+    /// The largest value of the parameter
+    pub max: f32,
+    /// The default value of the parameter (see [VoiceParams::default])
+    pub default: f32,
+    /// The resolution of the parameter, i.e. the smallest change that has
+    /// any effect on the fixed point implementation
+    pub step: f32,
+    /// The unit of the parameter
+    pub unit: ParamUnit,
+}
+
+impl ParamInfo {
+    /// The metadata for a parameter stored as the fixed point type `F`,
+    /// covering its full range
+    fn from_fixed<F: Fixed>(default: F, unit: ParamUnit) -> Self {
+        Self {
+            min: F::MIN.to_num(),
+            max: F::MAX.to_num(),
+            default: default.to_num(),
+            step: F::DELTA.to_num(),
+            unit,
+        }
+    }
+    /// The metadata for a tuning parameter from `-range` to `range`
+    /// semitones with a resolution of `step`
+    fn tune(range: f32, step: f32) -> Self {
+        Self {
+            min: -range,
+            max: range,
+            default: 0f32,
+            step,
+            unit: ParamUnit::Semitones,
+        }
+    }
+}
+
+impl ModDest {
+    /// The range and display metadata for the parameter targeted by this
+    /// destination.  Modulation depths are relative to this range, e.g. a
+    /// depth of 1 applied to [ModDest::Osc1Course] sweeps 32 semitones.
+    ///
+    /// [ModDest::Null] does not target any parameter, so it is described as
+    /// a [ParamUnit::Ratio] with a default of zero.
+    pub fn param_info(self) -> ParamInfo {
+        let voice = VoiceParams::<i16>::default();
+        let (osc1, osc2) = (&voice.oscs_p.primary, &voice.oscs_p.secondary);
+        let (filt, env_filt, env_amp) = (&voice.filt_p, &voice.filt_env_p, &voice.amp_env_p);
+        let (lfo, env) = (LfoParams::<i16>::default(), EnvParams::<i16>::default());
+        let ratio = |x: ScalarFxP| ParamInfo::from_fixed(x, ParamUnit::Ratio);
+        let seconds = |x: EnvParamFxP| ParamInfo::from_fixed(x, ParamUnit::Seconds);
+        let hertz = |x: LfoFreqFxP| ParamInfo::from_fixed(x, ParamUnit::Hertz);
+        let fine_step = SignedNoteFxP::DELTA.to_num();
+        match self {
+            Self::Null => ratio(ScalarFxP::ZERO),
+            Self::Osc1Course | Self::Osc2Course => ParamInfo::tune(32f32, 1f32),
+            Self::Osc1Fine | Self::Osc2Fine => ParamInfo::tune(2f32, fine_step),
+            Self::Osc1Shape => ratio(osc1.shape),
+            Self::Osc1Sin => ratio(osc1.sin),
+            Self::Osc1Sq => ratio(osc1.sq),
+            Self::Osc1Tri => ratio(osc1.tri),
+            Self::Osc1Saw => ratio(osc1.saw),
+            Self::Osc1Level => ratio(osc1.osc_level),
+            Self::Osc1Morph => ratio(osc1.wave_morph.unwrap_or_default()),
+            Self::Osc2Shape => ratio(osc2.shape),
+            Self::Osc2Sin => ratio(osc2.sin),
+            Self::Osc2Sq => ratio(osc2.sq),
+            Self::Osc2Tri => ratio(osc2.tri),
+            Self::Osc2Saw => ratio(osc2.saw),
+            Self::Osc2Level => ratio(osc2.osc_level),
+            Self::Osc2Morph => ratio(osc2.wave_morph.unwrap_or_default()),
+            Self::RingOsc1 => ratio(voice.ring_p.mix_a),
+            Self::RingOsc2 => ratio(voice.ring_p.mix_b),
+            Self::RingMod => ratio(voice.ring_p.mix_mod),
+            Self::FiltCutoff => ParamInfo::from_fixed::<NoteFxP>(filt.cutoff, ParamUnit::Note),
+            Self::FiltRes => ratio(filt.resonance),
+            Self::FiltEnv => ratio(filt.env_mod),
+            Self::FiltKbd => ratio(filt.kbd_tracking),
+            Self::FiltVel => ratio(filt.vel_mod),
+            Self::FiltLow => ratio(filt.low_mix),
+            Self::FiltBand => ratio(filt.band_mix),
+            Self::FiltHigh => ratio(filt.high_mix),
+            Self::FiltDrive => ratio(filt.drive),
+            Self::EnvFiltA => seconds(env_filt.attack),
+            Self::EnvFiltD => seconds(env_filt.decay),
+            Self::EnvFiltS => ratio(env_filt.sustain),
+            Self::EnvFiltR => seconds(env_filt.release),
+            Self::EnvAmpA => seconds(env_amp.attack),
+            Self::EnvAmpD => seconds(env_amp.decay),
+            Self::EnvAmpS => ratio(env_amp.sustain),
+            Self::EnvAmpR => seconds(env_amp.release),
+            Self::MasterGain => ratio(voice.master_gain),
+            Self::Lfo1Rate | Self::Lfo2Rate => hertz(lfo.freq),
+            Self::Lfo1Depth | Self::Lfo2Depth => ratio(lfo.depth),
+            Self::Env1A | Self::Env2A => seconds(env.attack),
+            Self::Env1D | Self::Env2D => seconds(env.decay),
+            Self::Env1S | Self::Env2S => ratio(env.sustain),
+            Self::Env1R | Self::Env2R => seconds(env.release),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_dest_has_a_valid_range() {
+        for dest in ModDest::elements() {
+            let info = dest.param_info();
+            let name = dest.to_str();
+            assert!(info.min.is_finite() && info.max.is_finite(), "{name}");
+            assert!(info.min < info.max, "{name}: {info:?}");
+            assert!(
+                info.min <= info.default && info.default <= info.max,
+                "{name}: {info:?}"
+            );
+            assert!(info.step > 0f32, "{name}: {info:?}");
+            // There should be enough steps for a usable control
+            assert!(
+                (info.max - info.min) / info.step >= 64f32,
+                "{name}: {info:?}"
+            );
+        }
+        let cutoff = ModDest::FiltCutoff.param_info();
+        assert_eq!(cutoff.unit, ParamUnit::Note);
+        assert!(cutoff.max > 127f32);
+        assert_eq!(ModDest::Env1A.param_info().unit, ParamUnit::Seconds);
+    }
+}
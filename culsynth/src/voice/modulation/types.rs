@@ -1,6 +1,6 @@
 /// An enum representing a choice in modulation source
 #[repr(u16)]
-#[derive(Clone, Copy, Default)]
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
 pub enum ModSrc {
     /// MIDI Note On velocity
     #[default]
@@ -17,6 +17,13 @@ pub enum ModSrc {
     Lfo1,
     /// LFO #2
     Lfo2,
+    /// MIDI Note Off (release) velocity.  This is zero until the note is
+    /// released.
+    ReleaseVelocity,
+    /// Per-note pressure (e.g. MPE channel pressure)
+    Pressure,
+    /// Per-note "slide" (e.g. MPE CC #74)
+    Slide,
 }
 
 impl ModSrc {
@@ -29,6 +36,9 @@ impl ModSrc {
         ModSrc::Env2,
         ModSrc::Lfo1,
         ModSrc::Lfo2,
+        ModSrc::ReleaseVelocity,
+        ModSrc::Pressure,
+        ModSrc::Slide,
     ];
     /// An iterator over all the different elements in `ModSrc`
     pub const fn elements() -> &'static [ModSrc] {
@@ -40,7 +50,7 @@ impl ModSrc {
     }
     /// The last value in elements
     pub const fn max() -> Self {
-        Self::Lfo2
+        Self::Slide
     }
     /// The number of different modualtion sources
     pub const fn numel() -> usize {
@@ -56,13 +66,16 @@ impl ModSrc {
             Self::Env2 => "Envelope 2",
             Self::Lfo1 => "LFO 1",
             Self::Lfo2 => "LFO 2",
+            Self::ReleaseVelocity => "Release Velocity",
+            Self::Pressure => "Pressure",
+            Self::Slide => "Slide",
         }
     }
 }
 
 /// An enum representing a modulation destination
 #[repr(u16)]
-#[derive(Clone, Copy, PartialEq, Default)]
+#[derive(Clone, Copy, PartialEq, Default, Eq, Debug)]
 pub enum ModDest {
     /// The default is `ModDest::Null`, which is equivalent to no modulation
     #[default]
@@ -149,9 +162,96 @@ pub enum ModDest {
     Env2S,
     /// The release of modulation envelope 2
     Env2R,
+
+    /// The rate/frequency of LFO 1, in Hz
+    Lfo1Rate,
+    /// The modulation depth of LFO 1, from 0 to 1
+    Lfo1Depth,
+    /// The attack of modulation envelope 1
+    Env1A,
+    /// The decay of modulation envelope 1
+    Env1D,
+    /// The sustain of modulation envelope 1
+    Env1S,
+    /// The release of modulation envelope 1
+    Env1R,
+
+    // Patches store destinations by discriminant, so new destinations must be
+    // appended here to keep the existing discriminants stable
+    /// The overall output level of oscillator 1, applied after the
+    /// waveform mix
+    Osc1Level,
+    /// The overall output level of oscillator 2, applied after the
+    /// waveform mix
+    Osc2Level,
+    /// The wave morph position of oscillator 1.  This has no effect unless
+    /// the oscillator's wave morph is enabled.
+    Osc1Morph,
+    /// The wave morph position of oscillator 2.  This has no effect unless
+    /// the oscillator's wave morph is enabled.
+    Osc2Morph,
+    /// The overall output gain of the voice
+    MasterGain,
+    /// The filter input drive
+    FiltDrive,
 }
 
 impl ModDest {
+    /// An array containing all possible `ModDest` values, in order
+    pub const ELEM: [ModDest; Self::numel()] = [
+        ModDest::Null,
+        ModDest::Osc1Course,
+        ModDest::Osc1Fine,
+        ModDest::Osc1Shape,
+        ModDest::Osc1Sin,
+        ModDest::Osc1Sq,
+        ModDest::Osc1Tri,
+        ModDest::Osc1Saw,
+        ModDest::Osc2Course,
+        ModDest::Osc2Fine,
+        ModDest::Osc2Shape,
+        ModDest::Osc2Sin,
+        ModDest::Osc2Sq,
+        ModDest::Osc2Tri,
+        ModDest::Osc2Saw,
+        ModDest::RingOsc1,
+        ModDest::RingOsc2,
+        ModDest::RingMod,
+        ModDest::FiltCutoff,
+        ModDest::FiltRes,
+        ModDest::FiltEnv,
+        ModDest::FiltKbd,
+        ModDest::FiltVel,
+        ModDest::FiltLow,
+        ModDest::FiltBand,
+        ModDest::FiltHigh,
+        ModDest::EnvFiltA,
+        ModDest::EnvFiltD,
+        ModDest::EnvFiltS,
+        ModDest::EnvFiltR,
+        ModDest::EnvAmpA,
+        ModDest::EnvAmpD,
+        ModDest::EnvAmpS,
+        ModDest::EnvAmpR,
+        ModDest::Lfo2Rate,
+        ModDest::Lfo2Depth,
+        ModDest::Env2A,
+        ModDest::Env2D,
+        ModDest::Env2S,
+        ModDest::Env2R,
+        ModDest::Lfo1Rate,
+        ModDest::Lfo1Depth,
+        ModDest::Env1A,
+        ModDest::Env1D,
+        ModDest::Env1S,
+        ModDest::Env1R,
+        ModDest::Osc1Level,
+        ModDest::Osc2Level,
+        ModDest::Osc1Morph,
+        ModDest::Osc2Morph,
+        ModDest::MasterGain,
+        ModDest::FiltDrive,
+    ];
     /// Env2/Lfo2 may not modulate themselves/each other, so call this function
     /// when evaluating their modulation matrices to remap these invalid routes
     /// to `Self::Null`
@@ -166,6 +266,30 @@ impl ModDest {
             val => val,
         }
     }
+    /// Env1/Lfo1 may not modulate themselves/each other, so call this function
+    /// when evaluating their modulation matrices to remap these invalid routes
+    /// to `Self::Null`
+    pub const fn remove_primary_invalid_dest(self) -> Self {
+        match self {
+            Self::Lfo1Rate => Self::Null,
+            Self::Lfo1Depth => Self::Null,
+            Self::Env1A => Self::Null,
+            Self::Env1D => Self::Null,
+            Self::Env1S => Self::Null,
+            Self::Env1R => Self::Null,
+            val => val,
+        }
+    }
+    /// Returns false if routing `src` to this destination would cause an LFO
+    /// or envelope to modulate itself (or its sibling), and true otherwise
+    pub const fn is_valid_for(self, src: ModSrc) -> bool {
+        let remapped = match src {
+            ModSrc::Lfo1 | ModSrc::Env1 => self.remove_primary_invalid_dest(),
+            ModSrc::Lfo2 | ModSrc::Env2 => self.remove_secondary_invalid_dest(),
+            _ => self,
+        };
+        remapped as u16 == self as u16
+    }
     /// The string representation of this modulation destination.
     pub const fn to_str(&self) -> &'static str {
         match self {
@@ -177,6 +301,8 @@ impl ModDest {
             Self::Osc1Sq => "Osc1Sq",
             Self::Osc1Tri => "Osc1Tri",
             Self::Osc1Saw => "Osc1Saw",
+            Self::Osc1Level => "Osc1Level",
+            Self::Osc1Morph => "Osc1Morph",
             Self::Osc2Course => "Osc2Course",
             Self::Osc2Fine => "Osc2Fine",
             Self::Osc2Shape => "Osc2Shape",
@@ -184,6 +310,8 @@ impl ModDest {
             Self::Osc2Sq => "Osc2Sq",
             Self::Osc2Tri => "Osc2Tri",
             Self::Osc2Saw => "Osc2Saw",
+            Self::Osc2Level => "Osc2Level",
+            Self::Osc2Morph => "Osc2Morph",
             Self::RingOsc1 => "RingOsc1",
             Self::RingOsc2 => "RingOsc2",
             Self::RingMod => "RingMod",
@@ -195,6 +323,7 @@ impl ModDest {
             Self::FiltLow => "FiltLow",
             Self::FiltBand => "FiltBand",
             Self::FiltHigh => "FiltHigh",
+            Self::FiltDrive => "FiltDrive",
             Self::EnvFiltA => "EnvFiltA",
             Self::EnvFiltD => "EnvFiltD",
             Self::EnvFiltS => "EnvFiltS",
@@ -203,12 +332,19 @@ impl ModDest {
             Self::EnvAmpD => "EnvAmpD",
             Self::EnvAmpS => "EnvAmpS",
             Self::EnvAmpR => "EnvAmpR",
+            Self::MasterGain => "MasterGain",
             Self::Lfo2Rate => "Lfo2Rate",
             Self::Lfo2Depth => "Lfo2Depth",
             Self::Env2A => "Env2A",
             Self::Env2D => "Env2D",
             Self::Env2S => "Env2S",
             Self::Env2R => "Env2R",
+            Self::Lfo1Rate => "Lfo1Rate",
+            Self::Lfo1Depth => "Lfo1Depth",
+            Self::Env1A => "Env1A",
+            Self::Env1D => "Env1D",
+            Self::Env1S => "Env1S",
+            Self::Env1R => "Env1R",
         }
     }
     /// The first modulation destination, in order
@@ -217,7 +353,7 @@ impl ModDest {
     }
     /// The last modulation destination, in order
     pub const fn max() -> Self {
-        Self::Env2R
+        Self::FiltDrive
     }
     /// The number of modulation destinations
     pub const fn numel() -> usize {
@@ -226,13 +362,15 @@ impl ModDest {
     /// The last modulation destination before the secondary destinations
     ///
     /// The secondary modulation destinations are invalid destinations from
-    /// LFO2/ENV2 to avoid self/co-modulation
+    /// LFO2/ENV2 to avoid self/co-modulation.  Note that the primary
+    /// destinations (LFO1/ENV1 parameters) follow the secondary destinations,
+    /// so destinations past this point are not necessarily invalid.
     pub const fn max_secondary() -> Self {
         Self::EnvAmpR
     }
     /// An iterator over all modulation destinations
     pub fn elements() -> impl core::iter::Iterator<Item = ModDest> {
-        Self::elements_secondary_if(false)
+        Self::ELEM.iter().copied()
     }
     /// An iterator over all non-secondary modulation destinations
     ///
@@ -243,23 +381,19 @@ impl ModDest {
     /// An iterator that excludes the secondary modulation destinations if the
     /// argument is true, and includes them if it is false
     pub fn elements_secondary_if(sec: bool) -> impl core::iter::Iterator<Item = ModDest> {
-        let max = if sec {
-            Self::max_secondary()
-        } else {
-            Self::max()
-        };
-        ((Self::min() as u16)..=(max as u16)).map(|x| unsafe { core::mem::transmute(x) })
+        Self::elements().filter(move |dest| !sec || dest.remove_secondary_invalid_dest() == *dest)
+    }
+    /// An iterator over all of the modulation destinations that are valid
+    /// for the given source (see [ModDest::is_valid_for])
+    pub fn elements_for(src: ModSrc) -> impl core::iter::Iterator<Item = ModDest> {
+        Self::elements().filter(move |dest| dest.is_valid_for(src))
     }
 }
 
 impl TryFrom<u16> for ModDest {
     type Error = &'static str;
     fn try_from(value: u16) -> Result<Self, Self::Error> {
-        if value >= Self::min() as u16 && value <= Self::max() as u16 {
-            unsafe { Ok(core::mem::transmute(value)) }
-        } else {
-            Err("ModDest out of bounds")
-        }
+        Self::ELEM.get(value as usize).copied().ok_or("ModDest out of bounds")
     }
 }
 
@@ -290,6 +424,10 @@ pub struct OscModDest {
     pub tri: ModDest,
     /// Sawtooth output
     pub saw: ModDest,
+    /// Output level
+    pub osc_level: ModDest,
+    /// Wave morph position
+    pub wave_morph: ModDest,
 }
 
 /// The modulation destinations corresponding to oscillator 1
@@ -301,6 +439,8 @@ pub const OSC1_MOD_DEST: OscModDest = OscModDest {
     sq: ModDest::Osc1Sq,
     tri: ModDest::Osc1Tri,
     saw: ModDest::Osc1Saw,
+    osc_level: ModDest::Osc1Level,
+    wave_morph: ModDest::Osc1Morph,
 };
 
 /// The modulation destinations corresponding to oscillator 2
@@ -312,6 +452,8 @@ pub const OSC2_MOD_DEST: OscModDest = OscModDest {
     sq: ModDest::Osc2Sq,
     tri: ModDest::Osc2Tri,
     saw: ModDest::Osc2Saw,
+    osc_level: ModDest::Osc2Level,
+    wave_morph: ModDest::Osc2Morph,
 };
 
 /// A struct to allow expressing the different modulation destinations for a
@@ -343,3 +485,48 @@ pub const ENV_FILT_MOD_DEST: EnvModDest = EnvModDest {
     sustain: ModDest::EnvFiltS,
     release: ModDest::EnvFiltR,
 };
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mod_dest_from_u16() {
+        for (i, dest) in ModDest::elements().enumerate() {
+            assert!(ModDest::try_from(i as u16) == Ok(dest));
+        }
+        assert!(ModDest::try_from(ModDest::numel() as u16).is_err());
+        assert!(ModDest::try_from(u16::MAX).is_err());
+    }
+
+    #[test]
+    fn mod_dest_elements_match_discriminants() {
+        // ELEM must list every discriminant exactly once, in order
+        assert_eq!(ModDest::elements().count(), ModDest::numel());
+        for (i, dest) in ModDest::elements().enumerate() {
+            assert_eq!(dest as usize, i);
+        }
+        // The secondary destinations are exactly the range after
+        // max_secondary() up to the first primary destination
+        let secondary = (ModDest::max_secondary() as u16 + 1)..(ModDest::Lfo1Rate as u16);
+        let mut expected = ModDest::elements().filter(|d| !secondary.contains(&(*d as u16)));
+        assert!(ModDest::elements_secondary_if(true).eq(&mut expected));
+        assert!(ModDest::elements_secondary_if(false).eq(ModDest::elements()));
+        assert!(ModDest::elements_secondary().all(|d| d.remove_secondary_invalid_dest() == d));
+    }
+
+    #[test]
+    fn mod_dest_discriminants_are_stable() {
+        // Saved patches refer to destinations by these values
+        assert_eq!(ModDest::Osc1Saw as u16, 7);
+        assert_eq!(ModDest::Osc2Course as u16, 8);
+        assert_eq!(ModDest::RingOsc1 as u16, 15);
+        assert_eq!(ModDest::FiltCutoff as u16, 18);
+        assert_eq!(ModDest::EnvFiltA as u16, 26);
+        assert_eq!(ModDest::EnvAmpR as u16, 33);
+        assert_eq!(ModDest::Lfo2Rate as u16, 34);
+        assert_eq!(ModDest::Env2R as u16, 39);
+        assert_eq!(ModDest::Lfo1Rate as u16, 40);
+        assert_eq!(ModDest::Env1R as u16, 45);
+    }
+}
@@ -6,9 +6,31 @@ extern "C" {
 
 #include <stdint.h>
 
+/* The sample_rate argument of the fixed point (i16) process functions is
+ * either one of these presets, or any other host sample rate in Hz.  Other
+ * rates run at the nearest preset, with pitches and envelope times corrected
+ * so they sound the same.  Rates too far from the presets to correct (below
+ * about 1.1 kHz) are rejected with a return value of -1.  The corrected
+ * pitches saturate at the ends of the note range, so above the presets the
+ * lowest notes all play at note 0 (e.g. notes below 12 at 96 kHz), and well
+ * below them the highest notes are clamped likewise. */
 extern uint32_t CULSYNTH_SR_480;
 extern uint32_t CULSYNTH_SR_441;
 
+/* If enable is nonzero, the f32 process functions replace any non-finite
+ * (NaN or infinite) output samples with zero.  Disabled by default. */
+void culsynth_set_sanitize_output(uint8_t enable);
+/* Returns the number of output samples replaced since the last call, and
+ * resets the count */
+uint32_t culsynth_take_nonfinite_count();
+/* Returns the largest magnitude audio output sample (as a float, where 1.0
+ * is full scale) written by the amp, filter and oscillator process
+ * functions since the last call, and resets it */
+float culsynth_take_peak_level();
+/* Returns nonzero if any audio output sample reached full scale (1.0) since
+ * the last call, and resets the flag */
+uint8_t culsynth_take_clipped();
+
 void* culsynth_amp_i16_new();
 void culsynth_amp_i16_free(void*);
 int32_t culsynth_amp_i16_process(
@@ -1,28 +1,38 @@
+// Documentation (including safety requirements) lives in include/culsynth.h
+#![allow(clippy::missing_safety_doc)]
+
 use culsynth::context::{Context, ContextFxP};
 use culsynth::devices::*;
-use culsynth::{EnvParamFxP, NoteFxP, SampleFxP, ScalarFxP, SignedNoteFxP};
+use culsynth::{DspFormatBase, EnvParamFxP, NoteFxP, SampleFxP, ScalarFxP, SignedNoteFxP};
+use fixed::types::U16F16;
 
 use core::iter::zip;
+use core::sync::atomic::{AtomicBool, AtomicU32, Ordering};
 
 struct PtrIterator<T> {
     data: *mut T,
+    len: usize,
 }
 
 impl<T> PtrIterator<T> {
-    unsafe fn new(data: *mut T) -> Self {
-        Self { data }
+    /// Iterate over the `len` elements starting at `data`
+    unsafe fn new(data: *mut T, len: usize) -> Self {
+        Self { data, len }
     }
 }
 
 // This blows a gigantic hole through the borrow checker.  Only use this local
 // to a specific function otherwise memory leaks are *bound* to happen
-// TODO:  Make this less of a footgun
 impl<T: 'static> Iterator for PtrIterator<T> {
     type Item = &'static mut T;
     fn next(&mut self) -> Option<&'static mut T> {
+        if self.len == 0 {
+            return None;
+        }
         unsafe {
             let ret = &mut *(self.data);
             self.data = self.data.add(1);
+            self.len -= 1;
             Some(ret)
         }
     }
@@ -46,11 +56,127 @@ pub unsafe extern "C" fn culsynth_get_sr_441() -> u32 {
     SR_441_VAL
 }
 
-fn contextfxp_from_u32(sr: u32) -> Option<ContextFxP> {
+/// A fixed point context for a host sample rate, along with the adjustments
+/// needed to run the fixed point devices at a rate other than the one their
+/// context was built for
+struct FixedRate {
+    ctx: ContextFxP,
+    /// Added to each pitch so it sounds at the correct frequency
+    note_offset: SignedNoteFxP,
+    /// Applied to each time so it lasts the correct duration
+    time_scale: U16F16,
+}
+
+impl FixedRate {
+    fn new(ctx: ContextFxP) -> Self {
+        Self {
+            ctx,
+            note_offset: SignedNoteFxP::ZERO,
+            time_scale: U16F16::ONE,
+        }
+    }
+    /// Adapt an arbitrary host sample rate, in Hz, to the nearest supported
+    /// fixed point rate.  Returns `None` if the pitch correction is out of
+    /// range.
+    fn adapt(host_sr: u32) -> Option<Self> {
+        let ctx = if host_sr.abs_diff(48000) < host_sr.abs_diff(44100) {
+            ContextFxP::new_480()
+        } else {
+            ContextFxP::new_441()
+        };
+        let ctx_sr = ctx.sample_rate.value();
+        let ratio = ctx_sr as f32 / host_sr as f32;
+        let note_offset = SignedNoteFxP::checked_from_num(12f32 * ratio.log2())?;
+        // host_sr / ctx_sr, which fits easily given the note offset fits
+        let time_scale = ((host_sr as u64) << 16) / ctx_sr as u64;
+        Some(Self {
+            ctx,
+            note_offset,
+            time_scale: U16F16::from_bits(time_scale as u32),
+        })
+    }
+    /// Correct a pitch for the host rate.  This saturates at the ends of the
+    /// note range, so e.g. at 96kHz all notes below 12 play as note 0.
+    fn note(&self, note: NoteFxP) -> NoteFxP {
+        i16::apply_note_offset(note, self.note_offset)
+    }
+    fn time(&self, time: EnvParamFxP) -> EnvParamFxP {
+        EnvParamFxP::saturating_from_num(U16F16::from_num(time).wide_mul(self.time_scale))
+    }
+}
+
+/// Interpret the sample rate argument of a fixed point process function,
+/// which is either one of the preset rates or a host sample rate in Hz
+fn contextfxp_from_u32(sr: u32) -> Option<FixedRate> {
     match sr {
-        SR_480_VAL => Some(ContextFxP::new_480()),
-        SR_441_VAL => Some(ContextFxP::new_441()),
-        _ => None,
+        SR_480_VAL => Some(FixedRate::new(ContextFxP::new_480())),
+        SR_441_VAL => Some(FixedRate::new(ContextFxP::new_441())),
+        _ => FixedRate::adapt(sr),
+    }
+}
+
+static SANITIZE_OUTPUT: AtomicBool = AtomicBool::new(false);
+static NONFINITE_COUNT: AtomicU32 = AtomicU32::new(0);
+
+#[no_mangle]
+pub extern "C" fn culsynth_set_sanitize_output(enable: u8) {
+    SANITIZE_OUTPUT.store(enable != 0, Ordering::Relaxed);
+}
+
+#[no_mangle]
+pub extern "C" fn culsynth_take_nonfinite_count() -> u32 {
+    NONFINITE_COUNT.swap(0, Ordering::Relaxed)
+}
+
+/// If output sanitization is enabled, replace a non-finite sample with zero
+/// and count it
+fn sanitize(smp: f32) -> f32 {
+    if smp.is_finite() || !SANITIZE_OUTPUT.load(Ordering::Relaxed) {
+        return smp;
+    }
+    NONFINITE_COUNT.fetch_add(1, Ordering::Relaxed);
+    0f32
+}
+
+/// The largest magnitude audio output sample since the last call to
+/// [culsynth_take_peak_level], stored as the bits of an `f32`.  Comparing
+/// the bits of non-negative floats as integers preserves their order.
+static PEAK_LEVEL: AtomicU32 = AtomicU32::new(0);
+static CLIPPED: AtomicBool = AtomicBool::new(false);
+
+#[no_mangle]
+pub extern "C" fn culsynth_take_peak_level() -> f32 {
+    f32::from_bits(PEAK_LEVEL.swap(0, Ordering::Relaxed))
+}
+
+#[no_mangle]
+pub extern "C" fn culsynth_take_clipped() -> u8 {
+    CLIPPED.swap(false, Ordering::Relaxed) as u8
+}
+
+/// Tracks the largest magnitude output sample of a single process call, so
+/// the shared meter is only updated once per buffer
+#[derive(Default)]
+struct PeakMeter {
+    peak: f32,
+}
+
+impl PeakMeter {
+    fn f32(&mut self, smp: f32) -> f32 {
+        self.peak = self.peak.max(smp.abs());
+        smp
+    }
+    fn fxp(&mut self, smp: SampleFxP) -> i16 {
+        self.peak = self.peak.max(smp.unsigned_abs().to_num());
+        smp.to_bits()
+    }
+    /// Update the shared peak level, and set the clip flag if any sample
+    /// reached full scale
+    fn publish(self) {
+        PEAK_LEVEL.fetch_max(self.peak.to_bits(), Ordering::Relaxed);
+        if self.peak >= 1f32 {
+            CLIPPED.store(true, Ordering::Relaxed);
+        }
     }
 }
 
@@ -79,19 +205,21 @@ pub unsafe extern "C" fn culsynth_amp_i16_process(
         return -1;
     }
     let context = match contextfxp_from_u32(sr) {
-        Some(x) => x,
+        Some(x) => x.ctx,
         None => return -1,
     };
     let s = core::slice::from_raw_parts(signal.cast::<SampleFxP>(), samples as usize);
     let g = core::slice::from_raw_parts(gain.cast::<ScalarFxP>(), samples as usize);
+    let mut meter = PeakMeter::default();
     let mut processed = 0i32;
     for (o, smp) in zip(
-        PtrIterator::new(out),
-        (*p).process(&context, s.iter().copied(), g.into_iter().copied()),
+        PtrIterator::new(out, samples as usize),
+        (*p).process(&context, s.iter().copied(), g.iter().copied()),
     ) {
-        *o = smp.to_bits();
+        *o = meter.fxp(smp);
         processed += 1;
     }
+    meter.publish();
     processed
 }
 
@@ -121,20 +249,22 @@ pub unsafe extern "C" fn culsynth_amp_f32_process(
     }
     let s = core::slice::from_raw_parts(signal, samples as usize);
     let g = core::slice::from_raw_parts(gain, samples as usize);
+    let mut meter = PeakMeter::default();
     let mut processed = 0i32;
     for (o, smp) in zip(
-        PtrIterator::new(out),
+        PtrIterator::new(out, samples as usize),
         (*p).process(&Context::new(sr), s.iter().copied(), g.iter().copied()),
     ) {
-        *o = smp;
+        *o = meter.f32(sanitize(smp));
         processed += 1;
     }
+    meter.publish();
     processed
 }
 
 #[no_mangle]
 pub extern "C" fn culsynth_env_i16_new() -> *mut Env<i16> {
-    Box::into_raw(Box::new(Default::default()))
+    Box::into_raw(Box::default())
 }
 
 #[no_mangle]
@@ -166,7 +296,7 @@ pub unsafe extern "C" fn culsynth_env_i16_process(
     {
         return -1;
     }
-    let context = match contextfxp_from_u32(sr) {
+    let rate = match contextfxp_from_u32(sr) {
         Some(x) => x,
         None => return -1,
     };
@@ -176,13 +306,13 @@ pub unsafe extern "C" fn culsynth_env_i16_process(
     let s = core::slice::from_raw_parts(sustain.cast::<ScalarFxP>(), samples as usize);
     let r = core::slice::from_raw_parts(release.cast::<EnvParamFxP>(), samples as usize);
     let paramiter = new_env_param_iter()
-        .with_attack(a.iter().copied())
-        .with_decay(d.iter().copied())
+        .with_attack(a.iter().map(|x| rate.time(*x)))
+        .with_decay(d.iter().map(|x| rate.time(*x)))
         .with_sustain(s.iter().copied())
-        .with_release(r.iter().copied());
-    let out = (*p).process(&context, g.iter().map(|x| *x != 0), paramiter);
+        .with_release(r.iter().map(|x| rate.time(*x)));
+    let out = (*p).process(&rate.ctx, g.iter().map(|x| *x != 0), paramiter);
     let mut processed = 0i32;
-    for (o, smp) in zip(PtrIterator::new(signal), out) {
+    for (o, smp) in zip(PtrIterator::new(signal, samples as usize), out) {
         *o = smp.to_bits();
         processed += 1;
     }
@@ -233,11 +363,11 @@ pub unsafe extern "C" fn culsynth_env_f32_process(
         .with_decay(d.iter().copied())
         .with_sustain(s.iter().copied())
         .with_release(r.iter().copied());
-    let ctx = Context::<f32> { sample_rate: sr };
+    let ctx = Context::<f32>::new(sr);
     let out = (*p).process(&ctx, g.iter().map(|x| *x != 0), paramiter);
     let mut processed = 0i32;
-    for (o, smp) in zip(PtrIterator::new(signal), out) {
-        *o = smp;
+    for (o, smp) in zip(PtrIterator::new(signal, samples as usize), out) {
+        *o = sanitize(smp);
         processed += 1;
     }
     processed
@@ -277,10 +407,10 @@ pub unsafe extern "C" fn culsynth_filt_i16_process(
     {
         return -1;
     }
-    let low = PtrIterator::new(low);
-    let band = PtrIterator::new(band);
-    let high = PtrIterator::new(high);
-    let ctx = match contextfxp_from_u32(sr) {
+    let low = PtrIterator::new(low, samples as usize);
+    let band = PtrIterator::new(band, samples as usize);
+    let high = PtrIterator::new(high, samples as usize);
+    let rate = match contextfxp_from_u32(sr) {
         Some(x) => x,
         None => return -1,
     };
@@ -288,16 +418,18 @@ pub unsafe extern "C" fn culsynth_filt_i16_process(
     let c = core::slice::from_raw_parts(cutoff.cast::<NoteFxP>(), samples as usize);
     let r = core::slice::from_raw_parts(resonance.cast::<ScalarFxP>(), samples as usize);
     let params = new_filt_param_iter()
-        .with_cutoff(c.iter().copied())
+        .with_cutoff(c.iter().map(|x| rate.note(*x)))
         .with_resonance(r.iter().copied());
-    let out = (*p).process(&ctx, i.iter().copied(), params);
+    let out = (*p).process(&rate.ctx, i.iter().copied(), params);
+    let mut meter = PeakMeter::default();
     let mut processed = 0i32;
     for (l, (b, (h, o))) in zip(low, zip(band, zip(high, out))) {
-        *l = o.low.to_bits();
-        *b = o.band.to_bits();
-        *h = o.high.to_bits();
+        *l = meter.fxp(o.low);
+        *b = meter.fxp(o.band);
+        *h = meter.fxp(o.high);
         processed += 1;
     }
+    meter.publish();
     processed
 }
 
@@ -335,24 +467,26 @@ pub unsafe extern "C" fn culsynth_filt_f32_process(
     {
         return -1;
     }
-    let low = PtrIterator::new(low);
-    let band = PtrIterator::new(band);
-    let high = PtrIterator::new(high);
+    let low = PtrIterator::new(low, samples as usize);
+    let band = PtrIterator::new(band, samples as usize);
+    let high = PtrIterator::new(high, samples as usize);
     let i = core::slice::from_raw_parts(input, samples as usize);
     let c = core::slice::from_raw_parts(cutoff, samples as usize);
     let r = core::slice::from_raw_parts(resonance, samples as usize);
     let params = new_filt_param_iter()
         .with_cutoff(c.iter().copied())
         .with_resonance(r.iter().copied());
-    let ctx = Context::<f32> { sample_rate: sr };
+    let ctx = Context::<f32>::new(sr);
     let out = (*p).process(&ctx, i.iter().copied(), params);
+    let mut meter = PeakMeter::default();
     let mut processed = 0i32;
     for (l, (b, (h, o))) in zip(low, zip(band, zip(high, out))) {
-        *l = o.low;
-        *b = o.band;
-        *h = o.high;
+        *l = meter.f32(sanitize(o.low));
+        *b = meter.f32(sanitize(o.band));
+        *h = meter.f32(sanitize(o.high));
         processed += 1;
     }
+    meter.publish();
     processed
 }
 
@@ -391,11 +525,11 @@ pub unsafe extern "C" fn culsynth_osc_i16_process(
     {
         return -1;
     }
-    let sin = PtrIterator::new(sin);
-    let tri = PtrIterator::new(tri);
-    let sq = PtrIterator::new(sq);
-    let saw = PtrIterator::new(saw);
-    let ctx = match contextfxp_from_u32(sr) {
+    let sin = PtrIterator::new(sin, samples as usize);
+    let tri = PtrIterator::new(tri, samples as usize);
+    let sq = PtrIterator::new(sq, samples as usize);
+    let saw = PtrIterator::new(saw, samples as usize);
+    let rate = match contextfxp_from_u32(sr) {
         Some(x) => x,
         None => return -1,
     };
@@ -405,15 +539,17 @@ pub unsafe extern "C" fn culsynth_osc_i16_process(
     let params = new_osc_param_iter()
         .with_tune(tune_s.iter().copied())
         .with_shape(shape_s.iter().copied());
-    let out = (*p).process(&ctx, note_s.iter().copied(), params);
+    let out = (*p).process(&rate.ctx, note_s.iter().map(|x| rate.note(*x)), params);
+    let mut meter = PeakMeter::default();
     let mut processed = 0i32;
     for (n, (t, (q, (s, o)))) in zip(sin, zip(tri, zip(sq, zip(saw, out)))) {
-        *n = o.sin.to_bits();
-        *t = o.tri.to_bits();
-        *q = o.sq.to_bits();
-        *s = o.saw.to_bits();
+        *n = meter.fxp(o.sin);
+        *t = meter.fxp(o.tri);
+        *q = meter.fxp(o.sq);
+        *s = meter.fxp(o.saw);
         processed += 1;
     }
+    meter.publish();
     processed
 }
 
@@ -453,25 +589,174 @@ pub unsafe extern "C" fn culsynth_osc_f32_process(
     {
         return -1;
     }
-    let sin = PtrIterator::new(sin);
-    let tri = PtrIterator::new(tri);
-    let sq = PtrIterator::new(sq);
-    let saw = PtrIterator::new(saw);
+    let sin = PtrIterator::new(sin, samples as usize);
+    let tri = PtrIterator::new(tri, samples as usize);
+    let sq = PtrIterator::new(sq, samples as usize);
+    let saw = PtrIterator::new(saw, samples as usize);
     let note_s = core::slice::from_raw_parts(note, samples as usize);
     let shape_s = core::slice::from_raw_parts(shape, samples as usize);
     let tune_s = core::slice::from_raw_parts(tune, samples as usize);
     let params = new_osc_param_iter()
         .with_tune(tune_s.iter().copied())
         .with_shape(shape_s.iter().copied());
-    let ctx = Context::<f32> { sample_rate: sr };
+    let ctx = Context::<f32>::new(sr);
     let out = (*p).process(&ctx, note_s.iter().copied(), params);
+    let mut meter = PeakMeter::default();
     let mut processed = 0i32;
     for (n, (t, (q, (s, o)))) in zip(sin, zip(tri, zip(sq, zip(saw, out)))) {
-        *n = o.sin;
-        *t = o.tri;
-        *q = o.sq;
-        *s = o.saw;
+        *n = meter.f32(sanitize(o.sin));
+        *t = meter.f32(sanitize(o.tri));
+        *q = meter.f32(sanitize(o.sq));
+        *s = meter.f32(sanitize(o.saw));
         processed += 1;
     }
+    meter.publish();
     processed
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Mutex, MutexGuard};
+
+    /// The output sanitizing, nonfinite count and peak meter are global, so
+    /// tests that process audio hold this to avoid racing with each other
+    static GLOBALS: Mutex<()> = Mutex::new(());
+
+    fn lock_globals() -> MutexGuard<'static, ()> {
+        // A failed test poisons the lock, which should not fail the others
+        GLOBALS.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    #[test]
+    fn ptr_iterator_bounded() {
+        let mut buf = [0i16; 4];
+        let mut written = 0;
+        for (o, x) in zip(unsafe { PtrIterator::new(buf.as_mut_ptr(), 2) }, 1..10) {
+            *o = x;
+            written += 1;
+        }
+        assert_eq!(written, 2);
+        assert_eq!(buf, [1, 2, 0, 0]);
+    }
+
+    #[test]
+    fn sanitized_output_is_finite() {
+        let _globals = lock_globals();
+        // inf * 0 is NaN
+        let signal = [f32::INFINITY, 0.5f32, f32::INFINITY];
+        let gain = [0f32, 0.5f32, 0f32];
+        let mut out = [0f32; 3];
+        culsynth_set_sanitize_output(1);
+        let amp = culsynth_amp_f32_new();
+        let processed = unsafe {
+            culsynth_amp_f32_process(
+                amp,
+                48000f32,
+                3,
+                signal.as_ptr(),
+                gain.as_ptr(),
+                out.as_mut_ptr(),
+            )
+        };
+        unsafe { culsynth_amp_f32_free(amp) };
+        culsynth_set_sanitize_output(0);
+        assert_eq!(processed, 3);
+        assert!(out.iter().all(|x| x.is_finite()));
+        assert_eq!(out[1], 0.25f32);
+        assert_eq!(culsynth_take_nonfinite_count(), 2);
+        assert_eq!(culsynth_take_nonfinite_count(), 0);
+    }
+
+    #[test]
+    fn peak_level_and_clip_flag() {
+        let _globals = lock_globals();
+        let signal = [0.5f32, -1.5f32, 1.25f32, 0f32];
+        let gain = [1f32; 4];
+        let amp = culsynth_amp_f32_new();
+        let process = |len| unsafe {
+            let mut out = [0f32; 4];
+            culsynth_take_peak_level();
+            culsynth_take_clipped();
+            culsynth_amp_f32_process(
+                amp,
+                48000f32,
+                len,
+                signal.as_ptr(),
+                gain.as_ptr(),
+                out.as_mut_ptr(),
+            );
+            let max = out.iter().fold(0f32, |acc, x| acc.max(x.abs()));
+            (culsynth_take_peak_level(), culsynth_take_clipped(), max)
+        };
+        let (peak, clipped, max) = process(4);
+        assert_eq!((peak, clipped), (max, 1));
+        assert_eq!(peak, 1.5f32);
+        // Taking the level resets it
+        assert_eq!(culsynth_take_clipped(), 0);
+        assert_eq!(process(1), (0.5f32, 0, 0.5f32));
+        unsafe { culsynth_amp_f32_free(amp) };
+
+        let signal = [SampleFxP::lit("0.25"), SampleFxP::lit("-2")].map(|x| x.to_bits());
+        let gain = [u16::MAX; 2];
+        let mut out = [0i16; 2];
+        let amp = culsynth_amp_i16_new();
+        culsynth_take_peak_level();
+        culsynth_take_clipped();
+        unsafe {
+            culsynth_amp_i16_process(
+                amp,
+                CULSYNTH_SR_480,
+                2,
+                signal.as_ptr(),
+                gain.as_ptr(),
+                out.as_mut_ptr(),
+            );
+            culsynth_amp_i16_free(amp);
+        }
+        let max = out.iter().map(|x| SampleFxP::from_bits(*x).unsigned_abs()).max();
+        assert_eq!(culsynth_take_peak_level(), max.unwrap().to_num::<f32>());
+        assert_eq!(culsynth_take_clipped(), 1);
+    }
+
+    #[test]
+    fn adapted_rate_keeps_pitch() {
+        let _globals = lock_globals();
+        // One second of A440 at a host rate with no fixed point preset
+        const SR: u32 = 32000;
+        let note = vec![NoteFxP::lit("69").to_bits(); SR as usize];
+        let zero = vec![0u16; SR as usize];
+        let mut sin = vec![0i16; SR as usize];
+        let mut other = vec![0i16; SR as usize];
+        let osc = culsynth_osc_i16_new();
+        let processed = unsafe {
+            culsynth_osc_i16_process(
+                osc,
+                SR,
+                SR,
+                note.as_ptr(),
+                zero.as_ptr().cast(),
+                zero.as_ptr(),
+                sin.as_mut_ptr(),
+                other.as_mut_ptr(),
+                other.as_mut_ptr(),
+                other.as_mut_ptr(),
+            )
+        };
+        unsafe { culsynth_osc_i16_free(osc) };
+        assert_eq!(processed, SR as i32);
+        let crossings = sin.windows(2).filter(|w| w[0] < 0 && w[1] >= 0).count();
+        assert!(crossings.abs_diff(440) <= 1, "{crossings}");
+        // Rates too far from the presets to correct are rejected
+        assert!(contextfxp_from_u32(100).is_none());
+        assert!(contextfxp_from_u32(1000).is_none());
+        assert!(contextfxp_from_u32(1200).is_some());
+        // Times scale with the host rate, and pitches saturate at the ends of
+        // the note range
+        let rate = contextfxp_from_u32(96000).unwrap();
+        assert_eq!(rate.time(EnvParamFxP::lit("0.5")), EnvParamFxP::ONE);
+        assert_eq!(rate.time(EnvParamFxP::lit("5")), EnvParamFxP::MAX);
+        assert_eq!(rate.note(NoteFxP::lit("72")), NoteFxP::lit("60"));
+        assert_eq!(rate.note(NoteFxP::lit("6")), NoteFxP::ZERO);
+    }
+}
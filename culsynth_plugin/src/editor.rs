@@ -178,16 +178,9 @@ impl CulSynthEditor {
                 egui::ComboBox::from_id_source("MonoPoly")
                     .selected_text(voice_mode.to_str())
                     .show_ui(ui, |ui| {
-                        ui.selectable_value(
-                            &mut new_voice_mode,
-                            VoiceMode::Mono,
-                            VoiceMode::Mono.to_str(),
-                        );
-                        ui.selectable_value(
-                            &mut new_voice_mode,
-                            VoiceMode::Poly16,
-                            VoiceMode::Poly16.to_str(),
-                        );
+                        for mode in VoiceMode::ELEM {
+                            ui.selectable_value(&mut new_voice_mode, mode, mode.to_str());
+                        }
                     });
             });
         });
@@ -196,16 +189,17 @@ impl CulSynthEditor {
                 fixed_context.map(|ctx| {
                     let ret: Box<dyn VoiceAllocator> = match new_voice_mode {
                         VoiceMode::Mono => Box::new(MonoSynth::<i16>::new(ctx)),
-                        VoiceMode::Poly16 => Box::new(PolySynth::<i16>::new(ctx, 16)),
+                        mode => Box::new(PolySynth::<i16>::new(ctx, mode.num_voices())),
                     };
                     ret
                 })
             } else {
                 Some(match new_voice_mode {
                     VoiceMode::Mono => Box::new(MonoSynth::<f32>::new(Context::new(sr as f32))),
-                    VoiceMode::Poly16 => {
-                        Box::new(PolySynth::<f32>::new(Context::new(sr as f32), 16))
-                    }
+                    mode => Box::new(PolySynth::<f32>::new(
+                        Context::new(sr as f32),
+                        mode.num_voices(),
+                    )),
                 })
             }
         } else {
@@ -231,8 +225,7 @@ impl CulSynthEditor {
                         egui::ComboBox::from_id_source(id_str)
                             .selected_text(dest.to_str())
                             .show_ui(ui, |ui| {
-                                let sec = row.is_secondary();
-                                for value in ModDest::elements_secondary_if(sec) {
+                                for value in ModDest::elements_for(*src) {
                                     ui.selectable_value(&mut dest, value, value.to_str());
                                 }
                             });
@@ -1,6 +1,7 @@
 use std::sync::Arc;
 
-use culsynth::{EnvParamFxP, Fixed16, LfoFreqFxP, NoteFxP, ScalarFxP};
+use culsynth::voice::modulation::{ModDest, ParamUnit};
+use culsynth::{EnvParamFxP, Fixed16, LfoFreqFxP, NoteFxP, ScalarFxP, SignedNoteFxP};
 use fixed::traits::Fixed;
 use lazy_static::lazy_static;
 use nih_plug::prelude::*;
@@ -40,7 +41,8 @@ fn fixed_s2v_percent(s: &str) -> Option<i32> {
 }
 
 fn fixed_v2s_freq(x: i32) -> String {
-    let mut freq = culsynth::midi_note_to_frequency(NoteFxP::from_bits(x as u16));
+    let note = NoteFxP::from_bits(x as u16);
+    let mut freq = culsynth::midi_note_to_frequency(note);
     let khz = freq > 1000;
     if khz {
         freq /= 1000;
@@ -53,10 +55,13 @@ fn fixed_v2s_freq(x: i32) -> String {
         format!("{:.2}", freq)
     };
     s += if khz { " kHz" } else { " Hz" };
-    s
+    let (name, cents) = culsynth::midi_const::note_name(note);
+    s + &format!(" ({name} {cents:+} cents)")
 }
 
 fn fixed_s2v_freq(s: &str) -> Option<i32> {
+    // Ignore the note name appended by fixed_v2s_freq
+    let s = s.split('(').next().unwrap_or(s).trim();
     let groups = FREQ_REGEX.captures(s)?;
     let khz = groups.get(2)?.len() == 3; //khz is 3 chars
     let mut freq = groups.get(1)?.as_str().parse::<f32>().ok()?;
@@ -169,18 +174,52 @@ pub fn new_fixed_param_percent(name: impl Into<String>, default: ScalarFxP) -> I
     .with_string_to_value(Arc::new(fixed_s2v_percent))
 }
 
-/// Helper function to create a new `nih_plug::IntParam` for a [`NoteFxP`]
-/// as a frequency (maps the fixed point number to the MIDI tuning range in Hz)
-pub fn new_fixed_param_freq(name: impl Into<String>, default: NoteFxP) -> IntParam {
-    IntParam::new(
+/// Helper function to create a new `nih_plug::IntParam` for the parameter
+/// targeted by `dest`.  The range and display format come from the backend's
+/// [`ModDest::param_info`], so they are not hardcoded separately here.
+pub fn new_fixed_param_dest<F: Fixed>(
+    name: impl Into<String>,
+    dest: ModDest,
+    default: F,
+) -> IntParam
+where
+    F::Bits: Into<i32>,
+{
+    let info = dest.param_info();
+    // The raw fixed point representation of a value, as for new_fixed_param
+    let to_bits = |x: f32| -> i32 {
+        match info.unit {
+            ParamUnit::Ratio => ScalarFxP::saturating_from_num(x).to_bits().into(),
+            ParamUnit::Semitones => SignedNoteFxP::saturating_from_num(x).to_bits().into(),
+            ParamUnit::Note => NoteFxP::saturating_from_num(x).to_bits().into(),
+            ParamUnit::Seconds => EnvParamFxP::saturating_from_num(x).to_bits().into(),
+            ParamUnit::Hertz => LfoFreqFxP::saturating_from_num(x).to_bits().into(),
+        }
+    };
+    let param = IntParam::new(
         name,
         default.to_bits().into(),
         IntRange::Linear {
-            min: NoteFxP::MIN.to_bits().into(),
-            max: NoteFxP::MAX.to_bits().into(),
+            min: to_bits(info.min),
+            max: to_bits(info.max),
         },
     )
-    .with_smoother(SmoothingStyle::Linear(50.0))
-    .with_value_to_string(Arc::new(fixed_v2s_freq))
-    .with_string_to_value(Arc::new(fixed_s2v_freq))
+    .with_smoother(SmoothingStyle::Linear(50.0));
+    match info.unit {
+        ParamUnit::Ratio => param
+            .with_value_to_string(Arc::new(fixed_v2s_percent))
+            .with_string_to_value(Arc::new(fixed_s2v_percent)),
+        ParamUnit::Semitones => param
+            .with_value_to_string(Arc::new(fixed_v2s::<SignedNoteFxP>))
+            .with_string_to_value(Arc::new(fixed_s2v::<SignedNoteFxP>)),
+        ParamUnit::Note => param
+            .with_value_to_string(Arc::new(fixed_v2s_freq))
+            .with_string_to_value(Arc::new(fixed_s2v_freq)),
+        ParamUnit::Seconds => param
+            .with_value_to_string(Arc::new(fixed_v2s_time::<EnvParamFxP>))
+            .with_string_to_value(Arc::new(fixed_s2v_time::<EnvParamFxP>)),
+        ParamUnit::Hertz => param
+            .with_value_to_string(Arc::new(fixed_v2s_time::<LfoFreqFxP>))
+            .with_string_to_value(Arc::new(fixed_s2v_time::<LfoFreqFxP>)),
+    }
 }
@@ -46,14 +46,61 @@ const VERSION: &'static str = env!("CARGO_PKG_VERSION");
 pub enum VoiceMode {
     #[default]
     Mono,
+    Poly2,
+    Poly4,
+    Poly8,
     Poly16,
+    Poly32,
 }
 
 impl VoiceMode {
+    /// All of the voice modes, in order
+    pub const ELEM: [VoiceMode; 6] = [
+        Self::Mono,
+        Self::Poly2,
+        Self::Poly4,
+        Self::Poly8,
+        Self::Poly16,
+        Self::Poly32,
+    ];
+    /// Convert from the raw discriminant, falling back to `VoiceMode::Mono`
+    /// for unknown values
+    pub fn from_u8(value: u8) -> Self {
+        Self::ELEM
+            .into_iter()
+            .find(|mode| *mode as u8 == value)
+            .unwrap_or_default()
+    }
+    /// The voice mode of an existing voice allocator, falling back to
+    /// `VoiceMode::Mono` if the number of voices isn't one of the modes
+    fn from_voice_alloc(voices: &dyn VoiceAllocator) -> Self {
+        if !voices.is_poly() {
+            return Self::Mono;
+        }
+        Self::ELEM
+            .into_iter()
+            .find(|mode| *mode != Self::Mono && mode.num_voices() == voices.num_voices())
+            .unwrap_or_default()
+    }
+    /// The number of voices for this mode
+    pub fn num_voices(&self) -> usize {
+        match self {
+            Self::Mono => 1,
+            Self::Poly2 => 2,
+            Self::Poly4 => 4,
+            Self::Poly8 => 8,
+            Self::Poly16 => 16,
+            Self::Poly32 => 32,
+        }
+    }
     pub fn to_str(&self) -> &'static str {
         match self {
             Self::Mono => "Mono",
+            Self::Poly2 => "Poly2",
+            Self::Poly4 => "Poly4",
+            Self::Poly8 => "Poly8",
             Self::Poly16 => "Poly16",
+            Self::Poly32 => "Poly32",
         }
     }
 }
@@ -102,6 +149,24 @@ impl ContextReader {
     }
     pub fn voice_mode(&self) -> VoiceMode {
         let mode_u32 = self.context.voice_mode.load(Relaxed);
-        unsafe { std::mem::transmute((mode_u32 & 0xFF) as u8) }
+        VoiceMode::from_u8((mode_u32 & 0xFF) as u8)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn invalid_voice_mode_is_mono() {
+        let reader = ContextReader {
+            context: Arc::new(PluginContext::default()),
+        };
+        reader.context.voice_mode.store(VoiceMode::Poly16 as u32, Relaxed);
+        assert!(reader.voice_mode() == VoiceMode::Poly16);
+        reader.context.voice_mode.store(VoiceMode::Poly32 as u32, Relaxed);
+        assert!(reader.voice_mode() == VoiceMode::Poly32);
+        reader.context.voice_mode.store(0xFF, Relaxed);
+        assert!(reader.voice_mode() == VoiceMode::Mono);
     }
 }
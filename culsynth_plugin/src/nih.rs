@@ -1,3 +1,4 @@
+use crate::voicealloc::{render_frame, OutputLayout};
 use crate::*;
 use culsynth::context::Context;
 use culsynth::voice::VoiceParams;
@@ -42,6 +43,9 @@ pub struct CulSynthPlugin {
     cc_rx: Option<Receiver<(u8, u8)>>,
 
     context: Arc<PluginContext>,
+
+    /// The layout of the main audio output (mono or stereo)
+    layout: OutputLayout,
 }
 
 impl CulSynthPlugin {
@@ -80,6 +84,7 @@ impl Default for CulSynthPlugin {
             cc_rx: Some(cc_rx),
             voices: None,
             context: Arc::new(Default::default()),
+            layout: Default::default(),
         }
     }
 }
@@ -133,7 +138,7 @@ impl Plugin for CulSynthPlugin {
 
     fn initialize(
         &mut self,
-        _audio_io_layout: &AudioIOLayout,
+        audio_io_layout: &AudioIOLayout,
         buffer_config: &BufferConfig,
         _context: &mut impl InitContext<Self>,
     ) -> bool {
@@ -155,15 +160,11 @@ impl Plugin for CulSynthPlugin {
             16,
         ));
         let ctx = voice_alloc.get_context();
-        self.update_context(
-            ctx,
-            if voice_alloc.is_poly() {
-                VoiceMode::Poly16
-            } else {
-                VoiceMode::Mono
-            },
-        );
+        self.update_context(ctx, VoiceMode::from_voice_alloc(voice_alloc.as_ref()));
         self.context.bufsz.store(bufsz, Relaxed);
+        self.layout = OutputLayout::from_channels(
+            audio_io_layout.main_output_channels.map_or(0, NonZeroU32::get),
+        );
         self.voices = Some(voice_alloc);
         true
     }
@@ -177,11 +178,7 @@ impl Plugin for CulSynthPlugin {
         if let Ok(synth) = self.synth_rx.try_recv() {
             self.update_context(
                 synth.get_context(),
-                if synth.is_poly() {
-                    VoiceMode::Poly16
-                } else {
-                    VoiceMode::Mono
-                },
+                VoiceMode::from_voice_alloc(synth.as_ref()),
             );
             self.voices = Some(synth);
         }
@@ -201,6 +198,7 @@ impl Plugin for CulSynthPlugin {
         let smps = buffer.iter_samples();
         let dispatcher: &mut SyncSender<(u8, u8)> = &mut self.cc_tx;
         let mut matrix = Some((&self.params.modmatrix).into());
+        let layout = self.layout;
         // Replace ProcessContext with a MidiReceiver
         let mut next_event = context.next_event();
         for (smpid, ch_smps) in smps.enumerate() {
@@ -232,10 +230,13 @@ impl Plugin for CulSynthPlugin {
                 }
                 next_event = context.next_event();
             }
-            let out = voices.next(&params, matrix.take().as_ref());
-            for smp in ch_smps {
-                *smp = out;
-            }
+            render_frame(
+                &mut **voices,
+                layout,
+                &params,
+                matrix.take().as_ref(),
+                ch_smps,
+            );
         }
         // To save resources, a plugin can (and probably should!) only perform expensive
         // calculations that are only displayed on the GUI while the GUI is open
@@ -1,5 +1,5 @@
 use culsynth::devices::{EnvParams, LfoParams, MixOscParams, ModFiltParams, RingModParams};
-use culsynth::devices::{LfoOptions, LfoWave, SyncedMixOscsParams};
+use culsynth::devices::{FilterPoles, LfoOptions, LfoWave, OscSyncMode, SyncedMixOscsParams};
 use culsynth::voice::modulation::{ModDest, ModMatrix, ModSrc};
 use culsynth::voice::VoiceParams;
 use culsynth::{EnvParamFxP, IScalarFxP, LfoFreqFxP, NoteFxP, ScalarFxP, SignedNoteFxP};
@@ -9,7 +9,7 @@ use nih_plug_egui::EguiState;
 use std::sync::Arc;
 
 use crate::fixedparam::{
-    new_fixed_param, new_fixed_param_env, new_fixed_param_freq, new_fixed_param_lfo,
+    new_fixed_param, new_fixed_param_dest, new_fixed_param_env, new_fixed_param_lfo,
     new_fixed_param_percent,
 };
 
@@ -76,6 +76,10 @@ impl From<&OscPluginParams> for MixOscParams<i16> {
             sq: ScalarFxP::from_bits(value.sq.smoothed.next() as u16),
             tri: ScalarFxP::from_bits(value.tri.smoothed.next() as u16),
             saw: ScalarFxP::from_bits(value.saw.smoothed.next() as u16),
+            osc_level: ScalarFxP::MAX,
+            wave_morph: None,
+            invert: false,
+            key_sync: false,
         }
     }
 }
@@ -134,6 +138,7 @@ impl From<&LfoPluginParams> for LfoParams<i16> {
             freq: LfoFreqFxP::from_bits(value.rate.smoothed.next() as u16),
             depth: ScalarFxP::from_bits(value.depth.smoothed.next() as u16),
             opts: value.into(),
+            phase_offset: ScalarFxP::ZERO,
         }
     }
 }
@@ -167,6 +172,7 @@ impl From<&RingModPluginParams> for RingModParams<i16> {
             mix_a: ScalarFxP::from_bits(value.mix_a.smoothed.next() as u16),
             mix_b: ScalarFxP::from_bits(value.mix_b.smoothed.next() as u16),
             mix_mod: ScalarFxP::from_bits(value.mix_mod.smoothed.next() as u16),
+            spread: ScalarFxP::ZERO,
         }
     }
 }
@@ -202,14 +208,26 @@ pub struct FiltPluginParams {
 impl Default for FiltPluginParams {
     fn default() -> Self {
         Self {
-            env: new_fixed_param_percent("Filter Envelope Modulation", ScalarFxP::ZERO),
-            kbd: new_fixed_param_percent("Filter Keyboard Tracking", ScalarFxP::ZERO),
-            vel: new_fixed_param_percent("Filter Velocity Modulation", ScalarFxP::ZERO),
-            cutoff: new_fixed_param_freq("Filter Cutoff", NoteFxP::lit("127")),
-            res: new_fixed_param_percent("Filter Resonance", ScalarFxP::ZERO),
-            low: new_fixed_param_percent("Filter Low Pass", ScalarFxP::MAX),
-            band: new_fixed_param_percent("Filter Band Pass", ScalarFxP::ZERO),
-            high: new_fixed_param_percent("Filter High Pass", ScalarFxP::ZERO),
+            env: new_fixed_param_dest(
+                "Filter Envelope Modulation",
+                ModDest::FiltEnv,
+                ScalarFxP::ZERO,
+            ),
+            kbd: new_fixed_param_dest(
+                "Filter Keyboard Tracking",
+                ModDest::FiltKbd,
+                ScalarFxP::ZERO,
+            ),
+            vel: new_fixed_param_dest(
+                "Filter Velocity Modulation",
+                ModDest::FiltVel,
+                ScalarFxP::ZERO,
+            ),
+            cutoff: new_fixed_param_dest("Filter Cutoff", ModDest::FiltCutoff, NoteFxP::lit("127")),
+            res: new_fixed_param_dest("Filter Resonance", ModDest::FiltRes, ScalarFxP::ZERO),
+            low: new_fixed_param_dest("Filter Low Pass", ModDest::FiltLow, ScalarFxP::MAX),
+            band: new_fixed_param_dest("Filter Band Pass", ModDest::FiltBand, ScalarFxP::ZERO),
+            high: new_fixed_param_dest("Filter High Pass", ModDest::FiltHigh, ScalarFxP::ZERO),
         }
     }
 }
@@ -219,12 +237,15 @@ impl From<&FiltPluginParams> for ModFiltParams<i16> {
         ModFiltParams {
             env_mod: ScalarFxP::from_bits(value.env.smoothed.next() as u16),
             vel_mod: ScalarFxP::from_bits(value.vel.smoothed.next() as u16),
+            env_vel: ScalarFxP::ZERO,
             kbd_tracking: ScalarFxP::from_bits(value.kbd.smoothed.next() as u16),
             cutoff: NoteFxP::from_bits(value.cutoff.smoothed.next() as u16),
             resonance: ScalarFxP::from_bits(value.res.smoothed.next() as u16),
             low_mix: ScalarFxP::from_bits(value.low.smoothed.next() as u16),
             band_mix: ScalarFxP::from_bits(value.band.smoothed.next() as u16),
             high_mix: ScalarFxP::from_bits(value.high.smoothed.next() as u16),
+            drive: ScalarFxP::ZERO,
+            poles: FilterPoles::Two,
         }
     }
 }
@@ -263,6 +284,10 @@ impl From<&EnvPluginParams> for EnvParams<i16> {
             decay: EnvParamFxP::from_bits(value.d.smoothed.next() as u16),
             sustain: ScalarFxP::from_bits(value.s.smoothed.next() as u16),
             release: EnvParamFxP::from_bits(value.r.smoothed.next() as u16),
+            vel_attack: IScalarFxP::ZERO,
+            key_decay: IScalarFxP::ZERO,
+            vel_release: IScalarFxP::ZERO,
+            ..Default::default()
         }
     }
 }
@@ -285,8 +310,6 @@ pub struct ModMatrixRowParams {
     pub d: IntParam,
     #[id = "DM"]
     pub d_magnitude: IntParam,
-
-    is_secondary: bool,
 }
 
 impl ModMatrixRowParams {
@@ -300,17 +323,10 @@ impl ModMatrixRowParams {
                 ModDest::try_from(string).map(|x| x as i32).ok()
             }))
     }
-    fn new(name: &str, is_secondary: bool) -> Self {
-        let rng = if is_secondary {
-            IntRange::Linear {
-                min: ModDest::min() as i32,
-                max: ModDest::max_secondary() as i32,
-            }
-        } else {
-            IntRange::Linear {
-                min: ModDest::min() as i32,
-                max: ModDest::max() as i32,
-            }
+    fn new(name: &str) -> Self {
+        let rng = IntRange::Linear {
+            min: ModDest::min() as i32,
+            max: ModDest::max() as i32,
         };
         Self {
             a: Self::make_param(name.to_owned() + " A", rng),
@@ -321,7 +337,6 @@ impl ModMatrixRowParams {
             c_magnitude: new_fixed_param(name.to_owned() + " C Mag", IScalarFxP::ZERO),
             d: Self::make_param(name.to_owned() + " D", rng),
             d_magnitude: new_fixed_param(name.to_owned() + " D Mag", IScalarFxP::ZERO),
-            is_secondary,
         }
     }
     pub fn slot(&self, idx: usize) -> (&IntParam, &IntParam) {
@@ -341,9 +356,6 @@ impl ModMatrixRowParams {
     pub fn iter(&self) -> ModMatrixRowIterator {
         ModMatrixRowIterator { row: self, idx: 0 }
     }
-    pub fn is_secondary(&self) -> bool {
-        self.is_secondary
-    }
 }
 
 pub struct ModMatrixRowIterator<'a> {
@@ -380,6 +392,12 @@ pub struct ModMatrixPluginParams {
     pub lfo1: ModMatrixRowParams,
     #[nested(id_prefix = "M_L2_", group = "L2Mod")]
     pub lfo2: ModMatrixRowParams,
+    #[nested(id_prefix = "M_RV_", group = "RelVelMod")]
+    pub release_velocity: ModMatrixRowParams,
+    #[nested(id_prefix = "M_P_", group = "PressMod")]
+    pub pressure: ModMatrixRowParams,
+    #[nested(id_prefix = "M_S_", group = "SlideMod")]
+    pub slide: ModMatrixRowParams,
 }
 
 impl Default for ModMatrixPluginParams {
@@ -391,13 +409,16 @@ impl Default for ModMatrixPluginParams {
 impl ModMatrixPluginParams {
     pub fn new() -> Self {
         Self {
-            velocity: ModMatrixRowParams::new("MM Velocity", false),
-            aftertouch: ModMatrixRowParams::new("MM Aftertouch", false),
-            modwheel: ModMatrixRowParams::new("MM Modwheel", false),
-            env1: ModMatrixRowParams::new("MM Env 1", false),
-            env2: ModMatrixRowParams::new("MM Env 2", true),
-            lfo1: ModMatrixRowParams::new("MM LFO 1", false),
-            lfo2: ModMatrixRowParams::new("MM LFO 2", true),
+            velocity: ModMatrixRowParams::new("MM Velocity"),
+            aftertouch: ModMatrixRowParams::new("MM Aftertouch"),
+            modwheel: ModMatrixRowParams::new("MM Modwheel"),
+            env1: ModMatrixRowParams::new("MM Env 1"),
+            env2: ModMatrixRowParams::new("MM Env 2"),
+            lfo1: ModMatrixRowParams::new("MM LFO 1"),
+            lfo2: ModMatrixRowParams::new("MM LFO 2"),
+            release_velocity: ModMatrixRowParams::new("MM Release Velocity"),
+            pressure: ModMatrixRowParams::new("MM Pressure"),
+            slide: ModMatrixRowParams::new("MM Slide"),
         }
     }
     pub fn row(&self, src: ModSrc) -> &ModMatrixRowParams {
@@ -409,6 +430,9 @@ impl ModMatrixPluginParams {
             ModSrc::Env2 => &self.env2,
             ModSrc::Lfo1 => &self.lfo1,
             ModSrc::Lfo2 => &self.lfo2,
+            ModSrc::ReleaseVelocity => &self.release_velocity,
+            ModSrc::Pressure => &self.pressure,
+            ModSrc::Slide => &self.slide,
         }
     }
 }
@@ -549,7 +573,12 @@ impl From<&CulSynthParams> for VoiceParams<i16> {
             oscs_p: SyncedMixOscsParams {
                 primary: MixOscParams::from(&value.osc1),
                 secondary: MixOscParams::from(&value.osc2),
-                sync: value.osc_sync.value(),
+                sync: if value.osc_sync.value() {
+                    OscSyncMode::Hard
+                } else {
+                    OscSyncMode::Off
+                },
+                fm_index: ScalarFxP::ZERO,
             },
             ring_p: RingModParams::from(&value.ringmod),
             filt_p: ModFiltParams::from(&value.filt),
@@ -559,6 +588,11 @@ impl From<&CulSynthParams> for VoiceParams<i16> {
             lfo2_p: LfoParams::from(&value.lfo2),
             env1_p: EnvParams::from(&value.env1),
             env2_p: EnvParams::from(&value.env2),
+            glide_p: Default::default(),
+            sat_p: None,
+            master_gain: ScalarFxP::MAX,
+            note_sync: false,
+            bus: 0,
         }
     }
 }
@@ -7,7 +7,7 @@ use std::sync::mpsc::SyncSender;
 use culsynth::context::GenericContext;
 use culsynth::voice::modulation::ModMatrix;
 use culsynth::voice::{Voice, VoiceChannelInput, VoiceInput, VoiceParams};
-use culsynth::{IScalarFxP, NoteFxP, ScalarFxP, SignedNoteFxP};
+use culsynth::{DspFormatBase, IScalarFxP, NoteFxP, ScalarFxP, SignedNoteFxP};
 
 use wmidi::MidiMessage;
 
@@ -31,7 +31,8 @@ pub trait VoiceAllocator: Send {
     /// For the sample at the current index (see [VoiceAllocator::sample_tick]),
     /// process a 'note off' event with MIDI note number `n` and velocity `v`
     ///
-    /// Note:  Most implementations will ignore note off velocity
+    /// The note off velocity is passed to the voice as its release velocity
+    /// (see [VoiceInput::release_velocity])
     fn note_off(&mut self, n: u8, v: u8);
     /// For the sample at the current index (see [VoiceAllocator::sample_tick]),
     /// process a change in the aftertouch value
@@ -47,15 +48,86 @@ pub trait VoiceAllocator: Send {
     /// `set_pitch_bend_range(2, 2)` will set the pitch wheel to bend up/down
     /// a whole step.
     fn set_pitch_bend_range(&mut self, low: i8, high: i8);
-    /// Get the next sample
+    /// Release every playing note (including any held by the sustain pedal),
+    /// so that each voice fades out through its release phase
+    fn all_notes_off(&mut self);
+    /// Immediately silence every voice, resetting its state, e.g. to recover
+    /// from stuck notes
+    fn panic(&mut self);
+    /// Get the next sample.  For stereo voice allocators, this is the mono-sum
+    /// downmix of [VoiceAllocator::next_stereo].
     fn next(&mut self, params: &VoiceParams<i16>, matrix: Option<&ModMatrix<i16>>) -> f32;
+    /// Get the next sample as a `(left, right)` pair.
+    ///
+    /// The default implementation is for mono voice allocators, and returns
+    /// the output of [VoiceAllocator::next] on both channels.
+    fn next_stereo(
+        &mut self,
+        params: &VoiceParams<i16>,
+        matrix: Option<&ModMatrix<i16>>,
+    ) -> (f32, f32) {
+        let out = self.next(params, matrix);
+        (out, out)
+    }
+    /// Accumulate the next sample into `buses`, which should contain one
+    /// sample per output bus, according to [VoiceParams::bus] (or the bus
+    /// set by [VoiceAllocator::set_voice_bus]).  Output routed to a bus that
+    /// does not exist is dropped.
+    fn next_buses(
+        &mut self,
+        params: &VoiceParams<i16>,
+        matrix: Option<&ModMatrix<i16>>,
+        buses: &mut [f32],
+    ) {
+        let out = self.next(params, matrix);
+        if let Some(bus) = buses.get_mut(params.bus as usize) {
+            *bus += out;
+        }
+    }
+    /// If `global` is true, share a single LFO 1 between all voices so they
+    /// are modulated in phase, rather than each voice running (and
+    /// retriggering) its own.  The shared LFO free-runs, and is not modulated
+    /// by LFO 2 or envelope 2.  This has no effect on monophonic allocators.
+    fn set_global_lfo1(&mut self, _global: bool) {}
+    /// Detune each voice by a fixed random offset of up to `cents` cents in
+    /// either direction when it is assigned a note, for a slightly "analog"
+    /// ensemble feel.  The offset for each voice is deterministic given the
+    /// allocator's seed.  This has no effect on monophonic allocators.
+    fn set_voice_detune_cents(&mut self, _cents: f32) {}
+    /// Spread the voices across the stereo field, evenly spaced from
+    /// `spread` left of center to `spread` right of center (from 0, all
+    /// centered, to 1, the outermost voices panned hard left and right).
+    /// This has no effect on monophonic allocators.
+    fn set_voice_pan_spread(&mut self, _spread: f32) {}
+    /// Route the voice at index `voice` to output bus `bus`, or to
+    /// [VoiceParams::bus] if `bus` is `None` (the default), so polyphonic
+    /// allocators can split their voices between buses (see
+    /// [VoiceAllocator::next_buses]).  This has no effect on monophonic
+    /// allocators.
+    fn set_voice_bus(&mut self, _voice: usize, _bus: Option<u8>) {}
+    /// Enable or disable MIDI Polyphonic Expression (MPE).  When enabled, each
+    /// note is expected on its own MIDI channel (other than channel 1, the MPE
+    /// master channel), and pitch bend, channel pressure, and CC #74 (slide)
+    /// on that channel modulate only that note.  This has no effect on
+    /// monophonic allocators.
+    fn set_mpe(&mut self, _enabled: bool) {}
+    /// Handle `msg` as per-note MPE data, returning true if it was consumed
+    /// (see [VoiceAllocator::set_mpe])
+    fn handle_mpe(&mut self, _msg: &MidiMessage) -> bool {
+        false
+    }
     /// Get the process context for this voice allocator.
     fn get_context(&self) -> &dyn GenericContext;
     /// Is this Voice Allocator polyphonic?
     fn is_poly(&self) -> bool;
+    /// The number of voices this Voice Allocator can play at once
+    fn num_voices(&self) -> usize;
     /// Get the MIDI channel associated with this VoiceAllocator, or None for all channels
     fn get_channel(&self) -> Option<wmidi::Channel>;
-    /// Handle a MIDI control change message:
+    /// Handle a MIDI control change message.
+    ///
+    /// CC #64 is the sustain pedal: while it is held, note offs are deferred
+    /// until it is lifted, when all of the held notes are released together.
     fn handle_cc(
         &mut self,
         cc: wmidi::ControlFunction,
@@ -68,6 +140,9 @@ pub trait VoiceAllocator: Send {
                 return;
             }
         }
+        if self.handle_mpe(&msg) {
+            return;
+        }
         match msg {
             MidiMessage::NoteOn(_, note, velocity) => self.note_on(note.into(), velocity.into()),
             MidiMessage::NoteOff(_, note, velocity) => self.note_off(note.into(), velocity.into()),
@@ -84,8 +159,146 @@ pub trait VoiceAllocator: Send {
     }
 }
 
+/// The layout of the output channels the plugin is rendering into
+#[derive(Default, Clone, Copy, PartialEq, Debug)]
+pub enum OutputLayout {
+    /// A single channel, which receives the mono-sum of the voices
+    Mono,
+    /// Left and right channels
+    #[default]
+    Stereo,
+}
+
+impl OutputLayout {
+    /// Determine the output layout from the number of output channels
+    pub fn from_channels(channels: u32) -> Self {
+        if channels < 2 {
+            Self::Mono
+        } else {
+            Self::Stereo
+        }
+    }
+}
+
+/// Render the next frame of output from `voices` into `frame`, which should
+/// contain one sample per output channel.
+///
+/// For [OutputLayout::Mono], the mono-sum of the voices is written.  For
+/// [OutputLayout::Stereo], the first two channels receive the left and right
+/// outputs (any further channels are silenced).
+pub fn render_frame<'a>(
+    voices: &mut dyn VoiceAllocator,
+    layout: OutputLayout,
+    params: &VoiceParams<i16>,
+    matrix: Option<&ModMatrix<i16>>,
+    frame: impl IntoIterator<Item = &'a mut f32>,
+) {
+    match layout {
+        OutputLayout::Mono => {
+            let out = voices.next(params, matrix);
+            for smp in frame {
+                *smp = out;
+            }
+        }
+        OutputLayout::Stereo => {
+            let (left, right) = voices.next_stereo(params, matrix);
+            for (idx, smp) in frame.into_iter().enumerate() {
+                *smp = match idx {
+                    0 => left,
+                    1 => right,
+                    _ => 0f32,
+                };
+            }
+        }
+    }
+}
+
 mod monosynth;
 pub use monosynth::MonoSynth;
 
 mod polysynth;
 pub use polysynth::PolySynth;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use culsynth::context::Context;
+
+    /// A voice allocator with its output panned hard left
+    struct PannedSynth {
+        ctx: Context<f32>,
+    }
+
+    impl VoiceAllocator for PannedSynth {
+        fn note_on(&mut self, _n: u8, _v: u8) {}
+        fn note_off(&mut self, _n: u8, _v: u8) {}
+        fn aftertouch(&mut self, _v: u8) {}
+        fn pitch_bend(&mut self, _v: i16) {}
+        fn get_pitch_bend_range(&self) -> (i8, i8) {
+            (2, 2)
+        }
+        fn set_pitch_bend_range(&mut self, _low: i8, _high: i8) {}
+        fn all_notes_off(&mut self) {}
+        fn panic(&mut self) {}
+        fn next(&mut self, params: &VoiceParams<i16>, matrix: Option<&ModMatrix<i16>>) -> f32 {
+            let (left, right) = self.next_stereo(params, matrix);
+            (left + right) / 2f32
+        }
+        fn next_stereo(
+            &mut self,
+            _params: &VoiceParams<i16>,
+            _matrix: Option<&ModMatrix<i16>>,
+        ) -> (f32, f32) {
+            (1f32, 0f32)
+        }
+        fn get_context(&self) -> &dyn GenericContext {
+            &self.ctx
+        }
+        fn is_poly(&self) -> bool {
+            false
+        }
+        fn num_voices(&self) -> usize {
+            1
+        }
+        fn get_channel(&self) -> Option<wmidi::Channel> {
+            None
+        }
+        fn handle_cc(
+            &mut self,
+            _cc: wmidi::ControlFunction,
+            _value: u8,
+            _dispatcher: &mut dyn MidiCcHandler,
+        ) {
+        }
+    }
+
+    #[test]
+    fn panned_voice_stereo_output() {
+        let mut synth = PannedSynth {
+            ctx: Context::new(48000f32),
+        };
+        let params = VoiceParams::<i16>::default();
+        let mut stereo = [[0f32; 2]; 16];
+        for frame in stereo.iter_mut() {
+            render_frame(
+                &mut synth,
+                OutputLayout::Stereo,
+                &params,
+                None,
+                frame.iter_mut(),
+            );
+        }
+        assert!(stereo.iter().all(|[l, r]| *l == 1f32 && *r == 0f32));
+        let mut mono = [[0f32; 1]; 16];
+        for frame in mono.iter_mut() {
+            render_frame(
+                &mut synth,
+                OutputLayout::Mono,
+                &params,
+                None,
+                frame.iter_mut(),
+            );
+        }
+        assert!(mono.iter().all(|[m]| *m == 0.5f32));
+    }
+}
@@ -13,20 +13,32 @@ pub struct MonoSynth<T: DspFormat> {
     pitch_bend: SignedNoteFxP,
     note: NoteFxP,
     velocity: ScalarFxP,
+    release_velocity: ScalarFxP,
     aftertouch: ScalarFxP,
     modwheel: ScalarFxP,
     gate: bool,
+    /// The sustain pedal is held
+    sustain: bool,
+    /// The note has been released, but is held by the sustain pedal
+    sustained: bool,
 }
 
 impl<T: DspFormat> MonoSynth<T> {
     pub fn new(ctx: T::Context) -> Self {
+        Self::new_with_seed(ctx, random())
+    }
+    /// Create a new monosynth, deterministically seeding its voice
+    pub fn new_with_seed(ctx: T::Context, seed: u64) -> Self {
         Self {
-            voice: Voice::new_with_seeds(random(), random()),
+            voice: Voice::new_with_seed(seed),
             matrix: Default::default(),
             ctx,
             note: NoteFxP::lit("69"), //A440, nice
             gate: false,
+            sustain: false,
+            sustained: false,
             velocity: ScalarFxP::ZERO,
+            release_velocity: ScalarFxP::ZERO,
             aftertouch: ScalarFxP::ZERO,
             modwheel: ScalarFxP::ZERO,
             pitch_bend: SignedNoteFxP::ZERO,
@@ -45,12 +57,18 @@ where
     fn note_on(&mut self, note: u8, velocity: u8) {
         self.note = NoteFxP::from_num(note);
         self.gate = true;
+        self.sustained = false;
         self.velocity = ScalarFxP::from_bits((velocity as u16) << 9);
+        self.release_velocity = ScalarFxP::ZERO;
     }
-    fn note_off(&mut self, note: u8, _velocity: u8) {
+    fn note_off(&mut self, note: u8, velocity: u8) {
         if self.note == note {
-            self.gate = false;
-            //self.velocity = ScalarFxP::from_bits((velocity as u16) << 9);
+            if self.sustain {
+                self.sustained = true;
+            } else {
+                self.gate = false;
+            }
+            self.release_velocity = ScalarFxP::from_bits((velocity as u16) << 9);
         }
     }
     fn get_channel(&self) -> Option<wmidi::Channel> {
@@ -80,15 +98,27 @@ where
             fixed::types::I16F0::from_num(high),
         );
     }
+    fn all_notes_off(&mut self) {
+        self.gate = false;
+        self.sustained = false;
+        self.release_velocity = ScalarFxP::ZERO;
+    }
+    fn panic(&mut self) {
+        self.all_notes_off();
+        self.voice.reset();
+    }
     fn next(&mut self, params: &VoiceParams<i16>, matrix: Option<&ModMatrix<i16>>) -> f32 {
         let ch_input = &VoiceChannelInput::<i16> {
             aftertouch: self.aftertouch,
             modwheel: self.modwheel,
+            global_lfo1: None,
         };
         let input = &VoiceInput::<i16> {
-            note: self.note.add_signed(self.pitch_bend),
+            note: i16::apply_note_offset(self.note, self.pitch_bend),
             gate: self.gate,
             velocity: self.velocity,
+            release_velocity: self.release_velocity,
+            ..Default::default()
         };
         // Handle matrix conversion, if required
         let matrix_param = if let Some(matrix) = matrix {
@@ -111,6 +141,9 @@ where
     fn is_poly(&self) -> bool {
         false
     }
+    fn num_voices(&self) -> usize {
+        1
+    }
     fn handle_cc(
         &mut self,
         cc: wmidi::ControlFunction,
@@ -124,6 +157,13 @@ where
             wmidi::ControlFunction::MODULATION_WHEEL_LSB => {
                 self.modwheel |= ScalarFxP::from_bits((value as u16) << 2);
             }
+            wmidi::ControlFunction::DAMPER_PEDAL => {
+                self.sustain = value >= 64;
+                if !self.sustain && self.sustained {
+                    self.sustained = false;
+                    self.gate = false;
+                }
+            }
             _ => {
                 let _ = dispatcher.handle_cc(cc, value);
             }
@@ -1,24 +1,68 @@
 use std::collections::VecDeque;
 
 use super::*;
+use culsynth::devices::{Device, Lfo};
 use culsynth::DspFormat;
 use nih_plug::nih_error;
-use rand::random;
+use rand::{random, rngs::StdRng, Rng, SeedableRng};
+
+/// The MPE per-note pitch bend range, in semitones
+const MPE_BEND_RANGE: i16 = 48;
+
+/// The per-note expression data received on an MPE member channel
+#[derive(Clone, Copy, Default)]
+struct MpeChannel {
+    bend: SignedNoteFxP,
+    pressure: ScalarFxP,
+    slide: ScalarFxP,
+}
 
 struct PolySynthVoice<T: DspFormat> {
     voice: Voice<T>,
     vel: ScalarFxP,
+    rel_vel: ScalarFxP,
     note: NoteFxP,
     gate: bool,
+    /// The note has been released, but is held by the sustain pedal
+    sustained: bool,
+    /// The fixed random detune factor for this voice, between -1 and 1
+    detune_factor: f32,
+    detune: SignedNoteFxP,
+    /// The stereo position of this voice, from -1 (hard left) to 1 (hard
+    /// right)
+    pan: f32,
+    /// The output bus of this voice, overriding [VoiceParams::bus]
+    bus: Option<u8>,
+    /// The MIDI channel index this voice's note was played on (only used
+    /// for MPE)
+    channel: usize,
 }
 
 impl<T: DspFormat> PolySynthVoice<T> {
-    fn new() -> Self {
+    fn new(seed: u64) -> Self {
         Self {
-            voice: Voice::new_with_seeds(random(), random()),
+            voice: Voice::new_with_seed(seed),
             note: NoteFxP::from_num(69), //A440
             gate: false,
+            sustained: false,
             vel: ScalarFxP::ZERO,
+            rel_vel: ScalarFxP::ZERO,
+            detune_factor: StdRng::seed_from_u64(seed).gen_range(-1f32..=1f32),
+            detune: SignedNoteFxP::ZERO,
+            pan: 0f32,
+            bus: None,
+            channel: 0,
+        }
+    }
+    fn input(&self, pitch_bend: SignedNoteFxP, mpe: &MpeChannel) -> VoiceInput<i16> {
+        VoiceInput::<i16> {
+            note: i16::apply_note_offset(self.note, pitch_bend.saturating_add(self.detune)),
+            gate: self.gate,
+            velocity: self.vel,
+            release_velocity: self.rel_vel,
+            bend: mpe.bend,
+            pressure: mpe.pressure,
+            slide: mpe.slide,
         }
     }
 }
@@ -32,13 +76,24 @@ pub struct PolySynth<T: DspFormat> {
     pitch_bend: SignedNoteFxP,
     aftertouch: ScalarFxP,
     modwheel: ScalarFxP,
+    sustain: bool,
+    global_lfo1: Option<Lfo<T>>,
+    /// The seed the voices were derived from, used to seed the global LFO
+    seed: u64,
+    voice_detune_cents: f32,
+    mpe: Option<[MpeChannel; 16]>,
     ctx: T::Context,
 }
 
 impl<T: DspFormat> PolySynth<T> {
     pub fn new(context: T::Context, num_voices: usize) -> Self {
-        let voices = std::iter::repeat_with(|| PolySynthVoice::<T>::new())
-            .take(num_voices)
+        Self::new_with_seed(context, num_voices, random())
+    }
+    /// Create a new polysynth, deterministically seeding each voice with a
+    /// distinct seed derived from `seed`
+    pub fn new_with_seed(context: T::Context, num_voices: usize, seed: u64) -> Self {
+        let voices = (0..num_voices)
+            .map(|i| PolySynthVoice::<T>::new(seed.wrapping_add(i as u64)))
             .collect::<Box<[_]>>();
         let mut active_voices = VecDeque::<usize>::new();
         let mut inactive_voices = VecDeque::<usize>::new();
@@ -56,6 +111,11 @@ impl<T: DspFormat> PolySynth<T> {
             pitch_bend_range: (2i16.into(), 2i16.into()),
             aftertouch: ScalarFxP::ZERO,
             modwheel: ScalarFxP::ZERO,
+            sustain: false,
+            global_lfo1: None,
+            seed,
+            voice_detune_cents: 0f32,
+            mpe: None,
             ctx: context,
         }
     }
@@ -64,7 +124,102 @@ impl<T: DspFormat> PolySynth<T> {
         let voice = &mut self.voices[voice_index];
         voice.note = NoteFxP::from_num(note);
         voice.vel = ScalarFxP::from_bits((vel as u16) << 9);
+        voice.rel_vel = ScalarFxP::ZERO;
         voice.gate = true;
+        voice.sustained = false;
+        voice.detune = SignedNoteFxP::saturating_from_num(
+            voice.detune_factor * self.voice_detune_cents / 100.,
+        );
+    }
+    /// Release the first active voice matching `pred`, or if the sustain
+    /// pedal is held, mark it to be released when the pedal is lifted
+    fn release_where(&mut self, velocity: u8, pred: impl Fn(&PolySynthVoice<T>) -> bool) {
+        let Some(act_idx) = self.active_voices.iter().position(|idx| {
+            let voice = &self.voices[*idx];
+            !voice.sustained && pred(voice)
+        }) else {
+            return;
+        };
+        let rel_vel = ScalarFxP::from_bits((velocity as u16) << 9);
+        if self.sustain {
+            let voice = &mut self.voices[self.active_voices[act_idx]];
+            voice.sustained = true;
+            voice.rel_vel = rel_vel;
+        } else if let Some(vox_idx) = self.active_voices.remove(act_idx) {
+            self.inactive_voices.push_back(vox_idx);
+            self.voices[vox_idx].gate = false;
+            self.voices[vox_idx].rel_vel = rel_vel;
+        }
+    }
+    /// Release all of the voices held by the sustain pedal
+    fn release_sustained(&mut self) {
+        let voices = &mut self.voices;
+        let inactive_voices = &mut self.inactive_voices;
+        self.active_voices.retain(|idx| {
+            let voice = &mut voices[*idx];
+            if voice.sustained {
+                voice.sustained = false;
+                voice.gate = false;
+                inactive_voices.push_back(*idx);
+            }
+            voice.gate
+        });
+    }
+}
+
+impl<T: DspFormat> PolySynth<T>
+where
+    for<'a> ModMatrix<T>: From<&'a ModMatrix<i16>>,
+    for<'a> VoiceInput<T>: From<&'a VoiceInput<i16>>,
+    for<'a> VoiceChannelInput<T>: From<&'a VoiceChannelInput<i16>>,
+    for<'a> VoiceParams<T>: From<&'a VoiceParams<i16>>,
+{
+    /// Process the next sample of each voice, passing the voice and its
+    /// (attenuated) output to `sink`
+    fn next_voices(
+        &mut self,
+        params: &VoiceParams<i16>,
+        matrix: Option<&ModMatrix<i16>>,
+        mut sink: impl FnMut(&PolySynthVoice<T>, f32),
+    ) {
+        // Handle matrix conversion into a different format, if required
+        let matrix_param = if let Some(matrix) = matrix {
+            self.matrix = matrix.into();
+            Some(&self.matrix)
+        } else {
+            None
+        };
+        let params: VoiceParams<T> = params.into();
+        let mut ch_in: VoiceChannelInput<T> = (&VoiceChannelInput::<i16> {
+            aftertouch: self.aftertouch,
+            modwheel: self.modwheel,
+            global_lfo1: None,
+        })
+            .into();
+        ch_in.global_lfo1 = self
+            .global_lfo1
+            .as_mut()
+            .map(|lfo| lfo.next(&self.ctx, false, params.lfo1_p.clone()));
+        let pitch_bend = self.pitch_bend;
+        let no_mpe = MpeChannel::default();
+        for v in self.voices.iter_mut() {
+            // Skip voices that have fully decayed to save CPU (but still
+            // process them when the matrix changes so they pick it up)
+            if matrix_param.is_none() && !v.gate && !v.voice.is_active() {
+                continue;
+            }
+            let mpe = self.mpe.as_ref().map_or(&no_mpe, |channels| &channels[v.channel]);
+            let input = &v.input(pitch_bend, mpe);
+            let out = T::sample_to_float(v.voice.next(
+                &self.ctx,
+                matrix_param,
+                &input.into(),
+                &ch_in,
+                params.clone(),
+            ));
+            // Signal is a hair hot (0dB), so attenuate it just a bit...
+            sink(v, out / 8.);
+        }
     }
 }
 
@@ -87,17 +242,8 @@ where
     fn get_channel(&self) -> Option<wmidi::Channel> {
         None //TODO
     }
-    fn note_off(&mut self, note: u8, _velocity: u8) {
-        if let Some((act_idx, vox_idx)) = self
-            .active_voices
-            .iter()
-            .enumerate()
-            .find(|(_, idx)| self.voices[**idx].note == note)
-        {
-            self.inactive_voices.push_back(*vox_idx);
-            self.voices[*vox_idx].gate = false;
-            self.active_voices.remove(act_idx);
-        }
+    fn note_off(&mut self, note: u8, velocity: u8) {
+        self.release_where(velocity, |v| v.note == note);
     }
     fn aftertouch(&mut self, value: u8) {
         self.aftertouch = ScalarFxP::from_bits((value as u16) << 9);
@@ -115,6 +261,12 @@ where
             wmidi::ControlFunction::MODULATION_WHEEL_LSB => {
                 self.modwheel |= ScalarFxP::from_bits((value as u16) << 2);
             }
+            wmidi::ControlFunction::DAMPER_PEDAL => {
+                self.sustain = value >= 64;
+                if !self.sustain {
+                    self.release_sustained();
+                }
+            }
             _ => {
                 let _ = dispatcher.handle_cc(cc, value);
             }
@@ -141,35 +293,125 @@ where
             fixed::types::I16F0::from_num(high),
         );
     }
+    fn all_notes_off(&mut self) {
+        for idx in self.active_voices.drain(..) {
+            let voice = &mut self.voices[idx];
+            voice.gate = false;
+            voice.sustained = false;
+            voice.rel_vel = ScalarFxP::ZERO;
+            self.inactive_voices.push_back(idx);
+        }
+    }
+    fn panic(&mut self) {
+        self.all_notes_off();
+        for voice in self.voices.iter_mut() {
+            voice.voice.reset();
+        }
+    }
     fn next(&mut self, params: &VoiceParams<i16>, matrix: Option<&ModMatrix<i16>>) -> f32 {
-        let mut out = 0f32;
-        // Handle matrix conversion into a different format, if required
-        let matrix_param = if let Some(matrix) = matrix {
-            self.matrix = matrix.into();
-            Some(&self.matrix)
-        } else {
-            None
-        };
-        let ch_in = &VoiceChannelInput::<i16> {
-            aftertouch: self.aftertouch,
-            modwheel: self.modwheel,
-        };
-        for v in self.voices.iter_mut() {
-            let input = &VoiceInput::<i16> {
-                note: v.note.add_signed(self.pitch_bend),
-                gate: v.gate,
-                velocity: v.vel,
+        let (left, right) = self.next_stereo(params, matrix);
+        (left + right) / 2.
+    }
+    fn next_stereo(
+        &mut self,
+        params: &VoiceParams<i16>,
+        matrix: Option<&ModMatrix<i16>>,
+    ) -> (f32, f32) {
+        let (mut left, mut right) = (0f32, 0f32);
+        self.next_voices(params, matrix, |v, out| {
+            // Balance law: a centered voice is at full level in both channels
+            left += out * (1. - v.pan).min(1.);
+            right += out * (1. + v.pan).min(1.);
+        });
+        (left, right)
+    }
+    fn next_buses(
+        &mut self,
+        params: &VoiceParams<i16>,
+        matrix: Option<&ModMatrix<i16>>,
+        buses: &mut [f32],
+    ) {
+        let default_bus = params.bus;
+        self.next_voices(params, matrix, |v, out| {
+            if let Some(bus) = buses.get_mut(v.bus.unwrap_or(default_bus) as usize) {
+                *bus += out;
+            }
+        });
+    }
+    fn set_global_lfo1(&mut self, global: bool) {
+        if global != self.global_lfo1.is_some() {
+            // The voices use the seeds from `seed` onward, so take the next
+            let seed = self.seed.wrapping_add(self.voices.len() as u64);
+            self.global_lfo1 = global.then(|| Lfo::new(seed));
+        }
+    }
+    fn set_voice_detune_cents(&mut self, cents: f32) {
+        self.voice_detune_cents = cents;
+    }
+    fn set_voice_pan_spread(&mut self, spread: f32) {
+        let spread = spread.clamp(0., 1.);
+        let last = self.voices.len().saturating_sub(1);
+        for (i, v) in self.voices.iter_mut().enumerate() {
+            // A single voice stays centered
+            v.pan = match last {
+                0 => 0.,
+                _ => spread * (2. * i as f32 / last as f32 - 1.),
             };
-            out += T::sample_to_float(v.voice.next(
-                &self.ctx,
-                matrix_param,
-                &input.into(),
-                &ch_in.into(),
-                params.into(),
-            ));
         }
-        // Signal is a hair hot (0dB), so attenuate it just a bit...
-        out / 8.
+    }
+    fn set_voice_bus(&mut self, voice: usize, bus: Option<u8>) {
+        if let Some(v) = self.voices.get_mut(voice) {
+            v.bus = bus;
+        }
+    }
+    fn set_mpe(&mut self, enabled: bool) {
+        if enabled != self.mpe.is_some() {
+            self.mpe = enabled.then(Default::default);
+        }
+    }
+    fn handle_mpe(&mut self, msg: &MidiMessage) -> bool {
+        // Messages on the master channel are handled as usual
+        let channel = match msg.channel() {
+            Some(ch) if self.mpe.is_some() && ch != wmidi::Channel::Ch1 => ch.index() as usize,
+            _ => return false,
+        };
+        let scalar = |value: u8| ScalarFxP::from_bits((value as u16) << 9);
+        match msg {
+            MidiMessage::NoteOn(_, note, velocity) => {
+                self.note_on((*note).into(), (*velocity).into());
+                if let Some(idx) = self.active_voices.back() {
+                    self.voices[*idx].channel = channel;
+                }
+            }
+            MidiMessage::NoteOff(_, note, velocity) => {
+                let note: u8 = (*note).into();
+                self.release_where((*velocity).into(), |v| {
+                    v.note == note && v.channel == channel
+                });
+            }
+            MidiMessage::PitchBendChange(_, value) => {
+                let bend: u16 = (*value).into();
+                // Rescale the 14 bit bend to a full scale IScalarFxP
+                let bend = IScalarFxP::from_bits(((bend as i32 - 8192i32) << 2) as i16);
+                let range = fixed::types::I16F0::from_num(MPE_BEND_RANGE);
+                if let Some(channels) = self.mpe.as_mut() {
+                    channels[channel].bend =
+                        SignedNoteFxP::saturating_from_num(bend.wide_mul(range));
+                }
+            }
+            MidiMessage::ChannelPressure(_, value) => {
+                if let Some(channels) = self.mpe.as_mut() {
+                    channels[channel].pressure = scalar((*value).into());
+                }
+            }
+            MidiMessage::ControlChange(_, cc, value) if u8::from(*cc) == 74 => {
+                if let Some(channels) = self.mpe.as_mut() {
+                    channels[channel].slide = scalar((*value).into());
+                }
+            }
+            _ => return false,
+        }
+        true
     }
     fn get_context(&self) -> &dyn GenericContext {
         <T::Context as culsynth::context::GetContext>::get_context(&self.ctx)
@@ -177,4 +419,244 @@ where
     fn is_poly(&self) -> bool {
         true
     }
+    fn num_voices(&self) -> usize {
+        self.voices.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use culsynth::context::Context;
+    use culsynth::devices::{LfoOptions, LfoParams, LfoWave};
+
+    /// Voice parameters with a sawtooth passed straight to the output (the
+    /// default oscillator mix is silent)
+    fn audible_params() -> VoiceParams<i16> {
+        let mut params = VoiceParams::<i16>::default();
+        params.oscs_p.primary.saw = ScalarFxP::MAX;
+        params.ring_p.mix_a = ScalarFxP::MAX;
+        params.filt_p.low_mix = ScalarFxP::MAX;
+        params.filt_p.cutoff = NoteFxP::MAX;
+        params
+    }
+
+    #[test]
+    fn eight_voices_all_sound() {
+        let mut synth = PolySynth::<f32>::new_with_seed(Context::new(48000f32), 8, 0);
+        assert_eq!(synth.num_voices(), 8);
+        let notes = [60u8, 62, 64, 65, 67, 69, 71, 72];
+        for note in notes {
+            synth.note_on(note, 100);
+        }
+        let params = VoiceParams::<i16>::default();
+        for _ in 0..4800 {
+            synth.next(&params, None);
+        }
+        assert_eq!(synth.active_voices.len(), 8);
+        assert!(synth.inactive_voices.is_empty());
+        for note in notes {
+            let voice = synth.voices.iter().find(|v| v.note == note).unwrap();
+            assert!(voice.gate && voice.voice.is_active());
+        }
+    }
+
+    #[test]
+    fn seeded_global_lfo_is_reproducible() {
+        let lfo_output = |seed| {
+            let mut synth = PolySynth::<f32>::new_with_seed(Context::new(48000f32), 2, seed);
+            synth.set_global_lfo1(true);
+            let params = LfoParams::<f32> {
+                freq: 20f32,
+                depth: 1f32,
+                opts: LfoOptions::new(LfoWave::SampleHold, true, false),
+                phase_offset: 0f32,
+            };
+            let lfo = synth.global_lfo1.as_mut().unwrap();
+            (0..4800)
+                .map(|_| lfo.next(&synth.ctx, false, params.clone()))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(lfo_output(1), lfo_output(1));
+        assert_ne!(lfo_output(1), lfo_output(2));
+    }
+
+    #[test]
+    fn voice_detune_within_bound() {
+        let mut synth = PolySynth::<f32>::new_with_seed(Context::new(48000f32), 8, 0);
+        synth.set_voice_detune_cents(5f32);
+        for _ in 0..8 {
+            synth.note_on(60, 100);
+        }
+        let notes = synth
+            .voices
+            .iter()
+            .map(|v| v.input(SignedNoteFxP::ZERO, &Default::default()).note.to_num::<f32>())
+            .collect::<Vec<_>>();
+        // Allow for the resolution of the note offset
+        for note in notes.iter() {
+            assert!((note - 60f32).abs() <= 0.05 + 1. / 512., "{note}");
+        }
+        assert!(notes.iter().any(|note| *note != notes[0]), "{notes:?}");
+    }
+
+    #[test]
+    fn mpe_bends_each_voice() {
+        use wmidi::{Channel, Note, U14, U7};
+        let mut synth = PolySynth::<f32>::new_with_seed(Context::new(48000f32), 4, 0);
+        synth.set_mpe(true);
+        let (mut dispatcher, _rx) = std::sync::mpsc::sync_channel::<(u8, u8)>(4);
+        let velocity = U7::try_from(100).unwrap();
+        for ch in [Channel::Ch2, Channel::Ch3] {
+            synth.handle_midi(MidiMessage::NoteOn(ch, Note::C4, velocity), &mut dispatcher);
+        }
+        // Bend channel 2 up by a whole step (of the 48 semitone range)
+        let bend = U14::try_from(8192 + 8192 / 24).unwrap();
+        synth.handle_midi(
+            MidiMessage::PitchBendChange(Channel::Ch2, bend),
+            &mut dispatcher,
+        );
+        let channels = synth.mpe.unwrap();
+        let note = |ch: Channel| {
+            let v = synth.voices.iter().find(|v| v.channel == ch.index() as usize).unwrap();
+            let input = v.input(SignedNoteFxP::ZERO, &channels[v.channel]);
+            i16::apply_note_offset(input.note, input.bend).to_num::<f32>()
+        };
+        assert!(
+            (note(Channel::Ch2) - 62f32).abs() < 0.01,
+            "{}",
+            note(Channel::Ch2)
+        );
+        assert_eq!(note(Channel::Ch3), 60f32);
+        // Releasing the note on channel 3 leaves channel 2 playing
+        synth.handle_midi(
+            MidiMessage::NoteOff(Channel::Ch3, Note::C4, velocity),
+            &mut dispatcher,
+        );
+        assert_eq!(synth.active_voices.len(), 1);
+        assert_eq!(
+            synth.voices[synth.active_voices[0]].channel,
+            Channel::Ch2.index() as usize
+        );
+    }
+
+    #[test]
+    fn sustain_pedal_defers_note_off() {
+        let mut synth = PolySynth::<f32>::new_with_seed(Context::new(48000f32), 4, 0);
+        let (mut dispatcher, _rx) = std::sync::mpsc::sync_channel::<(u8, u8)>(4);
+        let params = VoiceParams::<i16>::default();
+        synth.handle_cc(wmidi::ControlFunction::DAMPER_PEDAL, 127, &mut dispatcher);
+        let notes = [60u8, 64, 67];
+        for note in notes {
+            synth.note_on(note, 100);
+        }
+        for note in notes {
+            synth.note_off(note, 100);
+        }
+        for _ in 0..4800 {
+            synth.next(&params, None);
+        }
+        assert_eq!(synth.active_voices.len(), notes.len());
+        for note in notes {
+            let voice = synth.voices.iter().find(|v| v.note == note).unwrap();
+            assert!(voice.gate && voice.sustained && voice.voice.is_active());
+        }
+        // Lifting the pedal releases every held note together
+        synth.handle_cc(wmidi::ControlFunction::DAMPER_PEDAL, 0, &mut dispatcher);
+        assert!(synth.active_voices.is_empty());
+        assert_eq!(synth.inactive_voices.len(), synth.num_voices());
+        assert!(synth.voices.iter().all(|v| !v.gate && !v.sustained));
+    }
+
+    #[test]
+    fn all_notes_off_releases_and_panic_silences() {
+        let params = audible_params();
+        let play = || {
+            let mut synth = PolySynth::<f32>::new_with_seed(Context::new(48000f32), 4, 0);
+            for note in [60u8, 64, 67] {
+                synth.note_on(note, 100);
+            }
+            for _ in 0..4800 {
+                synth.next(&params, None);
+            }
+            synth
+        };
+        let mut synth = play();
+        synth.all_notes_off();
+        assert!(synth.active_voices.is_empty());
+        assert_eq!(synth.inactive_voices.len(), synth.num_voices());
+        // The voices are still sounding, in their release phase
+        let active = synth.voices.iter().filter(|v| v.voice.is_active()).count();
+        assert_eq!(active, 3);
+        assert!(synth.voices.iter().all(|v| !v.gate));
+        assert!(synth.next(&params, None) != 0f32);
+
+        let mut synth = play();
+        synth.panic();
+        assert!(synth.active_voices.is_empty());
+        assert!(synth.voices.iter().all(|v| !v.gate && !v.voice.is_active()));
+        assert!((0..480).all(|_| synth.next(&params, None) == 0f32));
+    }
+
+    #[test]
+    fn panned_voices_stay_in_their_channel() {
+        let params = audible_params();
+        let mut synth = PolySynth::<f32>::new_with_seed(Context::new(48000f32), 2, 0);
+        synth.set_voice_pan_spread(1f32);
+        assert_eq!(
+            synth.voices.iter().map(|v| v.pan).collect::<Vec<_>>(),
+            [-1., 1.]
+        );
+        // The first note plays on voice 0 (hard left), the second on voice 1
+        // (hard right)
+        let mut peaks = [(0f32, 0f32); 2];
+        for peak in peaks.iter_mut() {
+            synth.note_on(60, 100);
+            for _ in 0..4800 {
+                let (left, right) = synth.next_stereo(&params, None);
+                peak.0 = peak.0.max(left.abs());
+                peak.1 = peak.1.max(right.abs());
+            }
+            synth.panic();
+        }
+        let [(left_l, left_r), (right_l, right_r)] = peaks;
+        assert!(left_l > 0f32 && left_r == 0f32, "{:?}", peaks[0]);
+        assert!(right_r > 0f32 && right_l == 0f32, "{:?}", peaks[1]);
+    }
+
+    #[test]
+    fn voices_accumulate_into_their_bus() {
+        let params = audible_params();
+        let mut synth = PolySynth::<f32>::new_with_seed(Context::new(48000f32), 2, 0);
+        // Voice 0 follows VoiceParams::bus (the main bus), voice 1 is sent
+        // to bus 1
+        synth.set_voice_bus(1, Some(1));
+        let mut peaks = [[0f32; 2]; 2];
+        for peak in peaks.iter_mut() {
+            synth.note_on(60, 100);
+            for _ in 0..4800 {
+                let mut buses = [0f32; 2];
+                synth.next_buses(&params, None, &mut buses);
+                for (p, b) in peak.iter_mut().zip(buses) {
+                    *p = p.max(b.abs());
+                }
+            }
+            synth.panic();
+        }
+        assert!(peaks[0][0] > 0f32 && peaks[0][1] == 0f32, "{:?}", peaks[0]);
+        assert!(peaks[1][1] > 0f32 && peaks[1][0] == 0f32, "{:?}", peaks[1]);
+        // With both voices playing, each bus carries one voice, and the
+        // buses sum to the mono output
+        synth.note_on(60, 100);
+        synth.note_on(60, 100);
+        let mut mono = PolySynth::<f32>::new_with_seed(Context::new(48000f32), 2, 0);
+        mono.note_on(60, 100);
+        mono.note_on(60, 100);
+        for _ in 0..480 {
+            let mut buses = [0f32; 2];
+            synth.next_buses(&params, None, &mut buses);
+            let out = mono.next(&params, None);
+            assert!((buses[0] + buses[1] - out).abs() < 1e-6, "{buses:?} {out}");
+        }
+    }
 }